@@ -0,0 +1,50 @@
+//! Stress pipeline: sequential vs parallel evaluation
+//!
+//! Evaluates 100 portfolios of 250 assets under 20 covariance stress
+//! scenarios. The parallel speedup depends on the rayon thread count.
+
+use covariance::stress::{CovarianceStress, StressPipeline};
+use criterion::{criterion_group, criterion_main, Criterion};
+use nalgebra::{DMatrix, DVector};
+
+const N_ASSETS: usize = 250;
+const N_PORTFOLIOS: usize = 100;
+const N_SCENARIOS: usize = 20;
+
+fn base_cov(n: usize) -> DMatrix<f64> {
+    let vols: Vec<f64> = (0..n).map(|i| 0.15 + 0.001 * i as f64).collect();
+    DMatrix::from_fn(n, n, |i, j| {
+        let rho = if i == j { 1.0 } else { 0.25 };
+        rho * vols[i] * vols[j]
+    })
+}
+
+fn portfolios(n: usize, count: usize) -> Vec<DVector<f64>> {
+    (0..count)
+        .map(|p| {
+            let raw = DVector::from_fn(n, |i, _| 1.0 + ((i * 13 + p * 7) % 11) as f64);
+            let total = raw.sum();
+            raw / total
+        })
+        .collect()
+}
+
+fn bench_stress_pipeline(c: &mut Criterion) {
+    let scenarios = (0..N_SCENARIOS)
+        .map(|k| CovarianceStress::VolatilityShock {
+            multiplier: 1.0 + 0.1 * k as f64,
+        })
+        .collect();
+    let pipeline = StressPipeline::new(base_cov(N_ASSETS), scenarios);
+    let portfolios = portfolios(N_ASSETS, N_PORTFOLIOS);
+
+    c.bench_function("stress_sequential_100x20", |b| {
+        b.iter(|| pipeline.run(&portfolios))
+    });
+    c.bench_function("stress_parallel_100x20", |b| {
+        b.iter(|| pipeline.run_parallel(&portfolios))
+    });
+}
+
+criterion_group!(benches, bench_stress_pipeline);
+criterion_main!(benches);
@@ -0,0 +1,221 @@
+//! Covariance update timing
+//!
+//! Re-estimating and re-optimizing on every new observation is costly and
+//! adds turnover, while a stale covariance leaves the portfolio
+//! mis-hedged. The advisor measures what staleness actually costs: the
+//! information ratio given up by holding the weights optimized under the
+//! old covariance rather than under one estimated from recent returns.
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use crate::estimator::SampleCovariance;
+use crate::{CovarianceError, Result};
+
+/// Longest wait the advisor suggests, in trading days
+const MAX_WAIT_DAYS: u32 = 21;
+
+/// Recommendation for a covariance refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateAdvice {
+    /// The old covariance costs more than the threshold; re-estimate now
+    UpdateNow,
+    /// The old covariance is still adequate; reassess after this many days
+    Wait(u32),
+}
+
+/// Decides when a covariance estimate is stale enough to replace
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CovarianceUpdateAdvisor {
+    /// Relative information ratio loss above which the covariance is
+    /// refreshed, in `(0, 1]`; defaults to 0.01 (1%)
+    pub ir_loss_threshold: f64,
+}
+
+impl Default for CovarianceUpdateAdvisor {
+    fn default() -> Self {
+        Self {
+            ir_loss_threshold: 0.01,
+        }
+    }
+}
+
+impl CovarianceUpdateAdvisor {
+    /// Create an advisor, checking the threshold lies in `(0, 1]`
+    pub fn new(ir_loss_threshold: f64) -> Result<Self> {
+        let advisor = Self { ir_loss_threshold };
+        advisor.validate()?;
+        Ok(advisor)
+    }
+
+    /// Assess whether `old_cov` should be replaced
+    ///
+    /// The expected returns behind `optimal_weights_old` are backed out as
+    /// `μ = λ Σ_old w_old`, then re-optimized as `w_new = Σ_new⁻¹ μ / λ`
+    /// with `Σ_new` the sample covariance of `recent_returns`
+    /// (n_observations x n_assets). Both portfolios are scored by their
+    /// information ratio `μ'w / sqrt(w'Σ_new w)` under the new covariance,
+    /// and the relative loss `1 - IR_old / IR_new` is compared against
+    /// `ir_loss_threshold`.
+    ///
+    /// Below the threshold the advice is to wait. The loss is second order
+    /// in the covariance error, so if the error accrued linearly over the
+    /// recent window the threshold is reached after `T (sqrt(threshold /
+    /// loss) - 1)` more days for a window of `T` days; the suggestion is
+    /// that estimate, between 1 and 21 days.
+    pub fn assess(
+        &self,
+        old_cov: &DMatrix<f64>,
+        recent_returns: &DMatrix<f64>,
+        optimal_weights_old: &DVector<f64>,
+        risk_aversion: f64,
+    ) -> Result<UpdateAdvice> {
+        self.validate()?;
+        let n = optimal_weights_old.len();
+        for dim in [old_cov.nrows(), old_cov.ncols(), recent_returns.ncols()] {
+            if dim != n {
+                return Err(CovarianceError::DimensionMismatch {
+                    expected: n,
+                    got: dim,
+                });
+            }
+        }
+        if !(risk_aversion > 0.0 && risk_aversion.is_finite()) {
+            return Err(CovarianceError::InvalidInput(format!(
+                "Risk aversion {} must be positive",
+                risk_aversion
+            )));
+        }
+
+        let implied_returns = old_cov * optimal_weights_old * risk_aversion;
+        if implied_returns.iter().all(|&mu| mu == 0.0) {
+            return Err(CovarianceError::InvalidInput(
+                "Old weights imply zero expected returns".to_string(),
+            ));
+        }
+
+        let new_cov = SampleCovariance::estimate(recent_returns, 1)?;
+        let new_weights = new_cov
+            .clone()
+            .cholesky()
+            .ok_or(CovarianceError::SingularMatrix)?
+            .solve(&implied_returns)
+            / risk_aversion;
+
+        let information_ratio = |w: &DVector<f64>| {
+            let risk = w.dot(&(&new_cov * w)).max(0.0).sqrt();
+            if risk > 0.0 {
+                implied_returns.dot(w) / risk
+            } else {
+                0.0
+            }
+        };
+        let ir_new = information_ratio(&new_weights);
+        let ir_old = information_ratio(optimal_weights_old);
+        let loss = (1.0 - ir_old / ir_new).max(0.0);
+
+        if loss > self.ir_loss_threshold {
+            return Ok(UpdateAdvice::UpdateNow);
+        }
+
+        let window = recent_returns.nrows() as f64;
+        let days = if loss > 0.0 {
+            window * ((self.ir_loss_threshold / loss).sqrt() - 1.0)
+        } else {
+            f64::INFINITY
+        };
+        let days = days.clamp(1.0, MAX_WAIT_DAYS as f64) as u32;
+        Ok(UpdateAdvice::Wait(days))
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(self.ir_loss_threshold > 0.0 && self.ir_loss_threshold <= 1.0) {
+            return Err(CovarianceError::InvalidInput(format!(
+                "Information ratio loss threshold {} must be in (0, 1]",
+                self.ir_loss_threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, StandardNormal};
+
+    fn covariance(vols: &[f64], corr: &[[f64; 4]; 4]) -> DMatrix<f64> {
+        DMatrix::from_fn(4, 4, |i, j| corr[i][j] * vols[i] * vols[j])
+    }
+
+    /// Draw `n_obs` multivariate normal returns with covariance `cov`
+    fn simulate(cov: &DMatrix<f64>, n_obs: usize, seed: u64) -> DMatrix<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let n = cov.nrows();
+        let z = DMatrix::<f64>::from_fn(n_obs, n, |_, _| StandardNormal.sample(&mut rng));
+        let l = cov.clone().cholesky().unwrap().l();
+        z * l.transpose()
+    }
+
+    fn old_setup() -> (DMatrix<f64>, DVector<f64>) {
+        let corr = [
+            [1.0, 0.3, 0.2, 0.1],
+            [0.3, 1.0, 0.4, 0.2],
+            [0.2, 0.4, 1.0, 0.3],
+            [0.1, 0.2, 0.3, 1.0],
+        ];
+        let old_cov = covariance(&[0.010, 0.012, 0.015, 0.020], &corr);
+        let weights = DVector::from_vec(vec![0.35, 0.25, 0.25, 0.15]);
+        (old_cov, weights)
+    }
+
+    #[test]
+    fn test_stable_market_waits() {
+        let (old_cov, weights) = old_setup();
+        let returns = simulate(&old_cov, 2000, 11);
+
+        let advice = CovarianceUpdateAdvisor::default()
+            .assess(&old_cov, &returns, &weights, 3.0)
+            .unwrap();
+        assert!(matches!(advice, UpdateAdvice::Wait(days) if (1..=21).contains(&days)));
+
+        // A tighter threshold is reached sooner
+        let strict = CovarianceUpdateAdvisor::new(1e-12).unwrap();
+        assert_eq!(
+            strict.assess(&old_cov, &returns, &weights, 3.0).unwrap(),
+            UpdateAdvice::UpdateNow
+        );
+        assert!(CovarianceUpdateAdvisor::new(0.0).is_err());
+        assert!(CovarianceUpdateAdvisor::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_covariance_shift_updates_now() {
+        let (old_cov, weights) = old_setup();
+
+        // The most heavily held asset triples in volatility and the
+        // correlations between the first three assets rise sharply
+        let shifted_corr = [
+            [1.0, 0.8, 0.7, 0.1],
+            [0.8, 1.0, 0.8, 0.2],
+            [0.7, 0.8, 1.0, 0.3],
+            [0.1, 0.2, 0.3, 1.0],
+        ];
+        let new_cov = covariance(&[0.030, 0.012, 0.015, 0.020], &shifted_corr);
+        let returns = simulate(&new_cov, 250, 12);
+
+        let advisor = CovarianceUpdateAdvisor::default();
+        let advice = advisor.assess(&old_cov, &returns, &weights, 3.0).unwrap();
+        assert_eq!(advice, UpdateAdvice::UpdateNow);
+
+        assert!(matches!(
+            advisor.assess(&old_cov, &returns, &DVector::zeros(3), 3.0),
+            Err(CovarianceError::DimensionMismatch {
+                expected: 3,
+                got: 4
+            })
+        ));
+    }
+}
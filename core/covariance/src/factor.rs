@@ -8,18 +8,44 @@
 //! - F: Factor covariance matrix (n_factors x n_factors)
 //! - D: Specific risk diagonal matrix (n_assets x n_assets)
 
-use nalgebra::{DMatrix, DVector};
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
 
-use crate::matrix::{is_positive_semi_definite, symmetrize};
+use crate::estimator::{gaussian_log_likelihood, SampleCovariance};
+use crate::matrix::{
+    is_positive_semi_definite, symmetrize, CorrelationCrisisStress, CovMatrix, CovarMatrix,
+    LoadingMatrix,
+};
 use crate::{CovarianceError, Result};
 
+/// Information criterion for choosing the number of factors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelSelectionCriterion {
+    /// Akaike: `2p - 2 ln L`
+    AIC,
+    /// Bayesian (Schwarz): `p ln T - 2 ln L`
+    BIC,
+    /// Two-part minimum description length: `p/2 ln T - ln L` (in nats)
+    MDL,
+}
+
+impl ModelSelectionCriterion {
+    /// Criterion value for log-likelihood `ln L`, `p` parameters and `T` observations
+    fn score(&self, log_likelihood: f64, n_params: f64, n_obs: f64) -> f64 {
+        match self {
+            ModelSelectionCriterion::AIC => 2.0 * n_params - 2.0 * log_likelihood,
+            ModelSelectionCriterion::BIC => n_params * n_obs.ln() - 2.0 * log_likelihood,
+            ModelSelectionCriterion::MDL => 0.5 * n_params * n_obs.ln() - log_likelihood,
+        }
+    }
+}
+
 /// Factor model covariance representation
 #[derive(Debug, Clone)]
 pub struct FactorCovariance {
     /// Factor loadings (n_assets x n_factors)
-    pub loadings: DMatrix<f64>,
+    pub loadings: LoadingMatrix,
     /// Factor covariance (n_factors x n_factors)
-    pub factor_cov: DMatrix<f64>,
+    pub factor_cov: CovMatrix,
     /// Specific variances (n_assets)
     pub specific_var: DVector<f64>,
 }
@@ -27,8 +53,8 @@ pub struct FactorCovariance {
 impl FactorCovariance {
     /// Create a new factor covariance model
     pub fn new(
-        loadings: DMatrix<f64>,
-        factor_cov: DMatrix<f64>,
+        loadings: LoadingMatrix,
+        factor_cov: CovMatrix,
         specific_var: DVector<f64>,
     ) -> Result<Self> {
         let n_assets = loadings.nrows();
@@ -82,7 +108,7 @@ impl FactorCovariance {
     ///
     /// Note: This materializes the full n x n matrix, which may be expensive
     /// for large universes. Use factor-based operations when possible.
-    pub fn to_full_matrix(&self) -> DMatrix<f64> {
+    pub fn to_full_matrix(&self) -> CovarMatrix {
         let n = self.n_assets();
 
         // B * F
@@ -97,7 +123,7 @@ impl FactorCovariance {
             full[(i, i)] += self.specific_var[i];
         }
 
-        symmetrize(&full)
+        CovarMatrix::from_square(&full)
     }
 
     /// Compute portfolio variance using factor decomposition
@@ -213,7 +239,7 @@ impl FactorCovariance {
     }
 
     /// Update factor covariance (for rolling/updating models)
-    pub fn update_factor_covariance(&mut self, new_cov: DMatrix<f64>) -> Result<()> {
+    pub fn update_factor_covariance(&mut self, new_cov: CovMatrix) -> Result<()> {
         if new_cov.nrows() != self.n_factors() || new_cov.ncols() != self.n_factors() {
             return Err(CovarianceError::DimensionMismatch {
                 expected: self.n_factors(),
@@ -228,6 +254,128 @@ impl FactorCovariance {
         self.factor_cov = new_cov;
         Ok(())
     }
+
+    /// Re-estimate specific variances from realized returns
+    ///
+    /// Computes residuals `E = R - F * B^T` and sets each asset's specific
+    /// variance to the sample variance of its residual series.
+    ///
+    /// # Arguments
+    /// * `asset_returns` - Asset returns (n_observations x n_assets)
+    /// * `factor_returns` - Factor returns (n_observations x n_factors)
+    pub fn reestimate_specific_variances(
+        &mut self,
+        asset_returns: &DMatrix<f64>,
+        factor_returns: &DMatrix<f64>,
+    ) -> Result<()> {
+        if asset_returns.ncols() != self.n_assets() {
+            return Err(CovarianceError::DimensionMismatch {
+                expected: self.n_assets(),
+                got: asset_returns.ncols(),
+            });
+        }
+
+        if factor_returns.ncols() != self.n_factors() {
+            return Err(CovarianceError::DimensionMismatch {
+                expected: self.n_factors(),
+                got: factor_returns.ncols(),
+            });
+        }
+
+        if factor_returns.nrows() != asset_returns.nrows() {
+            return Err(CovarianceError::DimensionMismatch {
+                expected: asset_returns.nrows(),
+                got: factor_returns.nrows(),
+            });
+        }
+
+        let n_obs = asset_returns.nrows();
+        if n_obs < 2 {
+            return Err(CovarianceError::InsufficientObservations {
+                needed: 2,
+                got: n_obs,
+            });
+        }
+
+        // E = R - F * B^T
+        let residuals = asset_returns - factor_returns * self.loadings.transpose();
+
+        let specific_var = DVector::from_iterator(
+            self.n_assets(),
+            residuals
+                .column_iter()
+                .map(|e| e.variance() * n_obs as f64 / (n_obs - 1) as f64),
+        );
+
+        self.specific_var = specific_var;
+        Ok(())
+    }
+
+    /// Select the number of statistical factors by an information criterion
+    ///
+    /// For each `K` in `1..=max_factors` a PCA factor model (see
+    /// [`StatisticalFactorModel`]) is fitted and the Gaussian log-likelihood
+    /// of the demeaned returns under `B B^T + D` is penalized by the number
+    /// of free parameters, `n K - K (K - 1) / 2` loadings (net of rotations)
+    /// plus `n` specific variances. Returns the `K` minimizing the criterion.
+    pub fn optimal_n_factors(
+        returns: &DMatrix<f64>,
+        max_factors: usize,
+        criterion: ModelSelectionCriterion,
+    ) -> Result<usize> {
+        let (n_obs, n_assets) = returns.shape();
+        if max_factors == 0 || max_factors > n_assets {
+            return Err(CovarianceError::InvalidInput(format!(
+                "max_factors must be in 1..={}, got {}",
+                n_assets, max_factors
+            )));
+        }
+        if n_obs < 2 {
+            return Err(CovarianceError::InsufficientObservations {
+                needed: 2,
+                got: n_obs,
+            });
+        }
+
+        let means = returns.row_mean();
+        let centered = DMatrix::from_fn(n_obs, n_assets, |i, j| returns[(i, j)] - means[j]);
+
+        let mut best: Option<(usize, f64)> = None;
+        for k in 1..=max_factors {
+            let model = StatisticalFactorModel::fit(returns, k)?;
+            let log_likelihood =
+                gaussian_log_likelihood(&centered, model.to_full_matrix().into_inner());
+            if !log_likelihood.is_finite() {
+                continue;
+            }
+
+            let n_params = (n_assets * k + n_assets - k * (k - 1) / 2) as f64;
+            let score = criterion.score(log_likelihood, n_params, n_obs as f64);
+            if best.is_none_or(|(_, best_score)| score < best_score) {
+                best = Some((k, score));
+            }
+        }
+
+        best.map(|(k, _)| k).ok_or_else(|| {
+            CovarianceError::NumericalError(
+                "No factor model with a positive definite covariance".to_string(),
+            )
+        })
+    }
+
+    /// Atomically replace loadings, specific variances and factor covariance
+    ///
+    /// All components are validated together; on error the model is left
+    /// unchanged.
+    pub fn partial_update(
+        &mut self,
+        new_loadings: LoadingMatrix,
+        new_specific_var: DVector<f64>,
+        new_factor_cov: CovMatrix,
+    ) -> Result<()> {
+        *self = Self::new(new_loadings, new_factor_cov, new_specific_var)?;
+        Ok(())
+    }
 }
 
 /// Variance decomposition result
@@ -266,6 +414,82 @@ impl VarianceDecomposition {
     }
 }
 
+/// Statistical (PCA) factor model
+///
+/// Extracts factors endogenously from the eigendecomposition of the sample
+/// covariance `S = V D V^T`: loadings are `B = V_k * sqrt(D_k)` for the top
+/// `k` eigenpairs, so the factor covariance is the identity. Specific
+/// variances are the residual variances `diag(S - B B^T)`, i.e. the variance
+/// left after projecting returns onto the top `k` principal components.
+pub struct StatisticalFactorModel;
+
+impl StatisticalFactorModel {
+    /// Fit a `n_factors`-factor model to returns (n_observations x n_assets)
+    pub fn fit(returns: &DMatrix<f64>, n_factors: usize) -> Result<FactorCovariance> {
+        let n_assets = returns.ncols();
+        if n_factors == 0 || n_factors > n_assets {
+            return Err(CovarianceError::InvalidInput(format!(
+                "n_factors must be in 1..={}, got {}",
+                n_assets, n_factors
+            )));
+        }
+
+        let sample = SampleCovariance::estimate(returns, 1)?;
+        let eigen = SymmetricEigen::new(sample.clone());
+
+        // Eigenpairs by decreasing eigenvalue
+        let mut order: Vec<usize> = (0..n_assets).collect();
+        order.sort_by(|&a, &b| eigen.eigenvalues[b].total_cmp(&eigen.eigenvalues[a]));
+
+        let mut loadings = DMatrix::zeros(n_assets, n_factors);
+        for (k, &idx) in order.iter().take(n_factors).enumerate() {
+            let scale = eigen.eigenvalues[idx].max(0.0).sqrt();
+            loadings.set_column(k, &(eigen.eigenvectors.column(idx) * scale));
+        }
+
+        let common = &loadings * loadings.transpose();
+        let specific_var = DVector::from_iterator(
+            n_assets,
+            (0..n_assets).map(|i| (sample[(i, i)] - common[(i, i)]).max(0.0)),
+        );
+
+        FactorCovariance::new(
+            loadings,
+            DMatrix::identity(n_factors, n_factors),
+            specific_var,
+        )
+    }
+}
+
+/// Correlation crisis applied to the factor covariance only
+///
+/// Factor loadings and specific variances are unchanged, so the stress
+/// reaches asset correlations only through the common factors.
+pub struct FactorCorrelationStress;
+
+impl FactorCorrelationStress {
+    /// Stress the factor correlations of `model`
+    ///
+    /// See [`CorrelationCrisisStress::apply`] for the treatment of the
+    /// factor covariance.
+    pub fn apply(
+        model: &FactorCovariance,
+        correlation_multiplier: f64,
+        min_eigenvalue: f64,
+    ) -> Result<FactorCovariance> {
+        let factor_cov = CorrelationCrisisStress::apply(
+            &model.factor_cov,
+            correlation_multiplier,
+            min_eigenvalue,
+        );
+        FactorCovariance::new(
+            model.loadings.clone(),
+            symmetrize(&factor_cov),
+            model.specific_var.clone(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -328,7 +552,7 @@ mod tests {
 
         // Compare with full matrix calculation
         let full = model.to_full_matrix();
-        let var_full = weights.dot(&(&full * &weights));
+        let var_full = weights.dot(&(full.as_matrix() * &weights));
 
         assert!((var_factor - var_full).abs() < 1e-10);
     }
@@ -364,6 +588,83 @@ mod tests {
         assert!((sum_rc - vol).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_reestimate_specific_variances() {
+        let mut model = create_test_model();
+
+        let factor_returns = dmatrix![
+            0.01, -0.02;
+            -0.015, 0.01;
+            0.02, 0.005;
+            0.005, -0.01
+        ];
+        let noise = dmatrix![
+            0.01, -0.02, 0.0, 0.03, -0.01;
+            -0.01, 0.02, 0.01, -0.03, 0.02;
+            0.02, 0.01, -0.01, 0.01, -0.02;
+            -0.02, -0.01, 0.0, -0.01, 0.01
+        ];
+        let asset_returns = &factor_returns * model.loadings.transpose() + &noise;
+
+        model
+            .reestimate_specific_variances(&asset_returns, &factor_returns)
+            .unwrap();
+
+        // Residuals recover the noise exactly
+        for j in 0..5 {
+            let col = noise.column(j);
+            let mean = col.mean();
+            let var = col.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 3.0;
+            assert!((model.specific_var[j] - var).abs() < 1e-12);
+        }
+
+        // Mismatched factor count is rejected
+        let bad_factors = dmatrix![0.01; 0.02; 0.03; 0.04];
+        assert!(model
+            .reestimate_specific_variances(&asset_returns, &bad_factors)
+            .is_err());
+    }
+
+    #[test]
+    fn test_partial_update() {
+        let mut model = create_test_model();
+
+        let new_loadings = dmatrix![
+            1.0;
+            0.5;
+            0.0;
+            -0.5;
+            1.5
+        ];
+        let new_factor_cov = dmatrix![0.09];
+        let new_specific_var = dvector![0.02, 0.02, 0.02, 0.02, 0.02];
+
+        model
+            .partial_update(
+                new_loadings.clone(),
+                new_specific_var.clone(),
+                new_factor_cov.clone(),
+            )
+            .unwrap();
+
+        assert_eq!(model.n_factors(), 1);
+        let full = model.to_full_matrix();
+        for i in 0..5 {
+            for j in 0..5 {
+                let mut expected = new_loadings[i] * new_loadings[j] * 0.09;
+                if i == j {
+                    expected += 0.02;
+                }
+                assert!((full[(i, j)] - expected).abs() < 1e-12);
+            }
+        }
+
+        // Invalid update leaves the model untouched
+        let result = model.partial_update(new_loadings, dvector![0.02, 0.02], new_factor_cov);
+        assert!(result.is_err());
+        assert_eq!(model.specific_var.len(), 5);
+    }
+
     #[test]
     fn test_dimension_validation() {
         let loadings = dmatrix![1.0, 0.5; 0.8, 0.6];
@@ -374,4 +675,100 @@ mod tests {
         let result = FactorCovariance::new(loadings, factor_cov, specific_var);
         assert!(result.is_err());
     }
+
+    fn simulated_returns(n_obs: usize, n_assets: usize) -> DMatrix<f64> {
+        // Deterministic pseudo-random returns driven by two common components
+        DMatrix::from_fn(n_obs, n_assets, |t, j| {
+            let market = ((t * 7 + 3) as f64).sin() * 0.02;
+            let style = ((t * 13 + 1) as f64).cos() * 0.01;
+            let noise = ((t * 31 + j * 17) as f64).sin() * 0.005;
+            (1.0 + 0.1 * j as f64) * market + (j as f64 - 2.0) * style + noise
+        })
+    }
+
+    #[test]
+    fn test_statistical_factor_model_full_rank() {
+        let returns = simulated_returns(120, 5);
+        let sample = SampleCovariance::estimate(&returns, 1).unwrap();
+
+        let model = StatisticalFactorModel::fit(&returns, 5).unwrap();
+        assert_eq!(model.n_factors(), 5);
+        assert_eq!(model.factor_cov, DMatrix::identity(5, 5));
+
+        let full = model.to_full_matrix();
+        assert!((full.as_matrix() - &sample).abs().max() < 1e-12);
+        assert!(model.specific_var.iter().all(|&v| v.abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_statistical_factor_model_residual_variances() {
+        let returns = simulated_returns(120, 5);
+        let sample = SampleCovariance::estimate(&returns, 1).unwrap();
+
+        let model = StatisticalFactorModel::fit(&returns, 2).unwrap();
+        assert_eq!(model.loadings.shape(), (5, 2));
+        assert!(model.specific_var.iter().all(|&v| v >= 0.0));
+
+        // Total variance is preserved on the diagonal
+        let full = model.to_full_matrix();
+        for i in 0..5 {
+            assert!((full[(i, i)] - sample[(i, i)]).abs() < 1e-12);
+        }
+
+        assert!(StatisticalFactorModel::fit(&returns, 0).is_err());
+        assert!(StatisticalFactorModel::fit(&returns, 6).is_err());
+    }
+
+    #[test]
+    fn test_optimal_n_factors_recovers_three_factors() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use rand_distr::{Distribution, Normal};
+
+        let (n_obs, n_assets, n_factors) = (1000, 12, 3);
+        let mut rng = StdRng::seed_from_u64(11);
+        let unit = Normal::new(0.0, 1.0).unwrap();
+
+        let loadings = DMatrix::from_fn(n_assets, n_factors, |_, _| unit.sample(&mut rng));
+        let factor_vols = [0.03, 0.02, 0.015];
+        let factors = DMatrix::from_fn(n_obs, n_factors, |_, k| {
+            factor_vols[k] * unit.sample(&mut rng)
+        });
+        let specific_vols: Vec<f64> = (0..n_assets).map(|i| 0.01 + 0.0005 * i as f64).collect();
+        let noise = DMatrix::from_fn(n_obs, n_assets, |_, j| {
+            specific_vols[j] * unit.sample(&mut rng)
+        });
+        let returns = &factors * loadings.transpose() + noise;
+
+        for criterion in [
+            ModelSelectionCriterion::AIC,
+            ModelSelectionCriterion::BIC,
+            ModelSelectionCriterion::MDL,
+        ] {
+            let k = FactorCovariance::optimal_n_factors(&returns, 8, criterion).unwrap();
+            assert_eq!(k, 3, "{:?}", criterion);
+        }
+
+        assert!(
+            FactorCovariance::optimal_n_factors(&returns, 0, ModelSelectionCriterion::AIC).is_err()
+        );
+    }
+
+    #[test]
+    fn test_factor_correlation_stress() {
+        let loadings = DMatrix::from_row_slice(3, 2, &[1.0, 0.2, 0.8, -0.3, 1.1, 0.5]);
+        let factor_cov = DMatrix::from_row_slice(2, 2, &[0.04, 0.006, 0.006, 0.01]);
+        let specific_var = DVector::from_vec(vec![0.01, 0.02, 0.015]);
+        let model = FactorCovariance::new(loadings, factor_cov, specific_var).unwrap();
+
+        let stressed = FactorCorrelationStress::apply(&model, 3.0, 1e-10).unwrap();
+        assert_eq!(stressed.loadings, model.loadings);
+        assert_eq!(stressed.specific_var, model.specific_var);
+
+        // Factor volatilities are kept, their correlation triples from 0.3
+        let f = &stressed.factor_cov;
+        assert!((f[(0, 0)] - 0.04).abs() < 1e-12);
+        assert!((f[(1, 1)] - 0.01).abs() < 1e-12);
+        assert!((f[(0, 1)] / (f[(0, 0)] * f[(1, 1)]).sqrt() - 0.9).abs() < 1e-9);
+    }
 }
@@ -4,14 +4,30 @@
 //!
 //! # Features
 //! - Sample covariance estimation
-//! - Shrinkage estimators (Ledoit-Wolf)
-//! - Factor model covariance decomposition
-//! - Eigenvalue decomposition and conditioning
+//! - Shrinkage estimators (Ledoit-Wolf with identity or constant-correlation target, OAS,
+//!   cross-validated shrinkage path)
+//! - Multivariate Student-t estimation for fat-tailed returns
+//! - Minimum Covariance Determinant robust estimation (FastMCD)
+//! - Graphical lasso sparse precision matrices for asset dependency networks
+//! - Factor model covariance decomposition (fundamental and PCA statistical factors)
+//! - Regime-blended factor covariance for factor timing
+//! - GJR-GARCH asymmetric volatility model and DCC-GARCH dynamic correlations
+//! - Non-synchronous trading adjustments (return filling, Scholes-Williams betas)
+//! - Eigenvalue decomposition, conditioning and Marchenko-Pastur noise cleaning
+//! - Nearest correlation matrix repair (Higham alternating projections)
+//! - Correlation crisis stress tests for asset and factor covariances
+//! - Parallel portfolio evaluation across covariance stress scenarios
+//! - Asset clustering and block-diagonal covariance approximation
+//! - Covariance refresh timing from information ratio loss
 //! - Parallel computation support
 
+pub mod advisor;
+pub mod distribution;
 pub mod estimator;
 pub mod factor;
 pub mod matrix;
+pub mod stress;
+pub mod timing;
 
 use thiserror::Error;
 
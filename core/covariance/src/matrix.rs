@@ -2,10 +2,75 @@
 //!
 //! Provides efficient matrix operations optimized for covariance matrices.
 
+use std::ops::Deref;
+
 use nalgebra::{DMatrix, DVector, SymmetricEigen};
 
 use crate::{CovarianceError, Result};
 
+/// Tolerance for the symmetry check in [`CovarMatrix::try_new`]
+const SYMMETRY_TOL: f64 = 1e-10;
+
+/// Covariance matrix (n x n over assets, or k x k over factors)
+pub type CovMatrix = DMatrix<f64>;
+
+/// Factor loading matrix (n_assets x n_factors)
+pub type LoadingMatrix = DMatrix<f64>;
+
+/// Correlation matrix (n x n, unit diagonal)
+pub type CorrelMatrix = DMatrix<f64>;
+
+/// Covariance matrix validated to be square and symmetric
+#[derive(Debug, Clone, PartialEq)]
+pub struct CovarMatrix(DMatrix<f64>);
+
+impl CovarMatrix {
+    /// Wrap a matrix, checking squareness and symmetry
+    pub fn try_new(m: DMatrix<f64>) -> Result<Self> {
+        if m.nrows() != m.ncols() {
+            return Err(CovarianceError::DimensionMismatch {
+                expected: m.nrows(),
+                got: m.ncols(),
+            });
+        }
+        if !is_symmetric(&m, SYMMETRY_TOL) {
+            return Err(CovarianceError::InvalidInput(
+                "Covariance matrix is not symmetric".to_string(),
+            ));
+        }
+        Ok(Self(m))
+    }
+
+    /// Symmetrize a square matrix and wrap it
+    pub(crate) fn from_square(m: &DMatrix<f64>) -> Self {
+        debug_assert_eq!(m.nrows(), m.ncols());
+        Self(symmetrize(m))
+    }
+
+    /// Number of rows (and columns)
+    pub fn dim(&self) -> usize {
+        self.0.nrows()
+    }
+
+    /// Borrow the underlying matrix
+    pub fn as_matrix(&self) -> &DMatrix<f64> {
+        &self.0
+    }
+
+    /// Unwrap into the underlying matrix
+    pub fn into_inner(self) -> DMatrix<f64> {
+        self.0
+    }
+}
+
+impl Deref for CovarMatrix {
+    type Target = DMatrix<f64>;
+
+    fn deref(&self) -> &DMatrix<f64> {
+        &self.0
+    }
+}
+
 /// Check if a matrix is symmetric
 pub fn is_symmetric(matrix: &DMatrix<f64>, tol: f64) -> bool {
     if matrix.nrows() != matrix.ncols() {
@@ -155,10 +220,360 @@ pub fn matrix_sqrt(matrix: &DMatrix<f64>) -> Result<DMatrix<f64>> {
     Ok(v * d * v.transpose())
 }
 
+/// Support `(λ-, λ+)` of the Marchenko-Pastur distribution
+///
+/// The eigenvalues of the sample covariance of `n` independent
+/// observations of `p` uncorrelated variables with variance `sigma²`
+/// fall in `σ²(1 ± sqrt(q))²` as `n, p` grow with `q = p / n`.
+pub fn marchenko_pastur_bounds(q: f64, sigma: f64) -> (f64, f64) {
+    let variance = sigma * sigma;
+    let root = q.max(0.0).sqrt();
+    (
+        variance * (1.0 - root).powi(2),
+        variance * (1.0 + root).powi(2),
+    )
+}
+
+/// Random matrix theory cleaning of a sample covariance estimated from
+/// `n_obs` observations
+///
+/// Eigenvalues below the Marchenko-Pastur upper edge `λ+`, with `σ²` the
+/// mean eigenvalue and `q = p / n_obs`, are indistinguishable from noise
+/// and are replaced by their average; those above it are kept as signal.
+/// The trace and the eigenvectors are preserved.
+///
+/// # Panics
+///
+/// Panics if `n_obs` is zero.
+pub fn clean_eigenvalues_rmt(matrix: &DMatrix<f64>, n_obs: usize) -> DMatrix<f64> {
+    assert!(n_obs > 0, "n_obs must be positive");
+    let p = matrix.nrows();
+    if p == 0 {
+        return matrix.clone();
+    }
+
+    let eigen = SymmetricEigen::new(symmetrize(matrix));
+    let sigma = eigen.eigenvalues.mean().max(0.0).sqrt();
+    let (_, lambda_plus) = marchenko_pastur_bounds(p as f64 / n_obs as f64, sigma);
+
+    let bulk: Vec<f64> = eigen
+        .eigenvalues
+        .iter()
+        .copied()
+        .filter(|&ev| ev < lambda_plus)
+        .collect();
+    if bulk.is_empty() {
+        return symmetrize(matrix);
+    }
+    let bulk_mean = bulk.iter().sum::<f64>() / bulk.len() as f64;
+    let cleaned = eigen
+        .eigenvalues
+        .map(|ev| if ev < lambda_plus { bulk_mean } else { ev });
+
+    let v = &eigen.eigenvectors;
+    symmetrize(&(v * DMatrix::from_diagonal(&cleaned) * v.transpose()))
+}
+
+/// Check that a matrix is a correlation matrix up to `tol`
+///
+/// Square, symmetric, unit diagonal, off-diagonal entries in [-1, 1] and
+/// positive semi-definite.
+pub fn is_valid_correlation_matrix(matrix: &DMatrix<f64>, tol: f64) -> bool {
+    let n = matrix.nrows();
+    if matrix.ncols() != n || !is_symmetric(matrix, tol) {
+        return false;
+    }
+    let unit_diagonal = (0..n).all(|i| (matrix[(i, i)] - 1.0).abs() <= tol);
+    let bounded = matrix.iter().all(|x| x.abs() <= 1.0 + tol);
+    unit_diagonal && bounded && is_positive_semi_definite(matrix, tol)
+}
+
+/// Nearest correlation matrix in Frobenius norm (Higham, 2002)
+///
+/// Alternates projections onto the positive semi-definite matrices
+/// (clipping negative eigenvalues) and the unit-diagonal matrices, with
+/// Dykstra's correction on the PSD step so the iterates converge to the
+/// nearest point of the intersection rather than just any point in it.
+/// Stops once successive iterates differ by less than `tol` relative to
+/// their Frobenius norm, and fails if that takes more than `max_iter`
+/// iterations.
+pub fn nearest_correlation_matrix(
+    matrix: &DMatrix<f64>,
+    tol: f64,
+    max_iter: usize,
+) -> Result<DMatrix<f64>> {
+    let n = matrix.nrows();
+    if matrix.ncols() != n {
+        return Err(CovarianceError::DimensionMismatch {
+            expected: n,
+            got: matrix.ncols(),
+        });
+    }
+    if matrix.iter().any(|x| !x.is_finite()) {
+        return Err(CovarianceError::InvalidInput(
+            "Matrix has non-finite entries".to_string(),
+        ));
+    }
+
+    let mut y = symmetrize(matrix);
+    y.fill_diagonal(1.0);
+    let mut correction = DMatrix::zeros(n, n);
+    for _ in 0..max_iter {
+        let r = &y - &correction;
+        let x = make_positive_semi_definite(&r, 0.0);
+        correction = &x - &r;
+
+        let mut next = symmetrize(&x);
+        next.fill_diagonal(1.0);
+        let change = frobenius_norm(&(&next - &y)) / frobenius_norm(&next);
+        y = next;
+        if change < tol {
+            return Ok(y);
+        }
+    }
+
+    Err(CovarianceError::NumericalError(format!(
+        "Nearest correlation matrix did not converge in {} iterations",
+        max_iter
+    )))
+}
+
+/// Correlation crisis stress test
+///
+/// Scales all pairwise correlations while holding volatilities fixed, to
+/// model the correlation spike seen in market sell-offs.
+pub struct CorrelationCrisisStress;
+
+impl CorrelationCrisisStress {
+    /// Stress the correlations of `cov` by `correlation_multiplier`
+    ///
+    /// Off-diagonal correlations are multiplied by the multiplier and capped
+    /// to [-1, 1]. The stressed correlation matrix is repaired by clipping
+    /// its eigenvalues at `min_eigenvalue` and restoring the unit diagonal,
+    /// then rescaled by the original volatilities, so the result is PSD with
+    /// unchanged variances. Assets with zero variance stay uncorrelated.
+    pub fn apply(
+        cov: &DMatrix<f64>,
+        correlation_multiplier: f64,
+        min_eigenvalue: f64,
+    ) -> DMatrix<f64> {
+        let n = cov.nrows();
+        let vols: Vec<f64> = (0..n).map(|i| cov[(i, i)].max(0.0).sqrt()).collect();
+
+        let stressed = DMatrix::from_fn(n, n, |i, j| {
+            if i == j {
+                1.0
+            } else if vols[i] > 0.0 && vols[j] > 0.0 {
+                let rho = cov[(i, j)] / (vols[i] * vols[j]);
+                (rho * correlation_multiplier).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            }
+        });
+
+        let repaired = make_positive_semi_definite(&stressed, min_eigenvalue);
+        let scale: Vec<f64> = (0..n)
+            .map(|i| {
+                let d = repaired[(i, i)];
+                if d > 0.0 {
+                    vols[i] / d.sqrt()
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+
+        DMatrix::from_fn(n, n, |i, j| repaired[(i, j)] * scale[i] * scale[j])
+    }
+}
+
+/// Maximum Lloyd iterations for [`ClusteringMethod::KMeansCorrelation`]
+const MAX_KMEANS_ITERATIONS: usize = 100;
+
+/// Asset clustering algorithm for [`cluster_assets`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusteringMethod {
+    /// k-means on the rows of the correlation matrix, seeded by
+    /// farthest-point selection starting from the first asset
+    KMeansCorrelation,
+    /// Agglomerative clustering with Ward linkage on the correlation
+    /// distance `sqrt(2 (1 - ρ))`
+    WardHierarchical,
+}
+
+/// Group assets into `n_clusters` clusters of similar co-movement
+///
+/// `n_clusters` is clamped to `[1, n]`. Each cluster lists its asset
+/// indices in ascending order, and clusters are ordered by their first
+/// asset. Assets with zero variance are treated as uncorrelated.
+pub fn cluster_assets(
+    cov: &DMatrix<f64>,
+    n_clusters: usize,
+    method: ClusteringMethod,
+) -> Vec<Vec<usize>> {
+    let n = cov.nrows();
+    if n == 0 {
+        return Vec::new();
+    }
+    let k = n_clusters.clamp(1, n);
+
+    let vols: Vec<f64> = (0..n).map(|i| cov[(i, i)].max(0.0).sqrt()).collect();
+    let corr = DMatrix::from_fn(n, n, |i, j| {
+        if i == j {
+            1.0
+        } else if vols[i] > 0.0 && vols[j] > 0.0 {
+            (cov[(i, j)] / (vols[i] * vols[j])).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        }
+    });
+
+    let mut clusters = match method {
+        ClusteringMethod::KMeansCorrelation => kmeans_correlation(&corr, k),
+        ClusteringMethod::WardHierarchical => ward_hierarchical(&corr, k),
+    };
+
+    for cluster in &mut clusters {
+        cluster.sort_unstable();
+    }
+    clusters.retain(|c| !c.is_empty());
+    clusters.sort_by_key(|c| c[0]);
+    clusters
+}
+
+/// Zero the covariances between assets in different clusters
+///
+/// Each cluster keeps its diagonal block of `cov` unchanged; assets that
+/// appear in no cluster keep only their variance. Since every block is a
+/// principal submatrix, the result is PSD whenever `cov` is.
+pub fn block_diagonal_approximation(cov: &DMatrix<f64>, clusters: &[Vec<usize>]) -> DMatrix<f64> {
+    let n = cov.nrows();
+    let mut block = DMatrix::from_diagonal(&cov.diagonal());
+
+    for cluster in clusters {
+        for &i in cluster.iter().filter(|&&i| i < n) {
+            for &j in cluster.iter().filter(|&&j| j < n) {
+                block[(i, j)] = cov[(i, j)];
+            }
+        }
+    }
+
+    block
+}
+
+/// k-means over correlation rows with farthest-point seeding
+fn kmeans_correlation(corr: &DMatrix<f64>, k: usize) -> Vec<Vec<usize>> {
+    let n = corr.nrows();
+    let row = |i: usize| corr.row(i).transpose();
+
+    let mut centroids: Vec<DVector<f64>> = vec![row(0)];
+    while centroids.len() < k {
+        let farthest = (0..n)
+            .map(|i| {
+                let d = centroids
+                    .iter()
+                    .map(|c| (row(i) - c).norm_squared())
+                    .fold(f64::INFINITY, f64::min);
+                (i, d)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i);
+        centroids.push(row(farthest));
+    }
+
+    let mut assignment = vec![usize::MAX; n];
+    for _ in 0..MAX_KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (i, assigned) in assignment.iter_mut().enumerate() {
+            let nearest = centroids
+                .iter()
+                .enumerate()
+                .map(|(c, centroid)| (c, (row(i) - centroid).norm_squared()))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(c, _)| c);
+            if *assigned != nearest {
+                *assigned = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        // Empty clusters keep their previous centroid
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..n).filter(|&i| assignment[i] == c).collect();
+            if !members.is_empty() {
+                *centroid =
+                    members.iter().map(|&i| row(i)).sum::<DVector<f64>>() / members.len() as f64;
+            }
+        }
+    }
+
+    let mut clusters = vec![Vec::new(); k];
+    for (i, &c) in assignment.iter().enumerate() {
+        clusters[c].push(i);
+    }
+    clusters
+}
+
+/// Agglomerative Ward clustering via the Lance-Williams update
+fn ward_hierarchical(corr: &DMatrix<f64>, k: usize) -> Vec<Vec<usize>> {
+    let n = corr.nrows();
+
+    // Squared correlation distance between singleton clusters
+    let mut dist = DMatrix::from_fn(n, n, |i, j| 2.0 * (1.0 - corr[(i, j)]));
+    let mut clusters: Vec<Option<Vec<usize>>> = (0..n).map(|i| Some(vec![i])).collect();
+    let mut n_active = n;
+
+    while n_active > k {
+        let mut best = (0, 0, f64::INFINITY);
+        for a in 0..n {
+            if clusters[a].is_none() {
+                continue;
+            }
+            for b in (a + 1)..n {
+                if clusters[b].is_some() && dist[(a, b)] < best.2 {
+                    best = (a, b, dist[(a, b)]);
+                }
+            }
+        }
+        let (a, b, d_ab) = best;
+
+        let size_a = clusters[a].as_ref().map_or(0, Vec::len) as f64;
+        let size_b = clusters[b].as_ref().map_or(0, Vec::len) as f64;
+        for c in 0..n {
+            if c == a || c == b {
+                continue;
+            }
+            if let Some(members) = &clusters[c] {
+                let size_c = members.len() as f64;
+                let total = size_a + size_b + size_c;
+                let d = ((size_a + size_c) * dist[(a, c)] + (size_b + size_c) * dist[(b, c)]
+                    - size_c * d_ab)
+                    / total;
+                dist[(a, c)] = d;
+                dist[(c, a)] = d;
+            }
+        }
+
+        let merged = clusters[b].take().unwrap_or_default();
+        if let Some(target) = clusters[a].as_mut() {
+            target.extend(merged);
+        }
+        n_active -= 1;
+    }
+
+    clusters.into_iter().flatten().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use nalgebra::dmatrix;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, StandardNormal};
 
     #[test]
     fn test_is_symmetric() {
@@ -234,6 +649,77 @@ mod tests {
         assert!(condition_number(&reg) < condition_number(&cov));
     }
 
+    #[test]
+    fn test_clean_eigenvalues_rmt() {
+        let (lower, upper) = marchenko_pastur_bounds(0.25, 2.0);
+        assert!((lower - 1.0).abs() < 1e-12 && (upper - 9.0).abs() < 1e-12);
+
+        // Three factors over 50 assets with unit idiosyncratic variance,
+        // sampled 80 times (p/n = 0.625)
+        let (p, n_obs, k) = (50, 80, 3);
+        let mut rng = StdRng::seed_from_u64(42);
+        let loadings = DMatrix::<f64>::from_fn(p, k, |_, _| StandardNormal.sample(&mut rng));
+        let factors = DMatrix::<f64>::from_fn(n_obs, k, |_, _| StandardNormal.sample(&mut rng));
+        let noise = DMatrix::<f64>::from_fn(n_obs, p, |_, _| StandardNormal.sample(&mut rng));
+        let returns = factors * loadings.transpose() + noise;
+        let mean = returns.row_mean();
+        let centered = DMatrix::from_fn(n_obs, p, |t, j| returns[(t, j)] - mean[j]);
+        let sample = centered.transpose() * &centered / (n_obs - 1) as f64;
+
+        let cleaned = clean_eigenvalues_rmt(&sample, n_obs);
+        let distinct = |m: &DMatrix<f64>| {
+            let mut evs: Vec<f64> = SymmetricEigen::new(m.clone())
+                .eigenvalues
+                .iter()
+                .copied()
+                .collect();
+            evs.sort_by(f64::total_cmp);
+            evs.dedup_by(|a, b| (*a - *b).abs() < 1e-8 * b.abs().max(1.0));
+            evs.len()
+        };
+        assert_eq!(distinct(&sample), p);
+        // The factor eigenvalues survive and the noise collapses to one value
+        assert_eq!(distinct(&cleaned), k + 1);
+        assert!((trace(&cleaned) - trace(&sample)).abs() < 1e-9 * trace(&sample));
+        assert!(is_positive_semi_definite(&cleaned, 1e-10));
+    }
+
+    #[test]
+    fn test_nearest_correlation_matrix() {
+        // Higham's example: unit diagonal but indefinite
+        let a = dmatrix![
+            1.0, 1.0, 0.0;
+            1.0, 1.0, 1.0;
+            0.0, 1.0, 1.0
+        ];
+        assert!(!is_valid_correlation_matrix(&a, 1e-10));
+
+        let nearest = nearest_correlation_matrix(&a, 1e-10, 10_000).unwrap();
+        let expected = dmatrix![
+            1.0, 0.7607, 0.1573;
+            0.7607, 1.0, 0.7607;
+            0.1573, 0.7607, 1.0
+        ];
+        assert!((&nearest - &expected).abs().max() < 1e-4);
+        assert!(is_valid_correlation_matrix(&nearest, 1e-8));
+
+        // A valid correlation matrix is its own nearest one, and a matrix
+        // with a slightly perturbed diagonal is repaired
+        let valid = dmatrix![
+            1.0, 0.3;
+            0.3, 1.0
+        ];
+        assert!(is_valid_correlation_matrix(&valid, 1e-12));
+        let mut perturbed = valid.clone();
+        perturbed[(1, 1)] = 1.0 + 1e-9;
+        assert!(!is_valid_correlation_matrix(&perturbed, 1e-12));
+        let repaired = nearest_correlation_matrix(&perturbed, 1e-12, 100).unwrap();
+        assert!((&repaired - &valid).abs().max() < 1e-12);
+
+        assert!(nearest_correlation_matrix(&a, 1e-14, 1).is_err());
+        assert!(nearest_correlation_matrix(&DMatrix::zeros(2, 3), 1e-8, 100).is_err());
+    }
+
     #[test]
     fn test_vec_to_dmatrix() {
         let data = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
@@ -262,4 +748,113 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_covar_matrix_try_new() {
+        let cov = dmatrix![
+            0.04, 0.01;
+            0.01, 0.09
+        ];
+        let wrapped = CovarMatrix::try_new(cov.clone()).unwrap();
+        assert_eq!(wrapped.dim(), 2);
+        assert_eq!(wrapped.as_matrix(), &cov);
+
+        let asym = dmatrix![
+            0.04, 0.01;
+            0.02, 0.09
+        ];
+        assert!(CovarMatrix::try_new(asym).is_err());
+
+        let non_square = DMatrix::<f64>::zeros(2, 3);
+        assert!(matches!(
+            CovarMatrix::try_new(non_square),
+            Err(CovarianceError::DimensionMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_correlation_crisis_stress() {
+        let vols = [0.15, 0.20, 0.25, 0.30];
+        let corr = dmatrix![
+            1.0, 0.3, 0.2, 0.1;
+            0.3, 1.0, 0.4, 0.2;
+            0.2, 0.4, 1.0, 0.5;
+            0.1, 0.2, 0.5, 1.0
+        ];
+        let cov = DMatrix::from_fn(4, 4, |i, j| corr[(i, j)] * vols[i] * vols[j]);
+
+        let stressed = CorrelationCrisisStress::apply(&cov, 1.8, 1e-8);
+        assert!(is_positive_semi_definite(&stressed, 1e-10));
+
+        for i in 0..4 {
+            assert!((stressed[(i, i)] - cov[(i, i)]).abs() < 1e-12);
+            for j in 0..4 {
+                if i != j {
+                    let stressed_rho = stressed[(i, j)] / (vols[i] * vols[j]);
+                    assert!(stressed_rho >= corr[(i, j)]);
+                    assert!(stressed_rho <= 1.0);
+                }
+            }
+        }
+
+        // Doubling past perfect correlation still yields a valid covariance
+        let extreme = CorrelationCrisisStress::apply(&cov, 10.0, 1e-6);
+        assert!(is_positive_semi_definite(&extreme, 1e-10));
+    }
+
+    /// Two blocks of three assets, correlated within but not across blocks
+    fn two_block_covariance() -> DMatrix<f64> {
+        let vols = [0.1, 0.2, 0.15, 0.25, 0.3, 0.12];
+        let block = [0, 1, 0, 1, 0, 1];
+        DMatrix::from_fn(6, 6, |i, j| {
+            let rho = if i == j {
+                1.0
+            } else if block[i] == block[j] {
+                0.7
+            } else {
+                0.1
+            };
+            rho * vols[i] * vols[j]
+        })
+    }
+
+    #[test]
+    fn test_cluster_assets() {
+        let cov = two_block_covariance();
+        let expected = vec![vec![0, 2, 4], vec![1, 3, 5]];
+
+        for method in [
+            ClusteringMethod::KMeansCorrelation,
+            ClusteringMethod::WardHierarchical,
+        ] {
+            assert_eq!(cluster_assets(&cov, 2, method), expected);
+            assert_eq!(
+                cluster_assets(&cov, 1, method),
+                vec![(0..6).collect::<Vec<_>>()]
+            );
+            assert_eq!(cluster_assets(&cov, 10, method).len(), 6);
+        }
+    }
+
+    #[test]
+    fn test_block_diagonal_approximation() {
+        let cov = two_block_covariance();
+        let clusters = cluster_assets(&cov, 2, ClusteringMethod::WardHierarchical);
+        let block = block_diagonal_approximation(&cov, &clusters);
+
+        assert!(is_positive_semi_definite(&block, 1e-10));
+        assert_eq!(block.diagonal(), cov.diagonal());
+        for cluster in &clusters {
+            for &i in cluster {
+                for &j in cluster {
+                    assert_eq!(block[(i, j)], cov[(i, j)]);
+                }
+            }
+        }
+        assert_eq!(block[(0, 1)], 0.0);
+        assert_eq!(block[(5, 4)], 0.0);
+    }
 }
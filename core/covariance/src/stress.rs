@@ -0,0 +1,155 @@
+//! Covariance stress testing
+//!
+//! A stress pipeline applies a set of covariance scenarios to a base matrix
+//! once and then evaluates many portfolios against every stressed matrix.
+
+use nalgebra::{DMatrix, DVector};
+use rayon::prelude::*;
+
+use crate::matrix::CorrelationCrisisStress;
+
+/// Covariance stress scenario
+#[derive(Debug, Clone)]
+pub enum CovarianceStress {
+    /// Scale off-diagonal correlations, keeping variances unchanged
+    /// (see [`CorrelationCrisisStress`])
+    CorrelationCrisis {
+        correlation_multiplier: f64,
+        min_eigenvalue: f64,
+    },
+    /// Scale every volatility by `multiplier`, keeping correlations unchanged
+    VolatilityShock { multiplier: f64 },
+    /// Replace the covariance matrix outright
+    Custom(DMatrix<f64>),
+}
+
+impl CovarianceStress {
+    /// Stressed version of `cov`
+    pub fn apply(&self, cov: &DMatrix<f64>) -> DMatrix<f64> {
+        match self {
+            CovarianceStress::CorrelationCrisis {
+                correlation_multiplier,
+                min_eigenvalue,
+            } => CorrelationCrisisStress::apply(cov, *correlation_multiplier, *min_eigenvalue),
+            CovarianceStress::VolatilityShock { multiplier } => cov * (multiplier * multiplier),
+            CovarianceStress::Custom(stressed) => stressed.clone(),
+        }
+    }
+}
+
+/// Portfolio variances under a grid of covariance stress scenarios
+pub struct StressPipeline {
+    stressed: Vec<DMatrix<f64>>,
+}
+
+impl StressPipeline {
+    /// Build a pipeline, applying every scenario to `base_cov` up front
+    pub fn new(base_cov: DMatrix<f64>, stress_scenarios: Vec<CovarianceStress>) -> Self {
+        let stressed = stress_scenarios
+            .iter()
+            .map(|stress| stress.apply(&base_cov))
+            .collect();
+        Self { stressed }
+    }
+
+    /// Number of stress scenarios
+    pub fn n_scenarios(&self) -> usize {
+        self.stressed.len()
+    }
+
+    /// Stressed covariance matrices, in scenario order
+    pub fn stressed_covariances(&self) -> &[DMatrix<f64>] {
+        &self.stressed
+    }
+
+    /// Portfolio variances for every (portfolio, scenario) pair
+    ///
+    /// Returns an `n_portfolios × n_scenarios` grid, computed sequentially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a portfolio's length differs from the covariance dimension.
+    pub fn run(&self, portfolios: &[DVector<f64>]) -> Vec<Vec<f64>> {
+        portfolios
+            .iter()
+            .map(|weights| self.portfolio_variances(weights))
+            .collect()
+    }
+
+    /// Same grid as [`StressPipeline::run`], with portfolios evaluated in parallel
+    ///
+    /// # Panics
+    ///
+    /// Panics if a portfolio's length differs from the covariance dimension.
+    pub fn run_parallel(&self, portfolios: &[DVector<f64>]) -> Vec<Vec<f64>> {
+        portfolios
+            .par_iter()
+            .map(|weights| self.portfolio_variances(weights))
+            .collect()
+    }
+
+    /// Variance of one portfolio under each stressed covariance
+    fn portfolio_variances(&self, weights: &DVector<f64>) -> Vec<f64> {
+        self.stressed
+            .iter()
+            .map(|cov| weights.dot(&(cov * weights)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_cov(n: usize) -> DMatrix<f64> {
+        let vols: Vec<f64> = (0..n).map(|i| 0.1 + 0.02 * i as f64).collect();
+        DMatrix::from_fn(n, n, |i, j| {
+            let rho = if i == j { 1.0 } else { 0.3 };
+            rho * vols[i] * vols[j]
+        })
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let n = 8;
+        let cov = base_cov(n);
+        let scenarios = vec![
+            CovarianceStress::VolatilityShock { multiplier: 1.0 },
+            CovarianceStress::VolatilityShock { multiplier: 2.0 },
+            CovarianceStress::CorrelationCrisis {
+                correlation_multiplier: 2.5,
+                min_eigenvalue: 1e-8,
+            },
+            CovarianceStress::CorrelationCrisis {
+                correlation_multiplier: 0.0,
+                min_eigenvalue: 1e-8,
+            },
+            CovarianceStress::Custom(DMatrix::identity(n, n) * 0.05),
+        ];
+        let pipeline = StressPipeline::new(cov.clone(), scenarios);
+        assert_eq!(pipeline.n_scenarios(), 5);
+
+        let portfolios: Vec<DVector<f64>> = (0..10)
+            .map(|p| {
+                let raw = DVector::from_fn(n, |i, _| 1.0 + ((i * 7 + p * 3) % 5) as f64);
+                let total = raw.sum();
+                raw / total
+            })
+            .collect();
+
+        let parallel = pipeline.run_parallel(&portfolios);
+        assert_eq!(parallel, pipeline.run(&portfolios));
+        assert_eq!(parallel.len(), 10);
+        assert!(parallel.iter().all(|row| row.len() == 5));
+
+        for (weights, row) in portfolios.iter().zip(&parallel) {
+            let base = weights.dot(&(&cov * weights));
+            assert!((row[0] - base).abs() < 1e-15);
+            assert!((row[1] - 4.0 * base).abs() < 1e-12);
+            // Higher correlations raise a long-only portfolio's variance
+            assert!(row[2] > base);
+            assert!(row[3] < base);
+            assert!((row[4] - 0.05 * weights.norm_squared()).abs() < 1e-15);
+        }
+    }
+}
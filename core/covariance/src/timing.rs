@@ -0,0 +1,238 @@
+//! Regime-dependent factor covariance
+//!
+//! Factor covariances shift through the business cycle. The timing model
+//! keeps one factor covariance per economic regime and blends them
+//! according to where an indicator (e.g., a leading economic index) sits
+//! relative to the regime boundaries.
+
+use nalgebra::DMatrix;
+
+use crate::{CovarianceError, Result};
+
+/// Logistic transition width as a fraction of the indicator's standard deviation
+const TRANSITION_WIDTH: f64 = 0.25;
+
+/// Factor covariance blended by economic regime
+///
+/// Regimes are ordered by indicator level: regime `k` lies between
+/// `indicator_thresholds[k - 1]` and `indicator_thresholds[k]`. Crossing a
+/// threshold moves weight from one regime to the next along a logistic
+/// curve whose width scales with the dispersion of the historical
+/// `regime_indicators`.
+#[derive(Debug, Clone)]
+pub struct FactorTimingModel {
+    /// Factor covariance in each regime (n_factors x n_factors)
+    regimes: Vec<DMatrix<f64>>,
+    /// Historical indicator observations, used to size the transitions
+    regime_indicators: Vec<f64>,
+    /// Ascending indicator boundaries between consecutive regimes
+    indicator_thresholds: Vec<f64>,
+}
+
+impl FactorTimingModel {
+    /// Create a timing model
+    ///
+    /// Requires at least one regime, square covariances of equal size, and
+    /// one ascending threshold per pair of consecutive regimes.
+    pub fn new(
+        regimes: Vec<DMatrix<f64>>,
+        regime_indicators: Vec<f64>,
+        indicator_thresholds: Vec<f64>,
+    ) -> Result<Self> {
+        let Some(first) = regimes.first() else {
+            return Err(CovarianceError::InvalidInput(
+                "At least one regime is required".to_string(),
+            ));
+        };
+
+        let n_factors = first.nrows();
+        for cov in &regimes {
+            if cov.nrows() != n_factors || cov.ncols() != n_factors {
+                return Err(CovarianceError::DimensionMismatch {
+                    expected: n_factors,
+                    got: if cov.nrows() != n_factors {
+                        cov.nrows()
+                    } else {
+                        cov.ncols()
+                    },
+                });
+            }
+        }
+
+        if indicator_thresholds.len() != regimes.len() - 1 {
+            return Err(CovarianceError::DimensionMismatch {
+                expected: regimes.len() - 1,
+                got: indicator_thresholds.len(),
+            });
+        }
+
+        if indicator_thresholds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(CovarianceError::InvalidInput(
+                "Indicator thresholds must be strictly ascending".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            regimes,
+            regime_indicators,
+            indicator_thresholds,
+        })
+    }
+
+    /// Factor covariance in each regime
+    pub fn regimes(&self) -> &[DMatrix<f64>] {
+        &self.regimes
+    }
+
+    /// Historical indicator observations
+    pub fn regime_indicators(&self) -> &[f64] {
+        &self.regime_indicators
+    }
+
+    /// Ascending indicator boundaries between consecutive regimes
+    pub fn indicator_thresholds(&self) -> &[f64] {
+        &self.indicator_thresholds
+    }
+
+    /// Weight of each regime at the given indicator value
+    ///
+    /// With `p_k` the logistic probability of being above threshold `k`,
+    /// regime `k` receives `p_{k-1} - p_k` (taking `p_{-1} = 1` and
+    /// `p_K = 0`), so weights are non-negative and sum to one. Without
+    /// indicator dispersion the transitions become hard switches.
+    pub fn regime_weights(&self, current_indicator: f64) -> Vec<f64> {
+        let width = TRANSITION_WIDTH * self.indicator_std();
+
+        let above: Vec<f64> = self
+            .indicator_thresholds
+            .iter()
+            .map(|&threshold| logistic(current_indicator - threshold, width))
+            .collect();
+
+        (0..self.regimes.len())
+            .map(|k| {
+                let lower = if k == 0 { 1.0 } else { above[k - 1] };
+                let upper = above.get(k).copied().unwrap_or(0.0);
+                lower - upper
+            })
+            .collect()
+    }
+
+    /// Factor covariance blended by regime weights at `current_indicator`
+    pub fn blended_covariance(&self, current_indicator: f64) -> Result<DMatrix<f64>> {
+        let Some(first) = self.regimes.first() else {
+            return Err(CovarianceError::InvalidInput(
+                "At least one regime is required".to_string(),
+            ));
+        };
+        let n_factors = first.nrows();
+
+        Ok(self
+            .regimes
+            .iter()
+            .zip(self.regime_weights(current_indicator))
+            .fold(DMatrix::zeros(n_factors, n_factors), |acc, (cov, w)| {
+                acc + cov * w
+            }))
+    }
+
+    /// Sample standard deviation of the historical indicator
+    fn indicator_std(&self) -> f64 {
+        let n = self.regime_indicators.len();
+        if n < 2 {
+            return 0.0;
+        }
+
+        let mean = self.regime_indicators.iter().sum::<f64>() / n as f64;
+        let var = self
+            .regime_indicators
+            .iter()
+            .map(|x| (x - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1) as f64;
+        var.sqrt()
+    }
+}
+
+/// Logistic step of `distance` with the given width (a hard step if zero)
+fn logistic(distance: f64, width: f64) -> f64 {
+    if width > 0.0 {
+        1.0 / (1.0 + (-distance / width).exp())
+    } else if distance >= 0.0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    fn model() -> FactorTimingModel {
+        let recession = dmatrix![
+            0.09, 0.04;
+            0.04, 0.06
+        ];
+        let normal = dmatrix![
+            0.04, 0.01;
+            0.01, 0.03
+        ];
+        let expansion = dmatrix![
+            0.03, -0.005;
+            -0.005, 0.02
+        ];
+        // Indicator history spread over roughly [-2, 2]
+        let history: Vec<f64> = (0..41).map(|i| -2.0 + 0.1 * i as f64).collect();
+
+        FactorTimingModel::new(vec![recession, normal, expansion], history, vec![-0.5, 0.5])
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extremes_match_regimes() {
+        let model = model();
+
+        let low = model.blended_covariance(-20.0).unwrap();
+        let high = model.blended_covariance(20.0).unwrap();
+        assert!((low - &model.regimes()[0]).abs().max() < 1e-12);
+        assert!((high - &model.regimes()[2]).abs().max() < 1e-12);
+
+        // Midway between the thresholds the normal regime dominates
+        let weights = model.regime_weights(0.0);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(weights[1] > weights[0] && weights[1] > weights[2]);
+    }
+
+    #[test]
+    fn test_blends_smoothly() {
+        let model = model();
+
+        // At a threshold the regime below it keeps half its weight
+        let weights = model.regime_weights(-0.5);
+        assert!((weights[0] - 0.5).abs() < 1e-12);
+        assert!(weights[1] > 0.4);
+
+        // Small indicator moves produce small covariance moves
+        let mut prev = model.blended_covariance(-3.0).unwrap();
+        let mut x = -3.0;
+        while x < 3.0 {
+            x += 0.01;
+            let next = model.blended_covariance(x).unwrap();
+            assert!((&next - &prev).abs().max() < 1e-3);
+            assert!(model.regime_weights(x).iter().all(|&w| w >= 0.0));
+            prev = next;
+        }
+
+        // Invalid thresholds are rejected
+        assert!(FactorTimingModel::new(model.regimes().to_vec(), vec![], vec![0.5, -0.5]).is_err());
+        assert!(FactorTimingModel::new(vec![], vec![], vec![]).is_err());
+        let empty = FactorTimingModel {
+            regimes: vec![],
+            regime_indicators: vec![],
+            indicator_thresholds: vec![],
+        };
+        assert!(empty.blended_covariance(0.0).is_err());
+    }
+}
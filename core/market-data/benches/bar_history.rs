@@ -0,0 +1,68 @@
+//! Bar history persistence: bincode records vs JSON
+//!
+//! Saves and loads 10,000 one-minute bars with `BarHistory` and with a
+//! `serde_json` array of the same bars.
+
+use std::path::PathBuf;
+
+use chrono::{Duration, TimeZone, Utc};
+use criterion::{criterion_group, criterion_main, Criterion};
+use market_data::history::BarHistory;
+use market_data::ohlcv::{Bar, BarPeriod, BarType};
+
+const N_BARS: usize = 10_000;
+
+fn make_bars(n: usize) -> Vec<Bar> {
+    let base = Utc.with_ymd_and_hms(2024, 1, 15, 9, 30, 0).unwrap();
+    (0..n)
+        .map(|i| {
+            let close = 10.0 + (i % 100) as f64 * 0.01;
+            Bar {
+                symbol: "000001.SZ".to_string(),
+                timestamp: base + Duration::minutes(i as i64),
+                bar_type: BarType::Time(BarPeriod::Minute1),
+                open: close - 0.02,
+                high: close + 0.03,
+                low: close - 0.04,
+                close,
+                volume: 1000.0 + i as f64,
+                turnover: (1000.0 + i as f64) * close,
+                tick_count: 20,
+                vwap: close - 0.01,
+            }
+        })
+        .collect()
+}
+
+fn bench_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("bench_bar_history_{}_{}", name, std::process::id()))
+}
+
+fn bench_bar_history(c: &mut Criterion) {
+    let bars = make_bars(N_BARS);
+    let bin_path = bench_path("bin");
+    let json_path = bench_path("json");
+
+    c.bench_function("bincode_save_10000", |b| {
+        b.iter(|| BarHistory::save(&bars, &bin_path).unwrap())
+    });
+    c.bench_function("bincode_load_10000", |b| {
+        b.iter(|| BarHistory::load(&bin_path).unwrap())
+    });
+
+    c.bench_function("json_save_10000", |b| {
+        b.iter(|| std::fs::write(&json_path, serde_json::to_vec(&bars).unwrap()).unwrap())
+    });
+    c.bench_function("json_load_10000", |b| {
+        b.iter(|| {
+            let data = std::fs::read(&json_path).unwrap();
+            serde_json::from_slice::<Vec<Bar>>(&data).unwrap()
+        })
+    });
+
+    let _ = std::fs::remove_file(&bin_path);
+    let _ = std::fs::remove_file(&json_path);
+}
+
+criterion_group!(benches, bench_bar_history);
+criterion_main!(benches);
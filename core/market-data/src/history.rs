@@ -0,0 +1,444 @@
+//! Bar history persistence
+//!
+//! Stores completed bars in a compact binary file. The file starts with the
+//! magic bytes `BARH` and a little-endian `u32` format version. Each bar is
+//! then a record of a little-endian `u32` byte length followed by the
+//! bincode-encoded bar, so bars can be appended without rewriting the file,
+//! a record cut short by a crash can be detected and dropped, and a
+//! damaged record can be skipped without losing the ones after it.
+//!
+//! Version 2 records derive the bar period from the bar type. Version 1
+//! files, which had no header and stored the period separately, are
+//! rejected.
+
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ohlcv::{Bar, BarType};
+use crate::{MarketDataError, Result};
+
+/// Size of the record length prefix in bytes
+const LENGTH_PREFIX: usize = 4;
+
+/// Magic bytes opening a bar history file
+const MAGIC: [u8; 4] = *b"BARH";
+
+/// On-disk format version written by this build
+const FORMAT_VERSION: u32 = 2;
+
+/// Size of the file header: magic bytes and format version
+const HEADER_LEN: usize = MAGIC.len() + 4;
+
+/// On-disk bar layout
+///
+/// Timestamps are stored as integer seconds and nanoseconds rather than
+/// through `Bar`'s serde impl, which writes RFC 3339 strings.
+#[derive(Serialize, Deserialize)]
+struct BarRecord<'a> {
+    #[serde(borrow)]
+    symbol: Cow<'a, str>,
+    timestamp_secs: i64,
+    timestamp_nanos: u32,
+    bar_type: BarType,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    turnover: f64,
+    tick_count: u64,
+    vwap: f64,
+}
+
+impl<'a> From<&'a Bar> for BarRecord<'a> {
+    fn from(bar: &'a Bar) -> Self {
+        Self {
+            symbol: Cow::Borrowed(&bar.symbol),
+            timestamp_secs: bar.timestamp.timestamp(),
+            timestamp_nanos: bar.timestamp.timestamp_subsec_nanos(),
+            bar_type: bar.bar_type,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+            turnover: bar.turnover,
+            tick_count: bar.tick_count,
+            vwap: bar.vwap,
+        }
+    }
+}
+
+impl BarRecord<'_> {
+    fn into_bar(self) -> Option<Bar> {
+        Some(Bar {
+            symbol: self.symbol.into_owned(),
+            timestamp: Utc
+                .timestamp_opt(self.timestamp_secs, self.timestamp_nanos)
+                .single()?,
+            bar_type: self.bar_type,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume,
+            turnover: self.turnover,
+            tick_count: self.tick_count,
+            vwap: self.vwap,
+        })
+    }
+}
+
+/// Binary bar history file
+pub struct BarHistory;
+
+impl BarHistory {
+    /// Write `bars` to `path`, replacing any existing file
+    pub fn save(bars: &[Bar], path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        Self::write_header(&mut writer)?;
+        let mut buffer = Vec::new();
+        for bar in bars {
+            Self::write_record(&mut writer, &mut buffer, bar)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Append a single bar to `path`, creating the file if needed
+    ///
+    /// Fails without writing if an existing file has another format version.
+    pub fn save_incremental(bar: &Bar, path: &Path) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let is_new = file.metadata()?.len() == 0;
+        if !is_new {
+            let mut header = [0u8; HEADER_LEN];
+            file.read_exact(&mut header).map_err(|_| {
+                MarketDataError::SerializationError("incomplete file header".to_string())
+            })?;
+            Self::check_header(&header)?;
+        }
+
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            Self::write_header(&mut writer)?;
+        }
+        Self::write_record(&mut writer, &mut Vec::new(), bar)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read all bars from `path`
+    ///
+    /// Fails with a serialization error if the header is missing or has
+    /// another format version, a record cannot be decoded, or the file
+    /// ends in a truncated record; see [`BarHistory::repair`].
+    pub fn load(path: &Path) -> Result<Vec<Bar>> {
+        let mut data = Vec::new();
+        BufReader::new(File::open(path)?).read_to_end(&mut data)?;
+        let records = Self::check_header(&data)?;
+
+        let (ranges, valid_len) = Self::split_records(records);
+        let mut bars = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            let start = HEADER_LEN + range.start;
+            bars.push(Self::decode_record(&records[range]).ok_or_else(|| {
+                MarketDataError::SerializationError(format!("undecodable record at byte {}", start))
+            })?);
+        }
+        if valid_len != records.len() {
+            return Err(MarketDataError::SerializationError(format!(
+                "truncated record at byte {} of {}",
+                HEADER_LEN + valid_len,
+                data.len()
+            )));
+        }
+        Ok(bars)
+    }
+
+    /// Drop undecodable records and a truncated trailing record from `path`
+    ///
+    /// A complete record that fails to decode is skipped and the records
+    /// after it are kept; a partial record at the end of the file is cut
+    /// off. A damaged length prefix cannot be skipped past, so the rest of
+    /// the file from there counts as one truncated record. Returns the
+    /// number of records removed (zero if the file was intact).
+    pub fn repair(path: &Path) -> Result<usize> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        let records = Self::check_header(&data)?;
+
+        let (ranges, valid_len) = Self::split_records(records);
+        let kept: Vec<Range<usize>> = ranges
+            .iter()
+            .filter(|range| Self::decode_record(&records[range.start..range.end]).is_some())
+            .cloned()
+            .collect();
+        let skipped = ranges.len() - kept.len();
+        let truncated = usize::from(valid_len != records.len());
+        if skipped == 0 {
+            if truncated == 1 {
+                OpenOptions::new()
+                    .write(true)
+                    .open(path)?
+                    .set_len((HEADER_LEN + valid_len) as u64)?;
+            }
+            return Ok(truncated);
+        }
+
+        // Rewrite beside the original and swap it in, so a crash leaves
+        // one of the two intact
+        let repaired_path = path.with_extension("repair");
+        let mut writer = BufWriter::new(File::create(&repaired_path)?);
+        Self::write_header(&mut writer)?;
+        for range in kept {
+            writer.write_all(&records[range])?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&repaired_path, path)?;
+        Ok(skipped + truncated)
+    }
+
+    /// Write the magic bytes and current format version
+    fn write_header<W: Write>(writer: &mut W) -> Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Check the file header, returning the records that follow it
+    fn check_header(data: &[u8]) -> Result<&[u8]> {
+        if data.get(..MAGIC.len()) != Some(&MAGIC[..]) {
+            return Err(MarketDataError::SerializationError(
+                "not a bar history file (or a version 1 file without a header)".to_string(),
+            ));
+        }
+        let version = data
+            .get(MAGIC.len()..HEADER_LEN)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or_else(|| {
+                MarketDataError::SerializationError("incomplete file header".to_string())
+            })?;
+        if version != FORMAT_VERSION {
+            return Err(MarketDataError::SerializationError(format!(
+                "unsupported bar history format version {} (expected {})",
+                version, FORMAT_VERSION
+            )));
+        }
+        Ok(&data[HEADER_LEN..])
+    }
+
+    /// Encode `bar` into `buffer` and write it as a length-prefixed record
+    fn write_record<W: Write>(writer: &mut W, buffer: &mut Vec<u8>, bar: &Bar) -> Result<()> {
+        buffer.clear();
+        bincode::serialize_into(&mut *buffer, &BarRecord::from(bar))
+            .map_err(|e| MarketDataError::SerializationError(e.to_string()))?;
+        let len = u32::try_from(buffer.len()).map_err(|_| {
+            MarketDataError::SerializationError(format!(
+                "record of {} bytes exceeds the u32 length prefix",
+                buffer.len()
+            ))
+        })?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(buffer)?;
+        Ok(())
+    }
+
+    /// Payload length of the record at the start of `data`, if its prefix is complete
+    fn record_len(data: &[u8]) -> Option<usize> {
+        let prefix: [u8; LENGTH_PREFIX] = data.get(..LENGTH_PREFIX)?.try_into().ok()?;
+        Some(u32::from_le_bytes(prefix) as usize)
+    }
+
+    /// Byte ranges of the complete records, length prefix included, and
+    /// the offset where the last of them ends
+    fn split_records(data: &[u8]) -> (Vec<Range<usize>>, usize) {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+
+        while let Some(len) = Self::record_len(&data[offset..]) {
+            let end = offset + LENGTH_PREFIX + len;
+            if end > data.len() {
+                break;
+            }
+            ranges.push(offset..end);
+            offset = end;
+        }
+
+        (ranges, offset)
+    }
+
+    /// Decode one length-prefixed record
+    fn decode_record(record: &[u8]) -> Option<Bar> {
+        bincode::deserialize::<BarRecord>(&record[LENGTH_PREFIX..])
+            .ok()
+            .and_then(BarRecord::into_bar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ohlcv::BarPeriod;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bar_history_{}_{}.bin", name, std::process::id()))
+    }
+
+    fn make_bars(n: usize) -> Vec<Bar> {
+        let base = Utc.with_ymd_and_hms(2024, 1, 15, 9, 30, 0).unwrap();
+        (0..n)
+            .map(|i| {
+                let close = 10.0 + (i % 100) as f64 * 0.01;
+                Bar {
+                    symbol: "000001.SZ".to_string(),
+                    timestamp: base + Duration::minutes(i as i64),
+                    bar_type: BarType::Time(BarPeriod::Minute1),
+                    open: close - 0.02,
+                    high: close + 0.03,
+                    low: close - 0.04,
+                    close,
+                    volume: 1000.0 + i as f64,
+                    turnover: (1000.0 + i as f64) * close,
+                    tick_count: 20 + (i % 7) as u64,
+                    vwap: close - 0.01,
+                }
+            })
+            .collect()
+    }
+
+    fn assert_bars_eq(a: &[Bar], b: &[Bar]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert_eq!(x.symbol, y.symbol);
+            assert_eq!(x.timestamp, y.timestamp);
+            assert_eq!(x.bar_type, y.bar_type);
+            assert_eq!(x.open, y.open);
+            assert_eq!(x.high, y.high);
+            assert_eq!(x.low, y.low);
+            assert_eq!(x.close, y.close);
+            assert_eq!(x.volume, y.volume);
+            assert_eq!(x.turnover, y.turnover);
+            assert_eq!(x.tick_count, y.tick_count);
+            assert_eq!(x.vwap, y.vwap);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = temp_path("roundtrip");
+        let bars = make_bars(10_000);
+
+        BarHistory::save(&bars, &path).unwrap();
+        let loaded = BarHistory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_bars_eq(&loaded, &bars);
+    }
+
+    #[test]
+    fn test_incremental_and_repair() {
+        let path = temp_path("incremental");
+        let bars = make_bars(5);
+
+        BarHistory::save(&bars[..3], &path).unwrap();
+        for bar in &bars[3..] {
+            BarHistory::save_incremental(bar, &path).unwrap();
+        }
+        assert_bars_eq(&BarHistory::load(&path).unwrap(), &bars);
+        assert_eq!(BarHistory::repair(&path).unwrap(), 0);
+
+        // Simulate a crash halfway through writing the last record
+        let len = std::fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+        assert!(matches!(
+            BarHistory::load(&path),
+            Err(MarketDataError::SerializationError(_))
+        ));
+
+        assert_eq!(BarHistory::repair(&path).unwrap(), 1);
+        assert_bars_eq(&BarHistory::load(&path).unwrap(), &bars[..4]);
+
+        // Appending after repair continues the history
+        BarHistory::save_incremental(&bars[4], &path).unwrap();
+        let loaded = BarHistory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_bars_eq(&loaded, &bars);
+    }
+
+    #[test]
+    fn test_repair_skips_damaged_record() {
+        let path = temp_path("damaged");
+        let bars = make_bars(5);
+        BarHistory::save(&bars, &path).unwrap();
+
+        // Make the second record's symbol invalid UTF-8, then cut the last
+        // record short
+        let mut data = std::fs::read(&path).unwrap();
+        let record_len = (data.len() - HEADER_LEN) / bars.len();
+        data[HEADER_LEN + record_len + LENGTH_PREFIX + 8] = 0xFF;
+        data.truncate(data.len() - 10);
+        std::fs::write(&path, &data).unwrap();
+        assert!(BarHistory::load(&path).is_err());
+
+        assert_eq!(BarHistory::repair(&path).unwrap(), 2);
+        let loaded = BarHistory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_bars_eq(
+            &loaded,
+            &[bars[0].clone(), bars[2].clone(), bars[3].clone()],
+        );
+    }
+
+    #[test]
+    fn test_format_version() {
+        let path = temp_path("version");
+        let bars = make_bars(2);
+
+        BarHistory::save(&bars, &path).unwrap();
+        let mut data = std::fs::read(&path).unwrap();
+        assert_eq!(&data[..4], b"BARH");
+        assert_eq!(data[4..8], 2u32.to_le_bytes());
+
+        // A file from another version is neither read nor appended to
+        data[4..8].copy_from_slice(&1u32.to_le_bytes());
+        std::fs::write(&path, &data).unwrap();
+        assert!(matches!(
+            BarHistory::load(&path),
+            Err(MarketDataError::SerializationError(_))
+        ));
+        assert!(BarHistory::save_incremental(&bars[0], &path).is_err());
+        assert!(BarHistory::repair(&path).is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), data);
+
+        // Headerless version 1 files start with a record length
+        std::fs::write(&path, &data[8..]).unwrap();
+        assert!(BarHistory::load(&path).is_err());
+
+        // Appending to a missing file writes the header first
+        std::fs::remove_file(&path).unwrap();
+        BarHistory::save_incremental(&bars[0], &path).unwrap();
+        let loaded = BarHistory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_bars_eq(&loaded, &bars[..1]);
+    }
+}
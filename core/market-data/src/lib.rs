@@ -4,13 +4,29 @@
 //! 
 //! # Features
 //! - Real-time tick processing with sub-millisecond latency
-//! - OHLCV bar aggregation (1m, 5m, 15m, 30m, 60m, daily)
-//! - Snapshot management for market state
+//! - Tick validation against exchange price increments and z-score outlier filtering
+//! - Realized variance: two-scales, Parkinson and Garman-Klass estimators
+//! - Aligned multi-symbol return matrices for covariance estimation
+//! - OHLCV bar aggregation (1m, 5m, 15m, 30m, 60m, daily, weekly, monthly, quarterly,
+//!   custom-length, tick-count, volume and dollar bars)
+//! - Renko bricks with fixed or ATR-based brick sizes
+//! - Compact binary bar history persistence
+//! - Intraday volume profiles for VWAP slicing
+//! - Order book reconstruction from add/cancel/modify/execute events
+//! - Snapshot management for market state, with real-time top-N rankings and
+//!   top-N book depth behind the `level2` feature
+//! - Candlestick pattern recognition
+//! - Synthetic correlated daily bars from geometric Brownian motion
 //! - Symbol subscription management
 
 pub mod tick;
 pub mod ohlcv;
 pub mod snapshot;
+pub mod patterns;
+pub mod history;
+pub mod volume_profile;
+pub mod orderbook;
+pub mod simulator;
 
 use thiserror::Error;
 
@@ -28,6 +44,9 @@ pub enum MarketDataError {
     #[error("Invalid timestamp: {0}")]
     InvalidTimestamp(i64),
 
+    #[error("Invalid bar period: {0}")]
+    InvalidBarPeriod(String),
+
     #[error("Symbol not subscribed: {0}")]
     NotSubscribed(String),
 
@@ -36,6 +55,24 @@ pub enum MarketDataError {
 
     #[error("Snapshot error: {0}")]
     SnapshotError(String),
+
+    #[error("Order book error: {0}")]
+    OrderBookError(String),
+
+    #[error("Simulation error: {0}")]
+    SimulationError(String),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+
+    #[error("Insufficient observations: need at least {needed}, got {got}")]
+    InsufficientObservations { needed: usize, got: usize },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
 }
 
 pub type Result<T> = std::result::Result<T, MarketDataError>;
@@ -2,7 +2,10 @@
 //!
 //! Aggregates tick data into OHLCV (Open, High, Low, Close, Volume) bars.
 
-use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::tick::Tick;
@@ -23,10 +26,71 @@ pub enum BarPeriod {
     Minute60,
     /// Daily bars
     Daily,
+    /// Weekly bars starting Monday 00:00 UTC
+    Week,
+    /// Calendar month bars starting on the 1st at 00:00 UTC
+    Month,
+    /// Calendar quarter bars starting on January, April, July or October 1st
+    Quarter,
+    /// Bars of a fixed number of seconds, aligned to the Unix epoch
+    Custom(CustomPeriod),
+}
+
+/// Longest custom bar period: 100 years of 365.25 days
+pub const MAX_CUSTOM_PERIOD_SECONDS: i64 = 36_525 * 86_400;
+
+/// Length of a custom bar period in seconds
+///
+/// Always between one second and [`MAX_CUSTOM_PERIOD_SECONDS`], so bar
+/// boundaries stay within the range chrono can represent. Deserialization
+/// applies the same check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "i64", into = "i64")]
+pub struct CustomPeriod(i64);
+
+impl CustomPeriod {
+    /// Create a period of `seconds`, failing outside the supported range
+    pub fn new(seconds: i64) -> Result<Self> {
+        if (1..=MAX_CUSTOM_PERIOD_SECONDS).contains(&seconds) {
+            Ok(Self(seconds))
+        } else {
+            Err(MarketDataError::InvalidBarPeriod(format!(
+                "{}s is outside 1s to {}s",
+                seconds, MAX_CUSTOM_PERIOD_SECONDS
+            )))
+        }
+    }
+
+    /// Length in seconds
+    pub fn seconds(self) -> i64 {
+        self.0
+    }
+}
+
+impl TryFrom<i64> for CustomPeriod {
+    type Error = MarketDataError;
+
+    fn try_from(seconds: i64) -> Result<Self> {
+        Self::new(seconds)
+    }
+}
+
+impl From<CustomPeriod> for i64 {
+    fn from(period: CustomPeriod) -> Self {
+        period.0
+    }
 }
 
 impl BarPeriod {
+    /// Custom period of `seconds`, see [`CustomPeriod::new`]
+    pub fn custom(seconds: i64) -> Result<Self> {
+        CustomPeriod::new(seconds).map(BarPeriod::Custom)
+    }
+
     /// Get duration in seconds
+    ///
+    /// Months and quarters are nominally 30 and 90 days; their bars follow
+    /// the calendar.
     pub fn seconds(&self) -> i64 {
         match self {
             BarPeriod::Minute1 => 60,
@@ -35,6 +99,10 @@ impl BarPeriod {
             BarPeriod::Minute30 => 1800,
             BarPeriod::Minute60 => 3600,
             BarPeriod::Daily => 86400,
+            BarPeriod::Week => 604800,
+            BarPeriod::Month => 2592000,
+            BarPeriod::Quarter => 7776000,
+            BarPeriod::Custom(period) => period.seconds(),
         }
     }
 
@@ -44,6 +112,84 @@ impl BarPeriod {
     }
 }
 
+impl FromStr for BarPeriod {
+    type Err = MarketDataError;
+
+    /// Parse a period such as `"5m"`, `"1d"`, `"1W"`, `"1M"` or `"1Q"`
+    ///
+    /// The count is followed by a unit: `s`, `m` (minutes), `h`, `d`, `W`,
+    /// `M` (months) or `Q`. Periods matching a named variant parse to it
+    /// (`"60m"` and `"1h"` are both `Minute60`), other multiples of
+    /// seconds, minutes, hours and days become `Custom` if they are within
+    /// [`MAX_CUSTOM_PERIOD_SECONDS`]. Weeks, months and quarters follow the
+    /// calendar, so only a count of one is accepted.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || MarketDataError::InvalidBarPeriod(s.to_string());
+        let s = s.trim();
+        let unit_start = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (count, unit) = s.split_at(unit_start);
+        let count: i64 = count.parse().map_err(|_| invalid())?;
+        if count <= 0 {
+            return Err(invalid());
+        }
+
+        let unit_seconds = match unit {
+            "W" | "M" | "Q" if count != 1 => return Err(invalid()),
+            "W" => return Ok(BarPeriod::Week),
+            "M" => return Ok(BarPeriod::Month),
+            "Q" => return Ok(BarPeriod::Quarter),
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => return Err(invalid()),
+        };
+        let seconds = count.checked_mul(unit_seconds).ok_or_else(invalid)?;
+
+        Ok(match seconds {
+            60 => BarPeriod::Minute1,
+            300 => BarPeriod::Minute5,
+            900 => BarPeriod::Minute15,
+            1800 => BarPeriod::Minute30,
+            3600 => BarPeriod::Minute60,
+            86400 => BarPeriod::Daily,
+            _ => BarPeriod::custom(seconds).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// How an aggregator decides where one bar ends and the next begins
+///
+/// Time bars sample the clock, so they oversample quiet markets and
+/// undersample busy ones; activity-based bars sample trading instead and
+/// complete on the tick that reaches their threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BarType {
+    /// Fixed clock intervals
+    Time(BarPeriod),
+    /// Complete once cumulative volume reaches this threshold
+    Volume(f64),
+    /// Complete once cumulative traded value (price * volume) reaches this
+    /// threshold
+    Dollar(f64),
+    /// Complete after this many ticks
+    TickCount(u64),
+}
+
+impl BarType {
+    /// Check whether an activity-based bar has reached its threshold
+    ///
+    /// Always false for time bars, which complete on the clock.
+    pub fn is_satisfied(&self, bar: &Bar) -> bool {
+        match *self {
+            BarType::Time(_) => false,
+            BarType::Volume(threshold) => bar.volume >= threshold,
+            BarType::Dollar(threshold) => bar.turnover >= threshold,
+            BarType::TickCount(threshold) => bar.tick_count >= threshold,
+        }
+    }
+}
+
 /// OHLCV bar representing aggregated price/volume data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Bar {
@@ -51,8 +197,8 @@ pub struct Bar {
     pub symbol: String,
     /// Bar start timestamp
     pub timestamp: DateTime<Utc>,
-    /// Bar period
-    pub period: BarPeriod,
+    /// How the bar was formed
+    pub bar_type: BarType,
     /// Opening price
     pub open: f64,
     /// Highest price
@@ -74,12 +220,23 @@ pub struct Bar {
 impl Bar {
     /// Create a new bar from the first tick
     pub fn new(tick: &Tick, period: BarPeriod) -> Self {
-        let bar_start = Self::align_timestamp(tick.timestamp, period);
+        Self::with_type(tick, BarType::Time(period))
+    }
+
+    /// Create a new bar of any type from the first tick
+    ///
+    /// Time bars start at their period boundary, activity-based bars at the
+    /// first tick's timestamp.
+    pub fn with_type(tick: &Tick, bar_type: BarType) -> Self {
+        let timestamp = match bar_type {
+            BarType::Time(period) => Self::align_timestamp(tick.timestamp, period),
+            _ => tick.timestamp,
+        };
 
         Self {
             symbol: tick.symbol.clone(),
-            timestamp: bar_start,
-            period,
+            timestamp,
+            bar_type,
             open: tick.price,
             high: tick.price,
             low: tick.price,
@@ -91,21 +248,68 @@ impl Bar {
         }
     }
 
+    /// Period of a time bar, `None` for activity-based bars
+    pub fn period(&self) -> Option<BarPeriod> {
+        match self.bar_type {
+            BarType::Time(period) => Some(period),
+            _ => None,
+        }
+    }
+
     /// Align timestamp to bar boundary
+    ///
+    /// Weeks start on Monday, months on the 1st and quarters on the 1st of
+    /// January, April, July and October, all at 00:00 UTC. Other periods
+    /// are whole multiples of their length since the Unix epoch.
     pub fn align_timestamp(ts: DateTime<Utc>, period: BarPeriod) -> DateTime<Utc> {
-        let secs = ts.timestamp();
-        let period_secs = period.seconds();
-        let aligned_secs = (secs / period_secs) * period_secs;
-        DateTime::from_timestamp(aligned_secs, 0).unwrap_or(ts)
+        let date = ts.date_naive();
+        let start = match period {
+            BarPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            BarPeriod::Month => date.with_day(1).unwrap_or(date),
+            BarPeriod::Quarter => {
+                let first_month = (date.month0() / 3) * 3 + 1;
+                NaiveDate::from_ymd_opt(date.year(), first_month, 1).unwrap_or(date)
+            }
+            _ => {
+                let period_secs = period.seconds();
+                let aligned_secs = (ts.timestamp() / period_secs) * period_secs;
+                return DateTime::from_timestamp(aligned_secs, 0).unwrap_or(ts);
+            }
+        };
+        start.and_time(NaiveTime::MIN).and_utc()
+    }
+
+    /// End of the bar starting at `start`, the start of the next bar
+    fn period_end(start: DateTime<Utc>, period: BarPeriod) -> DateTime<Utc> {
+        let months = match period {
+            BarPeriod::Month => 1,
+            BarPeriod::Quarter => 3,
+            _ => {
+                return start
+                    .checked_add_signed(period.duration())
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC)
+            }
+        };
+        start
+            .checked_add_months(Months::new(months))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
     }
 
     /// Check if a tick belongs to this bar
+    ///
+    /// A time bar accepts ticks within its period, an activity-based bar
+    /// any tick until its threshold is reached. Both require the same
+    /// symbol.
     pub fn accepts(&self, tick: &Tick) -> bool {
         if tick.symbol != self.symbol {
             return false;
         }
-        let tick_bar_start = Self::align_timestamp(tick.timestamp, self.period);
-        tick_bar_start == self.timestamp
+        match self.bar_type {
+            BarType::Time(period) => {
+                Self::align_timestamp(tick.timestamp, period) == self.timestamp
+            }
+            _ => !self.bar_type.is_satisfied(self),
+        }
     }
 
     /// Update bar with a new tick
@@ -116,6 +320,12 @@ impl Bar {
             ));
         }
 
+        self.absorb(tick);
+        Ok(())
+    }
+
+    /// Add a tick's price and volume without checking that it belongs
+    fn absorb(&mut self, tick: &Tick) {
         self.high = self.high.max(tick.price);
         self.low = self.low.min(tick.price);
         self.close = tick.price;
@@ -127,13 +337,17 @@ impl Bar {
         if self.volume > 0.0 {
             self.vwap = self.turnover / self.volume;
         }
-
-        Ok(())
     }
 
-    /// Check if bar is complete (past its end time)
+    /// Check if bar is complete
+    ///
+    /// Time bars are complete past their end time, activity-based bars
+    /// once they reach their threshold.
     pub fn is_complete(&self, current_time: DateTime<Utc>) -> bool {
-        current_time >= self.timestamp + self.period.duration()
+        match self.bar_type {
+            BarType::Time(period) => current_time >= Self::period_end(self.timestamp, period),
+            _ => self.bar_type.is_satisfied(self),
+        }
     }
 
     /// Calculate bar range (high - low)
@@ -148,6 +362,18 @@ impl Bar {
         (self.close - self.open).abs()
     }
 
+    /// Calculate upper shadow (high - max(open, close))
+    #[inline]
+    pub fn upper_shadow(&self) -> f64 {
+        self.high - self.open.max(self.close)
+    }
+
+    /// Calculate lower shadow (min(open, close) - low)
+    #[inline]
+    pub fn lower_shadow(&self) -> f64 {
+        self.open.min(self.close) - self.low
+    }
+
     /// Check if bar is bullish (close > open)
     #[inline]
     pub fn is_bullish(&self) -> bool {
@@ -162,12 +388,70 @@ impl Bar {
         }
         (self.close - self.open) / self.open * 100.0
     }
+
+    /// Typical price `(high + low + close) / 3`
+    #[inline]
+    pub fn typical_price(&self) -> f64 {
+        (self.high + self.low + self.close) / 3.0
+    }
+
+    /// Median price `(high + low) / 2`
+    #[inline]
+    pub fn median_price(&self) -> f64 {
+        (self.high + self.low) / 2.0
+    }
+
+    /// Weighted close `(high + low + 2 * close) / 4`
+    #[inline]
+    pub fn weighted_close(&self) -> f64 {
+        (self.high + self.low + self.close * 2.0) / 4.0
+    }
+
+    /// True range `max(high - low, |high - prev_close|, |low - prev_close|)`
+    ///
+    /// The range extended to cover a gap from the previous bar's close.
+    #[inline]
+    pub fn true_range(&self, prev_close: f64) -> f64 {
+        self.range()
+            .max((self.high - prev_close).abs())
+            .max((self.low - prev_close).abs())
+    }
+
+    /// Wilder-smoothed average true range of consecutive bars
+    ///
+    /// The first bar has no previous close, so its true range is its
+    /// range. The average starts as the mean of the first `period` true
+    /// ranges (all of them for shorter slices) and each later bar updates
+    /// it as `ATR = ((period - 1) ATR + TR) / period`; 14 is the usual
+    /// period. Returns NaN for an empty slice or a zero period.
+    pub fn average_true_range(bars: &[Bar], period: usize) -> f64 {
+        let Some(first) = bars.first() else {
+            return f64::NAN;
+        };
+        if period == 0 {
+            return f64::NAN;
+        }
+        let true_ranges = std::iter::once(first.range()).chain(
+            bars.windows(2)
+                .map(|pair| pair[1].true_range(pair[0].close)),
+        );
+
+        let mut atr = 0.0;
+        for (i, tr) in true_ranges.enumerate() {
+            atr = if i < period {
+                (atr * i as f64 + tr) / (i + 1) as f64
+            } else {
+                (atr * (period - 1) as f64 + tr) / period as f64
+            };
+        }
+        atr
+    }
 }
 
 /// Bar aggregator that processes ticks into bars
 pub struct BarAggregator {
-    /// Bar period
-    period: BarPeriod,
+    /// How bars are formed
+    bar_type: BarType,
     /// Current incomplete bar
     current_bar: Option<Bar>,
     /// Completed bars
@@ -177,24 +461,43 @@ pub struct BarAggregator {
 }
 
 impl BarAggregator {
-    /// Create a new bar aggregator
+    /// Create a new time bar aggregator
     pub fn new(period: BarPeriod, max_bars: usize) -> Self {
+        Self::with_bar_type(BarType::Time(period), max_bars)
+    }
+
+    /// Create an aggregator forming bars of any type
+    ///
+    /// Activity-based bars are stamped with their first tick's time and
+    /// complete on the tick that reaches the threshold (volume and dollar
+    /// bars keep the whole of that tick). A symbol change also completes a
+    /// bar.
+    pub fn with_bar_type(bar_type: BarType, max_bars: usize) -> Self {
         Self {
-            period,
+            bar_type,
             current_bar: None,
             completed_bars: Vec::with_capacity(max_bars),
             max_bars,
         }
     }
 
+    /// Bar type formed by this aggregator
+    pub fn bar_type(&self) -> BarType {
+        self.bar_type
+    }
+
     /// Process a tick, potentially completing a bar
+    ///
+    /// When a symbol change completes the current bar and the new symbol's
+    /// first tick already reaches the threshold, the completed bar is
+    /// returned and the new one on the next call to `process` or `flush`.
     pub fn process(&mut self, tick: &Tick) -> Option<Bar> {
         let mut completed = None;
 
         match &mut self.current_bar {
             Some(bar) if bar.accepts(tick) => {
                 // Tick belongs to current bar
-                bar.update(tick).ok();
+                bar.absorb(tick);
             }
             Some(_) | None => {
                 // New bar needed - complete current if exists
@@ -202,10 +505,18 @@ impl BarAggregator {
                     completed = Some(bar.clone());
                     self.store_completed(bar);
                 }
-                self.current_bar = Some(Bar::new(tick, self.period));
+                self.current_bar = Some(Bar::with_type(tick, self.bar_type));
             }
         }
 
+        let reached = self
+            .current_bar
+            .as_ref()
+            .is_some_and(|bar| self.bar_type.is_satisfied(bar));
+        if reached && completed.is_none() {
+            completed = self.flush();
+        }
+
         completed
     }
 
@@ -244,6 +555,242 @@ impl BarAggregator {
     }
 }
 
+/// Downsample bars to a coarser period
+///
+/// Bars are grouped by symbol and by their start aligned to
+/// `target_period`, in order of each group's first bar. A group takes its
+/// open from the first bar, its close from the last, the extreme high and
+/// low, and the summed volume, turnover and tick count, with VWAP
+/// recomputed from the totals (the close if there is no volume).
+///
+/// Fails unless every input bar is a time bar lying entirely within one
+/// `target_period` bar, so weeks that straddle a month end cannot be
+/// resampled to months, and unless each symbol's bars are in strictly
+/// increasing time order.
+pub fn resample(bars: &[Bar], target_period: BarPeriod) -> Result<Vec<Bar>> {
+    let mut resampled: Vec<Bar> = Vec::new();
+    let mut index: HashMap<(&str, DateTime<Utc>), usize> = HashMap::new();
+    let mut last_start: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for bar in bars {
+        let BarType::Time(period) = bar.bar_type else {
+            return Err(MarketDataError::AggregationError(format!(
+                "cannot resample {:?} bars by time",
+                bar.bar_type
+            )));
+        };
+        let start = Bar::align_timestamp(bar.timestamp, target_period);
+        if Bar::period_end(bar.timestamp, period) > Bar::period_end(start, target_period) {
+            return Err(MarketDataError::AggregationError(format!(
+                "{:?} bar at {} does not fit in one {:?} bar",
+                period, bar.timestamp, target_period
+            )));
+        }
+        if let Some(previous) = last_start.insert(bar.symbol.as_str(), bar.timestamp) {
+            if bar.timestamp <= previous {
+                return Err(MarketDataError::AggregationError(format!(
+                    "{} bar at {} is not after the bar at {}",
+                    bar.symbol, bar.timestamp, previous
+                )));
+            }
+        }
+
+        match index.get(&(bar.symbol.as_str(), start)) {
+            Some(&group) => {
+                let target = &mut resampled[group];
+                target.high = target.high.max(bar.high);
+                target.low = target.low.min(bar.low);
+                target.close = bar.close;
+                target.volume += bar.volume;
+                target.turnover += bar.turnover;
+                target.tick_count += bar.tick_count;
+            }
+            None => {
+                index.insert((bar.symbol.as_str(), start), resampled.len());
+                resampled.push(Bar {
+                    timestamp: start,
+                    bar_type: BarType::Time(target_period),
+                    ..bar.clone()
+                });
+            }
+        }
+    }
+
+    for bar in &mut resampled {
+        bar.vwap = if bar.volume > 0.0 {
+            bar.turnover / bar.volume
+        } else {
+            bar.close
+        };
+    }
+    Ok(resampled)
+}
+
+/// Direction of a Renko brick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RenkoDirection {
+    /// Price rose by one brick
+    Up,
+    /// Price fell by one brick
+    Down,
+}
+
+/// Renko brick, recording a price move of exactly one brick size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenkoBar {
+    /// Symbol identifier
+    pub symbol: String,
+    /// Brick direction
+    pub direction: RenkoDirection,
+    /// Price level the brick starts from
+    pub open: f64,
+    /// Price level the brick ends at, one brick size from `open`
+    pub close: f64,
+    /// Timestamp of the tick that completed the brick
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Smallest brick size relative to the price accepted by
+/// [`RenkoAggregator::process`]
+const MIN_RELATIVE_BRICK_SIZE: f64 = 1e-9;
+
+/// Most bricks a single tick may lay
+const MAX_BRICKS_PER_TICK: usize = 10_000;
+
+/// Slack on the brick count so a move of exactly `k` bricks lays `k` bricks
+/// despite rounding in the division
+const BRICK_COUNT_TOL: f64 = 1e-9;
+
+/// Renko brick aggregator
+///
+/// Ignores time and small fluctuations: a brick is laid each time price
+/// moves a full `brick_size` beyond the last brick. Continuing in the same
+/// direction takes one brick size; reversing takes two, since the reversal
+/// brick starts from the far side of the last brick.
+#[derive(Debug, Clone)]
+pub struct RenkoAggregator {
+    brick_size: f64,
+    /// Close of the last brick, or the first price before any brick
+    current_level: Option<f64>,
+    direction: Option<RenkoDirection>,
+    symbol: Option<String>,
+}
+
+impl RenkoAggregator {
+    /// Create an aggregator with a fixed, positive `brick_size`
+    pub fn new(brick_size: f64) -> Result<Self> {
+        if !(brick_size > 0.0 && brick_size.is_finite()) {
+            return Err(MarketDataError::AggregationError(format!(
+                "brick size must be positive, got {}",
+                brick_size
+            )));
+        }
+        Ok(Self {
+            brick_size,
+            current_level: None,
+            direction: None,
+            symbol: None,
+        })
+    }
+
+    /// Create an aggregator whose brick size is the average true range
+    ///
+    /// The brick size is [`Bar::average_true_range`] of `bars` with the
+    /// given smoothing period, so `bars` must hold at least `period` bars
+    /// to seed the average.
+    pub fn from_atr(bars: &[Bar], period: usize) -> Result<Self> {
+        if period == 0 || bars.len() < period {
+            return Err(MarketDataError::InsufficientObservations {
+                needed: period.max(1),
+                got: bars.len(),
+            });
+        }
+        Self::new(Bar::average_true_range(bars, period))
+    }
+
+    /// Brick size
+    pub fn brick_size(&self) -> f64 {
+        self.brick_size
+    }
+
+    /// Close of the last brick, or the starting price before the first
+    /// brick; `None` before any tick
+    pub fn current_level(&self) -> Option<f64> {
+        self.current_level
+    }
+
+    /// Process a tick, returning the bricks it completes in order
+    ///
+    /// A gap of several brick sizes lays several bricks at once. The first
+    /// tick only sets the starting level, and a tick for another symbol
+    /// restarts the chart from its price. Fails without changing the chart
+    /// on a non-finite price, or when the brick size is below a billionth
+    /// of the price or the tick would lay more than 10,000 bricks.
+    pub fn process(&mut self, tick: &Tick) -> Result<Vec<RenkoBar>> {
+        if !tick.price.is_finite() {
+            return Err(MarketDataError::AggregationError(format!(
+                "Renko price must be finite, got {}",
+                tick.price
+            )));
+        }
+        let size = self.brick_size;
+        if size < MIN_RELATIVE_BRICK_SIZE * tick.price.abs() {
+            return Err(MarketDataError::AggregationError(format!(
+                "brick size {} is too small for price {}",
+                size, tick.price
+            )));
+        }
+
+        let level = match self.current_level {
+            Some(level) if self.symbol.as_deref() == Some(tick.symbol.as_str()) => level,
+            _ => {
+                self.symbol = Some(tick.symbol.clone());
+                self.current_level = Some(tick.price);
+                self.direction = None;
+                return Ok(Vec::new());
+            }
+        };
+
+        // After a brick the opposite direction starts from its far side
+        let up_open = match self.direction {
+            Some(RenkoDirection::Down) => level + size,
+            _ => level,
+        };
+        let down_open = match self.direction {
+            Some(RenkoDirection::Up) => level - size,
+            _ => level,
+        };
+        let up_bricks = ((tick.price - up_open) / size + BRICK_COUNT_TOL).floor();
+        let down_bricks = ((down_open - tick.price) / size + BRICK_COUNT_TOL).floor();
+        let (direction, open, step, count) = if up_bricks >= 1.0 {
+            (RenkoDirection::Up, up_open, size, up_bricks)
+        } else if down_bricks >= 1.0 {
+            (RenkoDirection::Down, down_open, -size, down_bricks)
+        } else {
+            return Ok(Vec::new());
+        };
+        if count > MAX_BRICKS_PER_TICK as f64 {
+            return Err(MarketDataError::AggregationError(format!(
+                "price {} is {} bricks of {} from {}",
+                tick.price, count, size, level
+            )));
+        }
+
+        let count = count as usize;
+        let bricks = (0..count)
+            .map(|i| RenkoBar {
+                symbol: tick.symbol.clone(),
+                direction,
+                open: open + step * i as f64,
+                close: open + step * (i + 1) as f64,
+                timestamp: tick.timestamp,
+            })
+            .collect();
+        self.current_level = Some(open + step * count as f64);
+        self.direction = Some(direction);
+        Ok(bricks)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -267,6 +814,90 @@ mod tests {
         assert_eq!(BarPeriod::Minute1.seconds(), 60);
         assert_eq!(BarPeriod::Minute5.seconds(), 300);
         assert_eq!(BarPeriod::Daily.seconds(), 86400);
+        assert_eq!(BarPeriod::Week.seconds(), 604800);
+        assert_eq!(BarPeriod::Month.seconds(), 2592000);
+        assert_eq!(BarPeriod::Quarter.seconds(), 7776000);
+        assert_eq!(BarPeriod::custom(7200).unwrap().seconds(), 7200);
+        for seconds in [0, -60, MAX_CUSTOM_PERIOD_SECONDS + 1, i64::MAX] {
+            assert!(matches!(
+                BarPeriod::custom(seconds),
+                Err(MarketDataError::InvalidBarPeriod(_))
+            ));
+        }
+        assert!(BarPeriod::custom(MAX_CUSTOM_PERIOD_SECONDS).is_ok());
+
+        // Deserialization cannot bypass the range check
+        let json = serde_json::to_string(&BarPeriod::custom(7200).unwrap()).unwrap();
+        assert_eq!(json, r#"{"Custom":7200}"#);
+        assert_eq!(
+            serde_json::from_str::<BarPeriod>(&json).unwrap(),
+            BarPeriod::custom(7200).unwrap()
+        );
+        assert!(serde_json::from_str::<BarPeriod>(r#"{"Custom":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_bar_period() {
+        for (s, period) in [
+            ("1m", BarPeriod::Minute1),
+            ("5m", BarPeriod::Minute5),
+            ("60m", BarPeriod::Minute60),
+            ("1h", BarPeriod::Minute60),
+            ("1d", BarPeriod::Daily),
+            ("1W", BarPeriod::Week),
+            ("1M", BarPeriod::Month),
+            ("1Q", BarPeriod::Quarter),
+            ("10s", BarPeriod::custom(10).unwrap()),
+            ("2h", BarPeriod::custom(7200).unwrap()),
+        ] {
+            assert_eq!(s.parse::<BarPeriod>().unwrap(), period);
+        }
+        for s in [
+            "",
+            "m",
+            "0m",
+            "-5m",
+            "5x",
+            "2M",
+            "1.5h",
+            "100000000000000d",
+            "36526d",
+        ] {
+            assert!(matches!(
+                BarPeriod::from_str(s),
+                Err(MarketDataError::InvalidBarPeriod(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_calendar_alignment() {
+        // Thursday 2024-08-15 14:30
+        let ts = Utc.with_ymd_and_hms(2024, 8, 15, 14, 30, 0).unwrap();
+        let aligned = |period| Bar::align_timestamp(ts, period);
+        assert_eq!(
+            aligned(BarPeriod::Week),
+            Utc.with_ymd_and_hms(2024, 8, 12, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            aligned(BarPeriod::Month),
+            Utc.with_ymd_and_hms(2024, 8, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            aligned(BarPeriod::Quarter),
+            Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            aligned(BarPeriod::custom(7200).unwrap()),
+            Utc.with_ymd_and_hms(2024, 8, 15, 14, 0, 0).unwrap()
+        );
+
+        // A monthly bar for February 2024 completes on March 1st
+        let tick = make_tick("TEST", 10.0, 100.0, ts.with_month(2).unwrap());
+        let bar = Bar::new(&tick, BarPeriod::Month);
+        let march = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        assert!(!bar.is_complete(march - Duration::seconds(1)));
+        assert!(bar.is_complete(march));
     }
 
     #[test]
@@ -328,4 +959,361 @@ mod tests {
         assert_eq!(bar.body(), 2.0);
         assert!((bar.return_pct() - 20.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_tick_count_bars() {
+        let mut aggregator = BarAggregator::with_bar_type(BarType::TickCount(5), 100);
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+
+        // Ticks span several minutes; only the count matters
+        let prices = [10.0, 11.0, 12.0, 13.0, 9.0];
+        let ticks: Vec<Tick> = prices
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                let ts = base_time + Duration::seconds(50 * i as i64);
+                make_tick("TEST", p, 100.0, ts)
+            })
+            .collect();
+
+        for tick in &ticks[..4] {
+            assert!(aggregator.process(tick).is_none());
+        }
+
+        let bar = aggregator.process(&ticks[4]).unwrap();
+        assert_eq!(bar.tick_count, 5);
+        assert_eq!(bar.timestamp, base_time);
+        assert_eq!(bar.open, 10.0);
+        assert_eq!(bar.high, 13.0);
+        assert_eq!(bar.low, 9.0);
+        assert_eq!(bar.close, 9.0);
+        assert!(aggregator.current().is_none());
+        assert_eq!(aggregator.bars().len(), 1);
+    }
+
+    #[test]
+    fn test_volume_bars() {
+        let mut aggregator = BarAggregator::with_bar_type(BarType::Volume(100.0), 100);
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+
+        let volumes = [40.0, 50.0, 30.0, 10.0];
+        let ticks: Vec<Tick> = volumes
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| make_tick("TEST", 10.0, v, base_time + Duration::seconds(i as i64)))
+            .collect();
+
+        assert!(aggregator.process(&ticks[0]).is_none());
+        assert!(aggregator.process(&ticks[1]).is_none());
+
+        // Cumulative volume crosses 100 on the third tick
+        let bar = aggregator.process(&ticks[2]).unwrap();
+        assert_eq!(bar.volume, 120.0);
+        assert_eq!(bar.tick_count, 3);
+
+        // Next tick starts a new bar
+        assert!(aggregator.process(&ticks[3]).is_none());
+        assert_eq!(aggregator.current().unwrap().volume, 10.0);
+
+        // A symbol change whose first tick fills a bar emits the old bar first
+        let other = make_tick("OTHER", 20.0, 150.0, base_time + Duration::seconds(4));
+        let bar = aggregator.process(&other).unwrap();
+        assert_eq!((bar.symbol.as_str(), bar.volume), ("TEST", 10.0));
+        let next = make_tick("OTHER", 20.0, 5.0, base_time + Duration::seconds(5));
+        let bar = aggregator.process(&next).unwrap();
+        assert_eq!((bar.symbol.as_str(), bar.volume), ("OTHER", 150.0));
+        assert_eq!(aggregator.bars().len(), 3);
+        assert_eq!(aggregator.current().unwrap().volume, 5.0);
+    }
+
+    #[test]
+    fn test_dollar_bars() {
+        let mut aggregator = BarAggregator::with_bar_type(BarType::Dollar(1000.0), 100);
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+
+        // Twelve ticks of 500 traded value each: six bars of two ticks
+        let mut completed = Vec::new();
+        for i in 0..12 {
+            let price = if i % 2 == 0 { 10.0 } else { 12.5 };
+            let volume = 500.0 / price;
+            let tick = make_tick("TEST", price, volume, base_time + Duration::seconds(i));
+            completed.extend(aggregator.process(&tick));
+        }
+
+        assert_eq!(completed.len(), 6);
+        assert!(aggregator.current().is_none());
+        for (k, bar) in completed.iter().enumerate() {
+            assert!((bar.turnover - 1000.0).abs() < 1e-9);
+            assert_eq!(bar.tick_count, 2);
+            assert_eq!(bar.bar_type, BarType::Dollar(1000.0));
+            assert_eq!(bar.timestamp, base_time + Duration::seconds(2 * k as i64));
+            assert!(bar.is_complete(bar.timestamp));
+        }
+    }
+
+    #[test]
+    fn test_renko_bricks() {
+        let mut renko = RenkoAggregator::new(1.0).unwrap();
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let mut feed = |price: f64, i: i64| {
+            renko
+                .process(&make_tick(
+                    "TEST",
+                    price,
+                    10.0,
+                    base_time + Duration::seconds(i),
+                ))
+                .unwrap()
+        };
+
+        assert!(feed(100.0, 0).is_empty());
+        assert!(feed(100.9, 1).is_empty());
+
+        // A gap up of 2.5 bricks lays two up bricks
+        let bricks = feed(102.5, 2);
+        assert_eq!(bricks.len(), 2);
+        assert!(bricks.iter().all(|b| b.direction == RenkoDirection::Up));
+        assert_eq!((bricks[0].open, bricks[0].close), (100.0, 101.0));
+        assert_eq!((bricks[1].open, bricks[1].close), (101.0, 102.0));
+
+        // Falling one brick below the last close is not yet a reversal
+        assert!(feed(101.0, 3).is_empty());
+        let bricks = feed(100.0, 4);
+        assert_eq!(bricks.len(), 1);
+        assert_eq!(bricks[0].direction, RenkoDirection::Down);
+        assert_eq!((bricks[0].open, bricks[0].close), (101.0, 100.0));
+
+        // Continuing down takes a single brick, reversing up takes two
+        assert_eq!(feed(99.0, 5).len(), 1);
+        assert!(feed(100.5, 6).is_empty());
+        let bricks = feed(101.0, 7);
+        assert_eq!(bricks.len(), 1);
+        assert_eq!((bricks[0].open, bricks[0].close), (100.0, 101.0));
+
+        assert!(RenkoAggregator::new(0.0).is_err());
+    }
+
+    #[test]
+    fn test_renko_rejects_unusable_ticks() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let mut renko = RenkoAggregator::new(0.01).unwrap();
+        renko
+            .process(&make_tick("TEST", 10.0, 1.0, base_time))
+            .unwrap();
+
+        let mut bad = make_tick("TEST", 10.0, 1.0, base_time);
+        bad.price = f64::NAN;
+        assert!(renko.process(&bad).is_err());
+        bad.price = f64::INFINITY;
+        assert!(renko.process(&bad).is_err());
+
+        // A jump of 100,000 bricks is refused and leaves the chart as it was
+        assert!(renko
+            .process(&make_tick("TEST", 1010.0, 1.0, base_time))
+            .is_err());
+        assert_eq!(renko.current_level(), Some(10.0));
+        let bricks = renko
+            .process(&make_tick("TEST", 10.05, 1.0, base_time))
+            .unwrap();
+        assert_eq!(bricks.len(), 5);
+        assert!((bricks[4].close - 10.05).abs() < 1e-12);
+
+        // A brick size lost in the price's rounding
+        let mut tiny = RenkoAggregator::new(1e-12).unwrap();
+        assert!(tiny
+            .process(&make_tick("TEST", 10.0, 1.0, base_time))
+            .is_err());
+    }
+
+    #[test]
+    fn test_renko_from_atr() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let tick = make_tick("TEST", 10.0, 100.0, base_time);
+        let bar = |high: f64, low: f64, close: f64| Bar {
+            high,
+            low,
+            close,
+            ..Bar::new(&tick, BarPeriod::Daily)
+        };
+        let bars = [
+            bar(10.5, 9.5, 10.0),
+            // True ranges: 1.0, then 2.0 from the gap above the prior close
+            bar(10.6, 9.6, 10.4),
+            bar(12.4, 11.8, 12.0),
+        ];
+
+        // Seeded with the mean of 1.0 and 1.0, then (1.0 + 2.0) / 2
+        let renko = RenkoAggregator::from_atr(&bars, 2).unwrap();
+        assert!((renko.brick_size() - 1.5).abs() < 1e-12);
+        assert_eq!(renko.brick_size(), Bar::average_true_range(&bars, 2));
+        assert!(matches!(
+            RenkoAggregator::from_atr(&bars, 4),
+            Err(MarketDataError::InsufficientObservations { needed: 4, got: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_derived_prices() {
+        let tick = make_tick(
+            "TEST",
+            10.0,
+            100.0,
+            Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap(),
+        );
+        let bar = Bar {
+            high: 12.0,
+            low: 9.0,
+            close: 11.0,
+            ..Bar::new(&tick, BarPeriod::Daily)
+        };
+        assert!((bar.typical_price() - 32.0 / 3.0).abs() < 1e-12);
+        assert_eq!(bar.median_price(), 10.5);
+        assert_eq!(bar.weighted_close(), 10.75);
+
+        // Gaps widen the true range beyond the bar's own range
+        assert_eq!(bar.true_range(10.0), 3.0);
+        assert_eq!(bar.true_range(14.0), 5.0);
+        assert_eq!(bar.true_range(7.5), 4.5);
+    }
+
+    #[test]
+    fn test_average_true_range() {
+        let tick = make_tick(
+            "TEST",
+            10.0,
+            100.0,
+            Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap(),
+        );
+        let bar = |open: f64, close: f64| Bar {
+            open,
+            high: open.max(close) + 0.5,
+            low: open.min(close) - 0.5,
+            close,
+            ..Bar::new(&tick, BarPeriod::Daily)
+        };
+
+        // Ten bars gapping 5 away from the previous close, then bars that
+        // open at the previous close with a range of 2
+        let mut bars = vec![bar(100.0, 100.0)];
+        for i in 0..10 {
+            let close = bars[i].close;
+            bars.push(bar(close + 5.0, close + 5.0));
+        }
+        let gapped = Bar::average_true_range(&bars, 14);
+        assert!(gapped > 4.0);
+
+        let mut previous = gapped;
+        for i in 0..200 {
+            let close = bars.last().unwrap().close;
+            bars.push(bar(close, close + if i % 2 == 0 { 1.0 } else { -1.0 }));
+            let atr = Bar::average_true_range(&bars, 14);
+            assert!(atr <= previous + 1e-12);
+            previous = atr;
+        }
+        assert!((previous - bars.last().unwrap().range()).abs() < 1e-3);
+
+        assert!(Bar::average_true_range(&[], 14).is_nan());
+        assert!(Bar::average_true_range(&bars, 0).is_nan());
+        assert_eq!(Bar::average_true_range(&bars[..1], 14), bars[0].range());
+
+        // A shorter period forgets the early gaps sooner
+        assert!(Bar::average_true_range(&bars[..15], 3) < Bar::average_true_range(&bars[..15], 14));
+    }
+
+    #[test]
+    fn test_resample() {
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let mut aggregator = BarAggregator::new(BarPeriod::Minute1, 100);
+        for i in 0..30 {
+            let price = 10.0 + (i % 7) as f64 * 0.1;
+            let ts = base_time + Duration::seconds(20 * i);
+            aggregator.process(&make_tick("TEST", price, 10.0 + i as f64, ts));
+        }
+        aggregator.flush();
+        let minute_bars = aggregator.bars().to_vec();
+        assert_eq!(minute_bars.len(), 10);
+
+        let bars = resample(&minute_bars, BarPeriod::Minute5).unwrap();
+        assert_eq!(bars.len(), 2);
+        for (bar, group) in bars.iter().zip(minute_bars.chunks(5)) {
+            assert_eq!(bar.timestamp, group[0].timestamp);
+            assert_eq!(bar.period(), Some(BarPeriod::Minute5));
+            assert_eq!(bar.open, group[0].open);
+            assert_eq!(bar.close, group[4].close);
+            assert_eq!(
+                bar.high,
+                group.iter().map(|b| b.high).fold(f64::MIN, f64::max)
+            );
+            assert_eq!(
+                bar.low,
+                group.iter().map(|b| b.low).fold(f64::MAX, f64::min)
+            );
+            assert_eq!(bar.tick_count, 15);
+            let volume: f64 = group.iter().map(|b| b.volume).sum();
+            assert!((bar.volume - volume).abs() < 1e-9);
+            assert!((bar.vwap - bar.turnover / volume).abs() < 1e-12);
+        }
+
+        // The whole session falls in one daily bar
+        let daily = resample(&minute_bars, BarPeriod::Daily).unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].tick_count, 30);
+
+        assert!(matches!(
+            resample(&bars, BarPeriod::Minute1),
+            Err(MarketDataError::AggregationError(_))
+        ));
+
+        // Out of order bars
+        let mut shuffled = minute_bars.clone();
+        shuffled.swap(2, 3);
+        assert!(matches!(
+            resample(&shuffled, BarPeriod::Minute5),
+            Err(MarketDataError::AggregationError(_))
+        ));
+
+        // Activity bars have no period to nest
+        let volume_bar = Bar::with_type(
+            &make_tick("TEST", 10.0, 5.0, base_time),
+            BarType::Volume(100.0),
+        );
+        assert!(resample(&[volume_bar], BarPeriod::Daily).is_err());
+    }
+
+    #[test]
+    fn test_resample_requires_nesting_periods() {
+        let tick = |ts| make_tick("TEST", 10.0, 1.0, ts);
+
+        // The week of Monday 2024-01-29 runs into February
+        let into_february = Bar::new(
+            &tick(Utc.with_ymd_and_hms(2024, 1, 30, 10, 0, 0).unwrap()),
+            BarPeriod::Week,
+        );
+        assert!(resample(&[into_february], BarPeriod::Month).is_err());
+        // The week of Monday 2024-09-30 runs into the fourth quarter
+        let into_q4 = Bar::new(
+            &tick(Utc.with_ymd_and_hms(2024, 10, 1, 10, 0, 0).unwrap()),
+            BarPeriod::Week,
+        );
+        assert!(resample(&[into_q4], BarPeriod::Quarter).is_err());
+
+        // A week within January resamples to January
+        let inside = Bar::new(
+            &tick(Utc.with_ymd_and_hms(2024, 1, 10, 10, 0, 0).unwrap()),
+            BarPeriod::Week,
+        );
+        let monthly = resample(&[inside], BarPeriod::Month).unwrap();
+        assert_eq!(
+            monthly[0].timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        );
+
+        // 7-minute bars do not divide 10-minute bars
+        let ts = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        let seven_minutes = BarPeriod::custom(420).unwrap();
+        let bars: Vec<Bar> = (0..3)
+            .map(|i| Bar::new(&tick(ts + Duration::minutes(7 * i)), seven_minutes))
+            .collect();
+        assert!(resample(&bars, BarPeriod::custom(600).unwrap()).is_err());
+    }
 }
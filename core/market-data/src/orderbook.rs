@@ -0,0 +1,377 @@
+//! Order book reconstruction
+//!
+//! Rebuilds a price-level book from an order-by-order event feed. Where
+//! [`Tick`](crate::tick::Tick) carries only the top of book, the event feed
+//! tracks every resting order, so depth at any price is available.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{MarketDataError, Result};
+
+/// Level volumes at or below this are treated as empty
+const VOLUME_EPSILON: f64 = 1e-9;
+
+/// Order book event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OrderEventType {
+    /// New resting order
+    Add,
+    /// Resting order withdrawn in full
+    Cancel,
+    /// Resting order repriced or resized
+    Modify,
+    /// Resting order (partially) filled
+    Execute,
+}
+
+/// Side of the book an order rests on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    /// Buy order, resting on the bid
+    Buy,
+    /// Sell order, resting on the ask
+    Sell,
+}
+
+/// A single order book event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookEvent {
+    /// Event type
+    pub event_type: OrderEventType,
+    /// Symbol identifier (e.g., "000001.SZ")
+    pub symbol: String,
+    /// Exchange order identifier
+    pub order_id: u64,
+    /// Order side
+    pub side: Side,
+    /// Order price (new price for `Modify`, ignored for `Cancel` and `Execute`)
+    pub price: f64,
+    /// Order quantity (new quantity for `Modify`, filled quantity for
+    /// `Execute`, ignored for `Cancel`)
+    pub quantity: f64,
+    /// Event timestamp in UTC
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Price key ordered by `f64::total_cmp`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrderedF64(f64);
+
+impl Eq for OrderedF64 {}
+
+impl PartialOrd for OrderedF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// An order resting in the book
+#[derive(Debug, Clone, Copy)]
+struct RestingOrder {
+    side: Side,
+    price: f64,
+    quantity: f64,
+}
+
+/// Price-level order book for one symbol
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    symbol: String,
+    bids: BTreeMap<OrderedF64, f64>,
+    asks: BTreeMap<OrderedF64, f64>,
+    orders: HashMap<u64, RestingOrder>,
+    last_update: Option<DateTime<Utc>>,
+}
+
+impl OrderBook {
+    /// Create an empty book for `symbol`
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            orders: HashMap::new(),
+            last_update: None,
+        }
+    }
+
+    /// Symbol of the book
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Apply one event to the book
+    ///
+    /// `Add` rests a new order, `Cancel` removes an order entirely,
+    /// `Modify` moves an order to the event's price and quantity on its
+    /// original side, and `Execute` reduces an order by the filled
+    /// quantity, removing it once fully filled. Events for another symbol,
+    /// duplicate or unknown order ids, non-positive prices or quantities
+    /// and fills larger than the resting quantity are rejected without
+    /// changing the book.
+    pub fn process_event(&mut self, event: OrderBookEvent) -> Result<()> {
+        if event.symbol != self.symbol {
+            return Err(MarketDataError::InvalidSymbol(event.symbol));
+        }
+
+        match event.event_type {
+            OrderEventType::Add => {
+                if self.orders.contains_key(&event.order_id) {
+                    return Err(MarketDataError::OrderBookError(format!(
+                        "duplicate order id {}",
+                        event.order_id
+                    )));
+                }
+                validate_order(event.price, event.quantity)?;
+                let order = RestingOrder {
+                    side: event.side,
+                    price: event.price,
+                    quantity: event.quantity,
+                };
+                self.rest(event.order_id, order);
+            }
+            OrderEventType::Cancel => {
+                let order = self.take(event.order_id)?;
+                self.level_sub(order.side, order.price, order.quantity);
+            }
+            OrderEventType::Modify => {
+                validate_order(event.price, event.quantity)?;
+                let order = self.take(event.order_id)?;
+                self.level_sub(order.side, order.price, order.quantity);
+                let modified = RestingOrder {
+                    price: event.price,
+                    quantity: event.quantity,
+                    ..order
+                };
+                self.rest(event.order_id, modified);
+            }
+            OrderEventType::Execute => {
+                let resting = self.orders.get(&event.order_id).copied().ok_or_else(|| {
+                    MarketDataError::OrderBookError(format!("unknown order id {}", event.order_id))
+                })?;
+                if !(event.quantity > 0.0 && event.quantity <= resting.quantity + VOLUME_EPSILON) {
+                    return Err(MarketDataError::InvalidVolume(event.quantity));
+                }
+                self.level_sub(resting.side, resting.price, event.quantity);
+                let remaining = resting.quantity - event.quantity;
+                if remaining > VOLUME_EPSILON {
+                    if let Some(order) = self.orders.get_mut(&event.order_id) {
+                        order.quantity = remaining;
+                    }
+                } else {
+                    self.orders.remove(&event.order_id);
+                }
+            }
+        }
+
+        self.last_update = Some(event.timestamp);
+        Ok(())
+    }
+
+    /// Highest bid price and the volume resting there
+    pub fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids
+            .iter()
+            .next_back()
+            .map(|(price, &volume)| (price.0, volume))
+    }
+
+    /// Lowest ask price and the volume resting there
+    pub fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks
+            .iter()
+            .next()
+            .map(|(price, &volume)| (price.0, volume))
+    }
+
+    /// Total volume resting at `price` on `side` (0 if the level is empty)
+    pub fn volume_at(&self, side: Side, price: f64) -> f64 {
+        self.levels(side)
+            .get(&OrderedF64(price))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Number of resting orders
+    pub fn order_count(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Timestamp of the last applied event
+    pub fn last_update(&self) -> Option<DateTime<Utc>> {
+        self.last_update
+    }
+
+    fn levels(&self, side: Side) -> &BTreeMap<OrderedF64, f64> {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<OrderedF64, f64> {
+        match side {
+            Side::Buy => &mut self.bids,
+            Side::Sell => &mut self.asks,
+        }
+    }
+
+    /// Insert an order and add its quantity to its price level
+    fn rest(&mut self, order_id: u64, order: RestingOrder) {
+        *self
+            .levels_mut(order.side)
+            .entry(OrderedF64(order.price))
+            .or_insert(0.0) += order.quantity;
+        self.orders.insert(order_id, order);
+    }
+
+    /// Remove an order from the index (its level is left untouched)
+    fn take(&mut self, order_id: u64) -> Result<RestingOrder> {
+        self.orders.remove(&order_id).ok_or_else(|| {
+            MarketDataError::OrderBookError(format!("unknown order id {}", order_id))
+        })
+    }
+
+    /// Subtract volume from a price level, dropping the level once empty
+    fn level_sub(&mut self, side: Side, price: f64, quantity: f64) {
+        let levels = self.levels_mut(side);
+        let key = OrderedF64(price);
+        if let Some(volume) = levels.get_mut(&key) {
+            *volume -= quantity;
+            if *volume <= VOLUME_EPSILON {
+                levels.remove(&key);
+            }
+        }
+    }
+}
+
+/// Check that an order price and quantity are positive and finite
+fn validate_order(price: f64, quantity: f64) -> Result<()> {
+    if !(price > 0.0 && price.is_finite()) {
+        return Err(MarketDataError::InvalidPrice(price));
+    }
+    if !(quantity > 0.0 && quantity.is_finite()) {
+        return Err(MarketDataError::InvalidVolume(quantity));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use OrderEventType::{Add, Cancel, Execute, Modify};
+    use Side::{Buy, Sell};
+
+    fn event(
+        event_type: OrderEventType,
+        order_id: u64,
+        side: Side,
+        price: f64,
+        quantity: f64,
+    ) -> OrderBookEvent {
+        OrderBookEvent {
+            event_type,
+            symbol: "600000.SH".to_string(),
+            order_id,
+            side,
+            price,
+            quantity,
+            timestamp: Utc.with_ymd_and_hms(2024, 3, 4, 9, 30, 0).unwrap(),
+        }
+    }
+
+    fn apply(
+        book: &mut OrderBook,
+        event_type: OrderEventType,
+        order_id: u64,
+        side: Side,
+        price: f64,
+        quantity: f64,
+    ) {
+        book.process_event(event(event_type, order_id, side, price, quantity))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_add_cancel_modify_execute() {
+        let mut book = OrderBook::new("600000.SH");
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(book.best_ask(), None);
+
+        // Add: two bids at 10.00, one at 9.99, asks at 10.01 and 10.02
+        apply(&mut book, Add, 1, Buy, 10.00, 300.0);
+        apply(&mut book, Add, 2, Buy, 10.00, 200.0);
+        apply(&mut book, Add, 3, Buy, 9.99, 1000.0);
+        apply(&mut book, Add, 4, Sell, 10.02, 400.0);
+        apply(&mut book, Add, 5, Sell, 10.01, 100.0);
+        assert_eq!(book.best_bid(), Some((10.00, 500.0)));
+        assert_eq!(book.best_ask(), Some((10.01, 100.0)));
+        assert_eq!(book.order_count(), 5);
+
+        // Cancel: the best bid level shrinks, then empties
+        apply(&mut book, Cancel, 1, Buy, 0.0, 0.0);
+        assert_eq!(book.best_bid(), Some((10.00, 200.0)));
+        apply(&mut book, Cancel, 2, Buy, 0.0, 0.0);
+        assert_eq!(book.best_bid(), Some((9.99, 1000.0)));
+        assert_eq!(book.volume_at(Buy, 10.00), 0.0);
+
+        // Modify: reprice the 10.02 ask through the 10.01 ask
+        apply(&mut book, Modify, 4, Sell, 10.005, 250.0);
+        assert_eq!(book.best_ask(), Some((10.005, 250.0)));
+        assert_eq!(book.volume_at(Sell, 10.02), 0.0);
+        assert_eq!(book.volume_at(Sell, 10.01), 100.0);
+
+        // Execute: partial, then full fill of the best ask
+        apply(&mut book, Execute, 4, Sell, 10.005, 50.0);
+        assert_eq!(book.best_ask(), Some((10.005, 200.0)));
+        apply(&mut book, Execute, 4, Sell, 10.005, 200.0);
+        assert_eq!(book.best_ask(), Some((10.01, 100.0)));
+        assert_eq!(book.order_count(), 2);
+        assert!(book.last_update().is_some());
+    }
+
+    #[test]
+    fn test_rejected_events_leave_book_unchanged() {
+        let mut book = OrderBook::new("600000.SH");
+        apply(&mut book, Add, 1, Buy, 10.00, 300.0);
+
+        assert!(matches!(
+            book.process_event(event(Add, 1, Buy, 10.00, 100.0)),
+            Err(MarketDataError::OrderBookError(_))
+        ));
+        assert!(matches!(
+            book.process_event(event(Cancel, 9, Buy, 0.0, 0.0)),
+            Err(MarketDataError::OrderBookError(_))
+        ));
+        assert!(matches!(
+            book.process_event(event(Modify, 1, Buy, -1.0, 100.0)),
+            Err(MarketDataError::InvalidPrice(_))
+        ));
+        assert!(matches!(
+            book.process_event(event(Execute, 1, Buy, 10.00, 500.0)),
+            Err(MarketDataError::InvalidVolume(_))
+        ));
+
+        let mut other = event(Add, 2, Sell, 10.01, 100.0);
+        other.symbol = "000001.SZ".to_string();
+        assert!(matches!(
+            book.process_event(other),
+            Err(MarketDataError::InvalidSymbol(_))
+        ));
+
+        assert_eq!(book.best_bid(), Some((10.00, 300.0)));
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.order_count(), 1);
+    }
+}
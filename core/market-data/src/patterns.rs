@@ -0,0 +1,284 @@
+//! Candlestick pattern recognition
+//!
+//! Detects common single-, two- and three-bar candlestick patterns on
+//! sequences of completed OHLCV bars.
+
+use serde::{Deserialize, Serialize};
+
+use crate::ohlcv::Bar;
+
+/// Maximum body/range ratio for a doji
+const DOJI_BODY_RATIO: f64 = 0.05;
+
+/// Maximum star body relative to the first bar body in a morning star
+const STAR_BODY_RATIO: f64 = 0.3;
+
+/// Direction of a two-sided pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Side {
+    /// Bullish reversal
+    Bullish,
+    /// Bearish reversal
+    Bearish,
+}
+
+/// Candlestick pattern type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PatternKind {
+    /// Open and close nearly equal (body < 5% of range)
+    Doji,
+    /// Bullish bar with long lower shadow and small upper shadow
+    Hammer,
+    /// Bearish bar with long upper shadow and small lower shadow
+    ShootingStar,
+    /// Current body fully engulfs the prior body in the opposite direction
+    Engulfing(Side),
+    /// Bearish bar, small-bodied star gapping down, bullish recovery
+    MorningStar,
+}
+
+/// Detected candlestick pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandlePattern {
+    /// Pattern type
+    pub kind: PatternKind,
+    /// Indices of the bars forming the pattern (oldest first)
+    pub bar_indices: Vec<usize>,
+    /// Pattern strength in [0, 1]
+    pub confidence: f64,
+}
+
+/// Candlestick pattern recognizer
+pub struct PatternRecognizer;
+
+impl PatternRecognizer {
+    /// Detect all patterns in a bar sequence
+    ///
+    /// Patterns are returned ordered by their last bar index.
+    pub fn detect(bars: &[Bar]) -> Vec<CandlePattern> {
+        let mut patterns = Vec::new();
+
+        for (i, bar) in bars.iter().enumerate() {
+            if let Some(confidence) = Self::doji(bar) {
+                patterns.push(CandlePattern {
+                    kind: PatternKind::Doji,
+                    bar_indices: vec![i],
+                    confidence,
+                });
+            }
+
+            if let Some(confidence) = Self::hammer(bar) {
+                patterns.push(CandlePattern {
+                    kind: PatternKind::Hammer,
+                    bar_indices: vec![i],
+                    confidence,
+                });
+            }
+
+            if let Some(confidence) = Self::shooting_star(bar) {
+                patterns.push(CandlePattern {
+                    kind: PatternKind::ShootingStar,
+                    bar_indices: vec![i],
+                    confidence,
+                });
+            }
+
+            if i >= 1 {
+                if let Some((side, confidence)) = Self::engulfing(&bars[i - 1], bar) {
+                    patterns.push(CandlePattern {
+                        kind: PatternKind::Engulfing(side),
+                        bar_indices: vec![i - 1, i],
+                        confidence,
+                    });
+                }
+            }
+
+            if i >= 2 {
+                if let Some(confidence) = Self::morning_star(&bars[i - 2], &bars[i - 1], bar) {
+                    patterns.push(CandlePattern {
+                        kind: PatternKind::MorningStar,
+                        bar_indices: vec![i - 2, i - 1, i],
+                        confidence,
+                    });
+                }
+            }
+        }
+
+        patterns
+    }
+
+    fn doji(bar: &Bar) -> Option<f64> {
+        let range = bar.range();
+        let max_body = DOJI_BODY_RATIO * range;
+        if range <= 0.0 || bar.body() >= max_body {
+            return None;
+        }
+        Some(1.0 - bar.body() / max_body)
+    }
+
+    fn hammer(bar: &Bar) -> Option<f64> {
+        let body = bar.body();
+        if !bar.is_bullish() || bar.lower_shadow() <= 2.0 * body || bar.upper_shadow() >= body / 2.0
+        {
+            return None;
+        }
+        Some(bar.lower_shadow() / bar.range())
+    }
+
+    fn shooting_star(bar: &Bar) -> Option<f64> {
+        let body = bar.body();
+        if bar.close >= bar.open
+            || bar.upper_shadow() <= 2.0 * body
+            || bar.lower_shadow() >= body / 2.0
+        {
+            return None;
+        }
+        Some(bar.upper_shadow() / bar.range())
+    }
+
+    fn engulfing(prev: &Bar, curr: &Bar) -> Option<(Side, f64)> {
+        let prev_top = prev.open.max(prev.close);
+        let prev_bottom = prev.open.min(prev.close);
+        let curr_top = curr.open.max(curr.close);
+        let curr_bottom = curr.open.min(curr.close);
+
+        if prev.body() <= 0.0 || curr_top < prev_top || curr_bottom > prev_bottom {
+            return None;
+        }
+
+        let side = if curr.is_bullish() && prev.close < prev.open {
+            Side::Bullish
+        } else if curr.close < curr.open && prev.is_bullish() {
+            Side::Bearish
+        } else {
+            return None;
+        };
+
+        Some((side, 1.0 - prev.body() / curr.body()))
+    }
+
+    fn morning_star(first: &Bar, star: &Bar, last: &Bar) -> Option<f64> {
+        let first_body = first.body();
+        if first.close >= first.open || !last.is_bullish() {
+            return None;
+        }
+
+        // Star has a small body gapping below the first bar's close
+        if star.body() >= STAR_BODY_RATIO * first_body || star.open.max(star.close) >= first.close {
+            return None;
+        }
+
+        // Last bar must close above the midpoint of the first body
+        let midpoint = (first.open + first.close) / 2.0;
+        if last.close <= midpoint {
+            return None;
+        }
+
+        Some(((last.close - midpoint) / (first.open - midpoint)).min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ohlcv::{BarPeriod, BarType};
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn make_bar(index: i64, open: f64, high: f64, low: f64, close: f64) -> Bar {
+        let base = Utc.with_ymd_and_hms(2024, 1, 15, 10, 0, 0).unwrap();
+        Bar {
+            symbol: "TEST".to_string(),
+            timestamp: base + Duration::minutes(index),
+            bar_type: BarType::Time(BarPeriod::Minute1),
+            open,
+            high,
+            low,
+            close,
+            volume: 1000.0,
+            turnover: 1000.0 * close,
+            tick_count: 10,
+            vwap: close,
+        }
+    }
+
+    fn kinds(patterns: &[CandlePattern]) -> Vec<PatternKind> {
+        patterns.iter().map(|p| p.kind).collect()
+    }
+
+    #[test]
+    fn test_doji() {
+        let bars = vec![make_bar(0, 10.0, 11.0, 9.0, 10.05)];
+        let patterns = PatternRecognizer::detect(&bars);
+
+        assert_eq!(kinds(&patterns), vec![PatternKind::Doji]);
+        assert_eq!(patterns[0].bar_indices, vec![0]);
+        assert!(patterns[0].confidence > 0.0 && patterns[0].confidence <= 1.0);
+    }
+
+    #[test]
+    fn test_hammer() {
+        // Body 0.5, lower shadow 2.0, upper shadow 0.1
+        let bars = vec![make_bar(0, 10.0, 10.6, 8.0, 10.5)];
+        let patterns = PatternRecognizer::detect(&bars);
+
+        assert_eq!(kinds(&patterns), vec![PatternKind::Hammer]);
+        assert_eq!(patterns[0].bar_indices, vec![0]);
+    }
+
+    #[test]
+    fn test_shooting_star() {
+        // Body 0.5, upper shadow 2.0, lower shadow 0.1
+        let bars = vec![make_bar(0, 10.5, 12.5, 9.9, 10.0)];
+        let patterns = PatternRecognizer::detect(&bars);
+
+        assert_eq!(kinds(&patterns), vec![PatternKind::ShootingStar]);
+        assert_eq!(patterns[0].bar_indices, vec![0]);
+    }
+
+    #[test]
+    fn test_engulfing() {
+        let bars = vec![
+            make_bar(0, 10.5, 10.6, 9.9, 10.0),
+            make_bar(1, 9.9, 11.0, 9.8, 10.8),
+            make_bar(2, 11.0, 11.2, 9.5, 9.6),
+        ];
+        let patterns = PatternRecognizer::detect(&bars);
+
+        assert_eq!(
+            kinds(&patterns),
+            vec![
+                PatternKind::Engulfing(Side::Bullish),
+                PatternKind::Engulfing(Side::Bearish)
+            ]
+        );
+        assert_eq!(patterns[0].bar_indices, vec![0, 1]);
+        assert_eq!(patterns[1].bar_indices, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_morning_star() {
+        let bars = vec![
+            make_bar(0, 12.0, 12.1, 9.9, 10.0),
+            make_bar(1, 9.6, 9.8, 9.3, 9.5),
+            make_bar(2, 9.8, 11.8, 9.7, 11.5),
+        ];
+        let patterns = PatternRecognizer::detect(&bars);
+
+        let star = patterns
+            .iter()
+            .find(|p| p.kind == PatternKind::MorningStar)
+            .unwrap();
+        assert_eq!(star.bar_indices, vec![0, 1, 2]);
+        assert!((star.confidence - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_no_patterns() {
+        // Plain trending bars with moderate shadows
+        let bars = vec![
+            make_bar(0, 10.0, 10.6, 9.8, 10.5),
+            make_bar(1, 10.5, 11.1, 10.3, 11.0),
+        ];
+        assert!(PatternRecognizer::detect(&bars).is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+//! Synthetic market data
+//!
+//! Generates correlated daily OHLCV bars from geometric Brownian motion, so
+//! integration tests can run the full pipeline on data with known
+//! statistical properties.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use nalgebra::{DMatrix, DVector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::ohlcv::{Bar, BarPeriod, BarType};
+use crate::{MarketDataError, Result};
+
+/// Trading days per year, the time unit of drift and volatility
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Median daily volume of a simulated asset
+const BASE_VOLUME: f64 = 1_000_000.0;
+
+/// Average trade size used to derive tick counts
+const SHARES_PER_TICK: f64 = 500.0;
+
+/// Largest accepted deviation of a correlation diagonal entry from one
+const UNIT_DIAGONAL_TOL: f64 = 1e-12;
+
+/// Correlated geometric Brownian motion price simulator
+///
+/// Log prices follow `d ln S = (μ - σ²/2) dt + σ dW` with annualized drift
+/// `μ` and volatility `σ`, stepped one trading day (`dt = 1/252`) at a time
+/// with shocks correlated through the Cholesky factor of `correlation`.
+#[derive(Debug, Clone)]
+pub struct GbmMarketSimulator {
+    n_days: u32,
+    initial_prices: Vec<f64>,
+    drift: Vec<f64>,
+    volatility: Vec<f64>,
+    cholesky: DMatrix<f64>,
+    seed: u64,
+}
+
+impl GbmMarketSimulator {
+    /// Create a simulator for `n_assets` assets over `n_days` trading days
+    ///
+    /// `initial_prices` must be positive, `volatility` non-negative, and
+    /// `correlation` an `n_assets x n_assets` positive definite matrix with
+    /// a unit diagonal.
+    pub fn new(
+        n_assets: usize,
+        n_days: u32,
+        initial_prices: Vec<f64>,
+        drift: Vec<f64>,
+        volatility: Vec<f64>,
+        correlation: DMatrix<f64>,
+        seed: u64,
+    ) -> Result<Self> {
+        for (name, len) in [
+            ("initial prices", initial_prices.len()),
+            ("drifts", drift.len()),
+            ("volatilities", volatility.len()),
+            ("correlation rows", correlation.nrows()),
+            ("correlation columns", correlation.ncols()),
+        ] {
+            if len != n_assets {
+                return Err(MarketDataError::SimulationError(format!(
+                    "expected {} {}, got {}",
+                    n_assets, name, len
+                )));
+            }
+        }
+        if let Some(&price) = initial_prices
+            .iter()
+            .find(|&&p| !(p > 0.0 && p.is_finite()))
+        {
+            return Err(MarketDataError::InvalidPrice(price));
+        }
+        if volatility.iter().any(|&v| !(v >= 0.0 && v.is_finite())) {
+            return Err(MarketDataError::SimulationError(
+                "volatilities must be finite and non-negative".to_string(),
+            ));
+        }
+        if let Some(diagonal) = correlation
+            .diagonal()
+            .iter()
+            .find(|&&c| c.is_nan() || (c - 1.0).abs() > UNIT_DIAGONAL_TOL)
+        {
+            return Err(MarketDataError::SimulationError(format!(
+                "correlation diagonal must be one, got {}",
+                diagonal
+            )));
+        }
+        let cholesky = correlation
+            .cholesky()
+            .ok_or_else(|| {
+                MarketDataError::SimulationError(
+                    "correlation matrix is not positive definite".to_string(),
+                )
+            })?
+            .l();
+
+        Ok(Self {
+            n_days,
+            initial_prices,
+            drift,
+            volatility,
+            cholesky,
+            seed,
+        })
+    }
+
+    /// Simulate one daily bar per trading day for each asset
+    ///
+    /// Returns one series per asset, symbols `SIM000`, `SIM001`, ...,
+    /// dated on weekdays from 2024-01-02. Each bar opens at the previous
+    /// close (the initial price on day one) and closes at the simulated
+    /// price; the high and low extend `max(O, C)` and `min(O, C)` by a
+    /// random intraday range of about half a daily standard deviation.
+    /// Volume is lognormal around one million shares and VWAP is the
+    /// typical price `(H + L + C) / 3`. The same seed gives the same bars.
+    pub fn simulate_daily_bars(&self) -> Vec<Vec<Bar>> {
+        let n_assets = self.initial_prices.len();
+        let dt = 1.0 / TRADING_DAYS_PER_YEAR;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        let mut prices = self.initial_prices.clone();
+        let mut series: Vec<Vec<Bar>> = (0..n_assets)
+            .map(|_| Vec::with_capacity(self.n_days as usize))
+            .collect();
+
+        let mut date = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        for _ in 0..self.n_days {
+            let z = DVector::<f64>::from_fn(n_assets, |_, _| StandardNormal.sample(&mut rng));
+            let shocks = &self.cholesky * z;
+
+            for (i, bars) in series.iter_mut().enumerate() {
+                let sigma = self.volatility[i];
+                let daily_sigma = sigma * dt.sqrt();
+                let log_return =
+                    (self.drift[i] - 0.5 * sigma * sigma) * dt + daily_sigma * shocks[i];
+
+                let open = prices[i];
+                let close = open * log_return.exp();
+                let intraday_range = 0.5 * daily_sigma * rng.gen::<f64>();
+                let high = open.max(close) * (1.0 + intraday_range);
+                let low = open.min(close) * (1.0 - intraday_range);
+
+                let noise: f64 = StandardNormal.sample(&mut rng);
+                let volume = (BASE_VOLUME * (0.3 * noise).exp()).round();
+                let vwap = (high + low + close) / 3.0;
+
+                bars.push(Bar {
+                    symbol: format!("SIM{:03}", i),
+                    timestamp: date,
+                    bar_type: BarType::Time(BarPeriod::Daily),
+                    open,
+                    high,
+                    low,
+                    close,
+                    volume,
+                    turnover: volume * vwap,
+                    tick_count: (volume / SHARES_PER_TICK).ceil() as u64,
+                    vwap,
+                });
+                prices[i] = close;
+            }
+
+            date = next_weekday(date);
+        }
+
+        series
+    }
+}
+
+/// Next Monday-to-Friday date after `date`
+fn next_weekday(date: DateTime<Utc>) -> DateTime<Utc> {
+    let mut next = date + Duration::days(1);
+    while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+        next += Duration::days(1);
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correlation() -> DMatrix<f64> {
+        DMatrix::from_row_slice(
+            5,
+            5,
+            &[
+                1.0, 0.6, 0.3, 0.0, -0.2, //
+                0.6, 1.0, 0.4, 0.1, 0.0, //
+                0.3, 0.4, 1.0, 0.2, 0.1, //
+                0.0, 0.1, 0.2, 1.0, 0.5, //
+                -0.2, 0.0, 0.1, 0.5, 1.0,
+            ],
+        )
+    }
+
+    fn log_returns(bars: &[Bar]) -> Vec<f64> {
+        bars.iter().map(|bar| (bar.close / bar.open).ln()).collect()
+    }
+
+    fn sample_correlation(a: &[f64], b: &[f64]) -> f64 {
+        let n = a.len() as f64;
+        let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+        let cov: f64 = a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - mean_a) * (y - mean_b))
+            .sum();
+        let var_a: f64 = a.iter().map(|x| (x - mean_a).powi(2)).sum();
+        let var_b: f64 = b.iter().map(|y| (y - mean_b).powi(2)).sum();
+        cov / (var_a * var_b).sqrt()
+    }
+
+    fn simulator(n_days: u32) -> GbmMarketSimulator {
+        GbmMarketSimulator::new(
+            5,
+            n_days,
+            vec![10.0, 25.0, 8.5, 100.0, 42.0],
+            vec![0.05, 0.08, 0.03, 0.10, -0.02],
+            vec![0.20, 0.30, 0.25, 0.40, 0.15],
+            correlation(),
+            2024,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_simulated_bars() {
+        let simulator = simulator(252);
+        let series = simulator.simulate_daily_bars();
+
+        assert_eq!(series.len(), 5);
+        for (i, bars) in series.iter().enumerate() {
+            assert_eq!(bars.len(), 252);
+            assert_eq!(bars[0].open, [10.0, 25.0, 8.5, 100.0, 42.0][i]);
+            for (prev, bar) in bars.iter().zip(&bars[1..]) {
+                assert_eq!(bar.open, prev.close);
+                assert!(bar.timestamp > prev.timestamp);
+            }
+            for bar in bars {
+                assert!(bar.low > 0.0 && bar.open > 0.0 && bar.close > 0.0);
+                assert!(bar.high >= bar.open.max(bar.close));
+                assert!(bar.low <= bar.open.min(bar.close));
+                assert!(bar.volume > 0.0);
+            }
+        }
+
+        // Reproducible from the seed
+        assert_eq!(
+            simulator.simulate_daily_bars()[3][100].close,
+            series[3][100].close
+        );
+    }
+
+    #[test]
+    fn test_return_correlation() {
+        // A year of data estimates a correlation only to about ±0.06, so
+        // check the 0.05 tolerance on twenty years
+        let series = simulator(5040).simulate_daily_bars();
+        let returns: Vec<Vec<f64>> = series.iter().map(|bars| log_returns(bars)).collect();
+        let target = correlation();
+        for i in 0..5 {
+            for j in i + 1..5 {
+                let rho = sample_correlation(&returns[i], &returns[j]);
+                assert!(
+                    (rho - target[(i, j)]).abs() < 0.05,
+                    "correlation ({}, {}) = {}",
+                    i,
+                    j,
+                    rho
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let build = |prices: Vec<f64>, correlation: DMatrix<f64>| {
+            GbmMarketSimulator::new(2, 10, prices, vec![0.0; 2], vec![0.2; 2], correlation, 1)
+        };
+
+        assert!(build(vec![10.0, 20.0], DMatrix::identity(2, 2)).is_ok());
+        assert!(matches!(
+            build(vec![10.0, 0.0], DMatrix::identity(2, 2)),
+            Err(MarketDataError::InvalidPrice(_))
+        ));
+        assert!(matches!(
+            build(vec![10.0], DMatrix::identity(2, 2)),
+            Err(MarketDataError::SimulationError(_))
+        ));
+        // Correlation above one is not a valid correlation matrix
+        assert!(matches!(
+            build(
+                vec![10.0, 20.0],
+                DMatrix::from_row_slice(2, 2, &[1.0, 1.5, 1.5, 1.0])
+            ),
+            Err(MarketDataError::SimulationError(_))
+        ));
+        // A covariance matrix would scale the volatilities a second time
+        assert!(matches!(
+            build(
+                vec![10.0, 20.0],
+                DMatrix::from_row_slice(2, 2, &[0.04, 0.01, 0.01, 0.09])
+            ),
+            Err(MarketDataError::SimulationError(_))
+        ));
+    }
+}
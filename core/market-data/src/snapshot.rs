@@ -5,9 +5,11 @@
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
 use std::sync::Arc;
 
-use crate::tick::Tick;
+use crate::tick::{check_z_threshold, Tick, TickBuffer};
 use crate::Result;
 
 /// Market snapshot for a single symbol
@@ -133,8 +135,17 @@ pub struct SnapshotManager {
     snapshots: Arc<DashMap<String, SymbolSnapshot>>,
     /// Subscribed symbols
     subscriptions: Arc<DashMap<String, bool>>,
+    /// Outlier z-score thresholds by symbol
+    filter_thresholds: Arc<DashMap<String, f64>>,
+    /// Recent prices of filtered symbols
+    filter_buffers: Arc<DashMap<String, TickBuffer>>,
+    /// Symbols seeded from a previous close that have not ticked yet
+    seeded: Arc<DashMap<String, bool>>,
 }
 
+/// Recent ticks a filtered symbol's outlier scores are measured against
+const FILTER_BUFFER_CAPACITY: usize = 500;
+
 impl Default for SnapshotManager {
     fn default() -> Self {
         Self::new()
@@ -147,6 +158,9 @@ impl SnapshotManager {
         Self {
             snapshots: Arc::new(DashMap::new()),
             subscriptions: Arc::new(DashMap::new()),
+            filter_thresholds: Arc::new(DashMap::new()),
+            filter_buffers: Arc::new(DashMap::new()),
+            seeded: Arc::new(DashMap::new()),
         }
     }
 
@@ -159,6 +173,8 @@ impl SnapshotManager {
     pub fn unsubscribe(&self, symbol: &str) {
         self.subscriptions.remove(symbol);
         self.snapshots.remove(symbol);
+        self.filter_buffers.remove(symbol);
+        self.seeded.remove(symbol);
     }
 
     /// Check if symbol is subscribed
@@ -166,16 +182,72 @@ impl SnapshotManager {
         self.subscriptions.contains_key(symbol)
     }
 
+    /// Filter outlier ticks for a symbol
+    ///
+    /// From now on [`SnapshotManager::process_tick`] drops ticks whose
+    /// return from the last accepted price is more than `z_threshold`
+    /// standard deviations of the symbol's recent returns (see
+    /// [`TickBuffer::push_filtered`]), leaving the snapshot untouched.
+    /// Fails if `z_threshold` is not a positive finite number.
+    pub fn set_filter_threshold(&self, symbol: &str, z_threshold: f64) -> Result<()> {
+        check_z_threshold(z_threshold)?;
+        self.filter_thresholds
+            .insert(symbol.to_string(), z_threshold);
+        Ok(())
+    }
+
+    /// Outlier z-score threshold of a symbol, if it is filtered
+    pub fn filter_threshold(&self, symbol: &str) -> Option<f64> {
+        self.filter_thresholds.get(symbol).map(|r| *r)
+    }
+
+    /// Ticks of a symbol dropped as outliers
+    pub fn rejected_ticks(&self, symbol: &str) -> Vec<Tick> {
+        self.filter_buffers
+            .get(symbol)
+            .map(|buffer| buffer.rejected_ticks().iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Process a tick and update snapshot
+    ///
+    /// Ticks rejected by the symbol's outlier filter are not applied. The
+    /// first tick of a snapshot seeded by
+    /// [`SnapshotManager::initialize_from_closing_prices`] starts the
+    /// session like an unseeded symbol, keeping the seeded previous close
+    /// and price limits.
     pub fn process_tick(&self, tick: &Tick) -> Result<()> {
         if !self.is_subscribed(&tick.symbol) {
             // Auto-subscribe on first tick
             self.subscribe(&tick.symbol);
         }
 
+        if let Some(z_threshold) = self.filter_threshold(&tick.symbol) {
+            let accepted = self
+                .filter_buffers
+                .entry(tick.symbol.clone())
+                .or_insert_with(|| TickBuffer::new(FILTER_BUFFER_CAPACITY))
+                .push_filtered(tick.clone(), z_threshold)?;
+            if !accepted {
+                return Ok(());
+            }
+        }
+
+        let opening = self.seeded.remove(&tick.symbol).is_some();
         self.snapshots
             .entry(tick.symbol.clone())
-            .and_modify(|snapshot| snapshot.update(tick))
+            .and_modify(|snapshot| {
+                if opening {
+                    *snapshot = SymbolSnapshot {
+                        prev_close: snapshot.prev_close,
+                        upper_limit: snapshot.upper_limit,
+                        lower_limit: snapshot.lower_limit,
+                        ..SymbolSnapshot::from_tick(tick)
+                    };
+                } else {
+                    snapshot.update(tick);
+                }
+            })
             .or_insert_with(|| SymbolSnapshot::from_tick(tick));
 
         Ok(())
@@ -196,6 +268,67 @@ impl SnapshotManager {
         self.snapshots.len()
     }
 
+    /// The `n` biggest movers by `change_pct()`
+    ///
+    /// Gainers first, or losers first if `ascending`. Symbols with equal
+    /// changes are ranked by name.
+    pub fn top_movers(&self, n: usize, ascending: bool) -> Vec<SymbolSnapshot> {
+        if ascending {
+            self.top_by(n, |s| -s.change_pct())
+        } else {
+            self.top_by(n, SymbolSnapshot::change_pct)
+        }
+    }
+
+    /// The `n` symbols with the highest volume, ties ranked by name
+    pub fn top_volume(&self, n: usize) -> Vec<SymbolSnapshot> {
+        self.top_by(n, |s| s.volume)
+    }
+
+    /// The `n` symbols with the widest spreads in basis points, ties ranked
+    /// by name
+    pub fn top_spread_bps(&self, n: usize) -> Vec<SymbolSnapshot> {
+        self.top_by(n, SymbolSnapshot::spread_bps)
+    }
+
+    /// The `n` snapshots with the highest `score`, best first
+    ///
+    /// Keeps a min-heap of the best `n` seen so far, so ranking `N` symbols
+    /// costs `O(N log n)` and clones only snapshots that enter the heap.
+    fn top_by(&self, n: usize, score: impl Fn(&SymbolSnapshot) -> f64) -> Vec<SymbolSnapshot> {
+        if n == 0 {
+            return Vec::new();
+        }
+
+        let mut heap: BinaryHeap<Reverse<Ranked>> = BinaryHeap::with_capacity(n + 1);
+        for entry in self.snapshots.iter() {
+            let snapshot = entry.value();
+            let candidate_score = score(snapshot);
+            if heap.len() == n {
+                let Reverse(worst) = heap.peek().expect("heap holds n > 0 entries");
+                let order = rank_order(
+                    candidate_score,
+                    &snapshot.symbol,
+                    worst.score,
+                    &worst.snapshot.symbol,
+                );
+                if order != Ordering::Greater {
+                    continue;
+                }
+                heap.pop();
+            }
+            heap.push(Reverse(Ranked {
+                score: candidate_score,
+                snapshot: snapshot.clone(),
+            }));
+        }
+
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(ranked)| ranked.snapshot)
+            .collect()
+    }
+
     /// Set previous close price for a symbol
     pub fn set_prev_close(&self, symbol: &str, prev_close: f64) {
         if let Some(mut snapshot) = self.snapshots.get_mut(symbol) {
@@ -211,14 +344,95 @@ impl SnapshotManager {
         }
     }
 
+    /// Pre-populate snapshots from previous-day closing prices
+    ///
+    /// Subscribes each symbol and seeds its snapshot with a zero-volume tick
+    /// at `prev_close`, so `change_pct()` is measured against the prior close
+    /// from the first live tick. `limits` maps symbols to `(upper, lower)`.
+    ///
+    /// All closes are validated before any snapshot is touched, so an
+    /// invalid close leaves the manager unchanged.
+    pub fn initialize_from_closing_prices(
+        &self,
+        prev_closes: HashMap<String, f64>,
+        limits: HashMap<String, (f64, f64)>,
+    ) -> Result<()> {
+        let now = Utc::now();
+
+        let snapshots = prev_closes
+            .into_iter()
+            .map(|(symbol, prev_close)| {
+                let tick = Tick::new(symbol, now, prev_close, 0.0, prev_close, prev_close)?;
+                let mut snapshot = SymbolSnapshot::from_tick(&tick);
+                snapshot.prev_close = prev_close;
+                if let Some(&(upper, lower)) = limits.get(&snapshot.symbol) {
+                    snapshot.upper_limit = upper;
+                    snapshot.lower_limit = lower;
+                }
+                Ok(snapshot)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for snapshot in snapshots {
+            self.subscribe(&snapshot.symbol);
+            self.seeded.insert(snapshot.symbol.clone(), true);
+            self.snapshots.insert(snapshot.symbol.clone(), snapshot);
+        }
+
+        Ok(())
+    }
+
     /// Clear all snapshots
     pub fn clear(&self) {
         self.snapshots.clear();
+        self.filter_buffers.clear();
+        self.seeded.clear();
     }
 
-    /// Reset for new trading day (keep subscriptions, clear data)
+    /// Reset for new trading day (keep subscriptions and filter thresholds,
+    /// clear data)
     pub fn reset_for_new_day(&self) {
         self.snapshots.clear();
+        self.filter_buffers.clear();
+        self.seeded.clear();
+    }
+}
+
+/// A snapshot and its ranking score in [`SnapshotManager::top_by`]
+struct Ranked {
+    score: f64,
+    snapshot: SymbolSnapshot,
+}
+
+/// Ranking order: higher scores rank first, then symbols in name order
+fn rank_order(score: f64, symbol: &str, other_score: f64, other_symbol: &str) -> Ordering {
+    score
+        .total_cmp(&other_score)
+        .then_with(|| other_symbol.cmp(symbol))
+}
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> Ordering {
+        rank_order(
+            self.score,
+            &self.snapshot.symbol,
+            other.score,
+            &other.snapshot.symbol,
+        )
     }
 }
 
@@ -227,8 +441,181 @@ impl Clone for SnapshotManager {
         Self {
             snapshots: Arc::clone(&self.snapshots),
             subscriptions: Arc::clone(&self.subscriptions),
+            filter_thresholds: Arc::clone(&self.filter_thresholds),
+            filter_buffers: Arc::clone(&self.filter_buffers),
+            seeded: Arc::clone(&self.seeded),
+        }
+    }
+}
+
+/// Aggregate resting volume at one order book price
+#[cfg(feature = "level2")]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceLevel {
+    /// Level price
+    pub price: f64,
+    /// Total volume resting at the price
+    pub volume: f64,
+    /// Number of orders at the price
+    pub order_count: u32,
+}
+
+/// Top-N order book depth for a single symbol
+///
+/// Bids are ordered from the highest price down and asks from the lowest
+/// price up, so index 0 of each side is the best level.
+#[cfg(feature = "level2")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level2Snapshot {
+    /// Symbol identifier
+    pub symbol: String,
+    /// Last update timestamp
+    pub timestamp: DateTime<Utc>,
+    /// Bid levels, best first
+    pub bids: Vec<PriceLevel>,
+    /// Ask levels, best first
+    pub asks: Vec<PriceLevel>,
+}
+
+#[cfg(feature = "level2")]
+impl Level2Snapshot {
+    /// Create an empty book
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            timestamp: Utc::now(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+        }
+    }
+
+    /// Volume imbalance `(bid_vol - ask_vol) / (bid_vol + ask_vol)` over
+    /// the top `depth` levels of each side
+    ///
+    /// Ranges from -1 (only asks) to 1 (only bids); 0 if both sides are
+    /// empty.
+    pub fn book_imbalance(&self, depth: usize) -> f64 {
+        let bid_volume: f64 = self.bids.iter().take(depth).map(|l| l.volume).sum();
+        let ask_volume: f64 = self.asks.iter().take(depth).map(|l| l.volume).sum();
+        let total = bid_volume + ask_volume;
+        if total == 0.0 {
+            return 0.0;
+        }
+        (bid_volume - ask_volume) / total
+    }
+
+    /// Midpoint of the best bid and ask, if both sides are quoted
+    pub fn mid_price(&self) -> Option<f64> {
+        let (bid, ask) = (self.bids.first()?, self.asks.first()?);
+        Some((bid.price + ask.price) / 2.0)
+    }
+
+    /// Best bid and ask weighted by the volume on the opposite side
+    ///
+    /// `(bid * ask_vol + ask * bid_vol) / (bid_vol + ask_vol)` leans towards
+    /// the ask when bids outweigh asks, where the next trade is more likely
+    /// to lift the offer. Falls back to the plain mid without top-of-book
+    /// volume.
+    pub fn weighted_mid_price(&self) -> Option<f64> {
+        let (bid, ask) = (self.bids.first()?, self.asks.first()?);
+        let total = bid.volume + ask.volume;
+        if total <= 0.0 {
+            return Some((bid.price + ask.price) / 2.0);
+        }
+        Some((bid.price * ask.volume + ask.price * bid.volume) / total)
+    }
+
+    /// Spread between the average prices of buying and selling `volume`
+    ///
+    /// Walks each side of the book to fill `volume` units and returns the
+    /// average ask fill minus the average bid fill, the round-trip cost per
+    /// unit at that size. A non-positive `volume` gives the quoted spread.
+    /// Returns infinity if either side is too thin to fill `volume`.
+    pub fn spread_at_depth(&self, volume: f64) -> f64 {
+        match (
+            fill_price(&self.asks, volume),
+            fill_price(&self.bids, volume),
+        ) {
+            (Some(ask), Some(bid)) => ask - bid,
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+/// Average price of filling `volume` against `levels`, best level first
+#[cfg(feature = "level2")]
+fn fill_price(levels: &[PriceLevel], volume: f64) -> Option<f64> {
+    if volume <= 0.0 {
+        return levels.first().map(|l| l.price);
+    }
+
+    let mut remaining = volume;
+    let mut cost = 0.0;
+    for level in levels {
+        let filled = remaining.min(level.volume.max(0.0));
+        cost += filled * level.price;
+        remaining -= filled;
+        if remaining <= 0.0 {
+            return Some(cost / volume);
         }
     }
+    None
+}
+
+/// Thread-safe order book depth manager
+#[cfg(feature = "level2")]
+#[derive(Clone, Default)]
+pub struct Level2SnapshotManager {
+    /// Books by symbol
+    snapshots: Arc<DashMap<String, Level2Snapshot>>,
+}
+
+#[cfg(feature = "level2")]
+impl Level2SnapshotManager {
+    /// Create an empty manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the bid side of a symbol's book
+    ///
+    /// Levels are sorted best (highest price) first.
+    pub fn update_bid(&self, symbol: &str, mut levels: Vec<PriceLevel>) {
+        levels.sort_by(|a, b| b.price.total_cmp(&a.price));
+        self.update(symbol, |snapshot| snapshot.bids = levels);
+    }
+
+    /// Replace the ask side of a symbol's book
+    ///
+    /// Levels are sorted best (lowest price) first.
+    pub fn update_ask(&self, symbol: &str, mut levels: Vec<PriceLevel>) {
+        levels.sort_by(|a, b| a.price.total_cmp(&b.price));
+        self.update(symbol, |snapshot| snapshot.asks = levels);
+    }
+
+    /// Get the book of a symbol
+    pub fn get(&self, symbol: &str) -> Option<Level2Snapshot> {
+        self.snapshots.get(symbol).map(|r| r.clone())
+    }
+
+    /// Get number of tracked symbols
+    pub fn symbol_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Clear all books
+    pub fn clear(&self) {
+        self.snapshots.clear();
+    }
+
+    fn update(&self, symbol: &str, apply: impl FnOnce(&mut Level2Snapshot)) {
+        let mut snapshot = self
+            .snapshots
+            .entry(symbol.to_string())
+            .or_insert_with(|| Level2Snapshot::new(symbol));
+        snapshot.timestamp = Utc::now();
+        apply(&mut snapshot);
+    }
 }
 
 #[cfg(test)]
@@ -290,6 +677,87 @@ mod tests {
         assert_eq!(snapshot.low, 10.0);
     }
 
+    #[test]
+    fn test_outlier_filter() {
+        let manager = SnapshotManager::new();
+        manager.set_filter_threshold("TEST", 5.0).unwrap();
+        assert!(manager.set_filter_threshold("TEST", f64::NAN).is_err());
+        assert!(manager.set_filter_threshold("TEST", 0.0).is_err());
+        assert_eq!(manager.filter_threshold("TEST"), Some(5.0));
+        assert_eq!(manager.filter_threshold("OTHER"), None);
+
+        for i in 0..20 {
+            let tick = make_tick("TEST", 10.0 + 0.01 * (i % 4) as f64, 100.0);
+            manager.process_tick(&tick).unwrap();
+        }
+        manager
+            .process_tick(&make_tick("TEST", 1e6, 100.0))
+            .unwrap();
+
+        let snapshot = manager.get("TEST").unwrap();
+        assert_eq!(snapshot.high, 10.03);
+        assert_eq!(snapshot.last_price, 10.03);
+        assert_eq!(snapshot.volume, 2000.0);
+        let rejected = manager.rejected_ticks("TEST");
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].price, 1e6);
+
+        // A lasting move to a new level resumes snapshot updates
+        for _ in 0..10 {
+            manager
+                .process_tick(&make_tick("TEST", 12.0, 100.0))
+                .unwrap();
+        }
+        assert_eq!(manager.get("TEST").unwrap().last_price, 12.0);
+
+        // Symbols without a threshold are not filtered
+        manager
+            .process_tick(&make_tick("OTHER", 10.0, 100.0))
+            .unwrap();
+        manager
+            .process_tick(&make_tick("OTHER", 1e6, 100.0))
+            .unwrap();
+        assert_eq!(manager.get("OTHER").unwrap().last_price, 1e6);
+        assert!(manager.rejected_ticks("OTHER").is_empty());
+    }
+
+    #[test]
+    fn test_top_rankings() {
+        let manager = SnapshotManager::new();
+        // Changes of -4%, -2%, 0%, 2%, 4% twice over, so every change is tied
+        // between an "A" and a "B" symbol
+        for prefix in ["B", "A"] {
+            for i in 0..5 {
+                let symbol = format!("{}{}", prefix, i);
+                let price = 10.0 * (1.0 + 0.02 * (i as f64 - 2.0));
+                let volume = 100.0 * (i + 1) as f64;
+                manager
+                    .process_tick(&make_tick(&symbol, price, volume))
+                    .unwrap();
+                manager.set_prev_close(&symbol, 10.0);
+            }
+        }
+
+        let symbols = |snapshots: Vec<SymbolSnapshot>| -> Vec<String> {
+            snapshots.into_iter().map(|s| s.symbol).collect()
+        };
+        assert_eq!(symbols(manager.top_movers(3, false)), ["A4", "B4", "A3"]);
+        assert_eq!(symbols(manager.top_movers(3, true)), ["A0", "B0", "A1"]);
+        assert_eq!(symbols(manager.top_volume(2)), ["A4", "B4"]);
+        // Spreads are 0.02 on every symbol, widest in bps at the lowest price
+        assert_eq!(symbols(manager.top_spread_bps(2)), ["A0", "B0"]);
+
+        // Rankings agree with a full sort, and n beyond the universe is capped
+        let mut all = manager.get_all();
+        all.sort_by(|a, b| {
+            b.change_pct()
+                .total_cmp(&a.change_pct())
+                .then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        assert_eq!(symbols(manager.top_movers(100, false)), symbols(all));
+        assert!(manager.top_movers(0, false).is_empty());
+    }
+
     #[test]
     fn test_change_calculation() {
         let tick = make_tick("TEST", 11.0, 100.0);
@@ -310,4 +778,98 @@ mod tests {
         assert!(snapshot.is_at_upper_limit());
         assert!(!snapshot.is_at_lower_limit());
     }
+
+    #[test]
+    fn test_initialize_from_closing_prices() {
+        let manager = SnapshotManager::new();
+
+        let mut prev_closes = HashMap::new();
+        let mut limits = HashMap::new();
+        for i in 0..100 {
+            let symbol = format!("{:06}.SZ", i);
+            let prev_close = 10.0 + i as f64;
+            prev_closes.insert(symbol.clone(), prev_close);
+            limits.insert(symbol, (prev_close * 1.1, prev_close * 0.9));
+        }
+
+        manager
+            .initialize_from_closing_prices(prev_closes, limits)
+            .unwrap();
+        assert_eq!(manager.symbol_count(), 100);
+        assert!(manager.is_subscribed("000042.SZ"));
+
+        let seeded = manager.get("000042.SZ").unwrap();
+        assert_eq!(seeded.prev_close, 52.0);
+        assert_eq!(seeded.change_pct(), 0.0);
+        assert!((seeded.upper_limit - 57.2).abs() < 1e-10);
+
+        // First tick opens 5% above the previous close
+        let tick = make_tick("000042.SZ", 54.6, 100.0);
+        manager.process_tick(&tick).unwrap();
+
+        let snapshot = manager.get("000042.SZ").unwrap();
+        assert!((snapshot.change_pct() - 5.0).abs() < 1e-10);
+        assert_eq!(snapshot.open, 54.6);
+        assert_eq!(snapshot.low, 54.6);
+        assert_eq!(snapshot.volume, 100.0);
+        assert!((snapshot.upper_limit - 57.2).abs() < 1e-10);
+
+        // Later ticks update the session as usual
+        manager
+            .process_tick(&make_tick("000042.SZ", 54.0, 50.0))
+            .unwrap();
+        let snapshot = manager.get("000042.SZ").unwrap();
+        assert_eq!(snapshot.open, 54.6);
+        assert_eq!(snapshot.low, 54.0);
+        assert_eq!(snapshot.volume, 150.0);
+    }
+
+    #[test]
+    fn test_initialize_rejects_invalid_close() {
+        let manager = SnapshotManager::new();
+        let prev_closes = HashMap::from([("GOOD".to_string(), 10.0), ("TEST".to_string(), 0.0)]);
+
+        assert!(manager
+            .initialize_from_closing_prices(prev_closes, HashMap::new())
+            .is_err());
+        // Nothing is seeded when any close is invalid
+        assert_eq!(manager.symbol_count(), 0);
+        assert!(!manager.is_subscribed("GOOD"));
+    }
+
+    #[cfg(feature = "level2")]
+    #[test]
+    fn test_level2_snapshot() {
+        let level = |price: f64, volume: f64| PriceLevel {
+            price,
+            volume,
+            order_count: 1,
+        };
+        let manager = Level2SnapshotManager::new();
+        // Levels arrive unordered and are kept best first
+        manager.update_bid("TEST", vec![level(9.98, 300.0), level(9.99, 100.0)]);
+        manager.update_ask("TEST", vec![level(10.02, 200.0), level(10.01, 300.0)]);
+        assert_eq!(manager.symbol_count(), 1);
+
+        let book = manager.get("TEST").unwrap();
+        assert_eq!(book.bids[0].price, 9.99);
+        assert_eq!(book.asks[0].price, 10.01);
+        assert!((book.mid_price().unwrap() - 10.0).abs() < 1e-12);
+        // Heavier top ask pulls the weighted mid towards the bid
+        let weighted = book.weighted_mid_price().unwrap();
+        assert!((weighted - (9.99 * 300.0 + 10.01 * 100.0) / 400.0).abs() < 1e-12);
+
+        assert!((book.book_imbalance(1) - (100.0 - 300.0) / 400.0).abs() < 1e-12);
+        assert!((book.book_imbalance(10) - (400.0 - 500.0) / 900.0).abs() < 1e-12);
+
+        assert!((book.spread_at_depth(0.0) - 0.02).abs() < 1e-12);
+        assert!((book.spread_at_depth(100.0) - 0.02).abs() < 1e-12);
+        // 200 units: asks all at 10.01, bids 100 at 9.99 and 100 at 9.98
+        assert!((book.spread_at_depth(200.0) - (10.01 - 9.985)).abs() < 1e-12);
+        assert_eq!(book.spread_at_depth(1000.0), f64::INFINITY);
+
+        let empty = Level2Snapshot::new("EMPTY");
+        assert_eq!(empty.mid_price(), None);
+        assert_eq!(empty.book_imbalance(5), 0.0);
+    }
 }
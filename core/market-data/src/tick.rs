@@ -3,9 +3,11 @@
 //! Handles real-time tick data with high-performance processing.
 
 use chrono::{DateTime, Utc};
+use nalgebra::DMatrix;
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
+use crate::ohlcv::{Bar, BarPeriod};
 use crate::{MarketDataError, Result};
 
 /// A single tick representing a trade or quote update
@@ -92,17 +94,43 @@ pub struct TickBuffer {
     capacity: usize,
     /// Internal ring buffer
     buffer: VecDeque<Tick>,
+    /// Ticks rejected by [`TickBuffer::push_filtered`], oldest first
+    rejected: VecDeque<Tick>,
+    /// Consecutive rejected ticks consistent with one another
+    rejection_streak: usize,
+    /// Smallest price move, flooring the return volatility of the filter
+    price_step: f64,
 }
 
+/// Ticks a buffer needs before [`TickBuffer::push_filtered`] filters
+const MIN_FILTER_TICKS: usize = 10;
+
+/// Consistent rejections after which [`TickBuffer::push_filtered`] accepts
+/// the new price level
+const READMIT_AFTER_REJECTIONS: usize = 5;
+
+/// Default price step of a [`TickBuffer`], the usual exchange tick
+const DEFAULT_PRICE_STEP: f64 = 0.01;
+
 impl TickBuffer {
     /// Create a new tick buffer with specified capacity
     pub fn new(capacity: usize) -> Self {
         Self {
             capacity,
             buffer: VecDeque::with_capacity(capacity),
+            rejected: VecDeque::new(),
+            rejection_streak: 0,
+            price_step: DEFAULT_PRICE_STEP,
         }
     }
 
+    /// Set the price step that floors the outlier filter's volatility
+    /// (0.01 by default)
+    pub fn with_price_step(mut self, price_step: f64) -> Self {
+        self.price_step = price_step;
+        self
+    }
+
     /// Push a new tick, evicting oldest if at capacity
     pub fn push(&mut self, tick: Tick) {
         if self.buffer.len() >= self.capacity {
@@ -111,6 +139,71 @@ impl TickBuffer {
         self.buffer.push_back(tick);
     }
 
+    /// Push a tick unless its price is an outlier, returning whether it was
+    /// accepted
+    ///
+    /// The log return from the last accepted price is scored against the
+    /// standard deviation of the log returns in the buffer, floored at one
+    /// price step, and the tick is rejected if `|z| > z_threshold`. Rejected
+    /// ticks are kept for audit in [`TickBuffer::rejected_ticks`], up to the
+    /// buffer capacity. After 5 consecutive rejections whose prices are
+    /// consistent with one another the price has genuinely moved, and the
+    /// fifth is accepted as the new level. Ticks are accepted unfiltered
+    /// until the buffer holds 10 ticks. Fails if `z_threshold` is not a
+    /// positive finite number.
+    pub fn push_filtered(&mut self, tick: Tick, z_threshold: f64) -> Result<bool> {
+        check_z_threshold(z_threshold)?;
+        let Some(last) = self.buffer.back().map(|t| t.price) else {
+            self.push(tick);
+            return Ok(true);
+        };
+        if self.buffer.len() < MIN_FILTER_TICKS {
+            self.push(tick);
+            return Ok(true);
+        }
+
+        let returns: Vec<f64> = self
+            .buffer
+            .iter()
+            .zip(self.buffer.iter().skip(1))
+            .map(|(prev, next)| (next.price / prev.price).ln())
+            .collect();
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let std = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        let sigma = std.max(self.price_step / last);
+        let is_outlier = |from: f64| ((tick.price / from).ln() / sigma).abs() > z_threshold;
+
+        if !is_outlier(last) {
+            self.rejection_streak = 0;
+            self.push(tick);
+            return Ok(true);
+        }
+
+        self.rejection_streak = match self.rejected.back() {
+            Some(prev) if self.rejection_streak > 0 && !is_outlier(prev.price) => {
+                self.rejection_streak + 1
+            }
+            _ => 1,
+        };
+        if self.rejection_streak >= READMIT_AFTER_REJECTIONS {
+            self.rejection_streak = 0;
+            self.push(tick);
+            return Ok(true);
+        }
+
+        if self.rejected.len() >= self.capacity {
+            self.rejected.pop_front();
+        }
+        self.rejected.push_back(tick);
+        Ok(false)
+    }
+
+    /// Ticks rejected as outliers by [`TickBuffer::push_filtered`]
+    pub fn rejected_ticks(&self) -> &VecDeque<Tick> {
+        &self.rejected
+    }
+
     /// Get the latest tick
     pub fn latest(&self) -> Option<&Tick> {
         self.buffer.back()
@@ -155,9 +248,400 @@ impl TickBuffer {
             .collect()
     }
 
-    /// Clear all ticks
+    /// Downsample to one representative tick per symbol and bar period
+    ///
+    /// Ticks are grouped by symbol and aligned bar start. Each group yields
+    /// a tick with price = group VWAP (last price if the group has no
+    /// volume), volume = total volume, timestamp = midpoint between the
+    /// first and last tick, and bid/ask quotes from the last tick. Groups
+    /// are returned in order of their first tick.
+    pub fn resample(&self, period: BarPeriod) -> Vec<Tick> {
+        let mut groups: Vec<Vec<&Tick>> = Vec::new();
+        let mut index: HashMap<(&str, DateTime<Utc>), usize> = HashMap::new();
+
+        for tick in &self.buffer {
+            let key = (
+                tick.symbol.as_str(),
+                Bar::align_timestamp(tick.timestamp, period),
+            );
+            let group = *index.entry(key).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(tick);
+        }
+
+        groups
+            .into_iter()
+            .map(|group| {
+                let first = group[0];
+                let last = group[group.len() - 1];
+                let (turnover, volume) = group.iter().fold((0.0, 0.0), |(turnover, vol), t| {
+                    (turnover + t.turnover, vol + t.volume)
+                });
+                let price = if volume > 0.0 {
+                    turnover / volume
+                } else {
+                    last.price
+                };
+
+                Tick {
+                    symbol: first.symbol.clone(),
+                    timestamp: first.timestamp + (last.timestamp - first.timestamp) / 2,
+                    price,
+                    volume,
+                    turnover: price * volume,
+                    bid: last.bid,
+                    ask: last.ask,
+                    bid_volume: last.bid_volume,
+                    ask_volume: last.ask_volume,
+                }
+            })
+            .collect()
+    }
+
+    /// Realized variance `sum (ln(p_{t+1} / p_t))²` over the buffered ticks
+    ///
+    /// The variance of the log price over the whole buffer window, not per
+    /// tick. At high frequencies it is inflated by bid-ask bounce; see
+    /// [`ZhangMyklandAitSahalia`]. `None` with fewer than 2 ticks.
+    pub fn realized_variance(&self) -> Option<f64> {
+        if self.buffer.len() < 2 {
+            return None;
+        }
+        let log_prices: Vec<f64> = self.buffer.iter().map(|t| t.price.ln()).collect();
+        Some(ZhangMyklandAitSahalia::realized_variance(&log_prices, 1))
+    }
+
+    /// Parkinson (1980) range-based variance per bar
+    ///
+    /// `mean((ln H/L)²) / (4 ln 2)`, which for a driftless Brownian motion
+    /// is unbiased for the variance over one bar and about five times as
+    /// efficient as squared close-to-close returns. Discrete sampling of
+    /// the high and low biases it down slightly. `None` with fewer than 2
+    /// bars.
+    pub fn parkinson_variance(&self, bars: &[Bar]) -> Option<f64> {
+        if bars.len() < 2 {
+            return None;
+        }
+        let mean_sq_range = bars
+            .iter()
+            .map(|bar| (bar.high / bar.low).ln().powi(2))
+            .sum::<f64>()
+            / bars.len() as f64;
+        Some(mean_sq_range / (4.0 * std::f64::consts::LN_2))
+    }
+
+    /// Garman-Klass (1980) variance per bar
+    ///
+    /// `mean(0.5 (ln H/L)² - (2 ln 2 - 1) (ln C/O)²)`, adding the open and
+    /// close to the range for a further gain in efficiency over
+    /// [`TickBuffer::parkinson_variance`]. `None` with fewer than 2 bars.
+    pub fn garman_klass_variance(&self, bars: &[Bar]) -> Option<f64> {
+        if bars.len() < 2 {
+            return None;
+        }
+        let total: f64 = bars
+            .iter()
+            .map(|bar| {
+                let range = (bar.high / bar.low).ln();
+                let body = (bar.close / bar.open).ln();
+                0.5 * range * range - (2.0 * std::f64::consts::LN_2 - 1.0) * body * body
+            })
+            .sum();
+        Some(total / bars.len() as f64)
+    }
+
+    /// Clear all ticks, including rejected ones
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.rejected.clear();
+        self.rejection_streak = 0;
+    }
+}
+
+/// Check an outlier z-score threshold is a positive finite number
+pub(crate) fn check_z_threshold(z_threshold: f64) -> Result<()> {
+    if z_threshold > 0.0 && z_threshold.is_finite() {
+        Ok(())
+    } else {
+        Err(MarketDataError::InvalidParameter(format!(
+            "z threshold {} must be positive and finite",
+            z_threshold
+        )))
+    }
+}
+
+/// Tolerance, in ticks, for a price to count as on the tick grid
+const TICK_GRID_TOL: f64 = 1e-9;
+
+/// Minimum price increment and valid price range for an exchange or symbol
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceTick {
+    /// Minimum price increment (e.g., 0.01)
+    pub increment: f64,
+    /// Lowest valid price
+    pub min_price: f64,
+    /// Highest valid price
+    pub max_price: f64,
+}
+
+/// Issue found when validating a tick
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValidationWarning {
+    /// Price is not a multiple of the price increment
+    InvalidPriceIncrement {
+        symbol: String,
+        price: f64,
+        increment: f64,
+    },
+    /// Price lies outside the rule's valid range
+    PriceOutOfRange {
+        symbol: String,
+        price: f64,
+        min_price: f64,
+        max_price: f64,
+    },
+}
+
+/// Tick validator applying exchange price rules
+///
+/// Rules are keyed by symbol or by exchange suffix (the part after the last
+/// `.`, e.g. "SZ" for "000001.SZ"); a symbol rule takes precedence.
+#[derive(Debug, Clone, Default)]
+pub struct TickValidator {
+    /// Price rules by symbol or exchange
+    price_tick_rules: HashMap<String, PriceTick>,
+}
+
+impl TickValidator {
+    /// Create a validator with no rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the price tick rules
+    pub fn with_price_tick_rules(mut self, rules: HashMap<String, PriceTick>) -> Self {
+        self.price_tick_rules = rules;
+        self
+    }
+
+    /// Rule applying to a symbol, if any
+    pub fn rule_for(&self, symbol: &str) -> Option<&PriceTick> {
+        self.price_tick_rules.get(symbol).or_else(|| {
+            symbol
+                .rsplit_once('.')
+                .and_then(|(_, exchange)| self.price_tick_rules.get(exchange))
+        })
+    }
+
+    /// Check a tick against its price rule
+    ///
+    /// Ticks without a matching rule produce no warnings.
+    pub fn validate(&self, tick: &Tick) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+        let Some(rule) = self.rule_for(&tick.symbol) else {
+            return warnings;
+        };
+
+        if rule.increment > 0.0 {
+            let ticks = tick.price / rule.increment;
+            if (ticks - ticks.round()).abs() >= TICK_GRID_TOL {
+                warnings.push(ValidationWarning::InvalidPriceIncrement {
+                    symbol: tick.symbol.clone(),
+                    price: tick.price,
+                    increment: rule.increment,
+                });
+            }
+        }
+
+        if tick.price < rule.min_price || tick.price > rule.max_price {
+            warnings.push(ValidationWarning::PriceOutOfRange {
+                symbol: tick.symbol.clone(),
+                price: tick.price,
+                min_price: rule.min_price,
+                max_price: rule.max_price,
+            });
+        }
+
+        warnings
+    }
+}
+
+/// Round a price to the nearest multiple of `increment`, halves rounding up
+///
+/// Floating-point noise in `price / increment` is removed first so that
+/// decimal halves such as 10.005 on a 0.01 grid round up as written.
+pub fn round_to_tick(price: f64, increment: f64) -> f64 {
+    if increment <= 0.0 {
+        return price;
+    }
+
+    let ticks = ((price / increment) / TICK_GRID_TOL).round() * TICK_GRID_TOL;
+    let n = ticks.round();
+
+    // Divide by an integral inverse (100 for 0.01) to avoid 10.010000000000002
+    let inverse = 1.0 / increment;
+    if (inverse - inverse.round()).abs() < TICK_GRID_TOL {
+        n / inverse.round()
+    } else {
+        n * increment
+    }
+}
+
+/// Per-symbol buffer capacity of a [`TickBufferGroup`]
+const GROUP_BUFFER_CAPACITY: usize = 10_000;
+
+/// Tick buffers for a fixed set of symbols
+///
+/// Bridges tick data to the covariance estimators by aligning the symbols'
+/// price histories into a return matrix.
+pub struct TickBufferGroup {
+    /// Symbols in column order
+    symbols: Vec<String>,
+    /// One buffer per symbol
+    buffers: HashMap<String, TickBuffer>,
+}
+
+impl TickBufferGroup {
+    /// Create a group with one buffer per symbol
+    pub fn new(symbols: Vec<String>) -> Self {
+        let buffers = symbols
+            .iter()
+            .map(|s| (s.clone(), TickBuffer::new(GROUP_BUFFER_CAPACITY)))
+            .collect();
+        Self { symbols, buffers }
+    }
+
+    /// Symbols in column order
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    /// Buffer for a symbol
+    pub fn buffer(&self, symbol: &str) -> Option<&TickBuffer> {
+        self.buffers.get(symbol)
+    }
+
+    /// Push a tick into its symbol's buffer
+    pub fn push(&mut self, tick: Tick) -> Result<()> {
+        match self.buffers.get_mut(&tick.symbol) {
+            Some(buffer) => {
+                buffer.push(tick);
+                Ok(())
+            }
+            None => Err(MarketDataError::NotSubscribed(tick.symbol)),
+        }
+    }
+
+    /// Log returns of the last `n_obs` shared timestamps (n_obs x n_symbols)
+    ///
+    /// Prices are sampled at every tick timestamp from any symbol, starting
+    /// once all symbols have traded. A symbol without a tick at a timestamp
+    /// carries its last price forward, giving a zero return there. Columns
+    /// follow the order of `symbols`.
+    pub fn to_return_matrix(&self, n_obs: usize) -> Result<DMatrix<f64>> {
+        let mut timestamps: Vec<DateTime<Utc>> = self
+            .buffers
+            .values()
+            .flat_map(|b| b.buffer.iter().map(|t| t.timestamp))
+            .collect();
+        timestamps.sort_unstable();
+        timestamps.dedup();
+
+        // Forward-filled price of each symbol at each timestamp
+        let filled: Vec<Vec<Option<f64>>> = self
+            .symbols
+            .iter()
+            .map(|symbol| {
+                let mut ticks = self.buffers[symbol].buffer.iter().peekable();
+                let mut last = None;
+                timestamps
+                    .iter()
+                    .map(|&ts| {
+                        while let Some(tick) = ticks.next_if(|t| t.timestamp <= ts) {
+                            last = Some(tick.price);
+                        }
+                        last
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let first_shared = (0..timestamps.len())
+            .find(|&t| filled.iter().all(|prices| prices[t].is_some()))
+            .unwrap_or(timestamps.len());
+        let n_prices = timestamps.len() - first_shared;
+        if n_prices < n_obs + 1 {
+            return Err(MarketDataError::InsufficientObservations {
+                needed: n_obs + 1,
+                got: n_prices,
+            });
+        }
+
+        let start = timestamps.len() - n_obs - 1;
+        Ok(DMatrix::from_fn(n_obs, self.symbols.len(), |i, j| {
+            let prev = filled[j][start + i].unwrap_or_default();
+            let curr = filled[j][start + i + 1].unwrap_or_default();
+            (curr / prev).ln()
+        }))
+    }
+}
+
+/// Two Scales Realized Volatility (Zhang, Mykland & Aït-Sahalia, 2005)
+///
+/// Realized variance computed from every tick is dominated by bid-ask bounce:
+/// its bias grows linearly with the number of ticks. TSRV averages realized
+/// variance over `K` offset subgrids (slow scale) and subtracts the noise
+/// bias estimated from the all-tick realized variance (fast scale).
+pub struct ZhangMyklandAitSahalia;
+
+impl ZhangMyklandAitSahalia {
+    /// Estimate the integrated variance of log prices over the tick window
+    ///
+    /// # Arguments
+    /// * `ticks` - Ticks in time order
+    /// * `n_subsamples` - Number of subgrids `K` (slow scale samples every K ticks),
+    ///   at least 2 since a single subgrid is the fast scale itself
+    pub fn estimate_integrated_variance(ticks: &[Tick], n_subsamples: usize) -> Result<f64> {
+        if n_subsamples < 2 {
+            return Err(MarketDataError::InvalidParameter(format!(
+                "n_subsamples must be at least 2, got {}",
+                n_subsamples
+            )));
+        }
+        if ticks.len() < 2 * n_subsamples {
+            return Err(MarketDataError::InsufficientObservations {
+                needed: 2 * n_subsamples,
+                got: ticks.len(),
+            });
+        }
+
+        let log_prices: Vec<f64> = ticks.iter().map(|t| t.price.ln()).collect();
+        let n = log_prices.len() - 1;
+        let k = n_subsamples;
+
+        // Fast scale: realized variance over every tick
+        let rv_all = Self::realized_variance(&log_prices, 1);
+
+        // Slow scale: average of realized variances on K offset subgrids,
+        // which is the K-lag realized variance scaled by 1/K
+        let rv_avg = Self::realized_variance(&log_prices, k) / k as f64;
+
+        let n_bar = (n - k + 1) as f64 / k as f64;
+        let tsrv = rv_avg - n_bar / n as f64 * rv_all;
+
+        // Small-sample adjustment
+        Ok(tsrv / (1.0 - n_bar / n as f64))
+    }
+
+    /// Sum of squared `lag`-tick log returns
+    fn realized_variance(log_prices: &[f64], lag: usize) -> f64 {
+        log_prices
+            .iter()
+            .zip(&log_prices[lag..])
+            .map(|(p0, p1)| (p1 - p0).powi(2))
+            .sum()
     }
 }
 
@@ -165,6 +649,9 @@ impl TickBuffer {
 mod tests {
     use super::*;
     use chrono::TimeZone;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Normal};
 
     fn make_tick(symbol: &str, price: f64, volume: f64, secs: i64) -> Tick {
         Tick::new(
@@ -248,6 +735,73 @@ mod tests {
         assert_eq!(buffer.latest().unwrap().price, 11.5);
     }
 
+    #[test]
+    fn test_push_filtered() {
+        let mut buffer = TickBuffer::new(100);
+        for i in 0..50 {
+            let price = 10.0 + 0.01 * (i % 5) as f64;
+            let tick = make_tick("TEST", price, 100.0, i);
+            assert!(buffer.push_filtered(tick, 5.0).unwrap());
+        }
+
+        let spike = make_tick("TEST", 1e6, 100.0, 50);
+        assert!(!buffer.push_filtered(spike, 5.0).unwrap());
+        assert_eq!(buffer.len(), 50);
+        assert_eq!(buffer.latest().unwrap().price, 10.04);
+        assert_eq!(buffer.rejected_ticks().len(), 1);
+        assert_eq!(buffer.rejected_ticks()[0].price, 1e6);
+
+        // Ordinary moves still get through
+        let tick = make_tick("TEST", 10.05, 100.0, 51);
+        assert!(buffer.push_filtered(tick, 5.0).unwrap());
+        assert_eq!(buffer.len(), 51);
+
+        for z_threshold in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let tick = make_tick("TEST", 10.05, 100.0, 52);
+            assert!(matches!(
+                buffer.push_filtered(tick, z_threshold),
+                Err(MarketDataError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_push_filtered_flat_and_jumps() {
+        // A flat buffer still filters, with one price step as volatility
+        let mut flat = TickBuffer::new(100);
+        for i in 0..20 {
+            flat.push(make_tick("TEST", 10.0, 100.0, i));
+        }
+        assert!(!flat
+            .push_filtered(make_tick("TEST", 1e6, 100.0, 20), 5.0)
+            .unwrap());
+        assert!(flat
+            .push_filtered(make_tick("TEST", 10.01, 100.0, 21), 5.0)
+            .unwrap());
+
+        // A genuine jump is re-admitted once it persists, and later ticks
+        // at the new level are accepted
+        let mut accepted = Vec::new();
+        for i in 0..10 {
+            let tick = make_tick("TEST", 12.0 + 0.01 * (i % 2) as f64, 100.0, 30 + i);
+            accepted.push(flat.push_filtered(tick, 5.0).unwrap());
+        }
+        assert_eq!(accepted[..4], [false; 4]);
+        assert!(accepted[4..].iter().all(|&a| a));
+        assert_eq!(flat.latest().unwrap().price, 12.01);
+
+        // An isolated spike does not count towards re-admitting its level
+        let mut buffer = TickBuffer::new(100);
+        for i in 0..20 {
+            buffer.push(make_tick("TEST", 10.0 + 0.01 * (i % 2) as f64, 100.0, i));
+        }
+        for i in 0..8 {
+            let price = if i % 2 == 0 { 1e6 } else { 10.0 };
+            let tick = make_tick("TEST", price, 100.0, 20 + i);
+            assert_eq!(buffer.push_filtered(tick, 5.0).unwrap(), price == 10.0);
+        }
+    }
+
     #[test]
     fn test_vwap() {
         let mut buffer = TickBuffer::new(10);
@@ -257,4 +811,266 @@ mod tests {
         // VWAP = 3000 / 200 = 15.0
         assert!((buffer.vwap().unwrap() - 15.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_resample_to_minute() {
+        let mut buffer = TickBuffer::new(100);
+        for i in 0..60 {
+            let price = 10.0 + 0.01 * (i % 7) as f64;
+            buffer.push(make_tick("TEST", price, 100.0 + i as f64, i));
+        }
+
+        let resampled = buffer.resample(BarPeriod::Minute1);
+        assert_eq!(resampled.len(), 1);
+
+        let tick = &resampled[0];
+        let total_volume: f64 = (0..60).map(|i| 100.0 + i as f64).sum();
+        assert!((tick.price - buffer.vwap().unwrap()).abs() < 1e-12);
+        assert!((tick.volume - total_volume).abs() < 1e-9);
+        assert_eq!(tick.timestamp, Utc.timestamp_opt(29, 500_000_000).unwrap());
+        assert_eq!(tick.bid, buffer.latest().unwrap().bid);
+        assert_eq!(tick.ask, buffer.latest().unwrap().ask);
+
+        // A second minute starts a new group
+        buffer.push(make_tick("TEST", 11.0, 50.0, 60));
+        let resampled = buffer.resample(BarPeriod::Minute1);
+        assert_eq!(resampled.len(), 2);
+        assert_eq!(resampled[1].price, 11.0);
+        assert_eq!(resampled[1].volume, 50.0);
+    }
+
+    #[test]
+    fn test_tsrv_removes_microstructure_noise() {
+        // One trading day of 1-second ticks, 1% daily volatility
+        let n_ticks = 23_400;
+        let true_iv = 1e-4;
+        let noise_sd = 5e-4;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let step = Normal::new(0.0, (true_iv / (n_ticks - 1) as f64).sqrt()).unwrap();
+        let noise = Normal::new(0.0, noise_sd).unwrap();
+
+        let mut efficient = 100.0_f64.ln();
+        let ticks: Vec<Tick> = (0..n_ticks)
+            .map(|i| {
+                if i > 0 {
+                    efficient += step.sample(&mut rng);
+                }
+                let observed = (efficient + noise.sample(&mut rng)).exp();
+                make_tick("TEST", observed, 100.0, i as i64)
+            })
+            .collect();
+
+        let tsrv = ZhangMyklandAitSahalia::estimate_integrated_variance(&ticks, 300).unwrap();
+
+        let log_prices: Vec<f64> = ticks.iter().map(|t| t.price.ln()).collect();
+        let raw_rv = ZhangMyklandAitSahalia::realized_variance(&log_prices, 1);
+
+        assert!((tsrv - true_iv).abs() < (raw_rv - true_iv).abs());
+        assert!((tsrv - true_iv).abs() / true_iv < 0.5);
+    }
+
+    #[test]
+    fn test_range_based_variance() {
+        // 2000 days of 500 driftless GBM steps with daily variance 1e-4
+        let (n_days, n_steps, daily_var) = (2000, 500, 1e-4);
+        let mut rng = StdRng::seed_from_u64(7);
+        let step = Normal::new(0.0, (daily_var / n_steps as f64).sqrt()).unwrap();
+
+        let mut close = 100.0_f64;
+        let mut bars = Vec::with_capacity(n_days);
+        let mut realized = Vec::with_capacity(n_days);
+        for _ in 0..n_days {
+            let mut buffer = TickBuffer::new(n_steps + 1);
+            let mut price = close;
+            buffer.push(make_tick("TEST", price, 100.0, 0));
+            for i in 1..=n_steps {
+                price *= step.sample(&mut rng).exp();
+                buffer.push(make_tick("TEST", price, 100.0, i as i64));
+            }
+            realized.push(buffer.realized_variance().unwrap());
+
+            let prices = buffer.buffer.iter().map(|t| t.price);
+            let first = buffer.buffer.front().unwrap();
+            let bar = Bar {
+                open: close,
+                high: prices.clone().fold(f64::MIN, f64::max),
+                low: prices.fold(f64::MAX, f64::min),
+                close: price,
+                ..Bar::new(first, BarPeriod::Daily)
+            };
+            bars.push(bar);
+            close = price;
+        }
+
+        let buffer = TickBuffer::new(1);
+        let mean_rv = realized.iter().sum::<f64>() / n_days as f64;
+        let parkinson = buffer.parkinson_variance(&bars).unwrap();
+        let garman_klass = buffer.garman_klass_variance(&bars).unwrap();
+        for estimate in [mean_rv, parkinson, garman_klass] {
+            assert!((estimate / daily_var - 1.0).abs() < 0.1, "{}", estimate);
+        }
+
+        // Over pairs of days, the range estimate varies far less than the
+        // mean squared close-to-close return
+        let spread = |estimates: Vec<f64>| {
+            let mean = estimates.iter().sum::<f64>() / estimates.len() as f64;
+            estimates.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / estimates.len() as f64
+        };
+        let pairs = bars.chunks(2);
+        let range_spread = spread(
+            pairs
+                .clone()
+                .map(|pair| buffer.parkinson_variance(pair).unwrap())
+                .collect(),
+        );
+        let close_spread = spread(
+            pairs
+                .map(|pair| {
+                    pair.iter()
+                        .map(|b| (b.close / b.open).ln().powi(2))
+                        .sum::<f64>()
+                        / 2.0
+                })
+                .collect(),
+        );
+        assert!(range_spread < 0.5 * close_spread);
+
+        assert_eq!(buffer.realized_variance(), None);
+        assert_eq!(buffer.parkinson_variance(&bars[..1]), None);
+        assert_eq!(buffer.garman_klass_variance(&bars[..1]), None);
+    }
+
+    #[test]
+    fn test_tsrv_insufficient_ticks() {
+        let ticks: Vec<Tick> = (0..9)
+            .map(|i| make_tick("TEST", 10.0 + i as f64 * 0.01, 100.0, i))
+            .collect();
+
+        assert!(matches!(
+            ZhangMyklandAitSahalia::estimate_integrated_variance(&ticks, 5),
+            Err(MarketDataError::InsufficientObservations { needed: 10, got: 9 })
+        ));
+    }
+
+    #[test]
+    fn test_tsrv_invalid_subsamples() {
+        let ticks: Vec<Tick> = (0..20)
+            .map(|i| make_tick("TEST", 10.0 + i as f64 * 0.01, 100.0, i))
+            .collect();
+
+        for n_subsamples in [0, 1] {
+            assert!(matches!(
+                ZhangMyklandAitSahalia::estimate_integrated_variance(&ticks, n_subsamples),
+                Err(MarketDataError::InvalidParameter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_group_return_matrix() {
+        let symbols = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let mut group = TickBufferGroup::new(symbols);
+
+        // A trades every second, B every other second, C starts late
+        for secs in 0..6 {
+            group
+                .push(make_tick("A", 10.0 + secs as f64, 100.0, secs))
+                .unwrap();
+        }
+        for secs in [0, 2, 4] {
+            group
+                .push(make_tick("B", 20.0 + secs as f64, 100.0, secs))
+                .unwrap();
+        }
+        for secs in [1, 5] {
+            group
+                .push(make_tick("C", 30.0 + secs as f64, 100.0, secs))
+                .unwrap();
+        }
+
+        assert!(matches!(
+            group.push(make_tick("D", 1.0, 100.0, 0)),
+            Err(MarketDataError::NotSubscribed(s)) if s == "D"
+        ));
+
+        let returns = group.to_return_matrix(3).unwrap();
+        assert_eq!(returns.shape(), (3, 3));
+
+        // Rows are the moves into seconds 3, 4 and 5
+        assert!((returns[(0, 0)] - (13.0_f64 / 12.0).ln()).abs() < 1e-12);
+        assert!((returns[(2, 0)] - (15.0_f64 / 14.0).ln()).abs() < 1e-12);
+
+        // B is forward-filled at second 3, then jumps at second 4
+        assert_eq!(returns[(0, 1)], 0.0);
+        assert!((returns[(1, 1)] - (24.0_f64 / 22.0).ln()).abs() < 1e-12);
+        assert_eq!(returns[(2, 1)], 0.0);
+
+        // C only moves at second 5
+        assert_eq!(returns[(0, 2)], 0.0);
+        assert_eq!(returns[(1, 2)], 0.0);
+        assert!((returns[(2, 2)] - (35.0_f64 / 31.0).ln()).abs() < 1e-12);
+
+        // C's first tick is at second 1, leaving five shared prices
+        assert!(matches!(
+            group.to_return_matrix(5),
+            Err(MarketDataError::InsufficientObservations { needed: 6, got: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_price_tick_rules() {
+        let mut rules = HashMap::new();
+        rules.insert(
+            "SZ".to_string(),
+            PriceTick {
+                increment: 0.01,
+                min_price: 0.01,
+                max_price: 10_000.0,
+            },
+        );
+        rules.insert(
+            "159915.SZ".to_string(),
+            PriceTick {
+                increment: 0.001,
+                min_price: 0.001,
+                max_price: 1_000.0,
+            },
+        );
+        let validator = TickValidator::new().with_price_tick_rules(rules);
+
+        assert!(validator
+            .validate(&make_tick("000001.SZ", 10.01, 100.0, 0))
+            .is_empty());
+        assert_eq!(
+            validator.validate(&make_tick("000001.SZ", 10.005, 100.0, 0)),
+            vec![ValidationWarning::InvalidPriceIncrement {
+                symbol: "000001.SZ".to_string(),
+                price: 10.005,
+                increment: 0.01,
+            }]
+        );
+
+        // The ETF's symbol rule allows a finer increment than its exchange
+        assert!(validator
+            .validate(&make_tick("159915.SZ", 2.345, 100.0, 0))
+            .is_empty());
+        assert!(matches!(
+            validator.validate(&make_tick("159915.SZ", 2000.0, 100.0, 0))[..],
+            [ValidationWarning::PriceOutOfRange { .. }]
+        ));
+
+        // No rule, no warnings
+        assert!(validator
+            .validate(&make_tick("AAPL", 1.2345, 100.0, 0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(10.005, 0.01), 10.01);
+        assert_eq!(round_to_tick(10.004, 0.01), 10.0);
+        assert_eq!(round_to_tick(2.3456, 0.001), 2.346);
+        assert_eq!(round_to_tick(101.3, 0.25), 101.25);
+    }
 }
@@ -0,0 +1,210 @@
+//! Intraday volume profiles
+//!
+//! Average share of daily volume traded in each time-of-day bucket, used to
+//! slice VWAP orders in proportion to expected market volume.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::ohlcv::{Bar, BarPeriod, BarType};
+use crate::{MarketDataError, Result};
+
+/// Seconds in a day
+const SECONDS_PER_DAY: u32 = 86_400;
+
+/// Fraction of daily volume by time of day
+///
+/// Buckets are the bar period of the history used to estimate the profile
+/// and are keyed by their start time in UTC, the timezone of bar
+/// timestamps. The fractions sum to one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntraVolumeProfile {
+    /// Bucket length in seconds
+    bucket_seconds: u32,
+    /// Volume fraction by bucket start, in seconds after midnight
+    fractions: BTreeMap<u32, f64>,
+}
+
+impl IntraVolumeProfile {
+    /// Estimate the profile from intraday bar history
+    ///
+    /// Each day's bar volumes are converted to fractions of that day's
+    /// total, then averaged per bucket across days; a bucket with no bar on
+    /// some day counts as zero for that day. Days without volume are
+    /// skipped. All bars must be time bars sharing one intraday period.
+    pub fn estimate(bars: &[Bar]) -> Result<Self> {
+        let period = match bars.first() {
+            Some(bar) => bar.period(),
+            None => return Err(MarketDataError::InsufficientObservations { needed: 1, got: 0 }),
+        };
+        let Some(period) = period.filter(|p| (1..SECONDS_PER_DAY as i64).contains(&p.seconds()))
+        else {
+            return Err(MarketDataError::AggregationError(
+                "volume profile needs intraday time bars".to_string(),
+            ));
+        };
+        if let Some(bar) = bars.iter().find(|bar| bar.period() != Some(period)) {
+            return Err(MarketDataError::AggregationError(format!(
+                "mixed bar types {:?} and {:?}",
+                BarType::Time(period),
+                bar.bar_type
+            )));
+        }
+        let bucket_seconds = period.seconds() as u32;
+
+        let mut days: HashMap<NaiveDate, Vec<(u32, f64)>> = HashMap::new();
+        for bar in bars {
+            let seconds = bar.timestamp.time().num_seconds_from_midnight();
+            let bucket = seconds - seconds % bucket_seconds;
+            days.entry(bar.timestamp.date_naive())
+                .or_default()
+                .push((bucket, bar.volume.max(0.0)));
+        }
+
+        let mut totals: BTreeMap<u32, f64> = BTreeMap::new();
+        let mut n_days = 0;
+        for day in days.values() {
+            let day_volume: f64 = day.iter().map(|(_, volume)| volume).sum();
+            if day_volume <= 0.0 {
+                continue;
+            }
+            n_days += 1;
+            for &(bucket, volume) in day {
+                *totals.entry(bucket).or_insert(0.0) += volume / day_volume;
+            }
+        }
+        if n_days == 0 {
+            return Err(MarketDataError::AggregationError(
+                "bar history has no volume".to_string(),
+            ));
+        }
+
+        // Each day contributes fractions summing to one; renormalize away rounding
+        let sum: f64 = totals.values().sum();
+        let fractions = totals
+            .into_iter()
+            .map(|(bucket, total)| (bucket, total / sum))
+            .collect();
+
+        Ok(Self {
+            bucket_seconds,
+            fractions,
+        })
+    }
+
+    /// Bucket length in seconds
+    pub fn bucket_seconds(&self) -> u32 {
+        self.bucket_seconds
+    }
+
+    /// Bucket start times and their volume fractions, in time order
+    pub fn buckets(&self) -> impl Iterator<Item = (NaiveTime, f64)> + '_ {
+        self.fractions.iter().map(|(&seconds, &fraction)| {
+            let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds, 0)
+                .expect("bucket start is within the day");
+            (time, fraction)
+        })
+    }
+
+    /// Expected fraction of daily volume traded in `[bar_time, bar_time + period)`
+    ///
+    /// Buckets partially covered by the interval contribute in proportion
+    /// to the overlap, so periods shorter or longer than the profile's
+    /// buckets are both supported. Intervals run to the end of the day at
+    /// most.
+    pub fn expected_volume_fraction(&self, bar_time: NaiveTime, period: BarPeriod) -> f64 {
+        let start = bar_time.num_seconds_from_midnight();
+        let length = period.seconds().clamp(0, SECONDS_PER_DAY as i64) as u32;
+        let end = (start + length).min(SECONDS_PER_DAY);
+        let first_bucket = start - start % self.bucket_seconds;
+
+        self.fractions
+            .range(first_bucket..end)
+            .map(|(&bucket, &fraction)| {
+                let overlap = (bucket + self.bucket_seconds).min(end) - bucket.max(start);
+                fraction * overlap as f64 / self.bucket_seconds as f64
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    /// Ten days of 1-minute bars from 09:30 to 11:30 with a U-shaped volume
+    /// curve and a spike at 10:00
+    fn make_history() -> Vec<Bar> {
+        let mut bars = Vec::new();
+        for day in 0..10 {
+            let open = Utc.with_ymd_and_hms(2024, 3, 4 + day, 9, 30, 0).unwrap();
+            let day_scale = 1.0 + 0.1 * day as f64;
+            for minute in 0..120 {
+                let distance = (minute as f64 - 59.5).abs();
+                let mut volume = 1000.0 + 20.0 * distance * distance;
+                if minute == 30 {
+                    volume *= 4.0;
+                }
+                bars.push(Bar {
+                    symbol: "600000.SH".to_string(),
+                    timestamp: open + Duration::minutes(minute),
+                    bar_type: BarType::Time(BarPeriod::Minute1),
+                    open: 10.0,
+                    high: 10.1,
+                    low: 9.9,
+                    close: 10.0,
+                    volume: volume * day_scale,
+                    turnover: volume * day_scale * 10.0,
+                    tick_count: 10,
+                    vwap: 10.0,
+                });
+            }
+        }
+        bars
+    }
+
+    #[test]
+    fn test_profile_from_minute_bars() {
+        let profile = IntraVolumeProfile::estimate(&make_history()).unwrap();
+        assert_eq!(profile.bucket_seconds(), 60);
+
+        let buckets: Vec<(NaiveTime, f64)> = profile.buckets().collect();
+        assert_eq!(buckets.len(), 120);
+        assert!(buckets.iter().all(|&(_, fraction)| fraction >= 0.0));
+        let total: f64 = buckets.iter().map(|(_, fraction)| fraction).sum();
+        assert!((total - 1.0).abs() < 1e-12);
+
+        let busiest = buckets.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert_eq!(busiest.0, NaiveTime::from_hms_opt(10, 0, 0).unwrap());
+        assert_eq!(
+            profile.expected_volume_fraction(busiest.0, BarPeriod::Minute1),
+            busiest.1
+        );
+
+        // Coarser periods sum buckets, finer intervals take a share of one
+        let open = NaiveTime::from_hms_opt(9, 30, 0).unwrap();
+        let first_five: f64 = buckets[..5].iter().map(|(_, fraction)| fraction).sum();
+        assert!(
+            (profile.expected_volume_fraction(open, BarPeriod::Minute5) - first_five).abs() < 1e-15
+        );
+        let half_minute = NaiveTime::from_hms_opt(9, 30, 30).unwrap();
+        let shifted = profile.expected_volume_fraction(half_minute, BarPeriod::Minute1);
+        assert!((shifted - 0.5 * (buckets[0].1 + buckets[1].1)).abs() < 1e-15);
+
+        let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
+        assert!((profile.expected_volume_fraction(midnight, BarPeriod::Daily) - 1.0).abs() < 1e-12);
+        let lunch = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        assert_eq!(
+            profile.expected_volume_fraction(lunch, BarPeriod::Minute30),
+            0.0
+        );
+
+        assert!(matches!(
+            IntraVolumeProfile::estimate(&[]),
+            Err(MarketDataError::InsufficientObservations { .. })
+        ));
+    }
+}
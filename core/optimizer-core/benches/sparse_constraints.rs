@@ -0,0 +1,52 @@
+//! Sparse vs dense sector constraint matrices
+//!
+//! Builds a sector exposure constraint over 500 assets in 10 sectors,
+//! reports the storage of the CSR matrix against the dense rows, and
+//! compares `evaluate` against a dense matrix-vector product.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use optimizer_core::constraints::LinearConstraint;
+
+const N_ASSETS: usize = 500;
+const N_SECTORS: usize = 10;
+
+fn bench_sector_constraint(c: &mut Criterion) {
+    let sectors: Vec<usize> = (0..N_ASSETS).map(|i| i % N_SECTORS).collect();
+    let constraint = LinearConstraint::sector_exposure(&sectors, N_SECTORS, 0.2);
+    let dense = constraint.dense_matrix();
+    let weights = vec![1.0 / N_ASSETS as f64; N_ASSETS];
+
+    let sparse = constraint.sparse_matrix();
+    let sparse_bytes = std::mem::size_of_val(sparse.indptr().raw_storage())
+        + std::mem::size_of_val(sparse.indices())
+        + std::mem::size_of_val(sparse.data());
+    let dense_bytes = N_SECTORS * N_ASSETS * std::mem::size_of::<f64>();
+    println!(
+        "sector constraint storage: dense {} bytes, sparse {} bytes ({:.1}x)",
+        dense_bytes,
+        sparse_bytes,
+        dense_bytes as f64 / sparse_bytes as f64
+    );
+
+    let mut group = c.benchmark_group("sector_constraint_500");
+    group.bench_function("sparse", |b| {
+        b.iter(|| constraint.evaluate(black_box(&weights)))
+    });
+    group.bench_function("dense", |b| {
+        b.iter(|| {
+            dense
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(black_box(&weights))
+                        .map(|(a, w)| a * w)
+                        .sum::<f64>()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_sector_constraint);
+criterion_main!(benches);
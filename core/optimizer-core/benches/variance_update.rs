@@ -0,0 +1,55 @@
+//! Incremental variance update vs full recomputation
+//!
+//! Changes two weights of an N-asset portfolio and compares
+//! `variance_update` against `portfolio_variance` as N grows.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use optimizer_core::problem::OptimizationProblem;
+
+fn make_problem(n: usize) -> OptimizationProblem {
+    let vols: Vec<f64> = (0..n).map(|i| 0.15 + 0.0001 * i as f64).collect();
+    let covariance = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    if i == j {
+                        vols[i] * vols[i]
+                    } else {
+                        0.25 * vols[i] * vols[j]
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    OptimizationProblem::builder(n)
+        .expected_returns(vec![0.05; n])
+        .covariance(covariance)
+        .build()
+        .unwrap()
+}
+
+fn bench_variance_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("variance_after_two_trades");
+
+    for n in [100, 400, 1600] {
+        let problem = make_problem(n);
+        let old = vec![1.0 / n as f64; n];
+        let old_variance = problem.portfolio_variance(&old);
+        let delta = [(3, 0.01), (n - 1, -0.01)];
+        let mut new = old.clone();
+        new[3] += 0.01;
+        new[n - 1] -= 0.01;
+
+        group.bench_with_input(BenchmarkId::new("full", n), &n, |b, _| {
+            b.iter(|| problem.portfolio_variance(black_box(&new)))
+        });
+        group.bench_with_input(BenchmarkId::new("incremental", n), &n, |b, _| {
+            b.iter(|| problem.variance_update(black_box(&old), old_variance, black_box(&delta)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_variance_update);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+//! Portfolio diversification analytics
+//!
+//! Weight concentration says little about risk concentration: an equally
+//! weighted portfolio of highly correlated assets is one bet. These
+//! measures work from the covariance instead.
+
+use nalgebra::{DMatrix, DVector};
+
+/// Diversification ratio `sum(w_i σ_i) / σ_p`
+///
+/// The weighted average asset volatility over the portfolio volatility. It
+/// is 1 when all assets are perfectly correlated and grows as correlations
+/// fall, reaching `sqrt(n)` for `n` equally weighted uncorrelated assets of
+/// equal volatility. Returns NaN for a portfolio with zero volatility.
+///
+/// # Panics
+///
+/// Panics if `weights` and `covariance` dimensions differ.
+pub fn diversification_ratio(weights: &[f64], covariance: &DMatrix<f64>) -> f64 {
+    let w = DVector::from_column_slice(weights);
+    let portfolio_vol = w.dot(&(covariance * &w)).max(0.0).sqrt();
+    if portfolio_vol == 0.0 {
+        return f64::NAN;
+    }
+
+    let weighted_vol: f64 = weights
+        .iter()
+        .enumerate()
+        .map(|(i, wi)| wi * covariance[(i, i)].max(0.0).sqrt())
+        .sum();
+    weighted_vol / portfolio_vol
+}
+
+/// Effective number of bets `exp(-sum(rc_i ln rc_i))`
+///
+/// The exponential entropy of the percentage risk contributions `rc_i = w_i
+/// (Σw)_i / w'Σw`: `n` when every asset contributes equally and 1 when a
+/// single asset carries all the risk. Assets with non-positive
+/// contributions (hedges) are left out of the entropy. Returns NaN for a
+/// portfolio with zero variance.
+///
+/// # Panics
+///
+/// Panics if `weights` and `covariance` dimensions differ.
+pub fn effective_number_of_bets(weights: &[f64], covariance: &DMatrix<f64>) -> f64 {
+    let w = DVector::from_column_slice(weights);
+    let marginal = covariance * &w;
+    let variance = w.dot(&marginal);
+    if variance <= 0.0 {
+        return f64::NAN;
+    }
+
+    let entropy: f64 = w
+        .component_mul(&marginal)
+        .iter()
+        .map(|c| c / variance)
+        .filter(|&rc| rc > 0.0)
+        .map(|rc| -rc * rc.ln())
+        .sum();
+    entropy.exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diversification_ratio() {
+        // Equal-weight uncorrelated assets with equal vol: DR = sqrt(n), and
+        // a single uncorrelated holding (or any fully correlated mix) is 1
+        let diagonal = DMatrix::from_diagonal_element(4, 4, 0.04);
+        assert!((diversification_ratio(&[0.25; 4], &diagonal) - 2.0).abs() < 1e-12);
+        assert!((diversification_ratio(&[1.0, 0.0, 0.0, 0.0], &diagonal) - 1.0).abs() < 1e-12);
+
+        let vols = [0.1, 0.2, 0.3];
+        let full_corr = DMatrix::from_fn(3, 3, |i, j| vols[i] * vols[j]);
+        for weights in [[1.0 / 3.0; 3], [0.5, 0.3, 0.2], [0.1, 0.1, 0.8]] {
+            assert!((diversification_ratio(&weights, &full_corr) - 1.0).abs() < 1e-12);
+        }
+
+        assert!(diversification_ratio(&[0.0; 4], &diagonal).is_nan());
+    }
+
+    #[test]
+    fn test_effective_number_of_bets() {
+        // Inverse-volatility weights on uncorrelated assets equalize risk
+        let vols = [0.1, 0.2, 0.25, 0.4, 0.5];
+        let covariance =
+            DMatrix::from_fn(5, 5, |i, j| if i == j { vols[i] * vols[i] } else { 0.0 });
+        let inverse: Vec<f64> = vols.iter().map(|v| 1.0 / v).collect();
+        let total: f64 = inverse.iter().sum();
+        let weights: Vec<f64> = inverse.iter().map(|x| x / total).collect();
+        assert!((effective_number_of_bets(&weights, &covariance) - 5.0).abs() < 1e-10);
+
+        // Equal weights concentrate risk in the volatile assets
+        let equal = effective_number_of_bets(&[0.2; 5], &covariance);
+        assert!(equal > 1.0 && equal < 5.0);
+
+        // A single holding is a single bet
+        let single = effective_number_of_bets(&[0.0, 0.0, 1.0, 0.0, 0.0], &covariance);
+        assert!((single - 1.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,293 @@
+//! Black-Litterman expected returns
+//!
+//! Starts from the returns implied by market equilibrium, `Π = δ Σ w_mkt`,
+//! and tilts them towards investor views in proportion to how confident
+//! each view is. The posterior returns feed straight into mean-variance
+//! optimization, which without views reproduces the market portfolio.
+
+use covariance::matrix::{inverse_spd, vec_to_dmatrix};
+use nalgebra::{DMatrix, DVector};
+
+use crate::{OptimizerError, Result};
+
+/// A view `p'μ = expected`, held with confidence in (0, 1)
+#[derive(Debug, Clone, PartialEq)]
+struct View {
+    picks: Vec<(usize, f64)>,
+    expected: f64,
+    confidence: f64,
+}
+
+/// Black-Litterman model over market equilibrium and investor views
+///
+/// Each view is a portfolio `p_k` whose expected return is `Q_k`. Its
+/// uncertainty is `ω_k = (1 - c_k) / c_k * p_k' τΣ p_k` for confidence
+/// `c_k`: a confidence of 0.5 trusts the view as much as the prior (He and
+/// Litterman's choice of Ω), and confidences close to 1 pin the posterior
+/// to the view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackLittermanModel {
+    /// Market capitalization weights w_mkt
+    pub equilibrium_weights: Vec<f64>,
+    /// Asset return covariance Σ
+    pub covariance: Vec<Vec<f64>>,
+    /// Market risk aversion δ
+    pub risk_aversion: f64,
+    /// Scale τ of the uncertainty in the equilibrium returns
+    pub tau: f64,
+    views: Vec<View>,
+}
+
+impl BlackLittermanModel {
+    /// Create a model without views
+    pub fn new(
+        equilibrium_weights: Vec<f64>,
+        covariance: Vec<Vec<f64>>,
+        risk_aversion: f64,
+        tau: f64,
+    ) -> Self {
+        Self {
+            equilibrium_weights,
+            covariance,
+            risk_aversion,
+            tau,
+            views: Vec::new(),
+        }
+    }
+
+    /// Add the view that asset `asset_idx` returns `expected`
+    pub fn add_absolute_view(mut self, asset_idx: usize, expected: f64, confidence: f64) -> Self {
+        self.views.push(View {
+            picks: vec![(asset_idx, 1.0)],
+            expected,
+            confidence,
+        });
+        self
+    }
+
+    /// Add the view that one basket outperforms another by `expected`
+    ///
+    /// The view portfolio is long `long_assets` and short `short_assets`,
+    /// each given as `(asset index, weight)` with positive weights, e.g.
+    /// `&[(0, 0.5), (1, 0.5)]` against `&[(2, 1.0)]`.
+    pub fn add_relative_view(
+        mut self,
+        long_assets: &[(usize, f64)],
+        short_assets: &[(usize, f64)],
+        expected: f64,
+        confidence: f64,
+    ) -> Self {
+        let picks = long_assets
+            .iter()
+            .copied()
+            .chain(short_assets.iter().map(|&(i, w)| (i, -w)))
+            .collect();
+        self.views.push(View {
+            picks,
+            expected,
+            confidence,
+        });
+        self
+    }
+
+    /// Number of views added
+    pub fn n_views(&self) -> usize {
+        self.views.len()
+    }
+
+    /// Equilibrium returns `Π = δ Σ w_mkt` implied by the market weights
+    pub fn implied_returns(&self) -> Result<Vec<f64>> {
+        let (sigma, weights) = self.inputs()?;
+        Ok((sigma * weights * self.risk_aversion)
+            .iter()
+            .copied()
+            .collect())
+    }
+
+    /// Posterior expected returns
+    ///
+    /// `E[R] = [(τΣ)^-1 + P'Ω^-1 P]^-1 [(τΣ)^-1 Π + P'Ω^-1 Q]`, with one
+    /// row of `P` and entry of `Q` per view and diagonal `Ω`. Without views
+    /// this is `Π`. Fails if `Σ` is not positive definite, `τ` is not
+    /// positive, a view refers to an asset outside the universe, or a
+    /// confidence is outside (0, 1).
+    pub fn posterior_returns(&self) -> Result<Vec<f64>> {
+        let (sigma, weights) = self.inputs()?;
+        let n = weights.len();
+        if !(self.tau > 0.0 && self.tau.is_finite()) {
+            return Err(OptimizerError::InvalidInput(format!(
+                "tau {} must be positive",
+                self.tau
+            )));
+        }
+
+        let implied = &sigma * &weights * self.risk_aversion;
+        if self.views.is_empty() {
+            return Ok(implied.iter().copied().collect());
+        }
+
+        let prior_cov = &sigma * self.tau;
+        let prior_precision = inverse_spd(&prior_cov).map_err(|_| {
+            OptimizerError::InvalidInput("Covariance is not positive definite".to_string())
+        })?;
+
+        let mut p = DMatrix::zeros(self.views.len(), n);
+        let mut q = DVector::zeros(self.views.len());
+        let mut omega_inv = DVector::zeros(self.views.len());
+        for (k, view) in self.views.iter().enumerate() {
+            if !(view.confidence > 0.0 && view.confidence < 1.0) {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "View confidence {} must be in (0, 1)",
+                    view.confidence
+                )));
+            }
+            for &(i, weight) in &view.picks {
+                if i >= n {
+                    return Err(OptimizerError::InvalidInput(format!(
+                        "View on asset {} outside a universe of {}",
+                        i, n
+                    )));
+                }
+                p[(k, i)] += weight;
+            }
+            q[k] = view.expected;
+
+            let row = p.row(k).transpose();
+            let view_variance = row.dot(&(&prior_cov * &row));
+            let omega = (1.0 - view.confidence) / view.confidence * view_variance;
+            if omega.is_nan() || omega <= 0.0 {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "View {} has no variance under the prior",
+                    k
+                )));
+            }
+            omega_inv[k] = 1.0 / omega;
+        }
+
+        let weighted_p = DMatrix::from_diagonal(&omega_inv) * &p;
+        let precision = &prior_precision + p.transpose() * &weighted_p;
+        let rhs = &prior_precision * implied + weighted_p.transpose() * q;
+        let posterior_cov = inverse_spd(&precision).map_err(|_| {
+            OptimizerError::NumericalError(
+                "Posterior precision is not positive definite".to_string(),
+            )
+        })?;
+
+        Ok((posterior_cov * rhs).iter().copied().collect())
+    }
+
+    /// Covariance and market weights as nalgebra types, checked for shape
+    fn inputs(&self) -> Result<(DMatrix<f64>, DVector<f64>)> {
+        let n = self.equilibrium_weights.len();
+        let sigma = vec_to_dmatrix(&self.covariance)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?;
+        if sigma.nrows() != n || sigma.ncols() != n {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: n,
+                got: sigma.nrows(),
+            });
+        }
+        Ok((sigma, DVector::from_column_slice(&self.equilibrium_weights)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::ConstraintSet;
+    use crate::problem::{ObjectiveType, OptimizationProblem};
+    use crate::solver::QpSolver;
+
+    fn covariance() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.040, 0.012, 0.010, 0.006],
+            vec![0.012, 0.050, 0.014, 0.008],
+            vec![0.010, 0.014, 0.060, 0.012],
+            vec![0.006, 0.008, 0.012, 0.030],
+        ]
+    }
+
+    fn model() -> BlackLittermanModel {
+        BlackLittermanModel::new(vec![0.4, 0.3, 0.2, 0.1], covariance(), 2.5, 0.05)
+    }
+
+    #[test]
+    fn test_no_views_gives_equilibrium() {
+        let implied = model().implied_returns().unwrap();
+        // Π_0 = 2.5 * (0.4*0.040 + 0.3*0.012 + 0.2*0.010 + 0.1*0.006)
+        assert!((implied[0] - 0.0555).abs() < 1e-12);
+        assert_eq!(model().posterior_returns().unwrap(), implied);
+    }
+
+    #[test]
+    fn test_views_tilt_posterior() {
+        let implied = model().implied_returns().unwrap();
+
+        // The precision form agrees with Π + τΣP'(PτΣP' + Ω)^-1 (Q - PΠ)
+        // for a single absolute view of confidence 0.5, where Ω = PτΣP'
+        // and the view pulls asset 2 halfway from Π_2 to Q
+        let posterior = model()
+            .add_absolute_view(2, 0.10, 0.5)
+            .posterior_returns()
+            .unwrap();
+        let shift = (0.10 - implied[2]) / 2.0;
+        for (i, row) in covariance().iter().enumerate() {
+            let expected = implied[i] + row[2] / covariance()[2][2] * shift;
+            assert!((posterior[i] - expected).abs() < 1e-10);
+        }
+
+        // More confidence moves the posterior closer to the view
+        let confident = model()
+            .add_absolute_view(2, 0.10, 0.95)
+            .posterior_returns()
+            .unwrap();
+        assert!(confident[2] > posterior[2] && confident[2] < 0.10);
+
+        // A relative view widens the spread between the two baskets
+        let relative = model()
+            .add_relative_view(&[(3, 1.0)], &[(0, 0.5), (1, 0.5)], 0.03, 0.8)
+            .posterior_returns()
+            .unwrap();
+        let spread = |mu: &[f64]| mu[3] - 0.5 * (mu[0] + mu[1]);
+        assert!(spread(&relative) > spread(&implied));
+        assert!(spread(&relative) < 0.03);
+
+        assert!(model()
+            .add_absolute_view(4, 0.10, 0.5)
+            .posterior_returns()
+            .is_err());
+        assert!(model()
+            .add_absolute_view(0, 0.10, 1.0)
+            .posterior_returns()
+            .is_err());
+    }
+
+    #[test]
+    fn test_posterior_feeds_mean_variance() {
+        let optimize = |expected_returns: Vec<f64>| {
+            let problem = OptimizationProblem::builder(4)
+                .expected_returns(expected_returns)
+                .covariance(covariance())
+                .constraints(ConstraintSet::long_only_full_investment(4))
+                .objective(ObjectiveType::MeanVariance)
+                .risk_aversion(2.5)
+                .build()
+                .unwrap();
+            QpSolver::default().solve(&problem).unwrap().weights
+        };
+
+        // Equilibrium returns recover the market portfolio
+        let market = optimize(model().posterior_returns().unwrap());
+        for (w, m) in market.iter().zip([0.4, 0.3, 0.2, 0.1]) {
+            assert!((w - m).abs() < 1e-3);
+        }
+
+        let bullish = optimize(
+            model()
+                .add_absolute_view(3, 0.12, 0.8)
+                .posterior_returns()
+                .unwrap(),
+        );
+        assert!(bullish[3] > 0.1 + 0.05);
+    }
+}
@@ -0,0 +1,341 @@
+//! Matrix-free conjugate gradient solver
+//!
+//! For very large universes the covariance matrix (let alone its Cholesky
+//! factor) may not fit in memory. The minimum variance portfolio only needs
+//! products Σv, which a factor model provides in O(n * k) without forming Σ.
+
+use covariance::factor::FactorCovariance;
+use nalgebra::DVector;
+
+use crate::constraints::{ConstraintSet, LinearConstraint};
+use crate::problem::{OptimizationResult, SolverStatus};
+use crate::solver::SolverConfig;
+use crate::{OptimizerError, Result};
+
+/// Covariance-vector product `v -> Σv`
+pub type MatVec = Box<dyn Fn(&[f64]) -> Vec<f64>>;
+
+/// Conjugate gradient solver for minimum variance portfolios
+pub struct CgSolver {
+    config: SolverConfig,
+}
+
+impl Default for CgSolver {
+    fn default() -> Self {
+        Self::new(SolverConfig::default())
+    }
+}
+
+impl CgSolver {
+    /// Create a new solver with given configuration
+    ///
+    /// `max_iterations` bounds the CG iterations and `eps_abs` is the
+    /// residual tolerance relative to the right-hand side norm.
+    pub fn new(config: SolverConfig) -> Self {
+        Self { config }
+    }
+
+    /// Covariance operator for a factor model: `B(F(B'v)) + Dv`
+    pub fn factor_matvec(model: FactorCovariance) -> MatVec {
+        Box::new(move |v: &[f64]| {
+            let v = DVector::from_column_slice(v);
+            let exposures = model.loadings.transpose() * &v;
+            let mut product = &model.loadings * (&model.factor_cov * exposures);
+            for i in 0..product.len() {
+                product[i] += model.specific_var[i] * v[i];
+            }
+            product.iter().cloned().collect()
+        })
+    }
+
+    /// Solve the fully invested minimum variance problem
+    ///
+    /// Solves `Σx = 1` by conjugate gradients and normalizes
+    /// `w = x / sum(x)`. The only supported linear constraint is full
+    /// investment. If box bounds are violated the solution is clipped and
+    /// renormalized, and the result is reported as `SubOptimal`. Expected
+    /// return and Sharpe ratio are not available from a covariance operator
+    /// and are reported as zero.
+    pub fn solve_min_variance(
+        &self,
+        matvec: &dyn Fn(&[f64]) -> Vec<f64>,
+        n: usize,
+        constraints: &ConstraintSet,
+    ) -> Result<OptimizationResult> {
+        if n == 0 {
+            return Err(OptimizerError::InvalidInput("Empty universe".to_string()));
+        }
+        if constraints.turnover_constraint.is_some()
+            || constraints.factor_constraints.is_some()
+            || constraints.density_constraint.is_some()
+            || constraints.cardinality_constraint.is_some()
+            || constraints.tracking_error_constraint.is_some()
+        {
+            return Err(OptimizerError::InvalidInput(
+                "CG solver supports only box and full-investment constraints".to_string(),
+            ));
+        }
+        if let Some(c) = constraints
+            .linear_constraints
+            .iter()
+            .find(|c| !is_full_investment(c, n))
+        {
+            return Err(OptimizerError::InvalidInput(format!(
+                "CG solver does not support linear constraint {}",
+                c.name
+            )));
+        }
+        if let Some(bounds) = &constraints.box_constraint {
+            if bounds.lower.len() != n || bounds.upper.len() != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: bounds.lower.len().min(bounds.upper.len()),
+                });
+            }
+        }
+
+        let (x, iterations, converged) = self.conjugate_gradient(matvec, &vec![1.0; n])?;
+
+        let sum: f64 = x.iter().sum();
+        if sum.abs() < 1e-300 {
+            return Err(OptimizerError::NumericalError(
+                "Minimum variance direction has zero budget".to_string(),
+            ));
+        }
+        let mut weights: Vec<f64> = x.iter().map(|xi| xi / sum).collect();
+
+        let mut status = if converged {
+            SolverStatus::Optimal
+        } else {
+            SolverStatus::MaxIterations
+        };
+
+        if let Some(bounds) = &constraints.box_constraint {
+            let violated = weights
+                .iter()
+                .enumerate()
+                .any(|(i, &w)| w < bounds.lower[i] || w > bounds.upper[i]);
+            if violated {
+                for (i, w) in weights.iter_mut().enumerate() {
+                    *w = w.max(bounds.lower[i]).min(bounds.upper[i]);
+                }
+                let clipped_sum: f64 = weights.iter().sum();
+                if clipped_sum > 0.0 {
+                    weights.iter_mut().for_each(|w| *w /= clipped_sum);
+                }
+                status = SolverStatus::SubOptimal;
+            }
+        }
+
+        let variance: f64 = matvec(&weights)
+            .iter()
+            .zip(&weights)
+            .map(|(sw, w)| sw * w)
+            .sum();
+
+        Ok(OptimizationResult {
+            variance,
+            volatility: variance.max(0.0).sqrt(),
+            iterations,
+            ..OptimizationResult::from_weights(weights, status)
+        })
+    }
+
+    /// Solve `Σx = b` for symmetric positive definite Σ
+    ///
+    /// Returns the solution, iteration count and whether the residual
+    /// tolerance was reached.
+    fn conjugate_gradient(
+        &self,
+        matvec: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+    ) -> Result<(Vec<f64>, u32, bool)> {
+        let n = b.len();
+        let tol = self.config.eps_abs * dot(b, b).sqrt();
+
+        let mut x = vec![0.0; n];
+        let mut r = b.to_vec();
+        let mut p = r.clone();
+        let mut rs_old = dot(&r, &r);
+
+        for iteration in 0..self.config.max_iterations {
+            if rs_old.sqrt() < tol {
+                return Ok((x, iteration, true));
+            }
+
+            let ap = matvec(&p);
+            if ap.len() != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: ap.len(),
+                });
+            }
+
+            let p_ap = dot(&p, &ap);
+            if p_ap <= 0.0 {
+                return Err(OptimizerError::NotPositiveSemiDefinite);
+            }
+
+            let alpha = rs_old / p_ap;
+            for i in 0..n {
+                x[i] += alpha * p[i];
+                r[i] -= alpha * ap[i];
+            }
+
+            let rs_new = dot(&r, &r);
+            let beta = rs_new / rs_old;
+            for i in 0..n {
+                p[i] = r[i] + beta * p[i];
+            }
+            rs_old = rs_new;
+        }
+
+        let converged = rs_old.sqrt() < tol;
+        Ok((x, self.config.max_iterations, converged))
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Check for the single-row `sum(w) == 1` constraint
+fn is_full_investment(constraint: &LinearConstraint, n: usize) -> bool {
+    let matrix = constraint.sparse_matrix();
+    constraint.is_equality
+        && matrix.rows() == 1
+        && matrix.cols() == n
+        && matrix.nnz() == n
+        && matrix.data().iter().all(|&a| a == 1.0)
+        && constraint.rhs == [1.0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::BoxConstraint;
+    use crate::problem::OptimizationProblem;
+    use crate::solver::QpSolver;
+    use nalgebra::{dmatrix, dvector};
+
+    fn dense_matvec(cov: Vec<Vec<f64>>) -> MatVec {
+        Box::new(move |v: &[f64]| {
+            cov.iter()
+                .map(|row| row.iter().zip(v).map(|(c, x)| c * x).sum())
+                .collect()
+        })
+    }
+
+    fn test_covariance() -> Vec<Vec<f64>> {
+        vec![
+            vec![0.04, 0.01, 0.02],
+            vec![0.01, 0.09, 0.03],
+            vec![0.02, 0.03, 0.0625],
+        ]
+    }
+
+    #[test]
+    fn test_cg_matches_qp_solver() {
+        let constraints = ConstraintSet::long_only_full_investment(3);
+        let problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(test_covariance())
+            .constraints(constraints.clone())
+            .build()
+            .unwrap();
+
+        let qp_config = SolverConfig {
+            eps_abs: 1e-10,
+            ..SolverConfig::default()
+        };
+        let qp = QpSolver::new(qp_config).solve(&problem).unwrap();
+
+        let matvec = dense_matvec(test_covariance());
+        let cg = CgSolver::default()
+            .solve_min_variance(&matvec, 3, &constraints)
+            .unwrap();
+
+        assert_eq!(cg.status, SolverStatus::Optimal);
+        assert!(cg.iterations <= 3);
+        for (a, b) in cg.weights.iter().zip(qp.weights.iter()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        assert!((cg.variance - qp.variance).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cg_factor_operator() {
+        let loadings = dmatrix![
+            1.0, 0.2;
+            0.8, -0.3;
+            1.2, 0.5;
+            0.9, 0.0
+        ];
+        let factor_cov = dmatrix![
+            0.03, 0.002;
+            0.002, 0.01
+        ];
+        let specific_var = dvector![0.02, 0.03, 0.025, 0.015];
+        let model = FactorCovariance::new(loadings, factor_cov, specific_var).unwrap();
+        let full = model.to_full_matrix().into_inner();
+
+        let factor_result = CgSolver::default()
+            .solve_min_variance(&CgSolver::factor_matvec(model), 4, &ConstraintSet::new())
+            .unwrap();
+
+        let dense: Vec<Vec<f64>> = (0..4)
+            .map(|i| (0..4).map(|j| full[(i, j)]).collect())
+            .collect();
+        let dense_result = CgSolver::default()
+            .solve_min_variance(&dense_matvec(dense), 4, &ConstraintSet::new())
+            .unwrap();
+
+        for (a, b) in factor_result
+            .weights
+            .iter()
+            .zip(dense_result.weights.iter())
+        {
+            assert!((a - b).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_cg_box_violation_is_suboptimal() {
+        // Covariance exceeds asset 0's variance, so min variance shorts asset 1
+        let cov = vec![vec![0.04, 0.05], vec![0.05, 0.09]];
+        let constraints = ConstraintSet::new().with_box(BoxConstraint::long_only(2));
+
+        let result = CgSolver::default()
+            .solve_min_variance(&dense_matvec(cov), 2, &constraints)
+            .unwrap();
+
+        assert_eq!(result.status, SolverStatus::SubOptimal);
+        assert!(result.weights.iter().all(|&w| w >= 0.0));
+
+        let short_bounds = ConstraintSet::new().with_box(BoxConstraint::long_only(1));
+        assert!(matches!(
+            CgSolver::default().solve_min_variance(
+                &dense_matvec(test_covariance()),
+                3,
+                &short_bounds
+            ),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 3,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cg_rejects_unsupported_constraints() {
+        let constraints =
+            ConstraintSet::new().with_linear(LinearConstraint::sector_exposure(&[0, 0, 1], 2, 0.5));
+
+        let result = CgSolver::default().solve_min_variance(
+            &dense_matvec(test_covariance()),
+            3,
+            &constraints,
+        );
+        assert!(result.is_err());
+    }
+}
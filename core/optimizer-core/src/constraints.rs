@@ -2,10 +2,15 @@
 //!
 //! Defines various constraints for portfolio optimization.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use sprs::CsMat;
+
+use crate::{OptimizerError, Result};
 
 /// Box constraints (lower and upper bounds for each asset)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BoxConstraint {
     /// Lower bounds for each asset weight
     pub lower: Vec<f64>,
@@ -44,10 +49,14 @@ impl BoxConstraint {
 }
 
 /// Linear constraint: A * w <= b or A * w == b
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The constraint matrix is accepted in dense row form but stored in
+/// compressed sparse row (CSR) format, since sector and factor constraint
+/// rows touch only a small fraction of a large universe.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LinearConstraint {
-    /// Constraint matrix (m x n)
-    pub matrix: Vec<Vec<f64>>,
+    /// Constraint matrix (m x n, CSR)
+    matrix: CsMat<f64>,
     /// Right-hand side vector (m)
     pub rhs: Vec<f64>,
     /// Is this an equality constraint?
@@ -58,28 +67,49 @@ pub struct LinearConstraint {
 
 impl LinearConstraint {
     /// Create a new linear inequality constraint (A * w <= b)
-    pub fn inequality(matrix: Vec<Vec<f64>>, rhs: Vec<f64>, name: &str) -> Self {
-        Self {
-            matrix,
-            rhs,
-            is_equality: false,
-            name: name.to_string(),
-        }
+    ///
+    /// Fails if the rows of `matrix` differ in length or `rhs` does not
+    /// have one entry per row.
+    pub fn inequality(matrix: Vec<Vec<f64>>, rhs: Vec<f64>, name: &str) -> Result<Self> {
+        Self::from_dense(&matrix, rhs, false, name)
     }
 
     /// Create a new linear equality constraint (A * w == b)
-    pub fn equality(matrix: Vec<Vec<f64>>, rhs: Vec<f64>, name: &str) -> Self {
-        Self {
-            matrix,
+    ///
+    /// Fails if the rows of `matrix` differ in length or `rhs` does not
+    /// have one entry per row.
+    pub fn equality(matrix: Vec<Vec<f64>>, rhs: Vec<f64>, name: &str) -> Result<Self> {
+        Self::from_dense(&matrix, rhs, true, name)
+    }
+
+    fn from_dense(
+        matrix: &[Vec<f64>],
+        rhs: Vec<f64>,
+        is_equality: bool,
+        name: &str,
+    ) -> Result<Self> {
+        if rhs.len() != matrix.len() {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: matrix.len(),
+                got: rhs.len(),
+            });
+        }
+        Ok(Self {
+            matrix: dense_to_csr(matrix)?,
             rhs,
-            is_equality: true,
+            is_equality,
             name: name.to_string(),
-        }
+        })
     }
 
     /// Create full investment constraint (sum of weights = 1)
     pub fn full_investment(n: usize) -> Self {
-        Self::equality(vec![vec![1.0; n]], vec![1.0], "full_investment")
+        Self {
+            matrix: rows_to_csr(&[vec![1.0; n]], n),
+            rhs: vec![1.0],
+            is_equality: true,
+            name: "full_investment".to_string(),
+        }
     }
 
     /// Create sector exposure constraint
@@ -97,27 +127,206 @@ impl LinearConstraint {
             }
         }
 
-        let rhs = vec![max_exposure; n_sectors];
-        Self::inequality(matrix, rhs, "sector_exposure")
+        Self {
+            matrix: rows_to_csr(&matrix, n),
+            rhs: vec![max_exposure; n_sectors],
+            is_equality: false,
+            name: "sector_exposure".to_string(),
+        }
     }
 
     /// Number of constraints
     pub fn n_constraints(&self) -> usize {
-        self.matrix.len()
+        self.matrix.rows()
     }
 
     /// Number of assets
     pub fn n_assets(&self) -> usize {
-        if self.matrix.is_empty() {
-            0
+        self.matrix.cols()
+    }
+
+    /// Sparse constraint matrix (CSR)
+    pub fn sparse_matrix(&self) -> &CsMat<f64> {
+        &self.matrix
+    }
+
+    /// Constraint matrix expanded to dense rows
+    pub fn dense_matrix(&self) -> Vec<Vec<f64>> {
+        let mut dense = vec![vec![0.0; self.n_assets()]; self.n_constraints()];
+        for (i, row) in self.matrix.outer_iterator().enumerate() {
+            for (j, &value) in row.iter() {
+                dense[i][j] = value;
+            }
+        }
+        dense
+    }
+
+    /// Evaluate A * w with a sparse matrix-vector product
+    pub fn evaluate(&self, weights: &[f64]) -> Vec<f64> {
+        self.matrix
+            .outer_iterator()
+            .map(|row| row.iter().map(|(j, &a)| a * weights[j]).sum())
+            .collect()
+    }
+}
+
+/// Single-row linear constraint with coefficients keyed by asset name
+///
+/// The row is laid out over the constraint's own `asset_names` and mapped
+/// onto an optimization universe by [`NamedLinearConstraint::into_linear`],
+/// so it stays correct however the universe is ordered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedLinearConstraint {
+    /// Assets the row is laid out over
+    asset_names: Vec<String>,
+    /// Coefficient for each of `asset_names`
+    row: Vec<f64>,
+    /// Coefficient names not found in `asset_names`
+    unknown_assets: Vec<String>,
+    /// Right-hand side
+    pub rhs: f64,
+    /// Is this an equality constraint?
+    pub is_equality: bool,
+    /// Constraint name for debugging
+    pub name: String,
+}
+
+impl NamedLinearConstraint {
+    /// Create a named inequality constraint (sum of coefficient * weight <= rhs)
+    ///
+    /// Assets without a coefficient get zero. Coefficients for names not in
+    /// `asset_names` are kept aside and rejected by `into_linear`.
+    pub fn inequality(
+        asset_names: &[String],
+        coefficients: HashMap<String, f64>,
+        rhs: f64,
+        name: &str,
+    ) -> Self {
+        Self::from_coefficients(asset_names, coefficients, rhs, false, name)
+    }
+
+    /// Create a named equality constraint (sum of coefficient * weight == rhs)
+    pub fn equality(
+        asset_names: &[String],
+        coefficients: HashMap<String, f64>,
+        rhs: f64,
+        name: &str,
+    ) -> Self {
+        Self::from_coefficients(asset_names, coefficients, rhs, true, name)
+    }
+
+    fn from_coefficients(
+        asset_names: &[String],
+        mut coefficients: HashMap<String, f64>,
+        rhs: f64,
+        is_equality: bool,
+        name: &str,
+    ) -> Self {
+        let row = asset_names
+            .iter()
+            .map(|asset| coefficients.remove(asset).unwrap_or(0.0))
+            .collect();
+        let mut unknown_assets: Vec<String> = coefficients.into_keys().collect();
+        unknown_assets.sort();
+
+        Self {
+            asset_names: asset_names.to_vec(),
+            row,
+            unknown_assets,
+            rhs,
+            is_equality,
+            name: name.to_string(),
+        }
+    }
+
+    /// Coefficient of `asset`, zero if it is not in the constraint
+    pub fn coefficient(&self, asset: &str) -> f64 {
+        self.asset_names
+            .iter()
+            .position(|name| name == asset)
+            .map_or(0.0, |i| self.row[i])
+    }
+
+    /// Map the constraint onto `universe`, the optimization's asset order
+    ///
+    /// Fails if a coefficient names an asset outside the constraint's asset
+    /// list or outside `universe`, or if `universe` repeats a name.
+    pub fn into_linear(self, universe: &[String]) -> Result<LinearConstraint> {
+        if !self.unknown_assets.is_empty() {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Constraint '{}' has coefficients for unlisted assets: {}",
+                self.name,
+                self.unknown_assets.join(", ")
+            )));
+        }
+
+        let mut positions = HashMap::with_capacity(universe.len());
+        for (i, asset) in universe.iter().enumerate() {
+            if positions.insert(asset.as_str(), i).is_some() {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "Duplicate asset '{}' in universe",
+                    asset
+                )));
+            }
+        }
+
+        let mut row = vec![0.0; universe.len()];
+        for (asset, &coefficient) in self.asset_names.iter().zip(&self.row) {
+            if coefficient == 0.0 {
+                continue;
+            }
+            let &i = positions.get(asset.as_str()).ok_or_else(|| {
+                OptimizerError::InvalidInput(format!(
+                    "Constraint '{}' references asset '{}' outside the universe",
+                    self.name, asset
+                ))
+            })?;
+            row[i] += coefficient;
+        }
+
+        if self.is_equality {
+            LinearConstraint::equality(vec![row], vec![self.rhs], &self.name)
         } else {
-            self.matrix[0].len()
+            LinearConstraint::inequality(vec![row], vec![self.rhs], &self.name)
         }
     }
 }
 
+/// Convert dense rows to CSR, dropping explicit zeros
+///
+/// Every row must have the length of the first.
+fn dense_to_csr(matrix: &[Vec<f64>]) -> Result<CsMat<f64>> {
+    let ncols = matrix.first().map_or(0, |row| row.len());
+    if let Some(row) = matrix.iter().find(|row| row.len() != ncols) {
+        return Err(OptimizerError::DimensionMismatch {
+            expected: ncols,
+            got: row.len(),
+        });
+    }
+    Ok(rows_to_csr(matrix, ncols))
+}
+
+/// Convert rows known to have `ncols` entries each to CSR
+fn rows_to_csr(matrix: &[Vec<f64>], ncols: usize) -> CsMat<f64> {
+    let mut indptr = Vec::with_capacity(matrix.len() + 1);
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    indptr.push(0);
+    for row in matrix {
+        for (j, &value) in row.iter().enumerate() {
+            if value != 0.0 {
+                indices.push(j);
+                data.push(value);
+            }
+        }
+        indptr.push(indices.len());
+    }
+
+    CsMat::new((matrix.len(), ncols), indptr, indices, data)
+}
+
 /// Turnover constraint
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TurnoverConstraint {
     /// Current portfolio weights
     pub current_weights: Vec<f64>,
@@ -136,7 +345,7 @@ impl TurnoverConstraint {
 }
 
 /// Factor exposure constraint
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FactorExposureConstraint {
     /// Factor loading matrix (n_assets x n_factors)
     pub factor_loadings: Vec<Vec<f64>>,
@@ -175,8 +384,125 @@ impl FactorExposureConstraint {
     }
 }
 
+/// Limit on the number of assets with large weights
+///
+/// E.g. "no more than 5 assets can exceed 10% weight" is
+/// `threshold = 0.10, max_count = 5`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightDensityConstraint {
+    /// Weight above which an asset counts towards the limit
+    pub threshold: f64,
+    /// Maximum number of assets above the threshold
+    pub max_count: usize,
+}
+
+impl WeightDensityConstraint {
+    /// Create a new weight density constraint
+    pub fn new(threshold: f64, max_count: usize) -> Self {
+        Self {
+            threshold,
+            max_count,
+        }
+    }
+
+    /// Number of weights strictly above the threshold
+    pub fn count_above(&self, weights: &[f64]) -> usize {
+        weights.iter().filter(|&&w| w > self.threshold).count()
+    }
+}
+
+/// Limit on the number of assets held
+///
+/// Weights with magnitude at or below `CardinalityConstraint::HOLDING_TOL`
+/// count as not held. Each held asset may also be required to carry at
+/// least `min_weight_if_held`, which rules out token positions. The minimum
+/// is imposed as a lower bound, so it needs long-only box bounds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardinalityConstraint {
+    /// Maximum number of assets with non-zero weight
+    pub max_assets: usize,
+    /// Smallest weight of a held asset (0 for no minimum)
+    #[serde(default)]
+    pub min_weight_if_held: f64,
+}
+
+impl CardinalityConstraint {
+    /// Smallest absolute weight counted as a holding
+    pub const HOLDING_TOL: f64 = 1e-8;
+
+    /// Create a new cardinality constraint
+    pub fn new(max_assets: usize) -> Self {
+        Self {
+            max_assets,
+            min_weight_if_held: 0.0,
+        }
+    }
+
+    /// Require every held asset to carry at least `min_weight_if_held`
+    pub fn with_min_weight_if_held(mut self, min_weight_if_held: f64) -> Self {
+        self.min_weight_if_held = min_weight_if_held;
+        self
+    }
+
+    /// Number of assets held
+    pub fn count_held(&self, weights: &[f64]) -> usize {
+        weights
+            .iter()
+            .filter(|w| w.abs() > Self::HOLDING_TOL)
+            .count()
+    }
+
+    /// Check the position limit and the minimum held weight
+    ///
+    /// Held weights may fall short of `min_weight_if_held` by up to
+    /// `HOLDING_TOL`; a short holding never meets a positive minimum.
+    pub fn is_satisfied(&self, weights: &[f64]) -> bool {
+        self.count_held(weights) <= self.max_assets
+            && weights.iter().all(|&w| {
+                w.abs() <= Self::HOLDING_TOL || w >= self.min_weight_if_held - Self::HOLDING_TOL
+            })
+    }
+}
+
+/// Limit on ex-ante tracking error against a benchmark
+///
+/// Bounds the active risk `sqrt((w - b)'Σ(w - b)) <= max_tracking_error`,
+/// in the same units as the portfolio volatility.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackingErrorConstraint {
+    /// Benchmark weights `b`
+    pub benchmark: Vec<f64>,
+    /// Maximum tracking error
+    pub max_tracking_error: f64,
+}
+
+impl TrackingErrorConstraint {
+    /// Create a new tracking error constraint
+    pub fn new(benchmark: Vec<f64>, max_tracking_error: f64) -> Self {
+        Self {
+            benchmark,
+            max_tracking_error,
+        }
+    }
+
+    /// Tracking error of `weights` under `covariance`
+    pub fn tracking_error(&self, weights: &[f64], covariance: &[Vec<f64>]) -> f64 {
+        let active: Vec<f64> = weights
+            .iter()
+            .zip(&self.benchmark)
+            .map(|(w, b)| w - b)
+            .collect();
+        let variance: f64 = covariance
+            .iter()
+            .zip(&active)
+            .map(|(row, a)| a * row.iter().zip(&active).map(|(c, x)| c * x).sum::<f64>())
+            .sum();
+        variance.max(0.0).sqrt()
+    }
+}
+
 /// Aggregate constraint set for portfolio optimization
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ConstraintSet {
     /// Box constraints
     pub box_constraint: Option<BoxConstraint>,
@@ -186,6 +512,15 @@ pub struct ConstraintSet {
     pub turnover_constraint: Option<TurnoverConstraint>,
     /// Factor exposure constraints
     pub factor_constraints: Option<FactorExposureConstraint>,
+    /// Weight density constraint
+    #[serde(default)]
+    pub density_constraint: Option<WeightDensityConstraint>,
+    /// Cardinality constraint
+    #[serde(default)]
+    pub cardinality_constraint: Option<CardinalityConstraint>,
+    /// Tracking error constraint
+    #[serde(default)]
+    pub tracking_error_constraint: Option<TrackingErrorConstraint>,
 }
 
 impl ConstraintSet {
@@ -218,12 +553,215 @@ impl ConstraintSet {
         self
     }
 
+    /// Add weight density constraint
+    pub fn with_weight_density(mut self, constraint: WeightDensityConstraint) -> Self {
+        self.density_constraint = Some(constraint);
+        self
+    }
+
+    /// Add cardinality constraint
+    pub fn with_cardinality(mut self, constraint: CardinalityConstraint) -> Self {
+        self.cardinality_constraint = Some(constraint);
+        self
+    }
+
+    /// Add tracking error constraint
+    pub fn with_tracking_error(mut self, constraint: TrackingErrorConstraint) -> Self {
+        self.tracking_error_constraint = Some(constraint);
+        self
+    }
+
     /// Create standard long-only constraints with full investment
     pub fn long_only_full_investment(n: usize) -> Self {
         Self::new()
             .with_box(BoxConstraint::long_only(n))
             .with_linear(LinearConstraint::full_investment(n))
     }
+
+    /// Whether a linear equality row requires the weights to sum to one
+    pub fn requires_full_investment(&self) -> bool {
+        self.linear_constraints
+            .iter()
+            .filter(|c| c.is_equality)
+            .any(|c| {
+                c.dense_matrix()
+                    .iter()
+                    .zip(&c.rhs)
+                    .any(|(row, &b)| b == 1.0 && row.iter().all(|&a| a == 1.0))
+            })
+    }
+
+    /// Restrict all constraints to a subset of the asset universe
+    ///
+    /// `assets` are indices into the current universe, in the order of the
+    /// new universe. Linear constraint rows keep their right-hand sides, so
+    /// e.g. full investment applies to the retained assets. Fails with
+    /// `DimensionMismatch` if an index lies outside any constraint's
+    /// universe.
+    pub fn select_assets(&self, assets: &[usize]) -> Result<Self> {
+        let required = assets.iter().max().map_or(0, |&i| i + 1);
+        let check = |len: usize| {
+            if len < required {
+                Err(OptimizerError::DimensionMismatch {
+                    expected: required,
+                    got: len,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        let pick = |values: &[f64]| -> Result<Vec<f64>> {
+            check(values.len())?;
+            Ok(assets.iter().map(|&i| values[i]).collect())
+        };
+
+        let box_constraint = match &self.box_constraint {
+            Some(b) => Some(BoxConstraint::new(pick(&b.lower)?, pick(&b.upper)?)),
+            None => None,
+        };
+        let linear_constraints = self
+            .linear_constraints
+            .iter()
+            .map(|c| {
+                check(c.n_assets())?;
+                let matrix: Vec<Vec<f64>> = c
+                    .dense_matrix()
+                    .iter()
+                    .map(|row| pick(row))
+                    .collect::<Result<_>>()?;
+                Ok(LinearConstraint {
+                    matrix: dense_to_csr(&matrix)?,
+                    rhs: c.rhs.clone(),
+                    is_equality: c.is_equality,
+                    name: c.name.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let turnover_constraint = match &self.turnover_constraint {
+            Some(t) => Some(TurnoverConstraint::new(
+                pick(&t.current_weights)?,
+                t.max_turnover,
+            )),
+            None => None,
+        };
+        let factor_constraints = match &self.factor_constraints {
+            Some(f) => {
+                check(f.factor_loadings.len())?;
+                Some(FactorExposureConstraint::new(
+                    assets
+                        .iter()
+                        .map(|&i| f.factor_loadings[i].clone())
+                        .collect(),
+                    f.lower.clone(),
+                    f.upper.clone(),
+                    f.factor_names.clone(),
+                ))
+            }
+            None => None,
+        };
+        let tracking_error_constraint = match &self.tracking_error_constraint {
+            Some(t) => Some(TrackingErrorConstraint::new(
+                pick(&t.benchmark)?,
+                t.max_tracking_error,
+            )),
+            None => None,
+        };
+
+        Ok(Self {
+            box_constraint,
+            linear_constraints,
+            turnover_constraint,
+            factor_constraints,
+            density_constraint: self.density_constraint.clone(),
+            cardinality_constraint: self.cardinality_constraint.clone(),
+            tracking_error_constraint,
+        })
+    }
+
+    /// Extend the constraints with one more asset whose weight is unconstrained
+    ///
+    /// The new asset gets infinite box bounds, zero factor exposure and
+    /// benchmark weight, and a coefficient of one in budget rows (rows
+    /// summing all weights) and zero in every other linear constraint row.
+    /// Its current weight in a turnover constraint is zero, so trading into
+    /// it counts toward turnover like trading any other asset.
+    pub fn append_unconstrained_asset(&self) -> Self {
+        let mut extended = self.clone();
+
+        if let Some(bounds) = &mut extended.box_constraint {
+            bounds.lower.push(f64::NEG_INFINITY);
+            bounds.upper.push(f64::INFINITY);
+        }
+
+        for constraint in &mut extended.linear_constraints {
+            let n = constraint.n_assets();
+            let matrix: Vec<Vec<f64>> = constraint
+                .dense_matrix()
+                .into_iter()
+                .map(|mut row| {
+                    let is_budget = row.iter().all(|&a| a == 1.0);
+                    row.push(if is_budget && n > 0 { 1.0 } else { 0.0 });
+                    row
+                })
+                .collect();
+            constraint.matrix = rows_to_csr(&matrix, n + 1);
+        }
+
+        if let Some(turnover) = &mut extended.turnover_constraint {
+            turnover.current_weights.push(0.0);
+        }
+
+        if let Some(factors) = &mut extended.factor_constraints {
+            let n_factors = factors.n_factors();
+            factors.factor_loadings.push(vec![0.0; n_factors]);
+        }
+
+        if let Some(tracking) = &mut extended.tracking_error_constraint {
+            tracking.benchmark.push(0.0);
+        }
+
+        extended
+    }
+
+    /// Copy of the constraints with every box narrowed by `tightening_factor`
+    ///
+    /// Both bounds move toward the box midpoint, keeping a width of
+    /// `(upper[i] - lower[i]) * (1 - tightening_factor)` with the factor
+    /// clamped to [0, 1]: 0 (or NaN) returns the original box and 1 pins
+    /// each weight to its midpoint. Bounds with an infinite side are left
+    /// unchanged. Whether the narrowed box still admits a solution is left
+    /// to the solver.
+    pub fn clone_with_tighter_box(&self, tightening_factor: f64) -> Self {
+        let mut tightened = self.clone();
+        if tightening_factor.is_nan() || tightening_factor <= 0.0 {
+            return tightened;
+        }
+        let factor = tightening_factor.min(1.0);
+
+        if let Some(bounds) = &mut tightened.box_constraint {
+            for (lower, upper) in bounds.lower.iter_mut().zip(bounds.upper.iter_mut()) {
+                if lower.is_finite() && upper.is_finite() {
+                    let width = *upper - *lower;
+                    *lower += 0.5 * width * factor;
+                    *upper = *lower + width * (1.0 - factor);
+                }
+            }
+        }
+
+        tightened
+    }
+
+    /// Copy of the constraints with the turnover limit scaled by `multiplier`
+    ///
+    /// Negative multipliers are treated as zero. Without a turnover
+    /// constraint the copy is unchanged.
+    pub fn clone_with_tighter_turnover(&self, multiplier: f64) -> Self {
+        let mut tightened = self.clone();
+        if let Some(turnover) = &mut tightened.turnover_constraint {
+            turnover.max_turnover *= multiplier.max(0.0);
+        }
+        tightened
+    }
 }
 
 #[cfg(test)]
@@ -247,6 +785,15 @@ mod tests {
         assert_eq!(constraint.rhs[0], 1.0);
     }
 
+    #[test]
+    fn test_cardinality_min_weight() {
+        let cardinality = CardinalityConstraint::new(3).with_min_weight_if_held(0.1);
+        assert!(cardinality.is_satisfied(&[0.6, 0.3, 0.1, 0.0]));
+        assert!(!cardinality.is_satisfied(&[0.95, 0.1, 0.05, 0.0]));
+        assert!(!cardinality.is_satisfied(&[0.7, 0.6, -0.3, 0.0]));
+        assert!(!cardinality.is_satisfied(&[0.4, 0.4, -0.2, 0.4]));
+    }
+
     #[test]
     fn test_sector_exposure() {
         // 5 assets in 2 sectors: [0, 0, 1, 1, 0]
@@ -256,10 +803,62 @@ mod tests {
         assert_eq!(constraint.n_constraints(), 2);
         assert_eq!(constraint.n_assets(), 5);
 
+        let matrix = constraint.dense_matrix();
         // Sector 0: assets 0, 1, 4
-        assert_eq!(constraint.matrix[0], vec![1.0, 1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(matrix[0], vec![1.0, 1.0, 0.0, 0.0, 1.0]);
         // Sector 1: assets 2, 3
-        assert_eq!(constraint.matrix[1], vec![0.0, 0.0, 1.0, 1.0, 0.0]);
+        assert_eq!(matrix[1], vec![0.0, 0.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_sparse_matches_dense() {
+        let dense = vec![
+            vec![1.0, 0.0, -0.5, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0],
+            vec![0.2, 0.3, 0.0, 1.0],
+        ];
+        let constraint =
+            LinearConstraint::inequality(dense.clone(), vec![0.1, 0.0, 0.5], "mixed").unwrap();
+
+        assert_eq!(constraint.n_constraints(), 3);
+        assert_eq!(constraint.n_assets(), 4);
+        assert_eq!(constraint.sparse_matrix().nnz(), 5);
+        assert_eq!(constraint.dense_matrix(), dense);
+
+        let weights = [0.4, 0.3, 0.2, 0.1];
+        let expected: Vec<f64> = dense
+            .iter()
+            .map(|row| row.iter().zip(&weights).map(|(a, w)| a * w).sum())
+            .collect();
+        assert_eq!(constraint.evaluate(&weights), expected);
+    }
+
+    #[test]
+    fn test_ragged_matrix_rejected() {
+        let ragged = vec![vec![1.0, 0.0, 1.0], vec![0.0, 1.0]];
+        assert!(matches!(
+            LinearConstraint::inequality(ragged, vec![0.5, 0.5], "ragged"),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 3,
+                got: 2
+            })
+        ));
+        assert!(matches!(
+            LinearConstraint::equality(vec![vec![1.0; 3]], vec![1.0, 0.0], "rhs"),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 1,
+                got: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_linear_constraint_serde() {
+        let constraint = LinearConstraint::full_investment(3);
+        let json = serde_json::to_string(&constraint).unwrap();
+        let back: LinearConstraint = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.dense_matrix(), vec![vec![1.0; 3]]);
+        assert!(back.is_equality);
     }
 
     #[test]
@@ -268,4 +867,169 @@ mod tests {
         assert!(constraints.box_constraint.is_some());
         assert_eq!(constraints.linear_constraints.len(), 1);
     }
+
+    #[test]
+    fn test_append_unconstrained_asset() {
+        let constraints = ConstraintSet::long_only_full_investment(2)
+            .with_linear(LinearConstraint::sector_exposure(&[0, 1], 2, 0.6));
+        let extended = constraints.append_unconstrained_asset();
+
+        let bounds = extended.box_constraint.unwrap();
+        assert_eq!(bounds.lower[2], f64::NEG_INFINITY);
+        assert_eq!(bounds.upper[2], f64::INFINITY);
+        assert_eq!(
+            extended.linear_constraints[0].dense_matrix(),
+            vec![vec![1.0; 3]]
+        );
+        assert_eq!(
+            extended.linear_constraints[1].dense_matrix(),
+            vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_clone_with_tighter_bounds() {
+        let constraints = ConstraintSet::new()
+            .with_box(BoxConstraint::new(
+                vec![0.0, 0.1, f64::NEG_INFINITY],
+                vec![0.4, 0.5, 0.3],
+            ))
+            .with_turnover(TurnoverConstraint::new(vec![0.3, 0.3, 0.4], 0.5));
+
+        assert_eq!(constraints.clone_with_tighter_box(0.0), constraints);
+        assert_eq!(constraints.clone_with_tighter_box(f64::NAN), constraints);
+
+        let halved = constraints.clone_with_tighter_box(0.5);
+        let bounds = halved.box_constraint.as_ref().unwrap();
+        assert!((bounds.lower[0] - 0.1).abs() < 1e-12);
+        assert!((bounds.upper[0] - 0.3).abs() < 1e-12);
+        assert!((bounds.lower[1] - 0.2).abs() < 1e-12);
+        assert!((bounds.upper[1] - 0.4).abs() < 1e-12);
+        assert_eq!(bounds.lower[2], f64::NEG_INFINITY);
+        assert_eq!(bounds.upper[2], 0.3);
+
+        // Fully tightened, each finite box is a single point
+        let pinned = constraints.clone_with_tighter_box(1.0);
+        let bounds = pinned.box_constraint.as_ref().unwrap();
+        assert_eq!(bounds.upper[..2], bounds.lower[..2]);
+        assert!((bounds.lower[0] - 0.2).abs() < 1e-12);
+        assert_eq!(pinned.turnover_constraint, constraints.turnover_constraint);
+
+        let tighter = constraints.clone_with_tighter_turnover(0.5);
+        assert_eq!(tighter.turnover_constraint.unwrap().max_turnover, 0.25);
+        assert_eq!(tighter.box_constraint, constraints.box_constraint);
+    }
+
+    #[test]
+    fn test_clone_with_tighter_box_full_investment() {
+        // Narrowing does not check the budget; an infeasible box is the
+        // solver's to report
+        let constraints = ConstraintSet::long_only_full_investment(4);
+        let halved = constraints.clone_with_tighter_box(0.5);
+        let bounds = halved.box_constraint.as_ref().unwrap();
+        assert_eq!(bounds.lower, vec![0.25; 4]);
+        assert_eq!(bounds.upper, vec![0.75; 4]);
+        assert_eq!(halved.linear_constraints, constraints.linear_constraints);
+
+        let pinned = constraints.clone_with_tighter_box(1.0);
+        assert_eq!(pinned.box_constraint.unwrap().upper, vec![0.5; 4]);
+    }
+
+    #[test]
+    fn test_requires_full_investment() {
+        assert!(ConstraintSet::long_only_full_investment(3).requires_full_investment());
+        assert!(!ConstraintSet::new()
+            .with_box(BoxConstraint::long_only(3))
+            .requires_full_investment());
+        // Dollar neutral is a budget of zero
+        let neutral = LinearConstraint::equality(vec![vec![1.0; 3]], vec![0.0], "neutral").unwrap();
+        assert!(!ConstraintSet::new()
+            .with_linear(neutral)
+            .requires_full_investment());
+    }
+
+    #[test]
+    fn test_select_assets() {
+        let constraints = ConstraintSet::long_only_full_investment(4)
+            .with_linear(LinearConstraint::sector_exposure(&[0, 1, 0, 1], 2, 0.6));
+        let subset = constraints.select_assets(&[3, 0]).unwrap();
+
+        assert_eq!(subset.box_constraint.unwrap().len(), 2);
+        assert_eq!(
+            subset.linear_constraints[0].dense_matrix(),
+            vec![vec![1.0; 2]]
+        );
+        assert_eq!(
+            subset.linear_constraints[1].dense_matrix(),
+            vec![vec![0.0, 1.0], vec![1.0, 0.0]]
+        );
+        assert_eq!(subset.linear_constraints[1].rhs, vec![0.6, 0.6]);
+
+        // A constraint laid out over a smaller universe cannot be indexed
+        let short = constraints.with_linear(LinearConstraint::full_investment(3));
+        assert!(matches!(
+            short.select_assets(&[3, 0]),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 4,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_named_linear_constraint() {
+        let universe: Vec<String> = (0..10).map(|i| format!("{:06}.SZ", i + 1)).collect();
+        // Lay the constraint out over the universe in reverse order
+        let asset_names: Vec<String> = universe.iter().rev().cloned().collect();
+        let coefficients = HashMap::from([
+            ("000002.SZ".to_string(), 1.0),
+            ("000005.SZ".to_string(), -0.5),
+            ("000010.SZ".to_string(), 2.0),
+        ]);
+
+        let named =
+            NamedLinearConstraint::inequality(&asset_names, coefficients, 0.3, "pair_trade");
+        assert_eq!(named.coefficient("000005.SZ"), -0.5);
+        assert_eq!(named.coefficient("000001.SZ"), 0.0);
+
+        let constraint = named.into_linear(&universe).unwrap();
+        assert!(!constraint.is_equality);
+        assert_eq!(constraint.name, "pair_trade");
+        assert_eq!(constraint.rhs, vec![0.3]);
+        assert_eq!(constraint.n_assets(), 10);
+        assert_eq!(constraint.sparse_matrix().nnz(), 3);
+
+        let mut expected = vec![0.0; 10];
+        expected[1] = 1.0;
+        expected[4] = -0.5;
+        expected[9] = 2.0;
+        assert_eq!(constraint.dense_matrix(), vec![expected]);
+    }
+
+    #[test]
+    fn test_named_linear_constraint_unknown_asset() {
+        let universe = vec!["A".to_string(), "B".to_string()];
+        let unlisted = NamedLinearConstraint::equality(
+            &universe,
+            HashMap::from([("A".to_string(), 1.0), ("Z".to_string(), 1.0)]),
+            1.0,
+            "unlisted",
+        );
+        assert!(matches!(
+            unlisted.into_linear(&universe),
+            Err(OptimizerError::InvalidInput(_))
+        ));
+
+        let wider = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let outside = NamedLinearConstraint::equality(
+            &wider,
+            HashMap::from([("C".to_string(), 1.0)]),
+            0.0,
+            "outside",
+        );
+        assert!(matches!(
+            outside.into_linear(&universe),
+            Err(OptimizerError::InvalidInput(_))
+        ));
+    }
 }
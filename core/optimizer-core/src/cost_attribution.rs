@@ -0,0 +1,155 @@
+//! Transaction cost attribution
+//!
+//! Splits the expected return of a rebalanced portfolio into the pre-cost
+//! return and the drag from trading into it.
+
+use serde::{Deserialize, Serialize};
+
+use crate::problem::{OptimizationResult, TransactionCostModel};
+use crate::{OptimizerError, Result};
+
+/// Pre-cost vs. post-cost return decomposition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostAttribution {
+    /// Expected return before transaction costs
+    pub pre_cost_return: f64,
+    /// Total transaction cost as a fraction of portfolio value
+    pub transaction_cost_return_drag: f64,
+    /// Expected return net of transaction costs
+    pub post_cost_return: f64,
+    /// Transaction cost per asset in currency units
+    pub per_asset_costs: Vec<f64>,
+    /// Cost drag as a percentage of the pre-cost return (0 if no return)
+    pub cost_as_pct_return: f64,
+}
+
+/// Transaction cost attribution calculator
+pub struct TransactionCostAttributor;
+
+impl TransactionCostAttributor {
+    /// Attribute transaction costs of moving from `current_weights` to the
+    /// optimized weights
+    ///
+    /// Each asset's trade value is `|w_target - w_current| * portfolio_value`.
+    /// Assets that are not traded incur no cost, including no fixed cost.
+    /// Fails if `current_weights` does not cover every optimized weight.
+    pub fn compute(
+        pre_cost_result: &OptimizationResult,
+        current_weights: &[f64],
+        cost_model: &TransactionCostModel,
+        portfolio_value: f64,
+    ) -> Result<CostAttribution> {
+        if current_weights.len() != pre_cost_result.weights.len() {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: pre_cost_result.weights.len(),
+                got: current_weights.len(),
+            });
+        }
+
+        let per_asset_costs: Vec<f64> = pre_cost_result
+            .weights
+            .iter()
+            .zip(current_weights)
+            .map(|(target, current)| {
+                let trade_value = (target - current) * portfolio_value;
+                if trade_value == 0.0 {
+                    0.0
+                } else {
+                    cost_model.cost(trade_value)
+                }
+            })
+            .collect();
+
+        let total_cost: f64 = per_asset_costs.iter().sum();
+        let drag = if portfolio_value > 0.0 {
+            total_cost / portfolio_value
+        } else {
+            0.0
+        };
+
+        let pre_cost_return = pre_cost_result.expected_return;
+        let cost_as_pct_return = if pre_cost_return != 0.0 {
+            drag / pre_cost_return.abs() * 100.0
+        } else {
+            0.0
+        };
+
+        Ok(CostAttribution {
+            pre_cost_return,
+            transaction_cost_return_drag: drag,
+            post_cost_return: pre_cost_return - drag,
+            per_asset_costs,
+            cost_as_pct_return,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolverStatus;
+
+    fn make_result(weights: Vec<f64>, expected_return: f64) -> OptimizationResult {
+        OptimizationResult {
+            expected_return,
+            variance: 0.04,
+            volatility: 0.2,
+            iterations: 10,
+            ..OptimizationResult::from_weights(weights, SolverStatus::Optimal)
+        }
+    }
+
+    #[test]
+    fn test_cost_attribution_identity() {
+        let result = make_result(vec![0.5, 0.3, 0.2], 0.08);
+        let cost_model = TransactionCostModel {
+            linear_cost: 0.001,
+            fixed_cost: 5.0,
+            impact_coefficient: 1e-8,
+            ..TransactionCostModel::default()
+        };
+
+        let attribution =
+            TransactionCostAttributor::compute(&result, &[0.4, 0.4, 0.2], &cost_model, 1_000_000.0)
+                .unwrap();
+
+        // Two trades of 100,000: 5 + 100 + 100 each, third asset untouched
+        assert!((attribution.per_asset_costs[0] - 205.0).abs() < 1e-6);
+        assert!((attribution.per_asset_costs[1] - 205.0).abs() < 1e-6);
+        assert_eq!(attribution.per_asset_costs[2], 0.0);
+        assert!((attribution.transaction_cost_return_drag - 4.1e-4).abs() < 1e-12);
+        assert!(
+            (attribution.pre_cost_return
+                - attribution.transaction_cost_return_drag
+                - attribution.post_cost_return)
+                .abs()
+                < 1e-12
+        );
+        assert!((attribution.cost_as_pct_return - 0.5125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_turnover_has_no_drag() {
+        let result = make_result(vec![0.6, 0.4], 0.07);
+        let cost_model = TransactionCostModel {
+            fixed_cost: 10.0,
+            ..TransactionCostModel::default()
+        };
+
+        let attribution =
+            TransactionCostAttributor::compute(&result, &[0.6, 0.4], &cost_model, 1_000_000.0)
+                .unwrap();
+
+        assert_eq!(attribution.transaction_cost_return_drag, 0.0);
+        assert_eq!(attribution.post_cost_return, attribution.pre_cost_return);
+        assert!(attribution.per_asset_costs.iter().all(|&c| c == 0.0));
+
+        assert!(matches!(
+            TransactionCostAttributor::compute(&result, &[1.0], &cost_model, 1_000_000.0),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+//! Multi-day execution scheduling
+//!
+//! Splits the trades from current to target weights across several days,
+//! respecting a participation limit and estimating market impact costs.
+//! Trades and ADV are in portfolio weight units (value / portfolio value),
+//! so costs are fractions of portfolio value.
+
+use serde::{Deserialize, Serialize};
+
+use crate::problem::OptimizationResult;
+use crate::{OptimizerError, Result};
+
+/// Linear (Kyle, 1985) price impact model
+///
+/// A trade moves the price by `lambda` times its size relative to average
+/// daily volume: `impact = lambda * trade / adv`, signed with the trade.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KyleLambdaModel {
+    /// Fractional price impact per unit of ADV traded
+    pub lambda: f64,
+}
+
+impl KyleLambdaModel {
+    /// Create a model with the given impact coefficient
+    pub fn new(lambda: f64) -> Self {
+        Self { lambda }
+    }
+
+    /// Fractional price impact of a signed trade (zero if nothing trades)
+    pub fn price_impact(&self, trade: f64, adv: f64) -> f64 {
+        if trade == 0.0 || adv <= 0.0 {
+            return 0.0;
+        }
+        self.lambda * trade / adv
+    }
+
+    /// Cost of a trade paying its full price impact
+    pub fn cost(&self, trade: f64, adv: f64) -> f64 {
+        (trade * self.price_impact(trade, adv)).abs()
+    }
+}
+
+/// Market liquidity inputs for execution scheduling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketConfig {
+    /// Average daily volume per asset
+    pub adv: Vec<f64>,
+    /// Maximum fraction of ADV traded per asset and day
+    pub max_participation: f64,
+    /// Price impact model
+    pub impact_model: KyleLambdaModel,
+}
+
+/// Trades planned for one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionDay {
+    /// Day index, starting at 0
+    pub day: usize,
+    /// Signed trade per asset
+    pub planned_trades: Vec<f64>,
+    /// Total impact cost of the day's trades
+    pub estimated_cost: f64,
+    /// Signed fractional price impact per asset
+    pub projected_market_impact: Vec<f64>,
+}
+
+/// Execution scheduler
+pub struct ExecutionScheduler;
+
+impl ExecutionScheduler {
+    /// Plan the trades from `current` to the target weights over `n_days`
+    ///
+    /// Each day trades `1 / days_left` of every asset's remaining imbalance,
+    /// capped at `max_participation * adv`. The last day trades whatever
+    /// remains up to the cap; an imbalance the cap prevents from closing is
+    /// left unexecuted.
+    ///
+    /// Fails if `current` or `adv` differ in length from the target
+    /// weights, or if `n_days` is zero.
+    pub fn schedule(
+        target: &OptimizationResult,
+        current: &[f64],
+        market_config: &MarketConfig,
+        n_days: usize,
+    ) -> Result<Vec<ExecutionDay>> {
+        let n = target.weights.len();
+        for len in [current.len(), market_config.adv.len()] {
+            if len != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: len,
+                });
+            }
+        }
+        if n_days == 0 {
+            return Err(OptimizerError::InvalidInput(
+                "Execution needs at least one day".to_string(),
+            ));
+        }
+
+        let impact = &market_config.impact_model;
+        let mut remaining: Vec<f64> = target
+            .weights
+            .iter()
+            .zip(current)
+            .map(|(t, c)| t - c)
+            .collect();
+
+        Ok((0..n_days)
+            .map(|day| {
+                let days_left = (n_days - day) as f64;
+                let planned_trades: Vec<f64> = remaining
+                    .iter()
+                    .zip(&market_config.adv)
+                    .map(|(&imbalance, &adv)| {
+                        let cap = (market_config.max_participation * adv).max(0.0);
+                        (imbalance / days_left).clamp(-cap, cap)
+                    })
+                    .collect();
+
+                for (r, t) in remaining.iter_mut().zip(&planned_trades) {
+                    *r -= t;
+                }
+
+                let projected_market_impact: Vec<f64> = planned_trades
+                    .iter()
+                    .zip(&market_config.adv)
+                    .map(|(&trade, &adv)| impact.price_impact(trade, adv))
+                    .collect();
+                let estimated_cost = planned_trades
+                    .iter()
+                    .zip(&market_config.adv)
+                    .map(|(&trade, &adv)| impact.cost(trade, adv))
+                    .sum();
+
+                ExecutionDay {
+                    day,
+                    planned_trades,
+                    estimated_cost,
+                    projected_market_impact,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolverStatus;
+
+    fn target(weights: Vec<f64>) -> OptimizationResult {
+        OptimizationResult::from_weights(weights, SolverStatus::Optimal)
+    }
+
+    #[test]
+    fn test_schedule_completes_trades() {
+        let target = target(vec![0.4, 0.35, 0.25]);
+        let current = [0.2, 0.5, 0.3];
+        let config = MarketConfig {
+            adv: vec![0.5, 0.3, 0.2],
+            max_participation: 0.2,
+            impact_model: KyleLambdaModel::new(0.1),
+        };
+
+        let days = ExecutionScheduler::schedule(&target, &current, &config, 4).unwrap();
+        assert_eq!(days.len(), 4);
+
+        for (i, c) in current.iter().enumerate() {
+            let executed: f64 = days.iter().map(|d| d.planned_trades[i]).sum();
+            assert!((executed - (target.weights[i] - c)).abs() < 1e-12);
+        }
+
+        for (k, day) in days.iter().enumerate() {
+            assert_eq!(day.day, k);
+            assert!(day.estimated_cost > 0.0);
+            // Impact has the sign of the trade
+            for (t, m) in day.planned_trades.iter().zip(&day.projected_market_impact) {
+                assert!(t * m >= 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_schedule_respects_participation() {
+        let target = target(vec![1.0, 0.0]);
+        let current = [0.0, 1.0];
+        let config = MarketConfig {
+            adv: vec![1.0, 1.0],
+            max_participation: 0.1,
+            impact_model: KyleLambdaModel::new(0.1),
+        };
+
+        // One day at 10% of ADV cannot close a 100% imbalance
+        let days = ExecutionScheduler::schedule(&target, &current, &config, 2).unwrap();
+        for day in &days {
+            assert!(day.planned_trades.iter().all(|t| t.abs() <= 0.1 + 1e-12));
+        }
+        let executed: f64 = days.iter().map(|d| d.planned_trades[0]).sum();
+        assert!((executed - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_schedule_rejects_bad_inputs() {
+        let target = target(vec![0.6, 0.4]);
+        let config = MarketConfig {
+            adv: vec![1.0, 1.0],
+            max_participation: 0.1,
+            impact_model: KyleLambdaModel::new(0.1),
+        };
+
+        assert!(matches!(
+            ExecutionScheduler::schedule(&target, &[0.5, 0.3, 0.2], &config, 2),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+        let short_adv = MarketConfig {
+            adv: vec![1.0],
+            ..config.clone()
+        };
+        assert!(matches!(
+            ExecutionScheduler::schedule(&target, &[0.5, 0.5], &short_adv, 2),
+            Err(OptimizerError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            ExecutionScheduler::schedule(&target, &[0.5, 0.5], &config, 0),
+            Err(OptimizerError::InvalidInput(_))
+        ));
+    }
+}
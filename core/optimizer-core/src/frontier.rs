@@ -0,0 +1,166 @@
+//! Efficient frontier plot data
+//!
+//! Collects solved portfolios into series that external plotting tools can
+//! consume directly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::problem::OptimizationResult;
+
+/// Efficient frontier points, sorted by increasing volatility
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EfficientFrontierData {
+    /// Portfolio volatilities
+    pub volatilities: Vec<f64>,
+    /// Portfolio expected returns
+    pub returns: Vec<f64>,
+    /// Portfolio Sharpe ratios
+    pub sharpe_ratios: Vec<f64>,
+}
+
+impl EfficientFrontierData {
+    /// Collect frontier points from optimization results
+    pub fn from_results(results: &[OptimizationResult]) -> EfficientFrontierData {
+        let mut points: Vec<&OptimizationResult> = results.iter().collect();
+        points.sort_by(|a, b| a.volatility.total_cmp(&b.volatility));
+
+        EfficientFrontierData {
+            volatilities: points.iter().map(|r| r.volatility).collect(),
+            returns: points.iter().map(|r| r.expected_return).collect(),
+            sharpe_ratios: points.iter().map(|r| r.sharpe_ratio).collect(),
+        }
+    }
+
+    /// Number of frontier points
+    pub fn len(&self) -> usize {
+        self.volatilities.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.volatilities.is_empty()
+    }
+
+    /// Serialize as `{volatilities: [...], returns: [...], sharpe_ratios: [...]}`
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "volatilities": self.volatilities,
+            "returns": self.returns,
+            "sharpe_ratios": self.sharpe_ratios,
+        })
+    }
+}
+
+/// Capital market line through the tangency portfolio
+///
+/// Returns `n_points` evenly spaced `(volatility, return)` pairs from zero
+/// volatility up to `max_vol`. The slope is the tangency portfolio's Sharpe
+/// ratio measured against `risk_free_rate`.
+pub fn capital_market_line(
+    risk_free_rate: f64,
+    tangency: &OptimizationResult,
+    max_vol: f64,
+    n_points: usize,
+) -> Vec<(f64, f64)> {
+    let slope = if tangency.volatility > 0.0 {
+        (tangency.expected_return - risk_free_rate) / tangency.volatility
+    } else {
+        0.0
+    };
+
+    let step = if n_points > 1 {
+        max_vol / (n_points - 1) as f64
+    } else {
+        0.0
+    };
+
+    (0..n_points)
+        .map(|i| {
+            let vol = i as f64 * step;
+            (vol, risk_free_rate + slope * vol)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::{OptimizationProblem, SolverStatus};
+    use crate::solver::QpSolver;
+
+    const RF: f64 = 0.02;
+
+    fn make_problem() -> OptimizationProblem {
+        OptimizationProblem::builder(2)
+            .expected_returns(vec![0.08, 0.14])
+            .covariance(vec![vec![0.04, 0.006], vec![0.006, 0.09]])
+            .risk_free_rate(RF)
+            .build()
+            .unwrap()
+    }
+
+    fn make_result(problem: &OptimizationProblem, weights: Vec<f64>) -> OptimizationResult {
+        QpSolver::build_result(problem, weights, 0, SolverStatus::Optimal)
+    }
+
+    /// Tangency weights w ∝ Σ^{-1}(μ - rf), normalized to sum to one
+    fn tangency_weights(problem: &OptimizationProblem) -> Vec<f64> {
+        let c = &problem.covariance;
+        let det = c[0][0] * c[1][1] - c[0][1] * c[1][0];
+        let excess: Vec<f64> = problem.expected_returns.iter().map(|r| r - RF).collect();
+        let raw = [
+            (c[1][1] * excess[0] - c[0][1] * excess[1]) / det,
+            (c[0][0] * excess[1] - c[1][0] * excess[0]) / det,
+        ];
+        let total = raw[0] + raw[1];
+        vec![raw[0] / total, raw[1] / total]
+    }
+
+    #[test]
+    fn test_frontier_data() {
+        let problem = make_problem();
+        let results: Vec<OptimizationResult> = [0.2, 1.0, 0.6]
+            .iter()
+            .map(|&w| make_result(&problem, vec![w, 1.0 - w]))
+            .collect();
+
+        let frontier = EfficientFrontierData::from_results(&results);
+        assert_eq!(frontier.len(), 3);
+        assert!(frontier.volatilities.windows(2).all(|v| v[0] <= v[1]));
+
+        let json = frontier.to_json();
+        assert_eq!(json["volatilities"].as_array().unwrap().len(), 3);
+        assert_eq!(json["returns"].as_array().unwrap().len(), 3);
+        assert_eq!(json["sharpe_ratios"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_cml_tangent_to_frontier() {
+        let problem = make_problem();
+        let w_t = tangency_weights(&problem);
+        let tangency = make_result(&problem, w_t.clone());
+
+        let cml = capital_market_line(RF, &tangency, 0.4, 41);
+        assert_eq!(cml.len(), 41);
+        assert_eq!(cml[0], (0.0, RF));
+        let slope = (cml[40].1 - cml[0].1) / (cml[40].0 - cml[0].0);
+
+        // CML passes through the tangency point
+        assert!((RF + slope * tangency.volatility - tangency.expected_return).abs() < 1e-4);
+
+        // Frontier slope dμ/dσ at the tangency point matches the CML slope
+        let h = 1e-6;
+        let up = make_result(&problem, vec![w_t[0] + h, w_t[1] - h]);
+        let down = make_result(&problem, vec![w_t[0] - h, w_t[1] + h]);
+        let frontier_slope =
+            (up.expected_return - down.expected_return) / (up.volatility - down.volatility);
+        assert!((frontier_slope - slope).abs() < 1e-4);
+
+        // No frontier portfolio lies above the CML
+        for i in 0..=100 {
+            let w = -0.5 + 2.0 * i as f64 / 100.0;
+            let point = make_result(&problem, vec![w, 1.0 - w]);
+            assert!(point.expected_return <= RF + slope * point.volatility + 1e-4);
+        }
+    }
+}
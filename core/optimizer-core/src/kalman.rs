@@ -0,0 +1,252 @@
+//! Kalman filter expected return estimation
+//!
+//! Treats expected returns as a latent state `μ_t = A μ_{t-1} + η_t`,
+//! `η_t ~ N(0, Q)`, observed each period through both realized returns and
+//! model signals. Each observation pulls the estimate towards the data in
+//! proportion to its precision.
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::{OptimizerError, Result};
+
+/// Kalman filter over expected returns
+///
+/// The observation at each step stacks realized returns and model signals,
+/// `y = [r_t; s_t] = H μ_t + ε_t` with `H = [I; I]` and `ε_t ~ N(0, R)`, so
+/// `observation_cov` is `2n x 2n`: its top-left block is the noise of
+/// realized returns, its bottom-right block the noise of the signals.
+#[derive(Debug, Clone)]
+pub struct KalmanReturnEstimator {
+    /// State noise covariance Q (n x n)
+    transition_cov: DMatrix<f64>,
+    /// Observation noise covariance R (2n x 2n)
+    observation_cov: DMatrix<f64>,
+    /// State transition matrix A (n x n)
+    transition: DMatrix<f64>,
+    /// Current estimate μ_t
+    mean: DVector<f64>,
+    /// Current estimate covariance P_t
+    state_cov: DMatrix<f64>,
+}
+
+impl KalmanReturnEstimator {
+    /// Create an estimator with prior `N(initial_mean, initial_cov)` and `A = I`
+    ///
+    /// Fails if the dimensions disagree or `observation_cov` is not
+    /// positive definite.
+    pub fn new(
+        initial_mean: DVector<f64>,
+        initial_cov: DMatrix<f64>,
+        transition_cov: DMatrix<f64>,
+        observation_cov: DMatrix<f64>,
+    ) -> Result<Self> {
+        let n = initial_mean.len();
+        for (matrix, dim) in [
+            (&initial_cov, n),
+            (&transition_cov, n),
+            (&observation_cov, 2 * n),
+        ] {
+            if matrix.nrows() != dim || matrix.ncols() != dim {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: dim,
+                    got: matrix.nrows().max(matrix.ncols()),
+                });
+            }
+        }
+        if observation_cov.clone().cholesky().is_none() {
+            return Err(OptimizerError::NotPositiveSemiDefinite);
+        }
+
+        Ok(Self {
+            transition_cov,
+            observation_cov,
+            transition: DMatrix::identity(n, n),
+            mean: initial_mean,
+            state_cov: initial_cov,
+        })
+    }
+
+    /// Use transition matrix `A` instead of the identity
+    pub fn with_transition(mut self, transition: DMatrix<f64>) -> Result<Self> {
+        let n = self.mean.len();
+        if transition.nrows() != n || transition.ncols() != n {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: n,
+                got: transition.nrows().max(transition.ncols()),
+            });
+        }
+        self.transition = transition;
+        Ok(self)
+    }
+
+    /// State noise covariance Q
+    pub fn transition_covariance(&self) -> &DMatrix<f64> {
+        &self.transition_cov
+    }
+
+    /// Observation noise covariance R
+    pub fn observation_covariance(&self) -> &DMatrix<f64> {
+        &self.observation_cov
+    }
+
+    /// Current expected return estimate
+    pub fn mean(&self) -> &DVector<f64> {
+        &self.mean
+    }
+
+    /// Covariance of the current estimate
+    pub fn state_covariance(&self) -> &DMatrix<f64> {
+        &self.state_cov
+    }
+
+    /// Fold in one period of realized returns and model signals
+    ///
+    /// Predicts `μ_{t|t-1} = A μ_{t-1}`, `P_{t|t-1} = A P A' + Q`, then
+    /// updates with gain `K = P H' (H P H' + R)^{-1}`. With `A = I` and
+    /// `Q = 0` this is the conjugate Gaussian posterior of a constant mean.
+    /// Returns the updated estimate.
+    ///
+    /// Fails if `observed_returns` or `model_signals` has the wrong length,
+    /// or if the innovation covariance `H P H' + R` is not positive
+    /// definite (possible when the prior covariance is indefinite); the
+    /// estimate is left unchanged.
+    pub fn update(
+        &mut self,
+        observed_returns: &DVector<f64>,
+        model_signals: &DVector<f64>,
+    ) -> Result<DVector<f64>> {
+        let n = self.mean.len();
+        for len in [observed_returns.len(), model_signals.len()] {
+            if len != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: len,
+                });
+            }
+        }
+
+        let mean_pred = &self.transition * &self.mean;
+        let cov_pred =
+            &self.transition * &self.state_cov * self.transition.transpose() + &self.transition_cov;
+
+        let mut h = DMatrix::zeros(2 * n, n);
+        h.view_mut((0, 0), (n, n)).fill_with_identity();
+        h.view_mut((n, 0), (n, n)).fill_with_identity();
+
+        let y = DVector::from_iterator(
+            2 * n,
+            observed_returns.iter().chain(model_signals.iter()).copied(),
+        );
+        let innovation = y - &h * &mean_pred;
+        let innovation_cov = &h * &cov_pred * h.transpose() + &self.observation_cov;
+
+        // K' = S^{-1} H P, since S and P are symmetric
+        let gain = innovation_cov
+            .cholesky()
+            .ok_or(OptimizerError::NotPositiveSemiDefinite)?
+            .solve(&(&h * &cov_pred))
+            .transpose();
+
+        self.mean = mean_pred + &gain * innovation;
+        self.state_cov = (DMatrix::identity(n, n) - &gain * &h) * cov_pred;
+        Ok(self.mean.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_converges_to_constant_return() {
+        let true_mu = DVector::from_vec(vec![0.05, -0.02, 0.08]);
+        let mut estimator = KalmanReturnEstimator::new(
+            DVector::from_vec(vec![0.5, 0.5, -0.5]),
+            DMatrix::identity(3, 3),
+            DMatrix::identity(3, 3) * 1e-8,
+            DMatrix::from_diagonal(&DVector::from_vec(vec![0.04, 0.04, 0.04, 0.01, 0.01, 0.01])),
+        )
+        .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut estimate = estimator.mean().clone();
+        for _ in 0..2000 {
+            let observed = true_mu.map(|mu| mu + rng.gen_range(-0.3..0.3));
+            let signals = true_mu.map(|mu| mu + rng.gen_range(-0.15..0.15));
+            estimate = estimator.update(&observed, &signals).unwrap();
+        }
+
+        assert!((estimate - &true_mu).amax() < 0.01);
+        assert!(estimator.state_covariance().diagonal().max() < 1e-4);
+    }
+
+    #[test]
+    fn test_static_state_is_conjugate_update() {
+        // One asset with Q = 0: precision-weighted average of prior and data
+        let (prior_mean, prior_var) = (0.0, 0.5);
+        let (return_var, signal_var) = (0.04, 0.01);
+        let mut estimator = KalmanReturnEstimator::new(
+            DVector::from_element(1, prior_mean),
+            DMatrix::from_element(1, 1, prior_var),
+            DMatrix::zeros(1, 1),
+            DMatrix::from_diagonal(&DVector::from_vec(vec![return_var, signal_var])),
+        )
+        .unwrap();
+
+        let data = [(0.10, 0.06), (0.02, 0.07), (0.09, 0.05), (0.04, 0.08)];
+        let mut estimate = 0.0;
+        for &(r, s) in &data {
+            estimate = estimator
+                .update(&DVector::from_element(1, r), &DVector::from_element(1, s))
+                .unwrap()[0];
+        }
+
+        let k = data.len() as f64;
+        let precision = 1.0 / prior_var + k / return_var + k / signal_var;
+        let weighted = prior_mean / prior_var
+            + data.iter().map(|(r, _)| r).sum::<f64>() / return_var
+            + data.iter().map(|(_, s)| s).sum::<f64>() / signal_var;
+        assert!((estimate - weighted / precision).abs() < 1e-12);
+        assert!((estimator.state_covariance()[(0, 0)] - 1.0 / precision).abs() < 1e-12);
+
+        assert!(matches!(
+            KalmanReturnEstimator::new(
+                DVector::zeros(2),
+                DMatrix::identity(2, 2),
+                DMatrix::identity(2, 2),
+                DMatrix::identity(2, 2),
+            ),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 4,
+                got: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_update_rejects_bad_inputs() {
+        let mut estimator = KalmanReturnEstimator::new(
+            DVector::zeros(1),
+            DMatrix::from_element(1, 1, -1.0),
+            DMatrix::zeros(1, 1),
+            DMatrix::identity(2, 2) * 0.01,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            estimator.update(&DVector::zeros(2), &DVector::zeros(1)),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 1,
+                got: 2
+            })
+        ));
+        // An indefinite prior makes the innovation covariance indefinite
+        assert!(matches!(
+            estimator.update(&DVector::zeros(1), &DVector::zeros(1)),
+            Err(OptimizerError::NotPositiveSemiDefinite)
+        ));
+        assert_eq!(estimator.mean()[0], 0.0);
+    }
+}
@@ -6,12 +6,49 @@
 //! - Mean-variance optimization (Markowitz)
 //! - Risk parity optimization
 //! - Maximum Sharpe ratio optimization
-//! - Custom constraint support (box, linear, sector, turnover)
-//! - Transaction cost modeling
+//! - Minimax regret robust optimization over return scenarios
+//! - Index tracking with cardinality limits and replication quality metrics
+//! - Custom constraint support (box, linear, sector, turnover), with linear rows keyed by asset name
+//! - Matrix-free conjugate gradient solver for large universes
+//! - Transaction cost modeling and pre/post-cost return attribution
+//! - Greedy rebalancing within a turnover budget
+//! - Multi-day execution scheduling with participation limits and market impact
+//! - Efficient frontier and capital market line plot data
+//! - Marginal utility analysis for weight changes
+//! - Diversification ratio and effective number of bets
+//! - Weight perturbation sensitivity of optimized portfolios
+//! - Monte Carlo weight uncertainty under perturbed return and covariance inputs
+//! - Solver hyperparameter tuning (grid search, Gaussian-process Bayesian optimization)
+//! - Portfolio insurance (CPPI) allocation strategy
+//! - Configurable optimization pipelines (universe filtering, vol targeting, rounding)
+//! - Cross-sectional return transforms (z-score, rank, winsorize)
+//! - Kalman filter expected returns fusing realized returns and model signals
+//! - Black-Litterman expected returns blending market equilibrium with investor views
+//! - HDF5 problem and result sessions (`hdf5` feature)
 
+pub mod analytics;
+pub mod black_litterman;
+pub mod cg;
 pub mod constraints;
+pub mod cost_attribution;
+pub mod execution;
+pub mod frontier;
+pub mod kalman;
+pub mod marginal;
+pub mod pipeline;
 pub mod problem;
+pub mod replication;
+pub mod robust;
+pub mod sensitivity;
+#[cfg(feature = "hdf5")]
+pub mod session;
 pub mod solver;
+pub mod strategies;
+pub mod tuning;
+pub mod turnover_budget;
+pub mod uncertainty;
+pub mod utils;
+pub mod weights;
 
 use thiserror::Error;
 
@@ -20,6 +57,9 @@ pub enum OptimizerError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Invalid weights: {0}")]
+    InvalidWeights(String),
+
     #[error("Dimension mismatch: expected {expected}, got {got}")]
     DimensionMismatch { expected: usize, got: usize },
 
@@ -37,6 +77,10 @@ pub enum OptimizerError {
 
     #[error("Numerical error: {0}")]
     NumericalError(String),
+
+    #[cfg(feature = "hdf5")]
+    #[error("HDF5 error: {0}")]
+    Hdf5(#[from] hdf5::Error),
 }
 
 pub type Result<T> = std::result::Result<T, OptimizerError>;
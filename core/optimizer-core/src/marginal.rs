@@ -0,0 +1,162 @@
+//! Marginal utility analysis
+//!
+//! Sensitivity of the mean-variance utility `w'μ - λ/2 * w'Σw` to small
+//! changes in individual asset weights.
+
+use serde::{Deserialize, Serialize};
+
+use crate::problem::OptimizationProblem;
+
+/// Marginal utilities within this distance of zero are treated as flat
+const HOLD_TOLERANCE: f64 = 1e-8;
+
+/// Direction in which a weight change improves utility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeDirection {
+    /// Increasing the weight increases utility
+    Buy,
+    /// Decreasing the weight increases utility
+    Sell,
+    /// Utility is locally flat in this weight
+    Hold,
+}
+
+/// Marginal utility calculator
+pub struct MarginalUtility;
+
+impl MarginalUtility {
+    /// Gradient of the mean-variance utility with respect to each weight
+    ///
+    /// `∂U/∂w_i = μ_i - λ * (Σw)_i`, using the problem's risk aversion λ.
+    pub fn compute(problem: &OptimizationProblem, weights: &[f64]) -> Vec<f64> {
+        let lambda = problem.risk_aversion;
+        (0..problem.n_assets)
+            .map(|i| {
+                let sigma_w: f64 = problem.covariance[i]
+                    .iter()
+                    .zip(weights)
+                    .map(|(c, w)| c * w)
+                    .sum();
+                problem.expected_returns[i] - lambda * sigma_w
+            })
+            .collect()
+    }
+
+    /// Trade direction implied by the sign of each marginal utility
+    ///
+    /// This is the unconstrained gradient sign; it does not account for the
+    /// budget constraint, which requires offsetting trades elsewhere.
+    pub fn optimal_trade_direction(
+        problem: &OptimizationProblem,
+        weights: &[f64],
+    ) -> Vec<TradeDirection> {
+        Self::compute(problem, weights)
+            .into_iter()
+            .map(|mu| {
+                if mu > HOLD_TOLERANCE {
+                    TradeDirection::Buy
+                } else if mu < -HOLD_TOLERANCE {
+                    TradeDirection::Sell
+                } else {
+                    TradeDirection::Hold
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{BoxConstraint, ConstraintSet, LinearConstraint};
+    use nalgebra::{DMatrix, DVector};
+
+    fn make_problem(risk_aversion: f64) -> OptimizationProblem {
+        let constraints = ConstraintSet::new()
+            .with_box(BoxConstraint::uniform(3, -1.0, 2.0))
+            .with_linear(LinearConstraint::full_investment(3));
+
+        OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(vec![
+                vec![0.04, 0.01, 0.02],
+                vec![0.01, 0.09, 0.03],
+                vec![0.02, 0.03, 0.0625],
+            ])
+            .constraints(constraints)
+            .risk_aversion(risk_aversion)
+            .build()
+            .unwrap()
+    }
+
+    /// Budget-constrained optimum: w = Σ^{-1}(μ - γ1) / λ with sum(w) = 1
+    fn optimal_weights(problem: &OptimizationProblem) -> Vec<f64> {
+        let n = problem.n_assets;
+        let sigma = DMatrix::from_fn(n, n, |i, j| problem.covariance[i][j]);
+        let inv = sigma.try_inverse().unwrap();
+        let mu = DVector::from_column_slice(&problem.expected_returns);
+        let ones = DVector::from_element(n, 1.0);
+
+        let inv_mu = &inv * &mu;
+        let inv_ones = &inv * &ones;
+        let gamma = (inv_mu.sum() - problem.risk_aversion) / inv_ones.sum();
+        ((inv_mu - inv_ones * gamma) / problem.risk_aversion)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_marginal_utility_gradient() {
+        let problem = make_problem(2.0);
+        let weights = vec![0.5, 0.3, 0.2];
+        let mu = MarginalUtility::compute(&problem, &weights);
+
+        // μ_0 - λ(Σw)_0 = 0.10 - 2 * (0.02 + 0.003 + 0.004)
+        assert!((mu[0] - 0.046).abs() < 1e-12);
+
+        // Matches a finite-difference derivative of the utility
+        let utility = |w: &[f64]| {
+            problem.portfolio_return(w)
+                - problem.risk_aversion / 2.0 * problem.portfolio_variance(w)
+        };
+        let h = 1e-6;
+        let mut bumped = weights.clone();
+        bumped[1] += h;
+        let numeric = (utility(&bumped) - utility(&weights)) / h;
+        assert!((numeric - mu[1]).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_marginal_utilities_equal_at_optimum() {
+        let problem = make_problem(4.0);
+        let weights = optimal_weights(&problem);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // All weights are interior, so the KKT conditions equalize the gradient
+        let mu = MarginalUtility::compute(&problem, &weights);
+        for m in &mu[1..] {
+            assert!((m - mu[0]).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_trade_direction() {
+        let problem = make_problem(2.0);
+
+        // All cash: every asset has positive expected return and no risk cost
+        let directions = MarginalUtility::optimal_trade_direction(&problem, &[0.0, 0.0, 0.0]);
+        assert!(directions.iter().all(|&d| d == TradeDirection::Buy));
+
+        // Heavily levered into asset 1: risk cost dominates
+        let directions = MarginalUtility::optimal_trade_direction(&problem, &[0.0, 5.0, 0.0]);
+        assert_eq!(directions[1], TradeDirection::Sell);
+
+        // At a zero-gradient point every asset is a hold
+        let sigma = DMatrix::from_fn(3, 3, |i, j| problem.covariance[i][j]);
+        let mu = DVector::from_column_slice(&problem.expected_returns);
+        let w = sigma.try_inverse().unwrap() * mu / problem.risk_aversion;
+        let directions = MarginalUtility::optimal_trade_direction(&problem, w.as_slice());
+        assert!(directions.iter().all(|&d| d == TradeDirection::Hold));
+    }
+}
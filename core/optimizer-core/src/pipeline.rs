@@ -0,0 +1,501 @@
+//! Portfolio optimization pipelines
+//!
+//! Chains universe filtering, optimization and weight post-processing into a
+//! single configurable workflow. Pipelines serialize with serde so they can
+//! be loaded from configuration files.
+
+use covariance::estimator::SampleCovariance;
+use covariance::matrix::dmatrix_to_vec;
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::{BoxConstraint, ConstraintSet};
+use crate::problem::{ObjectiveType, OptimizationProblem, OptimizationResult};
+use crate::solver::QpSolver;
+use crate::weights::PortfolioWeights;
+use crate::{OptimizerError, Result};
+
+/// Volatility below which a return series is treated as constant
+const DEFAULT_MIN_VOLATILITY: f64 = 1e-8;
+
+/// Universe filter applied to asset return histories
+///
+/// Assets with non-finite returns are always excluded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniverseFilter {
+    /// Minimum per-period return volatility (excludes suspended/constant series)
+    pub min_volatility: f64,
+    /// Maximum per-period return volatility
+    pub max_volatility: Option<f64>,
+    /// Minimum mean per-period return
+    pub min_mean_return: Option<f64>,
+}
+
+impl Default for UniverseFilter {
+    fn default() -> Self {
+        Self {
+            min_volatility: DEFAULT_MIN_VOLATILITY,
+            max_volatility: None,
+            min_mean_return: None,
+        }
+    }
+}
+
+impl UniverseFilter {
+    /// Create a filter excluding only constant or invalid series
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exclude assets more volatile than `max_volatility`
+    pub fn with_max_volatility(mut self, max_volatility: f64) -> Self {
+        self.max_volatility = Some(max_volatility);
+        self
+    }
+
+    /// Exclude assets with mean return below `min_mean_return`
+    pub fn with_min_mean_return(mut self, min_mean_return: f64) -> Self {
+        self.min_mean_return = Some(min_mean_return);
+        self
+    }
+
+    /// Check whether an asset's return history passes the filter
+    pub fn accepts(&self, returns: &[f64]) -> bool {
+        if returns.len() < 2 || returns.iter().any(|r| !r.is_finite()) {
+            return false;
+        }
+
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let volatility =
+            (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+        volatility >= self.min_volatility
+            && self.max_volatility.is_none_or(|max| volatility <= max)
+            && self.min_mean_return.is_none_or(|min| mean >= min)
+    }
+}
+
+/// Rounds weights to a lot size while preserving their total
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightRounder {
+    /// Weight increment (e.g., 0.01 for whole percentages)
+    pub lot_size: f64,
+}
+
+impl WeightRounder {
+    /// Create a rounder with the given lot size
+    pub fn new(lot_size: f64) -> Self {
+        Self { lot_size }
+    }
+
+    /// Round weights to multiples of the lot size within optional bounds
+    ///
+    /// Uses the largest remainder method: weights are floored to whole lots,
+    /// clamped to the lots that fit within `bounds`, and the lots needed to
+    /// restore the (rounded) total go one at a time to the weights with the
+    /// largest remainders that still have room under their upper bound
+    /// (surplus lots are taken back from the smallest remainders above
+    /// their lower bound). Fails if some bound admits no whole lot or the
+    /// bounds cannot hold the rounded total.
+    pub fn round(&self, weights: &[f64], bounds: Option<&BoxConstraint>) -> Result<Vec<f64>> {
+        if self.lot_size <= 0.0 || self.lot_size.is_nan() {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Lot size must be positive, got {}",
+                self.lot_size
+            )));
+        }
+        let n = weights.len();
+        if let Some(b) = bounds {
+            if b.lower.len() != n || b.upper.len() != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: b.lower.len().min(b.upper.len()),
+                });
+            }
+        }
+        let bound = |i: usize| {
+            bounds.map_or((f64::NEG_INFINITY, f64::INFINITY), |b| {
+                (b.lower[i], b.upper[i])
+            })
+        };
+
+        // Bounds in whole lots, with slack for bounds that sit on the grid
+        let mut min_lots = Vec::with_capacity(n);
+        let mut max_lots = Vec::with_capacity(n);
+        for i in 0..n {
+            let (lower, upper) = bound(i);
+            let lo = (lower / self.lot_size - LOT_TOL).ceil();
+            let hi = (upper / self.lot_size + LOT_TOL).floor();
+            if lo > hi {
+                return Err(OptimizerError::Infeasible(format!(
+                    "No multiple of {} lies within the bounds of asset {}",
+                    self.lot_size, i
+                )));
+            }
+            min_lots.push(lo);
+            max_lots.push(hi);
+        }
+
+        let lots: Vec<f64> = weights.iter().map(|w| w / self.lot_size).collect();
+        let mut rounded: Vec<f64> = (0..n)
+            .map(|i| lots[i].floor().clamp(min_lots[i], max_lots[i]))
+            .collect();
+
+        let target = lots.iter().sum::<f64>().round();
+        let mut missing = target - rounded.iter().sum::<f64>();
+        while missing != 0.0 {
+            let remainder = |i: usize| lots[i] - rounded[i];
+            let candidates = (0..n).filter(|&i| {
+                if missing > 0.0 {
+                    rounded[i] < max_lots[i]
+                } else {
+                    rounded[i] > min_lots[i]
+                }
+            });
+            let pick = if missing > 0.0 {
+                candidates.max_by(|&a, &b| remainder(a).total_cmp(&remainder(b)))
+            } else {
+                candidates.min_by(|&a, &b| remainder(a).total_cmp(&remainder(b)))
+            };
+            let Some(i) = pick else {
+                return Err(OptimizerError::Infeasible(format!(
+                    "Bounds cannot hold {} lots of {}",
+                    target, self.lot_size
+                )));
+            };
+            let step = missing.signum();
+            rounded[i] += step;
+            missing -= step;
+        }
+
+        Ok(rounded
+            .iter()
+            .enumerate()
+            .map(|(i, l)| {
+                let (lower, upper) = bound(i);
+                (l * self.lot_size).clamp(lower, upper)
+            })
+            .collect())
+    }
+}
+
+/// Slack, in lots, for a bound to count as a whole number of lots
+const LOT_TOL: f64 = 1e-9;
+
+/// Scales weights down to a target volatility, holding the rest in cash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityTargeter {
+    /// Target per-period volatility
+    pub target_volatility: f64,
+}
+
+impl VolatilityTargeter {
+    /// Create a targeter with the given volatility target
+    pub fn new(target_volatility: f64) -> Self {
+        Self { target_volatility }
+    }
+
+    /// Scale weights so portfolio volatility does not exceed the target
+    ///
+    /// Portfolios already at or below the target are left unchanged; the
+    /// targeter never adds leverage.
+    pub fn apply(&self, problem: &OptimizationProblem, weights: &[f64]) -> Result<Vec<f64>> {
+        if self.target_volatility <= 0.0 || self.target_volatility.is_nan() {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Target volatility must be positive, got {}",
+                self.target_volatility
+            )));
+        }
+
+        let volatility = problem.portfolio_variance(weights).max(0.0).sqrt();
+        let scale = if volatility > self.target_volatility {
+            self.target_volatility / volatility
+        } else {
+            1.0
+        };
+
+        Ok(weights.iter().map(|w| w * scale).collect())
+    }
+}
+
+/// Weight post-processing step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PostProcessStep {
+    /// Round to lot size
+    Round(WeightRounder),
+    /// Scale down to a volatility target
+    TargetVolatility(VolatilityTargeter),
+}
+
+/// Optimization pipeline: filter, optimize, post-process
+///
+/// Expected returns and covariance are estimated from the filtered returns
+/// history (sample mean and sample covariance). Constraints are specified on
+/// the full universe and restricted to the assets passing the filters; the
+/// default is long-only full investment. Tracking error benchmarks are
+/// restricted the same way.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    /// Universe filters, all of which an asset must pass
+    pub filters: Vec<UniverseFilter>,
+    /// Optimization objective
+    pub objective: ObjectiveType,
+    /// Constraints on the full universe
+    pub constraints: Option<ConstraintSet>,
+    /// Post-processing steps, applied in order
+    pub postprocessors: Vec<PostProcessStep>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    /// Create an empty minimum variance pipeline
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            objective: ObjectiveType::MinimizeVariance,
+            constraints: None,
+            postprocessors: Vec::new(),
+        }
+    }
+
+    /// Add a universe filter
+    pub fn preprocess(mut self, filter: UniverseFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    /// Set the optimization objective
+    pub fn set_objective(mut self, objective: ObjectiveType) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Set constraints on the full universe
+    pub fn set_constraints(mut self, constraints: ConstraintSet) -> Self {
+        self.constraints = Some(constraints);
+        self
+    }
+
+    /// Append a rounding step
+    pub fn postprocess(mut self, rounder: WeightRounder) -> Self {
+        self.postprocessors.push(PostProcessStep::Round(rounder));
+        self
+    }
+
+    /// Append a volatility targeting step
+    pub fn postprocess_liquidity(mut self, targeter: VolatilityTargeter) -> Self {
+        self.postprocessors
+            .push(PostProcessStep::TargetVolatility(targeter));
+        self
+    }
+
+    /// Run the pipeline on a returns history (n_observations x n_assets)
+    ///
+    /// The result covers the full universe; filtered-out assets get zero
+    /// weight and zero rows in the recorded covariance. Statistics are
+    /// computed for the post-processed weights.
+    pub fn run(&self, returns_matrix: &DMatrix<f64>) -> Result<OptimizationResult> {
+        let n_assets = returns_matrix.ncols();
+
+        let selected: Vec<usize> = (0..n_assets)
+            .filter(|&j| {
+                let column: Vec<f64> = returns_matrix.column(j).iter().copied().collect();
+                self.filters.iter().all(|f| f.accepts(&column))
+            })
+            .collect();
+        if selected.is_empty() {
+            return Err(OptimizerError::Infeasible(
+                "No assets pass the universe filters".to_string(),
+            ));
+        }
+
+        let returns = returns_matrix.select_columns(&selected);
+        let cov = SampleCovariance::estimate(&returns, 1)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?;
+        let n = selected.len();
+
+        let constraints = match &self.constraints {
+            Some(constraints) => constraints.select_assets(&selected)?,
+            None => ConstraintSet::long_only_full_investment(n),
+        };
+
+        let objective = match &self.objective {
+            ObjectiveType::MinimizeTrackingError { benchmark_weights } => {
+                if benchmark_weights.len() != n_assets {
+                    return Err(OptimizerError::DimensionMismatch {
+                        expected: n_assets,
+                        got: benchmark_weights.len(),
+                    });
+                }
+                ObjectiveType::MinimizeTrackingError {
+                    benchmark_weights: selected.iter().map(|&i| benchmark_weights[i]).collect(),
+                }
+            }
+            objective => objective.clone(),
+        };
+
+        let problem = OptimizationProblem::builder(n)
+            .expected_returns(returns.row_mean().iter().copied().collect())
+            .covariance(dmatrix_to_vec(&cov))
+            .constraints(constraints)
+            .objective(objective)
+            .build()?;
+
+        let result = QpSolver::default().solve(&problem)?;
+
+        let mut weights = result.weights.into_inner();
+        for step in &self.postprocessors {
+            weights = match step {
+                PostProcessStep::Round(rounder) => {
+                    rounder.round(&weights, problem.constraints.box_constraint.as_ref())?
+                }
+                PostProcessStep::TargetVolatility(targeter) => {
+                    targeter.apply(&problem, &weights)?
+                }
+            };
+        }
+
+        let mut full = QpSolver::build_result(&problem, weights, result.iterations, result.status);
+        full.regularization_applied = result.regularization_applied;
+
+        let mut full_weights = vec![0.0; n_assets];
+        let mut full_covariance = vec![vec![0.0; n_assets]; n_assets];
+        for (k, &asset) in selected.iter().enumerate() {
+            full_weights[asset] = full.weights[k];
+            for (l, &other) in selected.iter().enumerate() {
+                full_covariance[asset][other] = full.covariance[k][l];
+            }
+        }
+        full.weights = PortfolioWeights::unconstrained(full_weights);
+        full.covariance = full_covariance;
+
+        Ok(full)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::LinearConstraint;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 250 monthly-scale returns for 5 assets; asset 3 is very volatile and
+    /// asset 4 is suspended (constant zero returns)
+    fn make_returns() -> DMatrix<f64> {
+        let mut rng = StdRng::seed_from_u64(1);
+        let scales = [0.05, 0.075, 0.1, 1.0, 0.0];
+        DMatrix::from_fn(250, 5, |_, j| {
+            let market: f64 = rng.gen_range(-0.05..0.05);
+            market + scales[j] * rng.gen_range(-1.0..1.0)
+        })
+        .map_with_location(|_, j, r| if j == 4 { 0.0 } else { r })
+    }
+
+    fn make_pipeline() -> Pipeline {
+        let constraints = ConstraintSet::new()
+            .with_box(BoxConstraint::uniform(5, 0.0, 0.7))
+            .with_linear(LinearConstraint::full_investment(5));
+
+        Pipeline::new()
+            .preprocess(UniverseFilter::new().with_max_volatility(0.25))
+            .set_objective(ObjectiveType::MinimizeVariance)
+            .set_constraints(constraints)
+            .postprocess(WeightRounder::new(0.01))
+    }
+
+    #[test]
+    fn test_pipeline_run() {
+        let result = make_pipeline().run(&make_returns()).unwrap();
+
+        assert_eq!(result.weights.len(), 5);
+        assert_eq!(result.weights[3], 0.0);
+        assert_eq!(result.weights[4], 0.0);
+
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        for &w in result.weights.iter() {
+            assert!((-1e-9..=0.7).contains(&w));
+            let lots = w / 0.01;
+            assert!((lots - lots.round()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_pipeline_volatility_target() {
+        let returns = make_returns();
+        let unscaled = make_pipeline().run(&returns).unwrap();
+
+        let target = 0.5 * unscaled.volatility;
+        let scaled = make_pipeline()
+            .postprocess_liquidity(VolatilityTargeter::new(target))
+            .run(&returns)
+            .unwrap();
+
+        assert!((scaled.volatility - target).abs() < 1e-10);
+        assert!(scaled.weights.iter().sum::<f64>() < 1.0);
+    }
+
+    #[test]
+    fn test_pipeline_serde() {
+        let pipeline = make_pipeline().postprocess_liquidity(VolatilityTargeter::new(0.01));
+        let json = serde_json::to_string(&pipeline).unwrap();
+        let back: Pipeline = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(back.filters, pipeline.filters);
+        assert_eq!(back.postprocessors, pipeline.postprocessors);
+
+        let returns = make_returns();
+        assert_eq!(
+            back.run(&returns).unwrap().weights,
+            pipeline.run(&returns).unwrap().weights
+        );
+    }
+
+    #[test]
+    fn test_weight_rounder_preserves_total() {
+        let rounded = WeightRounder::new(0.05)
+            .round(&[0.333, 0.333, 0.334], None)
+            .unwrap();
+        assert!((rounded.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(rounded.iter().all(|&w| (0.3..=0.35 + 1e-12).contains(&w)));
+
+        assert!(WeightRounder::new(0.0).round(&[1.0], None).is_err());
+    }
+
+    #[test]
+    fn test_weight_rounder_respects_bounds() {
+        // Largest remainders would round 0.698 up past the 0.69 cap; the
+        // spare lot goes to the asset with room instead
+        let bounds = BoxConstraint::new(vec![0.0; 3], vec![0.69, 1.0, 1.0]);
+        let rounded = WeightRounder::new(0.01)
+            .round(&[0.698, 0.151, 0.151], Some(&bounds))
+            .unwrap();
+        assert_eq!(rounded[0], 0.69);
+        assert!((rounded.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        for &w in &rounded[1..] {
+            assert!((0.15..=0.16 + 1e-12).contains(&w));
+        }
+
+        // A lower bound above the floored lot pulls the weight up and the
+        // surplus comes off the other assets
+        let bounds = BoxConstraint::new(vec![0.2, 0.0, 0.0], vec![1.0; 3]);
+        let rounded = WeightRounder::new(0.1)
+            .round(&[0.15, 0.45, 0.4], Some(&bounds))
+            .unwrap();
+        assert!(rounded[0] >= 0.2);
+        assert!((rounded.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // No room anywhere for the rounded total
+        let tight = BoxConstraint::uniform(2, 0.0, 0.4);
+        assert!(WeightRounder::new(0.1)
+            .round(&[0.5, 0.5], Some(&tight))
+            .is_err());
+        assert!(WeightRounder::new(0.1).round(&[0.5], Some(&tight)).is_err());
+    }
+}
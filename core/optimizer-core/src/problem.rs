@@ -2,12 +2,17 @@
 //!
 //! Defines the portfolio optimization problem structure.
 
-use crate::constraints::ConstraintSet;
+use covariance::matrix::{is_positive_semi_definite, vec_to_dmatrix};
+
+use crate::analytics;
+use crate::constraints::{CardinalityConstraint, ConstraintSet};
+use crate::utils::{cross_sectional_rank, cross_sectional_zscore};
+use crate::weights::PortfolioWeights;
 use crate::{OptimizerError, Result};
 use serde::{Deserialize, Serialize};
 
 /// Optimization objective type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ObjectiveType {
     /// Minimize variance (risk)
     MinimizeVariance,
@@ -19,10 +24,128 @@ pub enum ObjectiveType {
     RiskParity,
     /// Mean-variance with risk aversion parameter
     MeanVariance,
+    /// Minimize tracking error variance (w - b)'Σ(w - b) against a benchmark
+    MinimizeTrackingError {
+        /// Benchmark weights `b` (n_assets)
+        benchmark_weights: Vec<f64>,
+    },
+    /// Minimize conditional value-at-risk (expected shortfall) of the
+    /// portfolio return over return scenarios
+    MinimizeCVaR {
+        /// Confidence level `α` in (0, 1), e.g. 0.95
+        confidence: f64,
+        /// Scenarios drawn from N(μ, Σ) when the problem has none
+        n_scenarios: usize,
+    },
+    /// Hierarchical risk parity: recursive inverse-variance allocation over
+    /// a correlation dendrogram, without inverting the covariance
+    HierarchicalRiskParity,
+    /// Maximize the diversification ratio `sum(w_i σ_i) / sqrt(w'Σw)`
+    MaxDiversification,
+}
+
+/// Smallest eigenvalue tolerated when checking covariance PSD-ness
+const PSD_TOLERANCE: f64 = 1e-10;
+
+/// Default holding period in years, matching annualized return inputs
+const DEFAULT_HOLD_PERIOD_YEARS: f64 = 1.0;
+
+fn default_hold_period() -> f64 {
+    DEFAULT_HOLD_PERIOD_YEARS
+}
+
+/// Risk-free rate term structure (yield curve)
+///
+/// Always holds at least one maturity, so interpolation cannot fail.
+/// Deserialization applies the same validation as [`RateTermStructure::new`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RateTermStructureData")]
+pub struct RateTermStructure {
+    /// Maturities in years (strictly increasing)
+    maturities: Vec<f64>,
+    /// Annualized rates at each maturity
+    rates: Vec<f64>,
+}
+
+/// Unvalidated serialized form of [`RateTermStructure`]
+#[derive(Deserialize)]
+struct RateTermStructureData {
+    maturities: Vec<f64>,
+    rates: Vec<f64>,
+}
+
+impl TryFrom<RateTermStructureData> for RateTermStructure {
+    type Error = OptimizerError;
+
+    fn try_from(data: RateTermStructureData) -> Result<Self> {
+        Self::new(data.maturities, data.rates)
+    }
+}
+
+impl RateTermStructure {
+    /// Create a term structure, validating the maturity grid
+    ///
+    /// Requires at least one maturity, one finite rate per maturity and
+    /// finite, strictly increasing maturities.
+    pub fn new(maturities: Vec<f64>, rates: Vec<f64>) -> Result<Self> {
+        if maturities.is_empty() {
+            return Err(OptimizerError::InvalidInput(
+                "Term structure has no maturities".to_string(),
+            ));
+        }
+        if rates.len() != maturities.len() {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: maturities.len(),
+                got: rates.len(),
+            });
+        }
+        if maturities.iter().chain(&rates).any(|x| !x.is_finite()) {
+            return Err(OptimizerError::InvalidInput(
+                "Term structure maturities and rates must be finite".to_string(),
+            ));
+        }
+        if maturities.windows(2).any(|m| m[1] <= m[0]) {
+            return Err(OptimizerError::InvalidInput(
+                "Maturities must be strictly increasing".to_string(),
+            ));
+        }
+
+        Ok(Self { maturities, rates })
+    }
+
+    /// Maturities in years
+    pub fn maturities(&self) -> &[f64] {
+        &self.maturities
+    }
+
+    /// Annualized rates at each maturity
+    pub fn rates(&self) -> &[f64] {
+        &self.rates
+    }
+
+    /// Rate for a holding period, linearly interpolated on the maturity grid
+    ///
+    /// Rates are extrapolated flat beyond the first and last maturities.
+    pub fn interpolate(&self, maturity_years: f64) -> f64 {
+        let n = self.maturities.len();
+        if maturity_years <= self.maturities[0] {
+            return self.rates[0];
+        }
+        if maturity_years >= self.maturities[n - 1] {
+            return self.rates[n - 1];
+        }
+
+        // First grid point strictly above the requested maturity
+        let hi = self.maturities.partition_point(|&m| m <= maturity_years);
+        let lo = hi - 1;
+        let frac =
+            (maturity_years - self.maturities[lo]) / (self.maturities[hi] - self.maturities[lo]);
+        self.rates[lo] + (self.rates[hi] - self.rates[lo]) * frac
+    }
 }
 
 /// Transaction cost model
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TransactionCostModel {
     /// Linear cost (e.g., commission rate)
     pub linear_cost: f64,
@@ -30,6 +153,12 @@ pub struct TransactionCostModel {
     pub fixed_cost: f64,
     /// Market impact coefficient (for quadratic impact)
     pub impact_coefficient: f64,
+    /// Expected holding period in years
+    #[serde(default = "default_hold_period")]
+    pub hold_period_years: f64,
+    /// Funding curve for carry cost (no carry if unset)
+    #[serde(default)]
+    pub funding_curve: Option<RateTermStructure>,
 }
 
 impl Default for TransactionCostModel {
@@ -38,22 +167,41 @@ impl Default for TransactionCostModel {
             linear_cost: 0.001, // 10 bps
             fixed_cost: 0.0,
             impact_coefficient: 0.0,
+            hold_period_years: DEFAULT_HOLD_PERIOD_YEARS,
+            funding_curve: None,
         }
     }
 }
 
 impl TransactionCostModel {
     /// Calculate transaction cost for a trade
+    ///
+    /// Execution cost only; financing is charged on positions, not trades,
+    /// and callers add [`Self::carry_cost`] separately.
     pub fn cost(&self, trade_value: f64) -> f64 {
         let abs_trade = trade_value.abs();
         self.fixed_cost
             + self.linear_cost * abs_trade
             + self.impact_coefficient * abs_trade * abs_trade
     }
+
+    /// Calculate the cost of financing a position over the holding period
+    ///
+    /// Uses the funding rate interpolated at `hold_period_years`. Not
+    /// included in [`Self::cost`].
+    pub fn carry_cost(&self, position_value: f64) -> f64 {
+        match &self.funding_curve {
+            Some(curve) => {
+                let rate = curve.interpolate(self.hold_period_years);
+                position_value.abs() * rate * self.hold_period_years
+            }
+            None => 0.0,
+        }
+    }
 }
 
 /// Portfolio optimization problem
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OptimizationProblem {
     /// Number of assets
     pub n_assets: usize,
@@ -73,6 +221,9 @@ pub struct OptimizationProblem {
     pub transaction_costs: Option<TransactionCostModel>,
     /// Current weights (for turnover/rebalancing)
     pub current_weights: Option<Vec<f64>>,
+    /// Asset return scenarios, e.g. historical returns (n_scenarios x n_assets)
+    #[serde(default)]
+    pub scenarios: Option<Vec<Vec<f64>>>,
 }
 
 impl OptimizationProblem {
@@ -81,6 +232,46 @@ impl OptimizationProblem {
         OptimizationProblemBuilder::new(n_assets)
     }
 
+    /// Append a zero-variance asset with return `rate` and use it as the risk-free rate
+    fn with_risk_free_asset(mut self, rate: f64) -> Result<Self> {
+        if !rate.is_finite() {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Risk-free rate {} is not finite",
+                rate
+            )));
+        }
+
+        let n = self.n_assets + 1;
+        self.n_assets = n;
+        self.expected_returns.push(rate);
+        for row in &mut self.covariance {
+            row.push(0.0);
+        }
+        self.covariance.push(vec![0.0; n]);
+        self.constraints = self.constraints.append_unconstrained_asset();
+        if let Some(current) = &mut self.current_weights {
+            current.push(0.0);
+        }
+        if let Some(scenarios) = &mut self.scenarios {
+            for scenario in scenarios {
+                scenario.push(rate);
+            }
+        }
+        if let ObjectiveType::MinimizeTrackingError { benchmark_weights } = &mut self.objective {
+            benchmark_weights.push(0.0);
+        }
+        self.risk_free_rate = rate;
+
+        let cov = vec_to_dmatrix(&self.covariance)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?;
+        if !is_positive_semi_definite(&cov, PSD_TOLERANCE) {
+            return Err(OptimizerError::NotPositiveSemiDefinite);
+        }
+
+        self.validate()?;
+        Ok(self)
+    }
+
     /// Validate the problem
     pub fn validate(&self) -> Result<()> {
         // Check dimensions
@@ -128,6 +319,75 @@ impl OptimizationProblem {
             }
         }
 
+        if let Some(cardinality) = &self.constraints.cardinality_constraint {
+            let min_weight = cardinality.min_weight_if_held;
+            if !(min_weight >= 0.0 && min_weight.is_finite()) {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "Minimum held weight {} must be finite and non-negative",
+                    min_weight
+                )));
+            }
+            if min_weight > 0.0 {
+                // The minimum is imposed as a lower bound on held assets, so
+                // it only applies to long-only problems
+                let lower = match &self.constraints.box_constraint {
+                    Some(bounds) => &bounds.lower,
+                    None => {
+                        return Err(OptimizerError::InvalidInput(
+                            "Minimum held weight requires long-only box bounds".to_string(),
+                        ))
+                    }
+                };
+                if let Some(i) = lower.iter().position(|&l| l.is_nan() || l < 0.0) {
+                    return Err(OptimizerError::InvalidInput(format!(
+                        "Minimum held weight requires long-only bounds, but asset {} has lower bound {}",
+                        i, lower[i]
+                    )));
+                }
+            }
+        }
+
+        if let Some(turnover) = &self.constraints.turnover_constraint {
+            if turnover.current_weights.len() != self.n_assets {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: self.n_assets,
+                    got: turnover.current_weights.len(),
+                });
+            }
+            if turnover.max_turnover.is_nan() || turnover.max_turnover < 0.0 {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "Maximum turnover {} must be non-negative",
+                    turnover.max_turnover
+                )));
+            }
+        }
+
+        if let Some(tracking) = &self.constraints.tracking_error_constraint {
+            if tracking.benchmark.len() != self.n_assets {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: self.n_assets,
+                    got: tracking.benchmark.len(),
+                });
+            }
+            let limit = tracking.max_tracking_error;
+            if !(limit >= 0.0 && limit.is_finite()) {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "Maximum tracking error {} must be finite and non-negative",
+                    limit
+                )));
+            }
+        }
+
+        // Check linear constraint dimensions
+        for constraint in &self.constraints.linear_constraints {
+            if constraint.n_assets() != self.n_assets {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: self.n_assets,
+                    got: constraint.n_assets(),
+                });
+            }
+        }
+
         // Check current weights dimensions
         if let Some(current) = &self.current_weights {
             if current.len() != self.n_assets {
@@ -138,6 +398,44 @@ impl OptimizationProblem {
             }
         }
 
+        // Check benchmark dimensions
+        if let ObjectiveType::MinimizeTrackingError { benchmark_weights } = &self.objective {
+            if benchmark_weights.len() != self.n_assets {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: self.n_assets,
+                    got: benchmark_weights.len(),
+                });
+            }
+        }
+
+        // Check CVaR parameters and scenario dimensions
+        if let ObjectiveType::MinimizeCVaR {
+            confidence,
+            n_scenarios,
+        } = &self.objective
+        {
+            if !(*confidence > 0.0 && *confidence < 1.0) {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "CVaR confidence {} must be in (0, 1)",
+                    confidence
+                )));
+            }
+            let available = self.scenarios.as_ref().map_or(*n_scenarios, Vec::len);
+            if available == 0 {
+                return Err(OptimizerError::InvalidInput(
+                    "CVaR needs at least one scenario".to_string(),
+                ));
+            }
+        }
+        for scenario in self.scenarios.iter().flatten() {
+            if scenario.len() != self.n_assets {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: self.n_assets,
+                    got: scenario.len(),
+                });
+            }
+        }
+
         Ok(())
     }
 
@@ -152,6 +450,38 @@ impl OptimizationProblem {
         variance
     }
 
+    /// Portfolio variance after changing a few weights
+    ///
+    /// Expands `(w + Δw)'Σ(w + Δw)` as
+    /// `old_variance + 2 Σ_i Δw_i (Σw)_i + Σ_i Σ_j Δw_i Δw_j Σ_ij` over the
+    /// changed indices only, costing `O(K·N + K²)` for `K` changes instead
+    /// of the `O(N²)` full recomputation. `old_variance` must be the
+    /// variance of `old_weights`. Repeated indices are summed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a changed index is out of range.
+    pub fn variance_update(
+        &self,
+        old_weights: &[f64],
+        old_variance: f64,
+        delta_weights: &[(usize, f64)],
+    ) -> f64 {
+        let mut variance = old_variance;
+        for &(i, dw_i) in delta_weights {
+            let sigma_w: f64 = self.covariance[i]
+                .iter()
+                .zip(old_weights)
+                .map(|(c, w)| c * w)
+                .sum();
+            variance += 2.0 * dw_i * sigma_w;
+            for &(j, dw_j) in delta_weights {
+                variance += dw_i * dw_j * self.covariance[i][j];
+            }
+        }
+        variance
+    }
+
     /// Calculate portfolio expected return for given weights
     pub fn portfolio_return(&self, weights: &[f64]) -> f64 {
         weights
@@ -170,6 +500,22 @@ impl OptimizationProblem {
         }
         (ret - self.risk_free_rate) / vol
     }
+
+    /// Calculate the diversification ratio for given weights
+    ///
+    /// See [`analytics::diversification_ratio`]; NaN at zero volatility.
+    pub fn diversification_ratio(&self, weights: &[f64]) -> f64 {
+        let n = self.n_assets;
+        let covariance = nalgebra::DMatrix::from_fn(n, n, |i, j| self.covariance[i][j]);
+        analytics::diversification_ratio(weights, &covariance)
+    }
+}
+
+/// Cross-sectional transform applied to expected returns at build time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReturnsTransform {
+    ZScore,
+    Rank,
 }
 
 /// Builder for OptimizationProblem
@@ -183,6 +529,10 @@ pub struct OptimizationProblemBuilder {
     risk_free_rate: f64,
     transaction_costs: Option<TransactionCostModel>,
     current_weights: Option<Vec<f64>>,
+    scenarios: Option<Vec<Vec<f64>>>,
+    returns_transform: Option<ReturnsTransform>,
+    yield_curve: Option<RateTermStructure>,
+    risk_free_asset: Option<f64>,
 }
 
 impl OptimizationProblemBuilder {
@@ -198,6 +548,10 @@ impl OptimizationProblemBuilder {
             risk_free_rate: 0.0,
             transaction_costs: None,
             current_weights: None,
+            scenarios: None,
+            returns_transform: None,
+            yield_curve: None,
+            risk_free_asset: None,
         }
     }
 
@@ -237,6 +591,16 @@ impl OptimizationProblemBuilder {
         self
     }
 
+    /// Derive the risk-free rate from a yield curve
+    ///
+    /// At build time the rate is read off the curve at the transaction cost
+    /// model's holding period (one year if no cost model is set), overriding
+    /// any scalar `risk_free_rate`.
+    pub fn yield_curve(mut self, ts: RateTermStructure) -> Self {
+        self.yield_curve = Some(ts);
+        self
+    }
+
     /// Set transaction costs
     pub fn transaction_costs(mut self, costs: TransactionCostModel) -> Self {
         self.transaction_costs = Some(costs);
@@ -249,16 +613,78 @@ impl OptimizationProblemBuilder {
         self
     }
 
+    /// Set return scenarios for CVaR objectives (n_scenarios x n_assets)
+    pub fn scenarios(mut self, scenarios: Vec<Vec<f64>>) -> Self {
+        self.scenarios = Some(scenarios);
+        self
+    }
+
+    /// Convert expected returns to cross-sectional z-scores when building
+    pub fn zscore_expected_returns(mut self) -> Self {
+        self.returns_transform = Some(ReturnsTransform::ZScore);
+        self
+    }
+
+    /// Convert expected returns to cross-sectional ranks in [0, 1] when building
+    pub fn rank_expected_returns(mut self) -> Self {
+        self.returns_transform = Some(ReturnsTransform::Rank);
+        self
+    }
+
+    /// Replicate an index by minimizing tracking error against its weights
+    ///
+    /// With `max_n_assets`, at most that many assets may be held; the
+    /// solver enforces the cardinality limit by branch and bound.
+    pub fn index_tracking(mut self, index_weights: Vec<f64>, max_n_assets: Option<usize>) -> Self {
+        self.objective = ObjectiveType::MinimizeTrackingError {
+            benchmark_weights: index_weights,
+        };
+        if let Some(max_assets) = max_n_assets {
+            self.constraints = self
+                .constraints
+                .with_cardinality(CardinalityConstraint::new(max_assets));
+        }
+        self
+    }
+
+    /// Append a risk-free asset (e.g., cash) to the universe when building
+    ///
+    /// The asset has expected return `rate`, zero variance and covariance,
+    /// and an unconstrained weight; it counts towards the full-investment
+    /// budget. The problem's risk-free rate is set to `rate`, so the asset
+    /// is the last of `n_assets + 1` assets.
+    pub fn include_risk_free_asset(mut self, rate: f64) -> Self {
+        self.risk_free_asset = Some(rate);
+        self
+    }
+
     /// Build the optimization problem
     pub fn build(self) -> Result<OptimizationProblem> {
         let expected_returns = self
             .expected_returns
             .ok_or_else(|| OptimizerError::InvalidInput("Expected returns not set".to_string()))?;
 
+        let expected_returns = match self.returns_transform {
+            Some(ReturnsTransform::ZScore) => cross_sectional_zscore(&expected_returns),
+            Some(ReturnsTransform::Rank) => cross_sectional_rank(&expected_returns),
+            None => expected_returns,
+        };
+
         let covariance = self
             .covariance
             .ok_or_else(|| OptimizerError::InvalidInput("Covariance not set".to_string()))?;
 
+        let risk_free_rate = match &self.yield_curve {
+            Some(curve) => {
+                let hold_period = self
+                    .transaction_costs
+                    .as_ref()
+                    .map_or(DEFAULT_HOLD_PERIOD_YEARS, |c| c.hold_period_years);
+                curve.interpolate(hold_period)
+            }
+            None => self.risk_free_rate,
+        };
+
         let problem = OptimizationProblem {
             n_assets: self.n_assets,
             expected_returns,
@@ -266,21 +692,26 @@ impl OptimizationProblemBuilder {
             constraints: self.constraints,
             objective: self.objective,
             risk_aversion: self.risk_aversion,
-            risk_free_rate: self.risk_free_rate,
+            risk_free_rate,
             transaction_costs: self.transaction_costs,
             current_weights: self.current_weights,
+            scenarios: self.scenarios,
         };
 
         problem.validate()?;
-        Ok(problem)
+
+        match self.risk_free_asset {
+            Some(rate) => problem.with_risk_free_asset(rate),
+            None => Ok(problem),
+        }
     }
 }
 
 /// Optimization result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OptimizationResult {
     /// Optimal weights
-    pub weights: Vec<f64>,
+    pub weights: PortfolioWeights,
     /// Portfolio expected return
     pub expected_return: f64,
     /// Portfolio variance
@@ -295,6 +726,132 @@ pub struct OptimizationResult {
     pub status: SolverStatus,
     /// Total transaction cost (if applicable)
     pub transaction_cost: Option<f64>,
+    /// Shrinkage intensity applied to an ill-conditioned covariance (if any)
+    #[serde(default)]
+    pub regularization_applied: Option<f64>,
+    /// Number of assets above the weight density threshold (0 if unconstrained)
+    #[serde(default)]
+    pub n_assets_above_threshold: usize,
+    /// Scenario CVaR at the objective's confidence (CVaR objectives only)
+    #[serde(default)]
+    pub cvar: Option<f64>,
+    /// Diversification ratio (maximum diversification objectives only)
+    #[serde(default)]
+    pub diversification_ratio: Option<f64>,
+    /// Tracking error against the constrained benchmark (if constrained)
+    #[serde(default)]
+    pub tracking_error: Option<f64>,
+    /// Covariance the statistics were computed against (empty if the
+    /// solver had no dense covariance)
+    #[serde(default)]
+    pub covariance: Vec<Vec<f64>>,
+}
+
+impl OptimizationResult {
+    /// Result holding `weights` with zero statistics and no iterations
+    ///
+    /// The optional diagnostics are unset. Callers fill in whichever
+    /// statistics they compute, typically with struct update syntax.
+    pub fn from_weights(weights: Vec<f64>, status: SolverStatus) -> Self {
+        Self {
+            weights: PortfolioWeights::unconstrained(weights),
+            expected_return: 0.0,
+            variance: 0.0,
+            volatility: 0.0,
+            sharpe_ratio: 0.0,
+            iterations: 0,
+            status,
+            transaction_cost: None,
+            regularization_applied: None,
+            n_assets_above_threshold: 0,
+            cvar: None,
+            diversification_ratio: None,
+            tracking_error: None,
+            covariance: Vec::new(),
+        }
+    }
+
+    /// Per-asset contributions to expected return and volatility
+    ///
+    /// `return_contribution = w_i μ_i` and `risk_contribution = w_i (Σw)_i
+    /// / σ_p`, which sum to the portfolio's expected return and volatility
+    /// when `expected_returns` are the ones the result was solved with.
+    /// `Σ` is the result's recorded covariance. `sharpe_contribution` is
+    /// their ratio, and zero for an asset that adds no risk; with zero
+    /// portfolio volatility every risk contribution is zero.
+    ///
+    /// Fails if `expected_returns` or the recorded covariance does not
+    /// match the number of weights.
+    pub fn attribution_table(&self, expected_returns: &[f64]) -> Result<Vec<AssetAttribution>> {
+        let weights: &[f64] = &self.weights;
+        let n = weights.len();
+        if expected_returns.len() != n {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: n,
+                got: expected_returns.len(),
+            });
+        }
+        if let Some(len) = std::iter::once(self.covariance.len())
+            .chain(self.covariance.iter().map(Vec::len))
+            .find(|&len| len != n)
+        {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: n,
+                got: len,
+            });
+        }
+
+        let marginal: Vec<f64> = self
+            .covariance
+            .iter()
+            .map(|row| row.iter().zip(weights).map(|(c, w)| c * w).sum())
+            .collect();
+        let volatility = weights
+            .iter()
+            .zip(&marginal)
+            .map(|(w, m)| w * m)
+            .sum::<f64>()
+            .max(0.0)
+            .sqrt();
+
+        Ok((0..n)
+            .map(|i| {
+                let return_contribution = weights[i] * expected_returns[i];
+                let risk_contribution = if volatility > 0.0 {
+                    weights[i] * marginal[i] / volatility
+                } else {
+                    0.0
+                };
+                let sharpe_contribution = if risk_contribution != 0.0 {
+                    return_contribution / risk_contribution
+                } else {
+                    0.0
+                };
+                AssetAttribution {
+                    asset_idx: i,
+                    weight: weights[i],
+                    return_contribution,
+                    risk_contribution,
+                    sharpe_contribution,
+                }
+            })
+            .collect())
+    }
+}
+
+/// One asset's row of a return and risk attribution table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssetAttribution {
+    /// Asset index
+    pub asset_idx: usize,
+    /// Portfolio weight
+    pub weight: f64,
+    /// Contribution to expected return
+    pub return_contribution: f64,
+    /// Contribution to volatility
+    pub risk_contribution: f64,
+    /// Return contribution per unit of risk contribution
+    pub sharpe_contribution: f64,
 }
 
 /// Solver status
@@ -350,7 +907,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let weights = vec![0.5, 0.5];
+        let weights = PortfolioWeights::try_new(vec![0.5, 0.5], 1e-10).unwrap();
 
         // Expected return = 0.5 * 0.10 + 0.5 * 0.15 = 0.125
         let ret = problem.portfolio_return(&weights);
@@ -361,6 +918,45 @@ mod tests {
         assert!((var - 0.0375).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_variance_update() {
+        let n = 6;
+        let covariance: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let vol_i = 0.1 + 0.02 * i as f64;
+                        let vol_j = 0.1 + 0.02 * j as f64;
+                        let rho = if i == j { 1.0 } else { 0.3 };
+                        rho * vol_i * vol_j
+                    })
+                    .collect()
+            })
+            .collect();
+        let problem = OptimizationProblem::builder(n)
+            .expected_returns(vec![0.05; n])
+            .covariance(covariance)
+            .build()
+            .unwrap();
+
+        let old = vec![0.1, 0.2, 0.15, 0.25, 0.2, 0.1];
+        let old_variance = problem.portfolio_variance(&old);
+
+        for delta in [
+            vec![(1, 0.05), (4, -0.05)],
+            vec![(0, -0.1), (2, 0.03), (5, 0.07)],
+            vec![(3, 0.02), (3, 0.01)],
+            vec![],
+        ] {
+            let mut new = old.clone();
+            for &(i, dw) in &delta {
+                new[i] += dw;
+            }
+            let updated = problem.variance_update(&old, old_variance, &delta);
+            assert!((updated - problem.portfolio_variance(&new)).abs() < 1e-15);
+        }
+    }
+
     #[test]
     fn test_validation() {
         let returns = vec![0.10, 0.15];
@@ -368,11 +964,63 @@ mod tests {
 
         // Wrong dimension
         let result = OptimizationProblem::builder(3)
-            .expected_returns(returns)
-            .covariance(cov)
+            .expected_returns(returns.clone())
+            .covariance(cov.clone())
             .build();
 
         assert!(result.is_err());
+
+        // A minimum held weight needs long-only bounds
+        let cardinality = CardinalityConstraint::new(1).with_min_weight_if_held(0.1);
+        for (bounds, ok) in [
+            (None, false),
+            (Some(BoxConstraint::uniform(2, -1.0, 1.0)), false),
+            (Some(BoxConstraint::long_only(2)), true),
+        ] {
+            let mut constraints = ConstraintSet::new().with_cardinality(cardinality.clone());
+            constraints.box_constraint = bounds;
+            let result = OptimizationProblem::builder(2)
+                .expected_returns(returns.clone())
+                .covariance(cov.clone())
+                .constraints(constraints)
+                .build();
+            assert_eq!(result.is_ok(), ok);
+        }
+    }
+
+    #[test]
+    fn test_builder_returns_transforms() {
+        let returns = vec![0.10, 0.15, 0.12];
+        let cov = vec![
+            vec![0.04, 0.01, 0.02],
+            vec![0.01, 0.09, 0.03],
+            vec![0.02, 0.03, 0.0625],
+        ];
+
+        let zscored = OptimizationProblem::builder(3)
+            .expected_returns(returns.clone())
+            .covariance(cov.clone())
+            .zscore_expected_returns()
+            .build()
+            .unwrap();
+        let mean: f64 = zscored.expected_returns.iter().sum::<f64>() / 3.0;
+        let std = (zscored
+            .expected_returns
+            .iter()
+            .map(|r| (r - mean).powi(2))
+            .sum::<f64>()
+            / 3.0)
+            .sqrt();
+        assert!(mean.abs() < 1e-10);
+        assert!((std - 1.0).abs() < 1e-10);
+
+        let ranked = OptimizationProblem::builder(3)
+            .expected_returns(returns)
+            .covariance(cov)
+            .rank_expected_returns()
+            .build()
+            .unwrap();
+        assert_eq!(ranked.expected_returns, vec![0.0, 1.0, 0.5]);
     }
 
     #[test]
@@ -380,4 +1028,116 @@ mod tests {
         let model = TransactionCostModel::default();
         assert!((model.cost(1000.0) - 1.0).abs() < 1e-10); // 10 bps = 0.1%
     }
+
+    #[test]
+    fn test_rate_term_structure_interpolation() {
+        let curve =
+            RateTermStructure::new(vec![0.25, 1.0, 5.0], vec![0.020, 0.030, 0.040]).unwrap();
+
+        // Exact at grid points
+        assert_eq!(curve.interpolate(0.25), 0.020);
+        assert_eq!(curve.interpolate(1.0), 0.030);
+        assert_eq!(curve.interpolate(5.0), 0.040);
+
+        // Linear between grid points
+        assert!((curve.interpolate(0.625) - 0.025).abs() < 1e-12);
+        assert!((curve.interpolate(2.0) - 0.0325).abs() < 1e-12);
+
+        // Flat beyond the ends
+        assert_eq!(curve.interpolate(0.1), 0.020);
+        assert_eq!(curve.interpolate(10.0), 0.040);
+
+        assert!(RateTermStructure::new(vec![1.0, 0.5], vec![0.02, 0.03]).is_err());
+        assert!(RateTermStructure::new(vec![1.0], vec![0.02, 0.03]).is_err());
+        assert!(RateTermStructure::new(vec![1.0], vec![f64::NAN]).is_err());
+        assert!(RateTermStructure::new(vec![], vec![]).is_err());
+
+        // Deserialization validates the grid
+        let json = serde_json::to_string(&curve).unwrap();
+        let back: RateTermStructure = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, curve);
+        assert!(
+            serde_json::from_str::<RateTermStructure>(r#"{"maturities":[],"rates":[]}"#).is_err()
+        );
+        assert!(serde_json::from_str::<RateTermStructure>(
+            r#"{"maturities":[2.0,1.0],"rates":[0.1,0.2]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_yield_curve_and_carry_cost() {
+        let curve = RateTermStructure::new(vec![0.5, 2.0], vec![0.02, 0.05]).unwrap();
+        let costs = TransactionCostModel {
+            hold_period_years: 1.0,
+            funding_curve: Some(curve.clone()),
+            ..Default::default()
+        };
+
+        // 1-year rate: 0.02 + (0.05 - 0.02) * (0.5 / 1.5) = 0.03
+        assert!((costs.carry_cost(-1000.0) - 30.0).abs() < 1e-9);
+        assert_eq!(TransactionCostModel::default().carry_cost(1000.0), 0.0);
+        // Trading costs exclude carry: 10 bps commission only
+        assert!((costs.cost(-1000.0) - 1.0).abs() < 1e-9);
+
+        let problem = OptimizationProblem::builder(2)
+            .expected_returns(vec![0.10, 0.15])
+            .covariance(vec![vec![0.04, 0.01], vec![0.01, 0.09]])
+            .transaction_costs(TransactionCostModel {
+                hold_period_years: 2.0,
+                ..Default::default()
+            })
+            .yield_curve(curve)
+            .build()
+            .unwrap();
+        assert!((problem.risk_free_rate - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_attribution_table() {
+        let problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(vec![
+                vec![0.04, 0.01, 0.02],
+                vec![0.01, 0.09, 0.03],
+                vec![0.02, 0.03, 0.0625],
+            ])
+            .constraints(ConstraintSet::long_only_full_investment(3))
+            .build()
+            .unwrap();
+        let result = crate::solver::QpSolver::default().solve(&problem).unwrap();
+
+        let table = result.attribution_table(&problem.expected_returns).unwrap();
+        assert_eq!(table.len(), 3);
+        let total_return: f64 = table.iter().map(|row| row.return_contribution).sum();
+        let total_risk: f64 = table.iter().map(|row| row.risk_contribution).sum();
+        assert!((total_return - result.expected_return).abs() < 1e-12);
+        assert!((total_risk - result.volatility).abs() < 1e-12);
+
+        for (i, row) in table.iter().enumerate() {
+            assert_eq!(row.asset_idx, i);
+            assert_eq!(row.weight, result.weights[i]);
+            assert!(
+                (row.sharpe_contribution * row.risk_contribution - row.return_contribution).abs()
+                    < 1e-12
+            );
+        }
+
+        assert!(matches!(
+            result.attribution_table(&[0.1, 0.2]),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 3,
+                got: 2
+            })
+        ));
+        // Without a recorded covariance there is no risk to attribute
+        let bare = OptimizationResult::from_weights(result.weights.to_vec(), SolverStatus::Optimal);
+        assert!(matches!(
+            bare.attribution_table(&problem.expected_returns),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 3,
+                got: 0
+            })
+        ));
+    }
 }
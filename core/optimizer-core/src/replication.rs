@@ -0,0 +1,146 @@
+//! Index replication quality
+//!
+//! Measures how closely a tracking portfolio (see
+//! `OptimizationProblemBuilder::index_tracking`) replicates its index.
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::CardinalityConstraint;
+use crate::problem::OptimizationResult;
+
+/// Replication quality of a portfolio against an index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexReplicationMetrics {
+    /// Ex-ante tracking error sqrt((w - b)'Σ(w - b))
+    pub tracking_error: f64,
+    /// Active share 0.5 * sum(|w - b|)
+    pub active_share: f64,
+    /// Number of index constituents held by the portfolio
+    pub n_replicated_assets: usize,
+    /// Squared correlation between portfolio and index returns
+    pub replication_ratio: f64,
+}
+
+/// Index replication quality calculator
+pub struct IndexReplicationQuality;
+
+impl IndexReplicationQuality {
+    /// Compute replication metrics of `portfolio` against `index_weights`
+    ///
+    /// The replication ratio is zero if either the portfolio or the index
+    /// has zero variance.
+    ///
+    /// # Panics
+    /// If the weights and covariance dimensions differ.
+    pub fn compute(
+        portfolio: &OptimizationResult,
+        index_weights: &[f64],
+        covariance: &DMatrix<f64>,
+    ) -> IndexReplicationMetrics {
+        let w = DVector::from_column_slice(&portfolio.weights);
+        let b = DVector::from_column_slice(index_weights);
+        let active = &w - &b;
+
+        let tracking_error = active.dot(&(covariance * &active)).max(0.0).sqrt();
+        let active_share = 0.5 * active.iter().map(|a| a.abs()).sum::<f64>();
+
+        let n_replicated_assets = w
+            .iter()
+            .zip(b.iter())
+            .filter(|(&wi, &bi)| {
+                wi.abs() > CardinalityConstraint::HOLDING_TOL
+                    && bi.abs() > CardinalityConstraint::HOLDING_TOL
+            })
+            .count();
+
+        let sigma_b = covariance * &b;
+        let cov_wb = w.dot(&sigma_b);
+        let var_w = w.dot(&(covariance * &w));
+        let var_b = b.dot(&sigma_b);
+        let replication_ratio = if var_w > 0.0 && var_b > 0.0 {
+            cov_wb * cov_wb / (var_w * var_b)
+        } else {
+            0.0
+        };
+
+        IndexReplicationMetrics {
+            tracking_error,
+            active_share,
+            n_replicated_assets,
+            replication_ratio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::{OptimizationProblem, SolverStatus};
+    use crate::solver::QpSolver;
+    use covariance::matrix::vec_to_dmatrix;
+
+    /// One-factor covariance for `n` assets with betas spread around one
+    fn one_factor_covariance(n: usize) -> Vec<Vec<f64>> {
+        let betas: Vec<f64> = (0..n).map(|i| 0.8 + 0.4 * i as f64 / n as f64).collect();
+        (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let specific = if i == j { 0.01 + 0.001 * i as f64 } else { 0.0 };
+                        0.04 * betas[i] * betas[j] + specific
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cardinality_replication_finds_dominant_assets() {
+        let n = 20;
+        let dominant = [2, 5, 9, 13, 17];
+        let index_weights: Vec<f64> = (0..n)
+            .map(|i| if dominant.contains(&i) { 0.17 } else { 0.01 })
+            .collect();
+        let cov = one_factor_covariance(n);
+
+        let problem = OptimizationProblem::builder(n)
+            .expected_returns(vec![0.08; n])
+            .covariance(cov.clone())
+            .index_tracking(index_weights.clone(), Some(5))
+            .build()
+            .unwrap();
+        let result = QpSolver::default().solve(&problem).unwrap();
+
+        let held: Vec<usize> = (0..n)
+            .filter(|&i| result.weights[i].abs() > CardinalityConstraint::HOLDING_TOL)
+            .collect();
+        assert_eq!(held, dominant.to_vec());
+        assert_ne!(result.status, SolverStatus::SubOptimal);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+
+        let metrics = IndexReplicationQuality::compute(
+            &result,
+            &index_weights,
+            &vec_to_dmatrix(&cov).unwrap(),
+        );
+        assert_eq!(metrics.n_replicated_assets, 5);
+        assert!(metrics.tracking_error > 0.0 && metrics.tracking_error < 0.05);
+        assert!(metrics.active_share >= 0.15 - 1e-6);
+        assert!(metrics.replication_ratio > 0.9 && metrics.replication_ratio <= 1.0 + 1e-12);
+    }
+
+    #[test]
+    fn test_perfect_replication() {
+        let index_weights = vec![0.5, 0.3, 0.2];
+        let cov = vec_to_dmatrix(&one_factor_covariance(3)).unwrap();
+        let portfolio =
+            OptimizationResult::from_weights(index_weights.clone(), SolverStatus::Optimal);
+
+        let metrics = IndexReplicationQuality::compute(&portfolio, &index_weights, &cov);
+        assert_eq!(metrics.tracking_error, 0.0);
+        assert_eq!(metrics.active_share, 0.0);
+        assert_eq!(metrics.n_replicated_assets, 3);
+        assert!((metrics.replication_ratio - 1.0).abs() < 1e-12);
+    }
+}
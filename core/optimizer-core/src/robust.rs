@@ -0,0 +1,190 @@
+//! Robust portfolio optimization
+//!
+//! Minimax regret allocation over a discrete set of return scenarios, with
+//! no assumption about how likely each scenario is.
+
+use crate::constraints::{BoxConstraint, ConstraintSet};
+use crate::problem::{OptimizationProblem, OptimizationResult};
+use crate::solver::QpSolver;
+use crate::{OptimizerError, Result};
+
+/// Minimax regret optimizer for long-only, fully invested portfolios
+///
+/// The regret of weights `w` in scenario `s` is `max_w' r_s'w' - r_s'w`,
+/// the return given up against the best portfolio in hindsight. Over the
+/// simplex the best portfolio holds the top asset, so the maximum is the
+/// largest return in the scenario. `solve` minimizes the worst regret
+/// across scenarios with the LP
+///
+/// ```text
+/// min z  s.t.  z >= max_returns[s] - r_s'w  for all s,  1'w = 1,  w >= 0
+/// ```
+pub struct MinimaxRegretSolver {
+    /// Asset returns per scenario (n_scenarios x n_assets)
+    pub scenario_returns: Vec<Vec<f64>>,
+    /// Best achievable return in each scenario
+    pub max_returns: Vec<f64>,
+}
+
+impl MinimaxRegretSolver {
+    /// Create a solver, precomputing each scenario's best achievable return
+    pub fn new(scenario_returns: Vec<Vec<f64>>) -> Self {
+        let max_returns = scenario_returns
+            .iter()
+            .map(|returns| returns.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+            .collect();
+        Self {
+            scenario_returns,
+            max_returns,
+        }
+    }
+
+    /// Regret of `weights` in each scenario
+    pub fn regrets(&self, weights: &[f64]) -> Vec<f64> {
+        self.scenario_returns
+            .iter()
+            .zip(&self.max_returns)
+            .map(|(returns, best)| {
+                best - returns.iter().zip(weights).map(|(r, w)| r * w).sum::<f64>()
+            })
+            .collect()
+    }
+
+    /// Largest regret of `weights` across scenarios
+    pub fn max_regret(&self, weights: &[f64]) -> f64 {
+        self.regrets(weights)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    /// Find the weights minimizing the maximum regret
+    ///
+    /// The LP is solved with OSQP at the default solver tolerances, so the
+    /// weights and regrets are accurate to about `eps_abs`. Return
+    /// statistics are computed from the scenario mean and covariance
+    /// of asset returns, with scenarios weighted equally.
+    pub fn solve(&self) -> Result<OptimizationResult> {
+        let n_scenarios = self.scenario_returns.len();
+        if n_scenarios == 0 {
+            return Err(OptimizerError::InvalidInput(
+                "Minimax regret requires at least one scenario".to_string(),
+            ));
+        }
+        let n = self.scenario_returns[0].len();
+        if n == 0 {
+            return Err(OptimizerError::InvalidInput(
+                "Minimax regret requires at least one asset".to_string(),
+            ));
+        }
+        for returns in &self.scenario_returns {
+            if returns.len() != n {
+                return Err(OptimizerError::DimensionMismatch {
+                    expected: n,
+                    got: returns.len(),
+                });
+            }
+            if returns.iter().any(|r| !r.is_finite()) {
+                return Err(OptimizerError::InvalidInput(
+                    "Scenario returns must be finite".to_string(),
+                ));
+            }
+        }
+
+        let problem = self.scenario_problem()?;
+        QpSolver::default().solve_min_regret(&problem, &self.scenario_returns, &self.max_returns)
+    }
+
+    /// Long-only, fully invested problem with the scenario mean and
+    /// covariance of asset returns
+    fn scenario_problem(&self) -> Result<OptimizationProblem> {
+        let n_scenarios = self.scenario_returns.len() as f64;
+        let n = self.scenario_returns[0].len();
+
+        let means: Vec<f64> = (0..n)
+            .map(|i| self.scenario_returns.iter().map(|r| r[i]).sum::<f64>() / n_scenarios)
+            .collect();
+        let covariance: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        self.scenario_returns
+                            .iter()
+                            .map(|r| (r[i] - means[i]) * (r[j] - means[j]))
+                            .sum::<f64>()
+                            / n_scenarios
+                    })
+                    .collect()
+            })
+            .collect();
+
+        OptimizationProblem::builder(n)
+            .expected_returns(means)
+            .covariance(covariance)
+            .constraints(ConstraintSet::new().with_box(BoxConstraint::long_only(n)))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::SolverStatus;
+
+    #[test]
+    fn test_minimax_beats_pure_assets() {
+        // Each asset wins one scenario; none is best everywhere
+        let scenarios = vec![
+            vec![0.12, 0.01, 0.04],
+            vec![0.00, 0.10, 0.03],
+            vec![0.02, 0.04, 0.09],
+        ];
+        let solver = MinimaxRegretSolver::new(scenarios);
+        assert_eq!(solver.max_returns, vec![0.12, 0.10, 0.09]);
+
+        let result = solver.solve().unwrap();
+        assert_eq!(result.status, SolverStatus::Optimal);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(result.weights.iter().all(|&w| w >= -1e-6));
+
+        let minimax = solver.max_regret(&result.weights);
+        for asset in 0..3 {
+            let mut pure = vec![0.0; 3];
+            pure[asset] = 1.0;
+            assert!(minimax < solver.max_regret(&pure) - 1e-3);
+        }
+
+        // At the optimum the binding scenarios share the same regret, and
+        // no feasible perturbation lowers the maximum
+        let regrets = solver.regrets(&result.weights);
+        assert!(regrets.iter().all(|&r| r <= minimax + 1e-6));
+        for i in 0..3 {
+            for j in 0..3 {
+                if i == j || result.weights[j] < 0.01 {
+                    continue;
+                }
+                let mut shifted = result.weights.to_vec();
+                shifted[i] += 0.01;
+                shifted[j] -= 0.01;
+                assert!(solver.max_regret(&shifted) >= minimax - 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimax_dominant_asset() {
+        // Asset 1 is best in every scenario, so holding it has zero regret
+        let solver = MinimaxRegretSolver::new(vec![vec![0.02, 0.05], vec![-0.01, 0.03]]);
+        let result = solver.solve().unwrap();
+        assert!((result.weights[1] - 1.0).abs() < 1e-6);
+        assert!(solver.max_regret(&result.weights).abs() < 1e-6);
+        assert!((result.expected_return - 0.04).abs() < 1e-6);
+
+        assert!(matches!(
+            MinimaxRegretSolver::new(vec![vec![0.01, 0.02], vec![0.03]]).solve(),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Weight perturbation sensitivity
+//!
+//! Measures how an optimized portfolio's return and variance respond when
+//! one asset's weight is bumped and the rest are rescaled to keep the
+//! budget, as a check on how flat the optimum is.
+
+use serde::{Deserialize, Serialize};
+
+use crate::problem::{OptimizationProblem, OptimizationResult};
+use crate::{OptimizerError, Result};
+
+/// Sensitivity of return and variance to single-asset weight bumps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerturbationReport {
+    /// Return change per unit bump, by asset
+    pub marginal_returns: Vec<f64>,
+    /// Variance change per unit bump, by asset
+    pub marginal_variances: Vec<f64>,
+    /// Asset with the largest absolute marginal variance
+    pub most_sensitive_asset: usize,
+    /// Asset with the smallest absolute marginal variance
+    pub least_sensitive_asset: usize,
+}
+
+/// Weight perturbation analysis
+pub struct PerturbationAnalysis;
+
+impl PerturbationAnalysis {
+    /// Bump each weight by `delta` and measure the finite-difference response
+    ///
+    /// For asset `i`, `w_i += delta` and all other weights are scaled by a
+    /// common factor so the total weight is unchanged. If the other weights
+    /// sum to zero they cannot absorb the bump and are left as they are.
+    /// Marginals are `(f(w') - f(w)) / delta`.
+    ///
+    /// Fails unless `delta` is positive and finite and the result has one
+    /// weight per asset of the problem.
+    pub fn compute(
+        result: &OptimizationResult,
+        problem: &OptimizationProblem,
+        delta: f64,
+    ) -> Result<PerturbationReport> {
+        if !(delta > 0.0 && delta.is_finite()) {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Perturbation size must be positive and finite, got {}",
+                delta
+            )));
+        }
+        let weights: &[f64] = &result.weights;
+        if weights.len() != problem.n_assets {
+            return Err(OptimizerError::DimensionMismatch {
+                expected: problem.n_assets,
+                got: weights.len(),
+            });
+        }
+        let total: f64 = weights.iter().sum();
+        let base_return = problem.portfolio_return(weights);
+        let base_variance = problem.portfolio_variance(weights);
+
+        let (marginal_returns, marginal_variances): (Vec<f64>, Vec<f64>) = (0..weights.len())
+            .map(|i| {
+                let others = total - weights[i];
+                let scale = if others.abs() > f64::EPSILON {
+                    (others - delta) / others
+                } else {
+                    1.0
+                };
+
+                let bumped: Vec<f64> = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &w)| if j == i { w + delta } else { w * scale })
+                    .collect();
+
+                (
+                    (problem.portfolio_return(&bumped) - base_return) / delta,
+                    (problem.portfolio_variance(&bumped) - base_variance) / delta,
+                )
+            })
+            .unzip();
+
+        let by_sensitivity = |a: &(usize, &f64), b: &(usize, &f64)| a.1.abs().total_cmp(&b.1.abs());
+        let most_sensitive_asset = marginal_variances
+            .iter()
+            .enumerate()
+            .max_by(by_sensitivity)
+            .map_or(0, |(i, _)| i);
+        let least_sensitive_asset = marginal_variances
+            .iter()
+            .enumerate()
+            .min_by(by_sensitivity)
+            .map_or(0, |(i, _)| i);
+
+        Ok(PerturbationReport {
+            marginal_returns,
+            marginal_variances,
+            most_sensitive_asset,
+            least_sensitive_asset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::ObjectiveType;
+    use crate::solver::QpSolver;
+
+    fn problem() -> OptimizationProblem {
+        OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(vec![
+                vec![0.04, 0.01, 0.02],
+                vec![0.01, 0.09, 0.03],
+                vec![0.02, 0.03, 0.0625],
+            ])
+            .objective(ObjectiveType::MinimizeVariance)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_min_variance_is_flat() {
+        let problem = problem();
+        let result = QpSolver::default().solve(&problem).unwrap();
+        assert!(result.weights.iter().all(|&w| w > 0.0 && w < 1.0));
+
+        let report = PerturbationAnalysis::compute(&result, &problem, 1e-6).unwrap();
+
+        // At an interior minimum the budget-neutral variance gradient vanishes
+        let first = report.marginal_variances[0];
+        for &mv in &report.marginal_variances {
+            assert!((mv - first).abs() < 1e-4);
+            assert!(mv.abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_off_optimum_sensitivities() {
+        let problem = problem();
+        let mut result = QpSolver::default().solve(&problem).unwrap();
+        result.weights = crate::weights::PortfolioWeights::unconstrained(vec![0.1, 0.8, 0.1]);
+
+        let report = PerturbationAnalysis::compute(&result, &problem, 1e-6).unwrap();
+
+        // Funding asset 0 out of the concentrated position cuts risk the most
+        assert_eq!(report.most_sensitive_asset, 0);
+        assert_eq!(report.least_sensitive_asset, 2);
+        assert!(report.marginal_variances[0] < 0.0);
+        assert!(report.marginal_variances[1] > 0.0);
+
+        // Bumping asset 1 funds it from assets 0 and 2 pro rata
+        let expected = 0.15 - (0.10 * 0.1 + 0.12 * 0.1) / 0.2;
+        assert!((report.marginal_returns[1] - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_perturbation_rejected() {
+        let problem = problem();
+        let result = QpSolver::default().solve(&problem).unwrap();
+
+        for delta in [0.0, -1e-6, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                PerturbationAnalysis::compute(&result, &problem, delta),
+                Err(OptimizerError::InvalidInput(_))
+            ));
+        }
+    }
+}
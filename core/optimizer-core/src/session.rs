@@ -0,0 +1,598 @@
+//! HDF5 optimization sessions
+//!
+//! Saves a problem together with its solution so it can be inspected from
+//! h5py or pandas. Matrices are stored as 2D datasets, vectors as 1D
+//! datasets, scalars as attributes and enums as string attributes:
+//!
+//! ```text
+//! /problem                 n_assets, risk_aversion, risk_free_rate, objective,
+//!                          [confidence, n_scenarios]
+//!   expected_returns, covariance, [benchmark_weights], [current_weights], [scenarios]
+//!   /transaction_costs     linear_cost, fixed_cost, impact_coefficient, hold_period_years
+//!     [funding_maturities, funding_rates]
+//!   /constraints
+//!     /box                 lower, upper
+//!     /linear              n_constraints
+//!       /0, /1, ...        matrix, rhs; is_equality, name
+//!     /turnover            current_weights; max_turnover
+//!     /factor              loadings, lower, upper, factor_names
+//!     /density             threshold, max_count
+//!     /cardinality         max_assets, [min_weight_if_held]
+//!     /tracking_error      benchmark; max_tracking_error
+//! /result                  expected_return, variance, volatility, sharpe_ratio,
+//!                          iterations, status, n_assets_above_threshold,
+//!                          [transaction_cost], [regularization_applied], [cvar],
+//!                          [diversification_ratio], [tracking_error]
+//!   weights
+//! ```
+//!
+//! Bracketed entries are only present when set. Requires the `hdf5` feature.
+
+use std::path::Path;
+
+use hdf5::types::VarLenUnicode;
+use hdf5::{File, Group, H5Type, Location};
+use ndarray::Array2;
+
+use crate::constraints::{
+    BoxConstraint, CardinalityConstraint, ConstraintSet, FactorExposureConstraint,
+    LinearConstraint, TrackingErrorConstraint, TurnoverConstraint, WeightDensityConstraint,
+};
+use crate::problem::{
+    ObjectiveType, OptimizationProblem, OptimizationResult, RateTermStructure, SolverStatus,
+    TransactionCostModel,
+};
+use crate::weights::PortfolioWeights;
+use crate::{OptimizerError, Result};
+
+/// Layout version written to the root `format_version` attribute
+const FORMAT_VERSION: u32 = 1;
+
+/// Problem and result stored in one HDF5 file
+pub struct OptimizationSession;
+
+impl OptimizationSession {
+    /// Write `problem` and `result` to `path`, replacing any existing file
+    pub fn save(
+        problem: &OptimizationProblem,
+        result: &OptimizationResult,
+        path: &Path,
+    ) -> Result<()> {
+        let file = File::create(path)?;
+        write_attr(&file, "format_version", &FORMAT_VERSION)?;
+
+        let group = file.create_group("problem")?;
+        write_problem(&group, problem)?;
+
+        let group = file.create_group("result")?;
+        write_result(&group, result)?;
+
+        file.close()?;
+        Ok(())
+    }
+
+    /// Read a problem and its result from `path`
+    ///
+    /// The loaded problem is validated before it is returned.
+    pub fn load(path: &Path) -> Result<(OptimizationProblem, OptimizationResult)> {
+        let file = File::open(path)?;
+        let version: u32 = read_attr(&file, "format_version")?;
+        if version != FORMAT_VERSION {
+            return Err(malformed(format!("unsupported format version {}", version)));
+        }
+
+        let problem = read_problem(&file.group("problem")?)?;
+        problem.validate()?;
+        let result = read_result(&file.group("result")?, &problem.covariance)?;
+
+        Ok((problem, result))
+    }
+}
+
+fn write_problem(group: &Group, problem: &OptimizationProblem) -> Result<()> {
+    write_attr(group, "n_assets", &(problem.n_assets as u64))?;
+    write_attr(group, "risk_aversion", &problem.risk_aversion)?;
+    write_attr(group, "risk_free_rate", &problem.risk_free_rate)?;
+    write_string_attr(group, "objective", objective_name(&problem.objective))?;
+    match &problem.objective {
+        ObjectiveType::MinimizeTrackingError { benchmark_weights } => {
+            write_vector(group, "benchmark_weights", benchmark_weights)?;
+        }
+        ObjectiveType::MinimizeCVaR {
+            confidence,
+            n_scenarios,
+        } => {
+            write_attr(group, "confidence", confidence)?;
+            write_attr(group, "n_scenarios", &(*n_scenarios as u64))?;
+        }
+        _ => {}
+    }
+
+    write_vector(group, "expected_returns", &problem.expected_returns)?;
+    write_matrix(group, "covariance", &problem.covariance, problem.n_assets)?;
+    if let Some(current) = &problem.current_weights {
+        write_vector(group, "current_weights", current)?;
+    }
+    if let Some(scenarios) = &problem.scenarios {
+        write_matrix(group, "scenarios", scenarios, problem.n_assets)?;
+    }
+
+    if let Some(costs) = &problem.transaction_costs {
+        let costs_group = group.create_group("transaction_costs")?;
+        write_attr(&costs_group, "linear_cost", &costs.linear_cost)?;
+        write_attr(&costs_group, "fixed_cost", &costs.fixed_cost)?;
+        write_attr(
+            &costs_group,
+            "impact_coefficient",
+            &costs.impact_coefficient,
+        )?;
+        write_attr(&costs_group, "hold_period_years", &costs.hold_period_years)?;
+        if let Some(curve) = &costs.funding_curve {
+            write_vector(&costs_group, "funding_maturities", curve.maturities())?;
+            write_vector(&costs_group, "funding_rates", curve.rates())?;
+        }
+    }
+
+    write_constraints(&group.create_group("constraints")?, &problem.constraints)
+}
+
+fn read_problem(group: &Group) -> Result<OptimizationProblem> {
+    let n_assets = read_attr::<u64>(group, "n_assets")? as usize;
+
+    let objective = match read_string_attr(group, "objective")?.as_str() {
+        "MinimizeVariance" => ObjectiveType::MinimizeVariance,
+        "MaximizeReturn" => ObjectiveType::MaximizeReturn,
+        "MaximizeSharpe" => ObjectiveType::MaximizeSharpe,
+        "RiskParity" => ObjectiveType::RiskParity,
+        "HierarchicalRiskParity" => ObjectiveType::HierarchicalRiskParity,
+        "MaxDiversification" => ObjectiveType::MaxDiversification,
+        "MeanVariance" => ObjectiveType::MeanVariance,
+        "MinimizeTrackingError" => ObjectiveType::MinimizeTrackingError {
+            benchmark_weights: read_vector(group, "benchmark_weights")?,
+        },
+        "MinimizeCVaR" => ObjectiveType::MinimizeCVaR {
+            confidence: read_attr(group, "confidence")?,
+            n_scenarios: read_attr::<u64>(group, "n_scenarios")? as usize,
+        },
+        other => return Err(malformed(format!("unknown objective '{}'", other))),
+    };
+
+    let transaction_costs = if group.link_exists("transaction_costs") {
+        let costs = group.group("transaction_costs")?;
+        let funding_curve = if costs.link_exists("funding_maturities") {
+            Some(RateTermStructure::new(
+                read_vector(&costs, "funding_maturities")?,
+                read_vector(&costs, "funding_rates")?,
+            )?)
+        } else {
+            None
+        };
+        Some(TransactionCostModel {
+            linear_cost: read_attr(&costs, "linear_cost")?,
+            fixed_cost: read_attr(&costs, "fixed_cost")?,
+            impact_coefficient: read_attr(&costs, "impact_coefficient")?,
+            hold_period_years: read_attr(&costs, "hold_period_years")?,
+            funding_curve,
+        })
+    } else {
+        None
+    };
+
+    let current_weights = if group.link_exists("current_weights") {
+        Some(read_vector(group, "current_weights")?)
+    } else {
+        None
+    };
+
+    let scenarios = if group.link_exists("scenarios") {
+        Some(read_matrix(group, "scenarios")?)
+    } else {
+        None
+    };
+
+    Ok(OptimizationProblem {
+        n_assets,
+        expected_returns: read_vector(group, "expected_returns")?,
+        covariance: read_matrix(group, "covariance")?,
+        constraints: read_constraints(&group.group("constraints")?)?,
+        objective,
+        risk_aversion: read_attr(group, "risk_aversion")?,
+        risk_free_rate: read_attr(group, "risk_free_rate")?,
+        transaction_costs,
+        current_weights,
+        scenarios,
+    })
+}
+
+fn write_constraints(group: &Group, constraints: &ConstraintSet) -> Result<()> {
+    if let Some(bounds) = &constraints.box_constraint {
+        let box_group = group.create_group("box")?;
+        write_vector(&box_group, "lower", &bounds.lower)?;
+        write_vector(&box_group, "upper", &bounds.upper)?;
+    }
+
+    let linear = group.create_group("linear")?;
+    write_attr(
+        &linear,
+        "n_constraints",
+        &(constraints.linear_constraints.len() as u64),
+    )?;
+    for (i, constraint) in constraints.linear_constraints.iter().enumerate() {
+        let row_group = linear.create_group(&i.to_string())?;
+        write_matrix(
+            &row_group,
+            "matrix",
+            &constraint.dense_matrix(),
+            constraint.n_assets(),
+        )?;
+        write_vector(&row_group, "rhs", &constraint.rhs)?;
+        write_attr(&row_group, "is_equality", &constraint.is_equality)?;
+        write_string_attr(&row_group, "name", &constraint.name)?;
+    }
+
+    if let Some(turnover) = &constraints.turnover_constraint {
+        let turnover_group = group.create_group("turnover")?;
+        write_vector(
+            &turnover_group,
+            "current_weights",
+            &turnover.current_weights,
+        )?;
+        write_attr(&turnover_group, "max_turnover", &turnover.max_turnover)?;
+    }
+
+    if let Some(factor) = &constraints.factor_constraints {
+        let factor_group = group.create_group("factor")?;
+        write_matrix(
+            &factor_group,
+            "loadings",
+            &factor.factor_loadings,
+            factor.n_factors(),
+        )?;
+        write_vector(&factor_group, "lower", &factor.lower)?;
+        write_vector(&factor_group, "upper", &factor.upper)?;
+        let names = factor
+            .factor_names
+            .iter()
+            .map(|name| to_varlen(name))
+            .collect::<Result<Vec<_>>>()?;
+        factor_group
+            .new_dataset_builder()
+            .with_data(names.as_slice())
+            .create("factor_names")?;
+    }
+
+    if let Some(density) = &constraints.density_constraint {
+        let density_group = group.create_group("density")?;
+        write_attr(&density_group, "threshold", &density.threshold)?;
+        write_attr(&density_group, "max_count", &(density.max_count as u64))?;
+    }
+
+    if let Some(cardinality) = &constraints.cardinality_constraint {
+        let cardinality_group = group.create_group("cardinality")?;
+        write_attr(
+            &cardinality_group,
+            "max_assets",
+            &(cardinality.max_assets as u64),
+        )?;
+        if cardinality.min_weight_if_held > 0.0 {
+            write_attr(
+                &cardinality_group,
+                "min_weight_if_held",
+                &cardinality.min_weight_if_held,
+            )?;
+        }
+    }
+
+    if let Some(tracking) = &constraints.tracking_error_constraint {
+        let tracking_group = group.create_group("tracking_error")?;
+        write_vector(&tracking_group, "benchmark", &tracking.benchmark)?;
+        write_attr(
+            &tracking_group,
+            "max_tracking_error",
+            &tracking.max_tracking_error,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn read_constraints(group: &Group) -> Result<ConstraintSet> {
+    let mut constraints = ConstraintSet::new();
+
+    if group.link_exists("box") {
+        let box_group = group.group("box")?;
+        constraints.box_constraint = Some(BoxConstraint::new(
+            read_vector(&box_group, "lower")?,
+            read_vector(&box_group, "upper")?,
+        ));
+    }
+
+    let linear = group.group("linear")?;
+    let n_linear = read_attr::<u64>(&linear, "n_constraints")? as usize;
+    for i in 0..n_linear {
+        let row_group = linear.group(&i.to_string())?;
+        let matrix = read_matrix(&row_group, "matrix")?;
+        let rhs = read_vector(&row_group, "rhs")?;
+        let name = read_string_attr(&row_group, "name")?;
+        let constraint = if read_attr::<bool>(&row_group, "is_equality")? {
+            LinearConstraint::equality(matrix, rhs, &name)?
+        } else {
+            LinearConstraint::inequality(matrix, rhs, &name)?
+        };
+        constraints.linear_constraints.push(constraint);
+    }
+
+    if group.link_exists("turnover") {
+        let turnover_group = group.group("turnover")?;
+        constraints.turnover_constraint = Some(TurnoverConstraint::new(
+            read_vector(&turnover_group, "current_weights")?,
+            read_attr(&turnover_group, "max_turnover")?,
+        ));
+    }
+
+    if group.link_exists("factor") {
+        let factor_group = group.group("factor")?;
+        let factor_names = factor_group
+            .dataset("factor_names")?
+            .read_raw::<VarLenUnicode>()?
+            .iter()
+            .map(|name| name.as_str().to_string())
+            .collect();
+        constraints.factor_constraints = Some(FactorExposureConstraint::new(
+            read_matrix(&factor_group, "loadings")?,
+            read_vector(&factor_group, "lower")?,
+            read_vector(&factor_group, "upper")?,
+            factor_names,
+        ));
+    }
+
+    if group.link_exists("density") {
+        let density_group = group.group("density")?;
+        constraints.density_constraint = Some(WeightDensityConstraint::new(
+            read_attr(&density_group, "threshold")?,
+            read_attr::<u64>(&density_group, "max_count")? as usize,
+        ));
+    }
+
+    if group.link_exists("cardinality") {
+        let cardinality_group = group.group("cardinality")?;
+        let max_assets = read_attr::<u64>(&cardinality_group, "max_assets")? as usize;
+        let min_weight =
+            read_optional_attr(&cardinality_group, "min_weight_if_held")?.unwrap_or(0.0);
+        constraints.cardinality_constraint =
+            Some(CardinalityConstraint::new(max_assets).with_min_weight_if_held(min_weight));
+    }
+
+    if group.link_exists("tracking_error") {
+        let tracking_group = group.group("tracking_error")?;
+        constraints.tracking_error_constraint = Some(TrackingErrorConstraint::new(
+            read_vector(&tracking_group, "benchmark")?,
+            read_attr(&tracking_group, "max_tracking_error")?,
+        ));
+    }
+
+    Ok(constraints)
+}
+
+fn write_result(group: &Group, result: &OptimizationResult) -> Result<()> {
+    write_vector(group, "weights", &result.weights)?;
+    write_attr(group, "expected_return", &result.expected_return)?;
+    write_attr(group, "variance", &result.variance)?;
+    write_attr(group, "volatility", &result.volatility)?;
+    write_attr(group, "sharpe_ratio", &result.sharpe_ratio)?;
+    write_attr(group, "iterations", &result.iterations)?;
+    write_string_attr(group, "status", status_name(result.status))?;
+    write_attr(
+        group,
+        "n_assets_above_threshold",
+        &(result.n_assets_above_threshold as u64),
+    )?;
+    if let Some(cost) = result.transaction_cost {
+        write_attr(group, "transaction_cost", &cost)?;
+    }
+    if let Some(intensity) = result.regularization_applied {
+        write_attr(group, "regularization_applied", &intensity)?;
+    }
+    if let Some(cvar) = result.cvar {
+        write_attr(group, "cvar", &cvar)?;
+    }
+    if let Some(ratio) = result.diversification_ratio {
+        write_attr(group, "diversification_ratio", &ratio)?;
+    }
+    if let Some(tracking_error) = result.tracking_error {
+        write_attr(group, "tracking_error", &tracking_error)?;
+    }
+    Ok(())
+}
+
+/// Read a result; its covariance is not stored and is taken from the problem
+fn read_result(group: &Group, covariance: &[Vec<f64>]) -> Result<OptimizationResult> {
+    let status = match read_string_attr(group, "status")?.as_str() {
+        "Optimal" => SolverStatus::Optimal,
+        "SubOptimal" => SolverStatus::SubOptimal,
+        "Infeasible" => SolverStatus::Infeasible,
+        "Unbounded" => SolverStatus::Unbounded,
+        "MaxIterations" => SolverStatus::MaxIterations,
+        "NumericalError" => SolverStatus::NumericalError,
+        other => return Err(malformed(format!("unknown solver status '{}'", other))),
+    };
+
+    Ok(OptimizationResult {
+        weights: PortfolioWeights::unconstrained(read_vector(group, "weights")?),
+        expected_return: read_attr(group, "expected_return")?,
+        variance: read_attr(group, "variance")?,
+        volatility: read_attr(group, "volatility")?,
+        sharpe_ratio: read_attr(group, "sharpe_ratio")?,
+        iterations: read_attr(group, "iterations")?,
+        status,
+        transaction_cost: read_optional_attr(group, "transaction_cost")?,
+        regularization_applied: read_optional_attr(group, "regularization_applied")?,
+        n_assets_above_threshold: read_attr::<u64>(group, "n_assets_above_threshold")? as usize,
+        cvar: read_optional_attr(group, "cvar")?,
+        diversification_ratio: read_optional_attr(group, "diversification_ratio")?,
+        tracking_error: read_optional_attr(group, "tracking_error")?,
+        covariance: covariance.to_vec(),
+    })
+}
+
+fn objective_name(objective: &ObjectiveType) -> &'static str {
+    match objective {
+        ObjectiveType::MinimizeVariance => "MinimizeVariance",
+        ObjectiveType::MaximizeReturn => "MaximizeReturn",
+        ObjectiveType::MaximizeSharpe => "MaximizeSharpe",
+        ObjectiveType::RiskParity => "RiskParity",
+        ObjectiveType::HierarchicalRiskParity => "HierarchicalRiskParity",
+        ObjectiveType::MaxDiversification => "MaxDiversification",
+        ObjectiveType::MeanVariance => "MeanVariance",
+        ObjectiveType::MinimizeTrackingError { .. } => "MinimizeTrackingError",
+        ObjectiveType::MinimizeCVaR { .. } => "MinimizeCVaR",
+    }
+}
+
+fn status_name(status: SolverStatus) -> &'static str {
+    match status {
+        SolverStatus::Optimal => "Optimal",
+        SolverStatus::SubOptimal => "SubOptimal",
+        SolverStatus::Infeasible => "Infeasible",
+        SolverStatus::Unbounded => "Unbounded",
+        SolverStatus::MaxIterations => "MaxIterations",
+        SolverStatus::NumericalError => "NumericalError",
+    }
+}
+
+fn malformed(message: String) -> OptimizerError {
+    OptimizerError::InvalidInput(format!("Malformed session file: {}", message))
+}
+
+fn to_varlen(value: &str) -> Result<VarLenUnicode> {
+    value
+        .parse()
+        .map_err(|_| malformed(format!("string '{}' cannot be stored", value)))
+}
+
+fn write_attr<T: H5Type>(location: &Location, name: &str, value: &T) -> Result<()> {
+    location
+        .new_attr::<T>()
+        .shape(())
+        .create(name)?
+        .write_scalar(value)?;
+    Ok(())
+}
+
+fn read_attr<T: H5Type>(location: &Location, name: &str) -> Result<T> {
+    Ok(location.attr(name)?.read_scalar()?)
+}
+
+fn read_optional_attr<T: H5Type>(location: &Location, name: &str) -> Result<Option<T>> {
+    if location.attr_names()?.iter().any(|attr| attr == name) {
+        read_attr(location, name).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_string_attr(location: &Location, name: &str, value: &str) -> Result<()> {
+    write_attr(location, name, &to_varlen(value)?)
+}
+
+fn read_string_attr(location: &Location, name: &str) -> Result<String> {
+    Ok(read_attr::<VarLenUnicode>(location, name)?
+        .as_str()
+        .to_string())
+}
+
+fn write_vector(group: &Group, name: &str, data: &[f64]) -> Result<()> {
+    group.new_dataset_builder().with_data(data).create(name)?;
+    Ok(())
+}
+
+fn read_vector(group: &Group, name: &str) -> Result<Vec<f64>> {
+    Ok(group.dataset(name)?.read_raw()?)
+}
+
+/// Store `rows` as an `rows.len() x n_cols` dataset
+fn write_matrix(group: &Group, name: &str, rows: &[Vec<f64>], n_cols: usize) -> Result<()> {
+    let flat: Vec<f64> = rows.iter().flatten().copied().collect();
+    let matrix = Array2::from_shape_vec((rows.len(), n_cols), flat).map_err(|_| {
+        malformed(format!(
+            "'{}' rows do not all have {} columns",
+            name, n_cols
+        ))
+    })?;
+    group
+        .new_dataset_builder()
+        .with_data(&matrix)
+        .create(name)?;
+    Ok(())
+}
+
+fn read_matrix(group: &Group, name: &str) -> Result<Vec<Vec<f64>>> {
+    let matrix = group.dataset(name)?.read_2d::<f64>()?;
+    Ok(matrix.outer_iter().map(|row| row.to_vec()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::{QpSolver, SolverConfig};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("session_{}_{}.h5", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_save_and_load_session() {
+        let constraints = ConstraintSet::long_only_full_investment(4)
+            .with_linear(LinearConstraint::sector_exposure(&[0, 0, 1, 1], 2, 0.7))
+            .with_factor_exposure(FactorExposureConstraint::new(
+                vec![
+                    vec![1.2, 0.3],
+                    vec![0.8, -0.1],
+                    vec![1.0, 0.5],
+                    vec![0.6, -0.4],
+                ],
+                vec![0.5, -0.5],
+                vec![1.5, 0.5],
+                vec!["market".to_string(), "size".to_string()],
+            ))
+            .with_cardinality(CardinalityConstraint::new(4));
+        let problem = OptimizationProblem::builder(4)
+            .expected_returns(vec![0.08, 0.10, 0.12, 0.07])
+            .covariance(vec![
+                vec![0.040, 0.006, 0.010, 0.004],
+                vec![0.006, 0.090, 0.012, 0.008],
+                vec![0.010, 0.012, 0.062, 0.005],
+                vec![0.004, 0.008, 0.005, 0.030],
+            ])
+            .constraints(constraints)
+            .objective(ObjectiveType::MeanVariance)
+            .risk_aversion(3.0)
+            .transaction_costs(TransactionCostModel::default())
+            .current_weights(vec![0.25; 4])
+            .build()
+            .unwrap();
+        let result = QpSolver::new(SolverConfig::default())
+            .solve(&problem)
+            .unwrap();
+
+        let path = temp_path("roundtrip");
+        OptimizationSession::save(&problem, &result, &path).unwrap();
+        let (loaded_problem, loaded_result) = OptimizationSession::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded_problem.covariance, problem.covariance);
+        assert_eq!(
+            loaded_problem.constraints.linear_constraints[1].dense_matrix(),
+            problem.constraints.linear_constraints[1].dense_matrix()
+        );
+        assert_eq!(loaded_problem, problem);
+
+        assert_eq!(&*loaded_result.weights, &*result.weights);
+        assert_eq!(loaded_result.status, result.status);
+        // Mean-variance objective μ'w - λ/2 w'Σw with λ = 3
+        let objective = |r: &OptimizationResult| r.expected_return - 1.5 * r.variance;
+        assert_eq!(objective(&loaded_result), objective(&result));
+        assert_eq!(loaded_result, result);
+    }
+}
@@ -1,12 +1,42 @@
 //! Quadratic programming solver
 //!
-//! Uses OSQP for convex QP problems.
-
+//! Uses OSQP for convex QP problems: minimum variance, tracking error,
+//! mean-variance and maximum Sharpe, and for the CVaR linear program. Risk parity, which is not a convex QP
+//! in the weights, and the gradient-descent fallback use projected
+//! first-order iterations. Hierarchical risk parity is allocated directly
+//! from a correlation dendrogram, and maximum diversification is solved as
+//! a homogenized QP like maximum Sharpe.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use covariance::matrix::{condition_number, regularize, vec_to_dmatrix};
+use nalgebra::{DMatrix, DVector};
+use osqp::{CscMatrix, Settings, Status};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::{
+    BoxConstraint, CardinalityConstraint, FactorExposureConstraint, LinearConstraint,
+    TrackingErrorConstraint, TurnoverConstraint,
+};
 use crate::problem::{ObjectiveType, OptimizationProblem, OptimizationResult, SolverStatus};
+use crate::weights::PortfolioWeights;
 use crate::{OptimizerError, Result};
 
+/// Per-iteration convergence callback
+///
+/// Called with `(iteration, current_weights, gradient_norm)` after each
+/// iteration; returning `false` stops the solver early.
+pub type ConvergenceCallback = Arc<dyn Fn(u32, &[f64], f64) -> bool + Send + Sync>;
+
 /// Solver configuration
-#[derive(Debug, Clone)]
+///
+/// The convergence callback is not serialized and deserializes as `None`.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SolverConfig {
     /// Maximum iterations
     pub max_iterations: u32,
@@ -16,6 +46,37 @@ pub struct SolverConfig {
     pub eps_rel: f64,
     /// Verbose output
     pub verbose: bool,
+    /// Penalty schedule for the full-investment constraint
+    pub penalty_schedule: PenaltySchedule,
+    /// Covariance condition number above which regularization is applied
+    pub max_condition_number: f64,
+    /// Custom stopping condition; early stops are reported as `SubOptimal`
+    #[serde(skip)]
+    pub convergence_callback: Option<ConvergenceCallback>,
+    /// Starting point for the iterative solvers
+    #[serde(default)]
+    pub initialization: InitializationStrategy,
+    /// Cap on the L2 norm of each gradient step direction
+    #[serde(default)]
+    pub gradient_clip: Option<f64>,
+    /// Scale each gradient to unit norm before applying the step size
+    #[serde(default)]
+    pub gradient_normalize: bool,
+    /// Solve convex objectives by gradient descent instead of OSQP
+    ///
+    /// OSQP is warm-started from the configured initialization; the penalty
+    /// schedule, convergence callback and gradient settings only take
+    /// effect on the gradient-descent paths.
+    #[serde(default)]
+    pub use_fallback: bool,
+    /// Relaxations solved by cardinality branch and bound before the best
+    /// portfolio found is returned as `SubOptimal`
+    #[serde(default = "default_cardinality_node_limit")]
+    pub cardinality_node_limit: usize,
+}
+
+fn default_cardinality_node_limit() -> usize {
+    DEFAULT_CARDINALITY_NODE_LIMIT
 }
 
 impl Default for SolverConfig {
@@ -25,10 +86,145 @@ impl Default for SolverConfig {
             eps_abs: 1e-6,
             eps_rel: 1e-6,
             verbose: false,
+            penalty_schedule: PenaltySchedule::default(),
+            max_condition_number: 1e8,
+            convergence_callback: None,
+            initialization: InitializationStrategy::default(),
+            gradient_clip: None,
+            gradient_normalize: false,
+            use_fallback: false,
+            cardinality_node_limit: DEFAULT_CARDINALITY_NODE_LIMIT,
+        }
+    }
+}
+
+impl fmt::Debug for SolverConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverConfig")
+            .field("max_iterations", &self.max_iterations)
+            .field("eps_abs", &self.eps_abs)
+            .field("eps_rel", &self.eps_rel)
+            .field("verbose", &self.verbose)
+            .field("penalty_schedule", &self.penalty_schedule)
+            .field("max_condition_number", &self.max_condition_number)
+            .field(
+                "convergence_callback",
+                &self.convergence_callback.as_ref().map(|_| "<callback>"),
+            )
+            .field("initialization", &self.initialization)
+            .field("gradient_clip", &self.gradient_clip)
+            .field("gradient_normalize", &self.gradient_normalize)
+            .field("use_fallback", &self.use_fallback)
+            .field("cardinality_node_limit", &self.cardinality_node_limit)
+            .finish()
+    }
+}
+
+/// Default [`SolverConfig::cardinality_node_limit`]
+const DEFAULT_CARDINALITY_NODE_LIMIT: usize = 1000;
+
+/// Rate `k` used by [`AnnealingSchedule::Exponential`]
+const EXPONENTIAL_ANNEALING_RATE: f64 = 5.0;
+
+/// Maximum alternating-projection rounds for linear inequality constraints
+const MAX_PROJECTION_ROUNDS: usize = 100;
+
+/// Violation below which a linear inequality is considered satisfied
+const FEASIBILITY_TOL: f64 = 1e-10;
+
+/// Bound magnitude OSQP treats as infinite
+const OSQP_INFINITY: f64 = 1e30;
+
+/// Maximum tangent cuts added to enforce a tracking error limit with OSQP
+const MAX_TRACKING_ERROR_CUTS: usize = 50;
+
+/// Multiple of the solver tolerances allowed on the weight sum of a fully
+/// invested solution
+const BUDGET_TOL_FACTOR: f64 = 10.0;
+
+/// Seed for CVaR scenarios drawn from the covariance
+const CVAR_SCENARIO_SEED: u64 = 42;
+
+/// How the penalty weight grows over the iteration budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnealingSchedule {
+    /// rho_t = rho_0 + (rho_f - rho_0) * t / T
+    Linear,
+    /// rho_t = rho_f - (rho_f - rho_0) * (exp(-k * t / T) - exp(-k)) / (1 - exp(-k))
+    Exponential,
+    /// rho_t = rho_0 * (rho_f / rho_0)^(t / T)
+    Geometric,
+}
+
+/// Quadratic penalty schedule for equality constraint enforcement
+///
+/// The constraint sum(w) = 1 is enforced by adding `rho_t * (sum(w) - 1)^2 / 2`
+/// to the objective, with `rho_t` annealed from `initial_rho` to `final_rho`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PenaltySchedule {
+    /// Penalty weight at the first iteration
+    pub initial_rho: f64,
+    /// Penalty weight at the end of the iteration budget
+    pub final_rho: f64,
+    /// Annealing schedule
+    pub schedule: AnnealingSchedule,
+}
+
+impl Default for PenaltySchedule {
+    fn default() -> Self {
+        Self {
+            initial_rho: 1.0,
+            final_rho: 1e8,
+            schedule: AnnealingSchedule::Exponential,
+        }
+    }
+}
+
+impl PenaltySchedule {
+    /// Penalty weight at iteration `t` of a `horizon`-iteration run
+    pub fn rho(&self, t: u32, horizon: u32) -> f64 {
+        let progress = if horizon == 0 {
+            1.0
+        } else {
+            (t as f64 / horizon as f64).min(1.0)
+        };
+
+        match self.schedule {
+            AnnealingSchedule::Linear => {
+                self.initial_rho + (self.final_rho - self.initial_rho) * progress
+            }
+            AnnealingSchedule::Exponential => {
+                // Normalized so the last step lands on final_rho exactly
+                let floor = (-EXPONENTIAL_ANNEALING_RATE).exp();
+                let remaining =
+                    ((-EXPONENTIAL_ANNEALING_RATE * progress).exp() - floor) / (1.0 - floor);
+                self.final_rho - (self.final_rho - self.initial_rho) * remaining
+            }
+            AnnealingSchedule::Geometric => {
+                self.initial_rho * (self.final_rho / self.initial_rho).powf(progress)
+            }
         }
     }
 }
 
+/// Starting weights for the iterative solvers
+///
+/// The starting point is always projected onto the feasible set before the
+/// first iteration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum InitializationStrategy {
+    /// 1/n in every asset
+    #[default]
+    EqualWeight,
+    /// Box lower bounds scaled to sum to one (equal weight if they sum to zero)
+    MinLower,
+    /// Lower bounds, with the rest of the budget filled up to the upper
+    /// bounds in order of decreasing expected return
+    MaxReturn,
+    /// Caller-supplied weights
+    Custom(Vec<f64>),
+}
+
 /// QP Solver using OSQP
 pub struct QpSolver {
     config: SolverConfig,
@@ -47,279 +243,910 @@ impl QpSolver {
     }
 
     /// Solve the optimization problem
+    ///
+    /// Ill-conditioned covariance matrices are regularized before solving;
+    /// in that case the result is reported as `SubOptimal` with the shrinkage
+    /// intensity in `regularization_applied`. Portfolio statistics are always
+    /// computed against the original covariance. Optimal and suboptimal
+    /// solutions of fully invested problems carry validated weights; a
+    /// solution whose weights miss the budget by more than ten times the
+    /// solver tolerances is an `InvalidWeights` error.
     pub fn solve(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        self.solve_conditioned(problem, Self::solve_with_cardinality)
+    }
+
+    /// Solve with the greedy cardinality heuristic instead of branch and bound
+    ///
+    /// While the cardinality constraint is violated, every holding outside
+    /// the `max_assets` largest (by absolute weight) is fixed at zero, the
+    /// kept assets' lower bounds are raised to `min_weight_if_held`, and the
+    /// problem is re-solved. One solve per round makes this much faster
+    /// than [`Self::solve`] but optimality is not guaranteed. Otherwise
+    /// behaves like [`Self::solve`].
+    pub fn solve_cardinality_greedy(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<OptimizationResult> {
+        self.solve_conditioned(problem, Self::greedy_cardinality)
+    }
+
+    /// Validate, regularize the covariance if needed, and run `solve`
+    fn solve_conditioned(
+        &self,
+        problem: &OptimizationProblem,
+        solve: fn(&Self, &OptimizationProblem) -> Result<OptimizationResult>,
+    ) -> Result<OptimizationResult> {
         problem.validate()?;
 
-        match problem.objective {
-            ObjectiveType::MinimizeVariance => self.solve_min_variance(problem),
-            ObjectiveType::MeanVariance => self.solve_mean_variance(problem),
-            ObjectiveType::MaximizeReturn => self.solve_max_return(problem),
-            ObjectiveType::MaximizeSharpe => self.solve_max_sharpe(problem),
-            ObjectiveType::RiskParity => self.solve_risk_parity(problem),
+        let (conditioned, regularization) = self.condition_covariance(problem)?;
+
+        let mut result = solve(self, &conditioned)?;
+        if problem.constraints.requires_full_investment()
+            && matches!(
+                result.status,
+                SolverStatus::Optimal | SolverStatus::SubOptimal
+            )
+        {
+            let tol = BUDGET_TOL_FACTOR * (self.config.eps_abs + self.config.eps_rel);
+            result.weights = PortfolioWeights::try_new(result.weights.to_vec(), tol)?;
+        }
+
+        match regularization {
+            Some(lambda) => {
+                let cvar = result.cvar;
+                let diversified = result.diversification_ratio.is_some();
+                let mut result = Self::build_result(
+                    problem,
+                    result.weights.into_inner(),
+                    result.iterations,
+                    SolverStatus::SubOptimal,
+                );
+                result.regularization_applied = Some(lambda);
+                result.cvar = cvar;
+                if diversified {
+                    result.diversification_ratio =
+                        Some(problem.diversification_ratio(&result.weights));
+                }
+                Ok(result)
+            }
+            None => Ok(result),
         }
     }
 
-    /// Solve minimum variance problem
-    fn solve_min_variance(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
-        // For now, use a simple analytical solution for unconstrained case
-        // or gradient descent for constrained case
-        // Full OSQP integration would go here
+    /// Tangency portfolios for different lending and borrowing rates
+    ///
+    /// With borrowing dearer than lending the capital market line bends:
+    /// investors below full risky exposure lend at `lending_rate` and hold
+    /// the tangency portfolio for that rate, while leveraged investors
+    /// (risky exposure above 1) borrow at `borrowing_rate` and hold the
+    /// tangency portfolio for the higher rate, further up the frontier.
+    /// Between the two, the efficient set follows the frontier itself.
+    ///
+    /// Returns the lending tangency followed by the borrowing tangency,
+    /// each solved as a max-Sharpe problem and reporting its Sharpe ratio
+    /// against its own rate. The borrowing tangency is omitted when its
+    /// expected return does not exceed `borrowing_rate`, as leverage would
+    /// then lose money. With equal rates the two portfolios coincide.
+    pub fn solve_max_sharpe_asymmetric_rf(
+        &self,
+        problem: &OptimizationProblem,
+        lending_rate: f64,
+        borrowing_rate: f64,
+    ) -> Result<Vec<OptimizationResult>> {
+        if borrowing_rate < lending_rate {
+            return Err(OptimizerError::InvalidInput(format!(
+                "borrowing rate {} is below lending rate {}",
+                borrowing_rate, lending_rate
+            )));
+        }
 
-        let n = problem.n_assets;
+        let tangency = |rate: f64| {
+            let mut at_rate = problem.clone();
+            at_rate.objective = ObjectiveType::MaximizeSharpe;
+            at_rate.risk_free_rate = rate;
+            self.solve(&at_rate)
+        };
 
-        // Simple equal-weight initial guess
-        let mut weights = vec![1.0 / n as f64; n];
+        let mut portfolios = vec![tangency(lending_rate)?];
+        let borrowing = tangency(borrowing_rate)?;
+        if borrowing.expected_return > borrowing_rate {
+            portfolios.push(borrowing);
+        }
 
-        // Project to satisfy constraints
-        self.project_to_feasible(&mut weights, problem)?;
+        Ok(portfolios)
+    }
 
-        // Gradient descent for min variance
-        let learning_rate = 0.01;
-        let mut iterations = 0;
+    /// Trace the Markowitz efficient frontier with `n_points` portfolios
+    ///
+    /// The frontier starts at the minimum-variance portfolio and ends at
+    /// the maximum expected return attainable under the problem's
+    /// constraints. In between, variance is minimized subject to an
+    /// equality constraint `μ'w = target` at evenly spaced target returns,
+    /// each solve warm-started from the previous point's weights. Results
+    /// are ordered by increasing expected return; the problem's own
+    /// objective is ignored.
+    ///
+    /// Returns `InvalidInput` if `n_points < 2` or the solver is configured
+    /// with `use_fallback`, whose penalty method cannot enforce the return
+    /// target. The maximum-return end is a linear program, so without a
+    /// box constraint (or other constraints bounding `μ'w`) it is unbounded
+    /// and `SolverFailed` is returned. When the constraints admit only one
+    /// expected return (for instance when all assets have the same one),
+    /// the minimum-variance portfolio is returned alone.
+    pub fn compute_efficient_frontier(
+        &self,
+        problem: &OptimizationProblem,
+        n_points: usize,
+    ) -> Result<Vec<OptimizationResult>> {
+        if n_points < 2 {
+            return Err(OptimizerError::InvalidInput(format!(
+                "efficient frontier needs at least 2 points, got {}",
+                n_points
+            )));
+        }
+        if self.config.use_fallback {
+            return Err(OptimizerError::InvalidInput(
+                "efficient frontier requires the OSQP solver".to_string(),
+            ));
+        }
+        problem.validate()?;
 
-        for _ in 0..self.config.max_iterations {
-            iterations += 1;
+        let mut min_variance = problem.clone();
+        min_variance.objective = ObjectiveType::MinimizeVariance;
+        let first = self.solve(&min_variance)?;
 
-            // Compute gradient: 2 * Σ * w
-            let mut gradient = vec![0.0; n];
-            for i in 0..n {
-                for j in 0..n {
-                    gradient[i] += 2.0 * problem.covariance[i][j] * weights[j];
-                }
+        // Highest attainable return: the LP max μ'w over the feasible set
+        let n = problem.n_assets;
+        let linear_term: Vec<f64> = problem.expected_returns.iter().map(|mu| -mu).collect();
+        let not_found = |reason: String| {
+            OptimizerError::SolverFailed(format!(
+                "maximum-return portfolio not found ({}); bound the weights with a box constraint",
+                reason
+            ))
+        };
+        let best = match self.run_osqp(
+            &vec![Vec::new(); n],
+            &linear_term,
+            &QpConstraints::for_weights(problem),
+            None,
+        ) {
+            Ok((best, _, SolverStatus::Optimal | SolverStatus::SubOptimal)) => best,
+            Ok((_, _, status)) => return Err(not_found(format!("status {:?}", status))),
+            Err(OptimizerError::SolverFailed(reason)) => return Err(not_found(reason)),
+            Err(e) => return Err(e),
+        };
+        let min_return = first.expected_return;
+        let max_return = problem.portfolio_return(&best);
+        if max_return - min_return <= self.config.eps_abs {
+            return Ok(vec![first]);
+        }
+
+        let mut frontier = Vec::with_capacity(n_points);
+        frontier.push(first);
+        for k in 1..n_points {
+            let target = min_return + (max_return - min_return) * k as f64 / (n_points - 1) as f64;
+            let mut at_target = min_variance.clone();
+            at_target
+                .constraints
+                .linear_constraints
+                .push(LinearConstraint::equality(
+                    vec![problem.expected_returns.clone()],
+                    vec![target],
+                    "target_return",
+                )?);
+
+            let previous = frontier[k - 1].weights.to_vec();
+            let solver = QpSolver::new(SolverConfig {
+                initialization: InitializationStrategy::Custom(previous),
+                ..self.config.clone()
+            });
+            frontier.push(solver.solve(&at_target)?);
+        }
+
+        Ok(frontier)
+    }
+
+    /// Dispatch to the solver for the problem's objective
+    fn solve_objective(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        if self.config.use_fallback {
+            return self.solve_objective_fallback(problem);
+        }
+
+        let n = problem.n_assets;
+        match &problem.objective {
+            ObjectiveType::MinimizeVariance => {
+                let p = Self::scaled_covariance(problem, 2.0);
+                self.solve_qp(problem, &p, &vec![0.0; n])
+            }
+            ObjectiveType::MinimizeTrackingError { benchmark_weights } => {
+                // (w - b)'Σ(w - b) = w'Σw - 2 b'Σw + const
+                let p = Self::scaled_covariance(problem, 2.0);
+                let q: Vec<f64> = p
+                    .iter()
+                    .map(|row| {
+                        -row.iter()
+                            .zip(benchmark_weights)
+                            .map(|(c, b)| c * b)
+                            .sum::<f64>()
+                    })
+                    .collect();
+                self.solve_qp(problem, &p, &q)
             }
+            ObjectiveType::MeanVariance => {
+                let p = Self::scaled_covariance(problem, problem.risk_aversion);
+                let q: Vec<f64> = problem.expected_returns.iter().map(|mu| -mu).collect();
+                self.solve_qp(problem, &p, &q)
+            }
+            ObjectiveType::MaximizeSharpe => self.solve_max_sharpe_qp(problem),
+            ObjectiveType::MinimizeCVaR {
+                confidence,
+                n_scenarios,
+            } => self.solve_min_cvar(problem, *confidence, *n_scenarios),
+            ObjectiveType::HierarchicalRiskParity => self.solve_hrp(problem),
+            ObjectiveType::MaxDiversification => self.solve_max_diversification(problem),
+            ObjectiveType::MaximizeReturn | ObjectiveType::RiskParity => {
+                self.solve_objective_fallback(problem)
+            }
+        }
+    }
 
-            // Update weights
-            for i in 0..n {
-                weights[i] -= learning_rate * gradient[i];
+    /// Dispatch to the first-order solver for the problem's objective
+    fn solve_objective_fallback(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<OptimizationResult> {
+        match &problem.objective {
+            ObjectiveType::MinimizeVariance => self.solve_min_variance(problem, None),
+            ObjectiveType::MinimizeTrackingError { benchmark_weights } => {
+                self.solve_min_variance(problem, Some(benchmark_weights))
             }
+            ObjectiveType::MeanVariance => self.solve_mean_variance(problem),
+            ObjectiveType::MaximizeReturn => self.solve_max_return(problem),
+            ObjectiveType::MaximizeSharpe => self.solve_max_sharpe(problem),
+            ObjectiveType::RiskParity => self.solve_risk_parity(problem),
+            ObjectiveType::MinimizeCVaR {
+                confidence,
+                n_scenarios,
+            } => self.solve_min_cvar(problem, *confidence, *n_scenarios),
+            ObjectiveType::HierarchicalRiskParity => self.solve_hrp(problem),
+            ObjectiveType::MaxDiversification => self.solve_max_diversification(problem),
+        }
+    }
 
-            // Project to feasible set
-            self.project_to_feasible(&mut weights, problem)?;
+    /// Covariance scaled by `factor`, as the dense quadratic term of a QP
+    fn scaled_covariance(problem: &OptimizationProblem, factor: f64) -> Vec<Vec<f64>> {
+        problem
+            .covariance
+            .iter()
+            .map(|row| row.iter().map(|c| factor * c).collect())
+            .collect()
+    }
 
-            // Check convergence
-            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
-            if grad_norm < self.config.eps_abs {
-                break;
+    /// Solve `min 0.5 w'Pw + q'w` over the feasible weights with OSQP
+    fn solve_qp(
+        &self,
+        problem: &OptimizationProblem,
+        p: &[Vec<f64>],
+        q: &[f64],
+    ) -> Result<OptimizationResult> {
+        let start = self.initial_weights(problem)?;
+        let constraints = QpConstraints::for_weights(problem);
+        let (weights, iterations, status) =
+            self.run_osqp(&upper_triangle(p), q, &constraints, Some(&start))?;
+        Ok(Self::build_result(problem, weights, iterations, status))
+    }
+
+    /// Solve max Sharpe ratio as a convex QP
+    ///
+    /// The ratio of excess return `(μ - rf)'w` to volatility, solved by
+    /// [`Self::solve_ratio_qp`]. When no feasible portfolio earns more than
+    /// the risk-free rate the transformed problem is infeasible, and the
+    /// gradient-ascent solver is used instead.
+    fn solve_max_sharpe_qp(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let excess: Vec<f64> = problem
+            .expected_returns
+            .iter()
+            .map(|mu| mu - problem.risk_free_rate)
+            .collect();
+
+        match self.solve_ratio_qp(problem, &excess) {
+            Ok((weights, iterations, status)) => {
+                Ok(Self::build_result(problem, weights, iterations, status))
             }
+            Err(OptimizerError::Infeasible(_)) => self.solve_max_sharpe(problem),
+            Err(e) => Err(e),
         }
+    }
 
-        let variance = problem.portfolio_variance(&weights);
-        let expected_return = problem.portfolio_return(&weights);
-        let volatility = variance.sqrt();
-        let sharpe = if volatility > 0.0 {
-            (expected_return - problem.risk_free_rate) / volatility
-        } else {
-            0.0
-        };
-
-        Ok(OptimizationResult {
-            weights,
-            expected_return,
-            variance,
-            volatility,
-            sharpe_ratio: sharpe,
-            iterations,
-            status: SolverStatus::Optimal,
-            transaction_cost: None,
-        })
+    /// Solve max diversification as a convex QP
+    ///
+    /// The diversification ratio is a Sharpe ratio with the asset
+    /// volatilities `σ` in place of excess returns, so [`Self::solve_ratio_qp`]
+    /// minimizes `y'Σy` subject to `σ'y = 1`. Solved with OSQP whether or
+    /// not `use_fallback` is set.
+    fn solve_max_diversification(
+        &self,
+        problem: &OptimizationProblem,
+    ) -> Result<OptimizationResult> {
+        let vols: Vec<f64> = (0..problem.n_assets)
+            .map(|i| problem.covariance[i][i].max(0.0).sqrt())
+            .collect();
+
+        let (weights, iterations, status) = self.solve_ratio_qp(problem, &vols)?;
+        let mut result = Self::build_result(problem, weights, iterations, status);
+        result.diversification_ratio = Some(problem.diversification_ratio(&result.weights));
+        Ok(result)
     }
 
-    /// Solve mean-variance problem: max μ'w - λ/2 * w'Σw
-    fn solve_mean_variance(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+    /// Maximize `c'w / sqrt(w'Σw)` over the feasible weights with OSQP
+    ///
+    /// Substituting `y = κw` with `κ > 0` and normalizing `c'y = 1` turns
+    /// the ratio into `min y'Σy`, with every constraint `l <= a'w <= u`
+    /// homogenized to `lκ <= a'y <= uκ` and the budget to `sum(y) = κ`.
+    /// The optimal weights are `w = y / κ`. Returns
+    /// [`OptimizerError::Infeasible`] when no feasible portfolio has a
+    /// positive numerator.
+    fn solve_ratio_qp(
+        &self,
+        problem: &OptimizationProblem,
+        numerator: &[f64],
+    ) -> Result<(Vec<f64>, u32, SolverStatus)> {
         let n = problem.n_assets;
-        let lambda = problem.risk_aversion;
+        let kappa = n;
 
-        let mut weights = vec![1.0 / n as f64; n];
-        self.project_to_feasible(&mut weights, problem)?;
+        let mut constraints = QpConstraints::for_weights(problem).homogenized(kappa);
+        constraints.push(numerator.iter().copied().enumerate().collect(), 1.0, 1.0);
+        constraints.push(vec![(kappa, 1.0)], 0.0, f64::INFINITY);
 
-        let learning_rate = 0.01;
-        let mut iterations = 0;
+        let mut p = Self::scaled_covariance(problem, 2.0);
+        for row in &mut p {
+            row.push(0.0);
+        }
+        p.push(vec![0.0; n + 1]);
 
-        for _ in 0..self.config.max_iterations {
-            iterations += 1;
+        let (solution, iterations, status) =
+            self.run_osqp(&upper_triangle(&p), &vec![0.0; n + 1], &constraints, None)?;
 
-            // Gradient: λ * Σ * w - μ
-            let mut gradient = vec![0.0; n];
-            for i in 0..n {
-                gradient[i] = -problem.expected_returns[i];
-                for j in 0..n {
-                    gradient[i] += lambda * problem.covariance[i][j] * weights[j];
-                }
-            }
+        let scale = solution[kappa];
+        if scale.is_nan() || scale <= 0.0 {
+            return Err(OptimizerError::NumericalError(format!(
+                "Ratio QP returned non-positive scale {}",
+                scale
+            )));
+        }
+        let weights = solution[..n].iter().map(|y| y / scale).collect();
+        Ok((weights, iterations, status))
+    }
 
-            // Update
-            for i in 0..n {
-                weights[i] -= learning_rate * gradient[i];
+    /// Minimize scenario CVaR with the Rockafellar-Uryasev linear program
+    ///
+    /// With portfolio loss `-r_k'w` in scenario `k` of `S`, CVaR at
+    /// confidence `α` is the minimum over `z` of `z + 1/((1-α)S) sum(s_k)`,
+    /// where the slacks `s_k >= -r_k'w - z` and `s_k >= 0` are each
+    /// scenario's loss beyond `z`. Minimizing jointly over the weights, `z`
+    /// and the slacks is a linear program, solved with OSQP whether or not
+    /// `use_fallback` is set; at the optimum `z` is the value-at-risk.
+    ///
+    /// The problem's scenarios are used when present; otherwise
+    /// `n_scenarios` returns are drawn from N(μ, Σ) with a fixed seed.
+    fn solve_min_cvar(
+        &self,
+        problem: &OptimizationProblem,
+        confidence: f64,
+        n_scenarios: usize,
+    ) -> Result<OptimizationResult> {
+        let n = problem.n_assets;
+        let drawn;
+        let scenarios = match &problem.scenarios {
+            Some(scenarios) => scenarios,
+            None => {
+                drawn = Self::draw_scenarios(problem, n_scenarios)?;
+                &drawn
             }
+        };
 
-            self.project_to_feasible(&mut weights, problem)?;
+        // Variables: weights, then z, then one slack per scenario
+        let threshold = n;
+        let slack = |k: usize| n + 1 + k;
+        let n_vars = n + 1 + scenarios.len();
 
-            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
-            if grad_norm < self.config.eps_abs {
-                break;
-            }
+        let mut constraints = QpConstraints::for_weights(problem);
+        for (k, scenario) in scenarios.iter().enumerate() {
+            let mut row: Vec<(usize, f64)> = scenario
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, r)| r != 0.0)
+                .collect();
+            row.push((threshold, 1.0));
+            row.push((slack(k), 1.0));
+            constraints.push(row, 0.0, f64::INFINITY);
+            constraints.push(vec![(slack(k), 1.0)], 0.0, f64::INFINITY);
         }
 
-        let variance = problem.portfolio_variance(&weights);
-        let expected_return = problem.portfolio_return(&weights);
-        let volatility = variance.sqrt();
-        let sharpe = if volatility > 0.0 {
-            (expected_return - problem.risk_free_rate) / volatility
-        } else {
-            0.0
-        };
+        let mut q = vec![0.0; n_vars];
+        q[threshold] = 1.0;
+        let tail_weight = 1.0 / ((1.0 - confidence) * scenarios.len() as f64);
+        for k in 0..scenarios.len() {
+            q[slack(k)] = tail_weight;
+        }
 
-        Ok(OptimizationResult {
-            weights,
-            expected_return,
-            variance,
-            volatility,
-            sharpe_ratio: sharpe,
-            iterations,
-            status: SolverStatus::Optimal,
-            transaction_cost: None,
-        })
+        let (solution, iterations, status) =
+            self.run_osqp(&vec![Vec::new(); n_vars], &q, &constraints, None)?;
+        let mut result = Self::build_result(problem, solution[..n].to_vec(), iterations, status);
+        result.cvar = Some(scenario_cvar(scenarios, &result.weights, confidence));
+        Ok(result)
     }
 
-    /// Solve max return problem
-    fn solve_max_return(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+    /// Minimize the largest regret over return scenarios with OSQP
+    ///
+    /// With `best_returns[s]` the best return achievable in scenario `s`,
+    /// the regret of `w` there is `best_returns[s] - r_s'w`. Minimizing the
+    /// bound `z` over the rows `r_s'w + z >= best_returns[s]` and the
+    /// problem's own constraints is a linear program in `(w, z)`.
+    pub(crate) fn solve_min_regret(
+        &self,
+        problem: &OptimizationProblem,
+        scenarios: &[Vec<f64>],
+        best_returns: &[f64],
+    ) -> Result<OptimizationResult> {
         let n = problem.n_assets;
+        let bound = n;
 
-        // For max return, put all weight in highest return asset (within constraints)
-        let mut weights = vec![0.0; n];
-
-        if let Some(box_constraint) = &problem.constraints.box_constraint {
-            // Find asset with highest return that can take max weight
-            let max_idx = problem
-                .expected_returns
+        let mut constraints = QpConstraints::for_weights(problem);
+        for (scenario, &best) in scenarios.iter().zip(best_returns) {
+            let mut row: Vec<(usize, f64)> = scenario
                 .iter()
+                .copied()
                 .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                .map(|(i, _)| i)
-                .unwrap_or(0);
+                .filter(|&(_, r)| r != 0.0)
+                .collect();
+            row.push((bound, 1.0));
+            constraints.push(row, best, f64::INFINITY);
+        }
 
-            // Put max weight in best asset, distribute rest equally
-            let max_weight = box_constraint.upper[max_idx];
-            let min_weights: f64 = box_constraint.lower.iter().sum();
-            let remaining = 1.0 - max_weight - (min_weights - box_constraint.lower[max_idx]);
+        let mut q = vec![0.0; n + 1];
+        q[bound] = 1.0;
 
-            for i in 0..n {
-                if i == max_idx {
-                    weights[i] = max_weight.min(1.0 - min_weights + box_constraint.lower[i]);
-                } else {
-                    weights[i] = box_constraint.lower[i];
-                }
-            }
+        let (solution, iterations, status) =
+            self.run_osqp(&vec![Vec::new(); n + 1], &q, &constraints, None)?;
+        Ok(Self::build_result(
+            problem,
+            solution[..n].to_vec(),
+            iterations,
+            status,
+        ))
+    }
 
-            // Distribute any remaining weight
-            let current_sum: f64 = weights.iter().sum();
-            if (current_sum - 1.0).abs() > 1e-10 {
-                let diff = 1.0 - current_sum;
-                // Add to second-best assets
-                let mut returns_indexed: Vec<_> = problem
+    /// Draw `n_scenarios` asset returns from N(μ, Σ) with a fixed seed
+    ///
+    /// Σ is factored through its eigendecomposition, so singular
+    /// covariances (e.g. with a risk-free asset) are supported.
+    fn draw_scenarios(problem: &OptimizationProblem, n_scenarios: usize) -> Result<Vec<Vec<f64>>> {
+        let n = problem.n_assets;
+        let eigen = vec_to_dmatrix(&problem.covariance)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?
+            .symmetric_eigen();
+        let scale = eigen.eigenvalues.map(|lambda| lambda.max(0.0).sqrt());
+        let factor = &eigen.eigenvectors * DMatrix::from_diagonal(&scale);
+
+        let mut rng = StdRng::seed_from_u64(CVAR_SCENARIO_SEED);
+        Ok((0..n_scenarios)
+            .map(|_| {
+                let z = DVector::<f64>::from_fn(n, |_, _| StandardNormal.sample(&mut rng));
+                let shock = &factor * z;
+                problem
                     .expected_returns
                     .iter()
-                    .enumerate()
-                    .collect();
-                returns_indexed.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+                    .zip(shock.iter())
+                    .map(|(mu, e)| mu + e)
+                    .collect()
+            })
+            .collect())
+    }
 
-                let mut remaining_diff = diff;
-                for (i, _) in returns_indexed {
-                    if remaining_diff <= 0.0 {
-                        break;
-                    }
-                    let can_add = box_constraint.upper[i] - weights[i];
-                    let to_add = can_add.min(remaining_diff);
-                    weights[i] += to_add;
-                    remaining_diff -= to_add;
-                }
+    /// Run OSQP on `min 0.5 x'Px + q'x` subject to `constraints`
+    ///
+    /// `p_upper` holds the upper triangle of `P` as `(row, value)` entries
+    /// per column, and the iterates start from `warm_start` when given. A
+    /// turnover limit is linearized over auxiliary variables after `x`,
+    /// which are dropped from the solution. A tracking error limit, a
+    /// second-order cone, is enforced by cutting planes: while the solution
+    /// exceeds it by more than `eps_abs`, the cone's tangent plane at the
+    /// solution is added as a row and the QP re-solved from there. After
+    /// `MAX_TRACKING_ERROR_CUTS` cuts the last solution is reported as
+    /// `SubOptimal`. Returns the primal solution, the iteration count
+    /// (summed over the solves) and the mapped status; terminations without
+    /// a solution are reported as errors.
+    fn run_osqp(
+        &self,
+        p_upper: &[Vec<(usize, f64)>],
+        q: &[f64],
+        constraints: &QpConstraints,
+        warm_start: Option<&[f64]>,
+    ) -> Result<(Vec<f64>, u32, SolverStatus)> {
+        let tracking = match &constraints.tracking_error {
+            Some(tracking) => tracking,
+            None => return self.run_osqp_once(p_upper, q, constraints, warm_start),
+        };
+
+        let mut with_cuts = constraints.clone();
+        let mut warm_start = warm_start.map(<[f64]>::to_vec);
+        let mut iterations = 0;
+        let mut n_cuts = 0;
+        loop {
+            let (x, solve_iterations, status) =
+                self.run_osqp_once(p_upper, q, &with_cuts, warm_start.as_deref())?;
+            iterations += solve_iterations;
+            let (row, upper) = match tracking.cut(&x, self.config.eps_abs) {
+                Some(cut) => cut,
+                None => return Ok((x, iterations, status)),
+            };
+            if n_cuts == MAX_TRACKING_ERROR_CUTS {
+                return Ok((x, iterations, SolverStatus::SubOptimal));
             }
-        } else {
-            // No box constraints - all in best asset
-            let max_idx = problem
-                .expected_returns
-                .iter()
-                .enumerate()
-                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-            weights[max_idx] = 1.0;
+            with_cuts.push(row, f64::NEG_INFINITY, upper);
+            n_cuts += 1;
+            warm_start = Some(x);
         }
+    }
 
-        let variance = problem.portfolio_variance(&weights);
-        let expected_return = problem.portfolio_return(&weights);
-        let volatility = variance.sqrt();
-        let sharpe = if volatility > 0.0 {
-            (expected_return - problem.risk_free_rate) / volatility
-        } else {
-            0.0
-        };
+    /// One OSQP solve, with any turnover limit linearized
+    fn run_osqp_once(
+        &self,
+        p_upper: &[Vec<(usize, f64)>],
+        q: &[f64],
+        constraints: &QpConstraints,
+        warm_start: Option<&[f64]>,
+    ) -> Result<(Vec<f64>, u32, SolverStatus)> {
+        let n_vars = q.len();
+        let turnover = constraints.turnover.as_ref();
+        let (constraints, n_aux) = constraints.linearize_turnover(n_vars);
+        let n = n_vars + n_aux;
+
+        let mut p_upper = p_upper.to_vec();
+        p_upper.resize(n, Vec::new());
+        let mut q = q.to_vec();
+        q.resize(n, 0.0);
+
+        let mut a_columns = vec![Vec::new(); n];
+        for (i, row) in constraints.rows.iter().enumerate() {
+            for &(j, value) in row {
+                a_columns[j].push((i, value));
+            }
+        }
+        let lower: Vec<f64> = constraints
+            .lower
+            .iter()
+            .map(|l| l.max(-OSQP_INFINITY))
+            .collect();
+        let upper: Vec<f64> = constraints
+            .upper
+            .iter()
+            .map(|u| u.min(OSQP_INFINITY))
+            .collect();
+
+        let settings = Settings::default()
+            .verbose(self.config.verbose)
+            .eps_abs(self.config.eps_abs)
+            .eps_rel(self.config.eps_rel)
+            .max_iter(self.config.max_iterations)
+            .polish(true);
+        let mut osqp = osqp::Problem::new(
+            csc_matrix(n, &p_upper),
+            &q,
+            csc_matrix(constraints.rows.len(), &a_columns),
+            &lower,
+            &upper,
+            &settings,
+        )
+        .map_err(|e| OptimizerError::SolverFailed(format!("OSQP setup failed: {:?}", e)))?;
+        if let Some(x) = warm_start {
+            let mut x = x.to_vec();
+            if let Some(turnover) = turnover {
+                let trades: Vec<f64> = turnover
+                    .current_weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, old)| (x[i] - old).abs())
+                    .collect();
+                x.extend(trades);
+            }
+            osqp.warm_start_x(&x);
+        }
 
-        Ok(OptimizationResult {
-            weights,
-            expected_return,
-            variance,
-            volatility,
-            sharpe_ratio: sharpe,
-            iterations: 1,
-            status: SolverStatus::Optimal,
-            transaction_cost: None,
+        let result = osqp.solve();
+        if let Some(x) = result.x() {
+            return Ok((x[..n_vars].to_vec(), result.iter(), osqp_status(&result)));
+        }
+        Err(match result {
+            Status::PrimalInfeasible(_) | Status::PrimalInfeasibleInaccurate(_) => {
+                OptimizerError::Infeasible("OSQP found the constraints infeasible".to_string())
+            }
+            Status::DualInfeasible(_) | Status::DualInfeasibleInaccurate(_) => {
+                OptimizerError::SolverFailed("OSQP found the objective unbounded".to_string())
+            }
+            Status::NonConvex(_) => {
+                OptimizerError::NumericalError("OSQP found the problem non-convex".to_string())
+            }
+            Status::MaxIterationsReached(_) => OptimizerError::MaxIterationsExceeded,
+            Status::TimeLimitReached(_) => OptimizerError::SolverFailed(
+                "OSQP reached its time limit without a solution".to_string(),
+            ),
+            Status::Solved(_) | Status::SolvedInaccurate(_) => OptimizerError::SolverFailed(
+                "OSQP reported a solution without primal values".to_string(),
+            ),
+            _ => OptimizerError::SolverFailed("OSQP stopped with an unknown status".to_string()),
         })
     }
 
-    /// Solve max Sharpe ratio problem
-    fn solve_max_sharpe(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
-        // Use mean-variance with varying risk aversion to trace efficient frontier
-        // Then find tangency portfolio
-        // Simplified: use gradient ascent on Sharpe ratio
+    /// Solve, enforcing the cardinality constraint by branch and bound
+    ///
+    /// Each node fixes a set of assets at zero and marks a set as held,
+    /// with lower bounds raised to `min_weight_if_held`, then solves the
+    /// relaxation without the position limit. Its objective bounds every
+    /// portfolio below the node, so nodes that cannot beat the incumbent
+    /// (seeded by the greedy heuristic) by more than `eps_abs` are pruned;
+    /// otherwise the largest
+    /// unmarked holding is branched on, held first and then excluded. Once
+    /// `max_assets` assets are held, all others are fixed at zero.
+    ///
+    /// Risk parity objectives have no cost to bound and use the greedy
+    /// heuristic alone. A node whose relaxation fails is pruned; the best
+    /// portfolio found is then reported as `SubOptimal`, as it is when
+    /// `cardinality_node_limit` relaxations are solved before the tree is
+    /// exhausted. Iterations are summed over all solves.
+    fn solve_with_cardinality(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let cardinality = match &problem.constraints.cardinality_constraint {
+            Some(cardinality) => cardinality,
+            None => return self.solve_with_density(problem),
+        };
+
+        let greedy = self.greedy_cardinality(problem)?;
+        let greedy_cost = match objective_cost(problem, &greedy) {
+            Some(cost) => cost,
+            None => return Ok(greedy),
+        };
 
         let n = problem.n_assets;
-        let mut weights = vec![1.0 / n as f64; n];
-        self.project_to_feasible(&mut weights, problem)?;
+        let mut iterations = greedy.iterations;
+        let mut incumbent = cardinality
+            .is_satisfied(&greedy.weights)
+            .then(|| (greedy_cost, greedy.clone()));
+
+        let mut pending = vec![CardinalityNode::default()];
+        let mut n_nodes = 0;
+        let mut failure = None;
+        while n_nodes < self.config.cardinality_node_limit {
+            let node = match pending.pop() {
+                Some(node) => node,
+                None => break,
+            };
+            n_nodes += 1;
+
+            let restricted = node.restrict(problem, cardinality);
+            let relaxed = match self.solve_with_density(&restricted) {
+                Ok(relaxed) => relaxed,
+                Err(OptimizerError::Infeasible(_)) => continue,
+                Err(e) => {
+                    failure = Some(e);
+                    continue;
+                }
+            };
+            iterations = iterations.saturating_add(relaxed.iterations);
+
+            let cost = objective_cost(problem, &relaxed).unwrap_or(f64::INFINITY);
+            if incumbent
+                .as_ref()
+                .is_some_and(|(best, _)| cost >= best - self.config.eps_abs)
+            {
+                continue;
+            }
+            if cardinality.is_satisfied(&relaxed.weights) {
+                incumbent = Some((cost, relaxed));
+                continue;
+            }
 
-        let learning_rate = 0.001;
-        let mut iterations = 0;
-        let rf = problem.risk_free_rate;
+            let branch = (0..n)
+                .filter(|i| !node.held.contains(i) && !node.excluded.contains(i))
+                .filter(|&i| relaxed.weights[i].abs() > CardinalityConstraint::HOLDING_TOL)
+                .max_by(|&a, &b| {
+                    relaxed.weights[a]
+                        .abs()
+                        .total_cmp(&relaxed.weights[b].abs())
+                });
+            if let Some(asset) = branch {
+                let mut excluded = node.clone();
+                excluded.excluded.push(asset);
+                let mut held = node;
+                held.held.push(asset);
+                pending.push(excluded);
+                pending.push(held);
+            }
+        }
 
-        for _ in 0..self.config.max_iterations {
-            iterations += 1;
+        let exhausted = pending.is_empty();
+        let mut result = match incumbent {
+            Some((_, result)) => result,
+            None if exhausted => {
+                return Err(failure.unwrap_or_else(|| {
+                    OptimizerError::Infeasible(format!(
+                        "No portfolio holds at most {} assets",
+                        cardinality.max_assets
+                    ))
+                }))
+            }
+            None => greedy,
+        };
+        result.iterations = iterations;
+        if !exhausted || failure.is_some() || !cardinality.is_satisfied(&result.weights) {
+            result.status = SolverStatus::SubOptimal;
+        }
+        Ok(result)
+    }
 
-            let ret = problem.portfolio_return(&weights);
-            let var = problem.portfolio_variance(&weights);
-            let vol = var.sqrt();
+    /// Enforce the cardinality constraint greedily
+    ///
+    /// See [`Self::solve_cardinality_greedy`]. Iterations are summed over
+    /// all solves. If the constraint still fails after one round per asset,
+    /// the result is reported as `SubOptimal`.
+    fn greedy_cardinality(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let mut result = self.solve_with_density(problem)?;
+        let cardinality = match &problem.constraints.cardinality_constraint {
+            Some(cardinality) => cardinality,
+            None => return Ok(result),
+        };
 
-            if vol < 1e-10 {
+        let n = problem.n_assets;
+        let mut restricted = problem.clone();
+        let mut iterations = result.iterations;
+
+        for _ in 0..n {
+            if cardinality.is_satisfied(&result.weights) {
                 break;
             }
 
-            // Gradient of Sharpe: (vol * ∂ret/∂w - (ret-rf) * ∂vol/∂w) / vol^2
-            let mut grad_ret = problem.expected_returns.clone();
-            let mut grad_var = vec![0.0; n];
-            for i in 0..n {
-                for j in 0..n {
-                    grad_var[i] += 2.0 * problem.covariance[i][j] * weights[j];
+            let mut held: Vec<usize> = (0..n)
+                .filter(|&i| result.weights[i].abs() > CardinalityConstraint::HOLDING_TOL)
+                .collect();
+            held.sort_by(|&a, &b| result.weights[b].abs().total_cmp(&result.weights[a].abs()));
+            let kept = held.len().min(cardinality.max_assets);
+
+            let bounds = restricted
+                .constraints
+                .box_constraint
+                .get_or_insert_with(|| BoxConstraint::uniform(n, f64::NEG_INFINITY, f64::INFINITY));
+            for &i in &held[kept..] {
+                bounds.lower[i] = 0.0;
+                bounds.upper[i] = 0.0;
+            }
+            if cardinality.min_weight_if_held > 0.0 {
+                for &i in &held[..kept] {
+                    bounds.lower[i] = bounds.lower[i].max(cardinality.min_weight_if_held);
                 }
             }
 
-            let mut gradient = vec![0.0; n];
-            for i in 0..n {
-                let grad_vol = grad_var[i] / (2.0 * vol);
-                gradient[i] = (vol * grad_ret[i] - (ret - rf) * grad_vol) / var;
+            result = self.solve_with_density(&restricted)?;
+            iterations = iterations.saturating_add(result.iterations);
+        }
+
+        result.iterations = iterations;
+        if !cardinality.is_satisfied(&result.weights) {
+            result.status = SolverStatus::SubOptimal;
+        }
+        Ok(result)
+    }
+
+    /// Solve, enforcing the weight density constraint heuristically
+    ///
+    /// While more than `max_count` assets exceed the threshold, the assets
+    /// above it beyond the `max_count` largest are fixed at zero and the
+    /// problem is re-solved. Iterations are summed over all solves. If the
+    /// limit still fails after one round per asset, the result is reported
+    /// as `SubOptimal`.
+    fn solve_with_density(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let mut result = self.solve_objective(problem)?;
+        let density = match &problem.constraints.density_constraint {
+            Some(density) => density,
+            None => return Ok(result),
+        };
+
+        let n = problem.n_assets;
+        let mut restricted = problem.clone();
+        let mut iterations = result.iterations;
+
+        for _ in 0..n {
+            if density.count_above(&result.weights) <= density.max_count {
+                break;
             }
 
-            // Ascent (maximize Sharpe)
-            for i in 0..n {
-                weights[i] += learning_rate * gradient[i];
+            // Keep the largest weights above the threshold, exclude the rest
+            let mut above: Vec<usize> = (0..n)
+                .filter(|&i| result.weights[i] > density.threshold)
+                .collect();
+            above.sort_by(|&a, &b| result.weights[b].total_cmp(&result.weights[a]));
+
+            let bounds = restricted
+                .constraints
+                .box_constraint
+                .get_or_insert_with(|| BoxConstraint::uniform(n, f64::NEG_INFINITY, f64::INFINITY));
+            for &i in &above[density.max_count..] {
+                bounds.lower[i] = 0.0;
+                bounds.upper[i] = 0.0;
             }
 
-            self.project_to_feasible(&mut weights, problem)?;
+            result = self.solve_objective(&restricted)?;
+            iterations = iterations.saturating_add(result.iterations);
+        }
 
-            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
-            if grad_norm < self.config.eps_abs {
-                break;
+        result.iterations = iterations;
+        if result.n_assets_above_threshold > density.max_count {
+            result.status = SolverStatus::SubOptimal;
+        }
+        Ok(result)
+    }
+
+    /// Regularize the covariance if its condition number exceeds the limit
+    ///
+    /// Bisects on the shrinkage intensity of `covariance::matrix::regularize`
+    /// for the smallest lambda bringing the condition number below
+    /// `max_condition_number`. Riskless (zero-variance) assets are singular
+    /// by construction and are left out of the check and the shrinkage.
+    fn condition_covariance<'a>(
+        &self,
+        problem: &'a OptimizationProblem,
+    ) -> Result<(Cow<'a, OptimizationProblem>, Option<f64>)> {
+        let risky: Vec<usize> = (0..problem.n_assets)
+            .filter(|&i| problem.covariance[i][i] > 0.0)
+            .collect();
+        if risky.is_empty() {
+            return Ok((Cow::Borrowed(problem), None));
+        }
+
+        let cov = vec_to_dmatrix(&problem.covariance)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?
+            .select_rows(&risky)
+            .select_columns(&risky);
+
+        if condition_number(&cov) < self.config.max_condition_number {
+            return Ok((Cow::Borrowed(problem), None));
+        }
+
+        let mut lo = 0.0;
+        let mut hi = 1.0;
+        if condition_number(&regularize(&cov, hi)) >= self.config.max_condition_number {
+            return Err(OptimizerError::NumericalError(
+                "Covariance cannot be regularized below the condition number limit".to_string(),
+            ));
+        }
+
+        while hi - lo > 1e-8 {
+            let mid = 0.5 * (lo + hi);
+            if condition_number(&regularize(&cov, mid)) < self.config.max_condition_number {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        let regularized = regularize(&cov, hi);
+        let mut conditioned = problem.clone();
+        for (a, &i) in risky.iter().enumerate() {
+            for (b, &j) in risky.iter().enumerate() {
+                conditioned.covariance[i][j] = regularized[(a, b)];
             }
         }
 
+        Ok((Cow::Owned(conditioned), Some(hi)))
+    }
+
+    /// Assemble a result with portfolio statistics for the given weights
+    pub(crate) fn build_result(
+        problem: &OptimizationProblem,
+        weights: Vec<f64>,
+        iterations: u32,
+        status: SolverStatus,
+    ) -> OptimizationResult {
         let variance = problem.portfolio_variance(&weights);
         let expected_return = problem.portfolio_return(&weights);
         let volatility = variance.sqrt();
@@ -328,156 +1155,1311 @@ impl QpSolver {
         } else {
             0.0
         };
-
-        Ok(OptimizationResult {
-            weights,
+        let n_assets_above_threshold = problem
+            .constraints
+            .density_constraint
+            .as_ref()
+            .map_or(0, |density| density.count_above(&weights));
+        let tracking_error = problem
+            .constraints
+            .tracking_error_constraint
+            .as_ref()
+            .map(|tracking| tracking.tracking_error(&weights, &problem.covariance));
+
+        OptimizationResult {
             expected_return,
             variance,
             volatility,
             sharpe_ratio: sharpe,
             iterations,
-            status: SolverStatus::Optimal,
-            transaction_cost: None,
-        })
+            n_assets_above_threshold,
+            tracking_error,
+            covariance: problem.covariance.clone(),
+            ..OptimizationResult::from_weights(weights, status)
+        }
     }
 
-    /// Solve risk parity problem (equal risk contribution)
-    fn solve_risk_parity(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+    /// Check whether the convergence callback requests an early stop
+    fn stop_requested(&self, iteration: u32, weights: &[f64], gradient_norm: f64) -> bool {
+        self.config
+            .convergence_callback
+            .as_ref()
+            .is_some_and(|callback| !callback(iteration, weights, gradient_norm))
+    }
+
+    /// Apply the configured gradient normalization and clipping in place
+    ///
+    /// Normalization rescales to unit norm; clipping then scales by
+    /// `clip / max(||g||, clip)`. Convergence checks use the raw gradient.
+    fn condition_gradient(&self, gradient: &mut [f64]) {
+        let norm = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+        let mut scale = 1.0;
+        let mut scaled_norm = norm;
+
+        if self.config.gradient_normalize && norm > 0.0 {
+            scale = 1.0 / norm;
+            scaled_norm = 1.0;
+        }
+        if let Some(clip) = self.config.gradient_clip {
+            if clip > 0.0 {
+                scale *= clip / scaled_norm.max(clip);
+            }
+        }
+
+        if scale != 1.0 {
+            for g in gradient.iter_mut() {
+                *g *= scale;
+            }
+        }
+    }
+
+    /// Solve minimum variance problem
+    ///
+    /// Box constraints are handled by projection; the full-investment
+    /// constraint is enforced through an annealed quadratic penalty
+    /// `rho_t * (sum(w) - 1)^2 / 2`, applied via its proximal operator so that
+    /// the step size depends on Σ alone and not on the growing `rho_t`.
+    /// Factor exposure bounds get the same treatment with the penalty
+    /// `rho_t * (max(0, B_f'w - U_f)^2 + max(0, L_f - B_f'w)^2) / 2`.
+    /// With a benchmark `b`, the active variance (w - b)'Σ(w - b) is
+    /// minimized instead.
+    fn solve_min_variance(
+        &self,
+        problem: &OptimizationProblem,
+        benchmark: Option<&[f64]>,
+    ) -> Result<OptimizationResult> {
         let n = problem.n_assets;
-        let mut weights = vec![1.0 / n as f64; n];
+        let schedule = self.config.penalty_schedule;
+
+        let mut weights = self.initial_weights(problem)?;
+
+        // Step size from the Gershgorin bound on the Lipschitz constant of 2Σw
+        let lipschitz = 2.0
+            * problem
+                .covariance
+                .iter()
+                .map(|row| row.iter().map(|c| c.abs()).sum::<f64>())
+                .fold(0.0, f64::max);
+        let step = if lipschitz > 0.0 {
+            1.0 / lipschitz
+        } else {
+            1.0
+        };
+
+        // Assets pinned by their bounds (lower == upper) cannot absorb the budget shift
+        let pinned: Vec<usize> = match &problem.constraints.box_constraint {
+            Some(bounds) => (0..n)
+                .filter(|&i| bounds.lower[i] >= bounds.upper[i])
+                .collect(),
+            None => Vec::new(),
+        };
+        let n_free = n - pinned.len();
 
-        let learning_rate = 0.01;
         let mut iterations = 0;
+        let mut status = SolverStatus::Optimal;
 
-        for _ in 0..self.config.max_iterations {
+        for t in 0..self.config.max_iterations {
             iterations += 1;
 
-            let var = problem.portfolio_variance(&weights);
-            if var < 1e-10 {
-                break;
-            }
-
-            // Marginal risk contribution
-            let mut mrc = vec![0.0; n];
+            // Gradient step on the variance: w - step * 2 * Σ * (w - b)
+            let active: Vec<f64> = match benchmark {
+                Some(b) => weights.iter().zip(b).map(|(w, b)| w - b).collect(),
+                None => weights.clone(),
+            };
+            let mut gradient = vec![0.0; n];
             for i in 0..n {
                 for j in 0..n {
-                    mrc[i] += problem.covariance[i][j] * weights[j];
+                    gradient[i] += 2.0 * problem.covariance[i][j] * active[j];
                 }
             }
-
-            // Risk contribution
-            let mut rc = vec![0.0; n];
-            for i in 0..n {
-                rc[i] = weights[i] * mrc[i] / var.sqrt();
+            self.condition_gradient(&mut gradient);
+            let mut candidate: Vec<f64> = weights
+                .iter()
+                .zip(&gradient)
+                .map(|(w, g)| w - step * g)
+                .collect();
+            for &i in &pinned {
+                candidate[i] = weights[i];
             }
 
-            // Target: equal risk contribution = 1/n of total risk
-            let target_rc = var.sqrt() / n as f64;
+            // Proximal step on the penalty: shrink the budget violation by
+            // 1 / (1 + rho * step * n_free)
+            let rho = schedule.rho(t, self.config.max_iterations);
+            let violation = candidate.iter().sum::<f64>() - 1.0;
+            let shift = rho * step * violation / (1.0 + rho * step * n_free as f64);
+            for c in &mut candidate {
+                *c -= shift;
+            }
 
-            // Gradient: push towards equal RC
-            let mut gradient = vec![0.0; n];
-            for i in 0..n {
-                gradient[i] = rc[i] - target_rc;
+            // Then on the factor exposure penalties, with the same weight
+            if let Some(factors) = &problem.constraints.factor_constraints {
+                Self::penalize_factor_exposures(&mut candidate, factors, rho * step, &pinned);
             }
 
-            // Update
+            let previous = weights.clone();
             for i in 0..n {
-                weights[i] -= learning_rate * gradient[i];
-                weights[i] = weights[i].max(1e-6); // Keep positive
+                let mut updated = candidate[i];
+                if let Some(box_constraint) = &problem.constraints.box_constraint {
+                    updated = updated
+                        .max(box_constraint.lower[i])
+                        .min(box_constraint.upper[i]);
+                }
+                weights[i] = updated;
+            }
+            if let Some(turnover) = &problem.constraints.turnover_constraint {
+                Self::project_turnover(&mut weights, turnover);
             }
+            if let Some(tracking) = &problem.constraints.tracking_error_constraint {
+                Self::pull_to_tracking_error(&mut weights, tracking, &problem.covariance);
+            }
+            let moves = weights.iter().zip(&previous).map(|(w, p)| (w - p).abs());
+            let max_move = moves.clone().fold(0.0, f64::max);
+            let move_sq: f64 = moves.map(|m| m * m).sum();
 
-            // Normalize
-            let sum: f64 = weights.iter().sum();
-            for w in &mut weights {
-                *w /= sum;
+            // Converged when the budget holds and the projected gradient vanishes
+            let violation = weights.iter().sum::<f64>() - 1.0;
+            if violation.abs() < self.config.eps_abs && max_move / step < self.config.eps_abs {
+                break;
             }
 
-            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
-            if grad_norm < self.config.eps_abs {
+            if self.stop_requested(iterations, &weights, move_sq.sqrt() / step) {
+                status = SolverStatus::SubOptimal;
                 break;
             }
         }
 
-        let variance = problem.portfolio_variance(&weights);
-        let expected_return = problem.portfolio_return(&weights);
-        let volatility = variance.sqrt();
-        let sharpe = if volatility > 0.0 {
-            (expected_return - problem.risk_free_rate) / volatility
-        } else {
-            0.0
-        };
-
-        Ok(OptimizationResult {
-            weights,
-            expected_return,
-            variance,
-            volatility,
-            sharpe_ratio: sharpe,
-            iterations,
-            status: SolverStatus::Optimal,
-            transaction_cost: None,
-        })
+        Ok(Self::build_result(problem, weights, iterations, status))
     }
 
-    /// Project weights to feasible set
-    fn project_to_feasible(
-        &self,
-        weights: &mut [f64],
-        problem: &OptimizationProblem,
-    ) -> Result<()> {
-        let n = weights.len();
+    /// Solve mean-variance problem: max μ'w - λ/2 * w'Σw
+    fn solve_mean_variance(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let n = problem.n_assets;
+        let lambda = problem.risk_aversion;
 
-        // Apply box constraints
-        if let Some(box_constraint) = &problem.constraints.box_constraint {
+        let mut weights = self.initial_weights(problem)?;
+
+        let learning_rate = 0.01;
+        let mut iterations = 0;
+        let mut status = SolverStatus::Optimal;
+
+        for _ in 0..self.config.max_iterations {
+            iterations += 1;
+
+            // Gradient: λ * Σ * w - μ
+            let mut gradient = vec![0.0; n];
             for i in 0..n {
-                weights[i] = weights[i]
-                    .max(box_constraint.lower[i])
-                    .min(box_constraint.upper[i]);
+                gradient[i] = -problem.expected_returns[i];
+                for j in 0..n {
+                    gradient[i] += lambda * problem.covariance[i][j] * weights[j];
+                }
             }
-        }
+            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+            self.condition_gradient(&mut gradient);
 
-        // Normalize to sum to 1 (for full investment constraint)
-        let sum: f64 = weights.iter().sum();
-        if sum > 0.0 {
-            for w in weights.iter_mut() {
-                *w /= sum;
+            // Update
+            for i in 0..n {
+                weights[i] -= learning_rate * gradient[i];
             }
 
-            // Re-apply box constraints after normalization
-            if let Some(box_constraint) = &problem.constraints.box_constraint {
-                for i in 0..n {
-                    weights[i] = weights[i]
-                        .max(box_constraint.lower[i])
-                        .min(box_constraint.upper[i]);
-                }
-                // Re-normalize
-                let sum: f64 = weights.iter().sum();
-                if sum > 0.0 && (sum - 1.0).abs() > 1e-10 {
-                    for w in weights.iter_mut() {
-                        *w /= sum;
-                    }
-                }
+            self.project_to_feasible(&mut weights, problem)?;
+
+            if grad_norm < self.config.eps_abs {
+                break;
+            }
+
+            if self.stop_requested(iterations, &weights, grad_norm) {
+                status = SolverStatus::SubOptimal;
+                break;
             }
         }
 
-        Ok(())
+        Ok(Self::build_result(problem, weights, iterations, status))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::constraints::ConstraintSet;
+    /// Solve max return problem
+    fn solve_max_return(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let n = problem.n_assets;
 
-    fn create_test_problem() -> OptimizationProblem {
-        let returns = vec![0.10, 0.15, 0.12];
-        let cov = vec![
-            vec![0.04, 0.01, 0.02],
-            vec![0.01, 0.09, 0.03],
-            vec![0.02, 0.03, 0.0625],
-        ];
+        // For max return, put all weight in highest return asset (within constraints)
+        let mut weights = vec![0.0; n];
 
-        OptimizationProblem::builder(3)
+        if let Some(box_constraint) = &problem.constraints.box_constraint {
+            // Find asset with highest return that can take max weight
+            let max_idx = problem
+                .expected_returns
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+
+            // Put max weight in best asset, distribute rest equally
+            let max_weight = box_constraint.upper[max_idx];
+            let min_weights: f64 = box_constraint.lower.iter().sum();
+            let remaining = 1.0 - max_weight - (min_weights - box_constraint.lower[max_idx]);
+
+            for i in 0..n {
+                if i == max_idx {
+                    weights[i] = max_weight.min(1.0 - min_weights + box_constraint.lower[i]);
+                } else {
+                    weights[i] = box_constraint.lower[i];
+                }
+            }
+
+            // Distribute any remaining weight
+            let current_sum: f64 = weights.iter().sum();
+            if (current_sum - 1.0).abs() > 1e-10 {
+                let diff = 1.0 - current_sum;
+                // Add to second-best assets
+                let mut returns_indexed: Vec<_> = problem
+                    .expected_returns
+                    .iter()
+                    .enumerate()
+                    .collect();
+                returns_indexed.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+
+                let mut remaining_diff = diff;
+                for (i, _) in returns_indexed {
+                    if remaining_diff <= 0.0 {
+                        break;
+                    }
+                    let can_add = box_constraint.upper[i] - weights[i];
+                    let to_add = can_add.min(remaining_diff);
+                    weights[i] += to_add;
+                    remaining_diff -= to_add;
+                }
+            }
+        } else {
+            // No box constraints - all in best asset
+            let max_idx = problem
+                .expected_returns
+                .iter()
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            weights[max_idx] = 1.0;
+        }
+
+        if problem.constraints.turnover_constraint.is_some()
+            || problem.constraints.tracking_error_constraint.is_some()
+        {
+            self.project_to_feasible(&mut weights, problem)?;
+        }
+
+        Ok(Self::build_result(
+            problem,
+            weights,
+            1,
+            SolverStatus::Optimal,
+        ))
+    }
+
+    /// Solve max Sharpe ratio problem
+    fn solve_max_sharpe(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        // Use mean-variance with varying risk aversion to trace efficient frontier
+        // Then find tangency portfolio
+        // Simplified: use gradient ascent on Sharpe ratio
+
+        let n = problem.n_assets;
+        let mut weights = vec![1.0 / n as f64; n];
+        self.project_to_feasible(&mut weights, problem)?;
+
+        let learning_rate = 0.001;
+        let mut iterations = 0;
+        let mut status = SolverStatus::Optimal;
+        let rf = problem.risk_free_rate;
+
+        for _ in 0..self.config.max_iterations {
+            iterations += 1;
+
+            let ret = problem.portfolio_return(&weights);
+            let var = problem.portfolio_variance(&weights);
+            let vol = var.sqrt();
+
+            if vol < 1e-10 {
+                break;
+            }
+
+            // Gradient of Sharpe: (vol * ∂ret/∂w - (ret-rf) * ∂vol/∂w) / vol^2
+            let mut grad_ret = problem.expected_returns.clone();
+            let mut grad_var = vec![0.0; n];
+            for i in 0..n {
+                for j in 0..n {
+                    grad_var[i] += 2.0 * problem.covariance[i][j] * weights[j];
+                }
+            }
+
+            let mut gradient = vec![0.0; n];
+            for i in 0..n {
+                let grad_vol = grad_var[i] / (2.0 * vol);
+                gradient[i] = (vol * grad_ret[i] - (ret - rf) * grad_vol) / var;
+            }
+            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+            self.condition_gradient(&mut gradient);
+
+            // Ascent (maximize Sharpe)
+            for i in 0..n {
+                weights[i] += learning_rate * gradient[i];
+            }
+
+            self.project_to_feasible(&mut weights, problem)?;
+
+            if grad_norm < self.config.eps_abs {
+                break;
+            }
+
+            if self.stop_requested(iterations, &weights, grad_norm) {
+                status = SolverStatus::SubOptimal;
+                break;
+            }
+        }
+
+        Ok(Self::build_result(problem, weights, iterations, status))
+    }
+
+    /// Solve risk parity problem (equal risk contribution)
+    fn solve_risk_parity(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let n = problem.n_assets;
+        let mut weights = vec![1.0 / n as f64; n];
+
+        let learning_rate = 0.01;
+        let mut iterations = 0;
+        let mut status = SolverStatus::Optimal;
+
+        for _ in 0..self.config.max_iterations {
+            iterations += 1;
+
+            let var = problem.portfolio_variance(&weights);
+            if var < 1e-10 {
+                break;
+            }
+
+            // Marginal risk contribution
+            let mut mrc = vec![0.0; n];
+            for i in 0..n {
+                for j in 0..n {
+                    mrc[i] += problem.covariance[i][j] * weights[j];
+                }
+            }
+
+            // Risk contribution
+            let mut rc = vec![0.0; n];
+            for i in 0..n {
+                rc[i] = weights[i] * mrc[i] / var.sqrt();
+            }
+
+            // Target: equal risk contribution = 1/n of total risk
+            let target_rc = var.sqrt() / n as f64;
+
+            // Gradient: push towards equal RC
+            let mut gradient = vec![0.0; n];
+            for i in 0..n {
+                gradient[i] = rc[i] - target_rc;
+            }
+            let grad_norm: f64 = gradient.iter().map(|g| g * g).sum::<f64>().sqrt();
+            self.condition_gradient(&mut gradient);
+
+            // Update
+            for i in 0..n {
+                weights[i] -= learning_rate * gradient[i];
+                weights[i] = weights[i].max(1e-6); // Keep positive
+            }
+
+            // Normalize
+            let sum: f64 = weights.iter().sum();
+            for w in &mut weights {
+                *w /= sum;
+            }
+
+            if grad_norm < self.config.eps_abs {
+                break;
+            }
+
+            if self.stop_requested(iterations, &weights, grad_norm) {
+                status = SolverStatus::SubOptimal;
+                break;
+            }
+        }
+
+        if problem.constraints.turnover_constraint.is_some()
+            || problem.constraints.tracking_error_constraint.is_some()
+        {
+            self.project_to_feasible(&mut weights, problem)?;
+        }
+
+        Ok(Self::build_result(problem, weights, iterations, status))
+    }
+
+    /// Hierarchical risk parity (López de Prado, 2016)
+    ///
+    /// Assets are clustered by single linkage on the distance `1 - |ρ_ij|`
+    /// and the dendrogram is bisected top-down: at each merge the two
+    /// sub-clusters split their parent's weight in inverse proportion to
+    /// their variances, each measured under inverse-variance weights on
+    /// its block of the quasi-diagonalized covariance.
+    ///
+    /// Box bounds are applied at each split: a sub-cluster's share is
+    /// clipped to what its members' bounds can hold and the remainder goes
+    /// to its sibling, so clipped weight is redistributed within the
+    /// enclosing cluster. Other constraints are ignored.
+    fn solve_hrp(&self, problem: &OptimizationProblem) -> Result<OptimizationResult> {
+        let n = problem.n_assets;
+        let cov = vec_to_dmatrix(&problem.covariance)
+            .map_err(|e| OptimizerError::InvalidInput(e.to_string()))?;
+
+        let vols: Vec<f64> = (0..n).map(|i| cov[(i, i)].max(0.0).sqrt()).collect();
+        let distance = DMatrix::from_fn(n, n, |i, j| {
+            if i == j {
+                0.0
+            } else if vols[i] > 0.0 && vols[j] > 0.0 {
+                1.0 - (cov[(i, j)] / (vols[i] * vols[j])).abs().min(1.0)
+            } else {
+                1.0
+            }
+        });
+        let dendrogram = single_linkage(&distance);
+
+        // Members of each cluster in dendrogram leaf order, which is the
+        // quasi-diagonal ordering of the covariance
+        let mut members: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+        for &(a, b) in &dendrogram {
+            let mut merged = members[a].clone();
+            merged.extend_from_slice(&members[b]);
+            members.push(merged);
+        }
+
+        let (lower, upper) = match &problem.constraints.box_constraint {
+            Some(bounds) => (bounds.lower.clone(), bounds.upper.clone()),
+            None => (vec![f64::NEG_INFINITY; n], vec![f64::INFINITY; n]),
+        };
+        let capacity = |cluster: usize, bounds: &[f64]| -> f64 {
+            members[cluster].iter().map(|&i| bounds[i]).sum()
+        };
+
+        let root = members.len() - 1;
+        if capacity(root, &lower) > 1.0 + self.config.eps_abs
+            || capacity(root, &upper) < 1.0 - self.config.eps_abs
+        {
+            return Err(OptimizerError::Infeasible(
+                "Box constraints do not admit fully invested weights".to_string(),
+            ));
+        }
+
+        let mut weights = vec![0.0; n];
+        let mut pending = vec![(root, 1.0)];
+        while let Some((cluster, budget)) = pending.pop() {
+            if cluster < n {
+                weights[cluster] = budget;
+                continue;
+            }
+            let (left, right) = dendrogram[cluster - n];
+            let v_left = cluster_variance(&cov, &members[left]);
+            let v_right = cluster_variance(&cov, &members[right]);
+            let alpha = if v_left + v_right > 0.0 {
+                v_right / (v_left + v_right)
+            } else {
+                0.5
+            };
+
+            let low = capacity(left, &lower).max(budget - capacity(right, &upper));
+            let high = capacity(left, &upper).min(budget - capacity(right, &lower));
+            let share = (alpha * budget).max(low).min(high);
+            pending.push((left, share));
+            pending.push((right, budget - share));
+        }
+
+        Ok(Self::build_result(
+            problem,
+            weights,
+            dendrogram.len() as u32,
+            SolverStatus::Optimal,
+        ))
+    }
+
+    /// Feasible starting weights from the configured initialization strategy
+    fn initial_weights(&self, problem: &OptimizationProblem) -> Result<Vec<f64>> {
+        let n = problem.n_assets;
+        let equal_weight = vec![1.0 / n as f64; n];
+        let bounds = problem.constraints.box_constraint.as_ref();
+
+        let mut weights = match &self.config.initialization {
+            InitializationStrategy::EqualWeight => equal_weight,
+            InitializationStrategy::MinLower => match bounds {
+                Some(b) if b.lower.iter().sum::<f64>() > 0.0 => {
+                    let total: f64 = b.lower.iter().sum();
+                    b.lower.iter().map(|l| l / total).collect()
+                }
+                _ => equal_weight,
+            },
+            InitializationStrategy::MaxReturn => {
+                let (mut weights, upper) = match bounds {
+                    Some(b) => (b.lower.clone(), b.upper.clone()),
+                    None => (vec![0.0; n], vec![1.0; n]),
+                };
+
+                let mut by_return: Vec<usize> = (0..n).collect();
+                by_return.sort_by(|&a, &b| {
+                    problem.expected_returns[b].total_cmp(&problem.expected_returns[a])
+                });
+
+                let mut remaining = 1.0 - weights.iter().sum::<f64>();
+                for i in by_return {
+                    if remaining <= 0.0 {
+                        break;
+                    }
+                    let add = (upper[i] - weights[i]).max(0.0).min(remaining);
+                    weights[i] += add;
+                    remaining -= add;
+                }
+                weights
+            }
+            InitializationStrategy::Custom(custom) => {
+                if custom.len() != n {
+                    return Err(OptimizerError::DimensionMismatch {
+                        expected: n,
+                        got: custom.len(),
+                    });
+                }
+                custom.clone()
+            }
+        };
+
+        self.project_to_feasible(&mut weights, problem)?;
+        Ok(weights)
+    }
+
+    /// Project weights to feasible set
+    fn project_to_feasible(
+        &self,
+        weights: &mut [f64],
+        problem: &OptimizationProblem,
+    ) -> Result<()> {
+        Self::clip_and_normalize(weights, problem);
+
+        let inequalities: Vec<_> = problem
+            .constraints
+            .linear_constraints
+            .iter()
+            .filter(|c| !c.is_equality)
+            .collect();
+        let factors = problem.constraints.factor_constraints.as_ref();
+        let turnover = problem.constraints.turnover_constraint.as_ref();
+        let tracking = problem.constraints.tracking_error_constraint.as_ref();
+        if inequalities.is_empty() && factors.is_none() && turnover.is_none() && tracking.is_none()
+        {
+            return Ok(());
+        }
+
+        // Alternate projections onto violated half-spaces a'w <= b, the
+        // turnover ball, the tracking error ellipsoid and the box/budget
+        // set; each row is visited through its sparse entries only
+        for _ in 0..MAX_PROJECTION_ROUNDS {
+            let mut violated = false;
+            if let Some(factors) = factors {
+                violated |= Self::project_factor_exposures(weights, factors);
+            }
+            if let Some(turnover) = turnover {
+                violated |= Self::project_turnover(weights, turnover);
+            }
+            if let Some(tracking) = tracking {
+                violated |= Self::pull_to_tracking_error(weights, tracking, &problem.covariance);
+            }
+            for constraint in &inequalities {
+                let matrix = constraint.sparse_matrix();
+                for (row, &rhs) in matrix.outer_iterator().zip(&constraint.rhs) {
+                    let lhs: f64 = row.iter().map(|(j, &a)| a * weights[j]).sum();
+                    let excess = lhs - rhs;
+                    if excess > FEASIBILITY_TOL {
+                        let norm_sq: f64 = row.iter().map(|(_, &a)| a * a).sum();
+                        for (j, &a) in row.iter() {
+                            weights[j] -= excess * a / norm_sq;
+                        }
+                        violated = true;
+                    }
+                }
+            }
+
+            if !violated {
+                break;
+            }
+            Self::clip_and_normalize(weights, problem);
+        }
+
+        Ok(())
+    }
+
+    /// Step each out-of-bounds factor exposure `f = B'w` back to its bound
+    ///
+    /// The step moves along the factor's loading vector `B_k`, by exactly
+    /// the distance to the violated bound: `w -= excess * B_k / ||B_k||^2`.
+    /// Returns whether any exposure was out of bounds.
+    fn project_factor_exposures(weights: &mut [f64], factors: &FactorExposureConstraint) -> bool {
+        let mut violated = false;
+
+        for k in 0..factors.lower.len().min(factors.upper.len()) {
+            let loading: Vec<f64> = factors
+                .factor_loadings
+                .iter()
+                .map(|row| row.get(k).copied().unwrap_or(0.0))
+                .collect();
+            let norm_sq: f64 = loading.iter().map(|b| b * b).sum();
+            if norm_sq == 0.0 {
+                continue;
+            }
+
+            let exposure: f64 = loading.iter().zip(weights.iter()).map(|(b, w)| b * w).sum();
+            let excess = if exposure > factors.upper[k] + FEASIBILITY_TOL {
+                exposure - factors.upper[k]
+            } else if exposure < factors.lower[k] - FEASIBILITY_TOL {
+                exposure - factors.lower[k]
+            } else {
+                continue;
+            };
+
+            for (w, b) in weights.iter_mut().zip(&loading) {
+                *w -= excess * b / norm_sq;
+            }
+            violated = true;
+        }
+
+        violated
+    }
+
+    /// Proximal step on the factor exposure penalties
+    ///
+    /// Each out-of-bounds exposure `B_k'w` is pulled towards its bound along
+    /// the loadings demeaned over the free (unpinned) assets, `d_k`, so the
+    /// step leaves the budget to its own penalty. For the penalty
+    /// `rho/2 * (max(0, B_k'w - U_k)^2 + max(0, L_k - B_k'w)^2)` and step
+    /// size `step`, `penalty = rho * step` and the violation shrinks by
+    /// `1 / (1 + penalty * ||d_k||^2)`.
+    fn penalize_factor_exposures(
+        weights: &mut [f64],
+        factors: &FactorExposureConstraint,
+        penalty: f64,
+        pinned: &[usize],
+    ) {
+        let n_free = weights.len() - pinned.len();
+        if n_free == 0 {
+            return;
+        }
+
+        for k in 0..factors.lower.len().min(factors.upper.len()) {
+            let loading: Vec<f64> = factors
+                .factor_loadings
+                .iter()
+                .map(|row| row.get(k).copied().unwrap_or(0.0))
+                .collect();
+            let exposure: f64 = loading.iter().zip(weights.iter()).map(|(b, w)| b * w).sum();
+            let excess = if exposure > factors.upper[k] {
+                exposure - factors.upper[k]
+            } else if exposure < factors.lower[k] {
+                exposure - factors.lower[k]
+            } else {
+                continue;
+            };
+
+            let mut direction = loading;
+            for &i in pinned {
+                direction[i] = 0.0;
+            }
+            let mean = direction.iter().sum::<f64>() / n_free as f64;
+            for (i, d) in direction.iter_mut().enumerate() {
+                if !pinned.contains(&i) {
+                    *d -= mean;
+                }
+            }
+            let norm_sq: f64 = direction.iter().map(|d| d * d).sum();
+            if norm_sq == 0.0 {
+                continue;
+            }
+
+            let fraction = penalty / (1.0 + penalty * norm_sq);
+            for (w, d) in weights.iter_mut().zip(&direction) {
+                *w -= excess * d * fraction;
+            }
+        }
+    }
+
+    /// Pull the weights within the turnover limit `sum |w - w_old| <= T`
+    ///
+    /// Buys and sells are each shrunk towards the current weights by a
+    /// common amount (a Euclidean projection onto an L1 ball, Duchi et al.,
+    /// 2008), to totals that add up to `T` with the net trade, and so the
+    /// budget, unchanged. Every weight moves towards its current value,
+    /// which keeps it inside any box that holds both. Returns whether the
+    /// limit was exceeded.
+    fn project_turnover(weights: &mut [f64], turnover: &TurnoverConstraint) -> bool {
+        let trades: Vec<f64> = weights
+            .iter()
+            .zip(&turnover.current_weights)
+            .map(|(w, old)| w - old)
+            .collect();
+        let buys: f64 = trades.iter().filter(|&&t| t > 0.0).sum();
+        let sells: f64 = -trades.iter().filter(|&&t| t < 0.0).sum::<f64>();
+        let limit = turnover.max_turnover.max(0.0);
+        if buys + sells <= limit + FEASIBILITY_TOL {
+            return false;
+        }
+
+        let net = buys - sells;
+        let buy_shrink = l1_threshold(
+            trades.iter().filter(|&&t| t > 0.0).copied(),
+            ((limit + net) / 2.0).max(0.0),
+        );
+        let sell_shrink = l1_threshold(
+            trades.iter().filter(|&&t| t < 0.0).map(|t| -t),
+            ((limit - net) / 2.0).max(0.0),
+        );
+
+        for ((w, old), trade) in weights
+            .iter_mut()
+            .zip(&turnover.current_weights)
+            .zip(trades)
+        {
+            let shrink = if trade > 0.0 { buy_shrink } else { sell_shrink };
+            *w = old + trade.signum() * (trade.abs() - shrink).max(0.0);
+        }
+        true
+    }
+
+    /// Pull the weights within the tracking error limit
+    ///
+    /// The active weights `w - b` are scaled down until the tracking error
+    /// meets `max_tracking_error`, moving along the line to the benchmark.
+    /// The result is a convex combination of `w` and `b`, so the budget and
+    /// any box or linear constraint that both satisfy still hold. Returns
+    /// whether the limit was exceeded.
+    fn pull_to_tracking_error(
+        weights: &mut [f64],
+        tracking: &TrackingErrorConstraint,
+        covariance: &[Vec<f64>],
+    ) -> bool {
+        let tracking_error = tracking.tracking_error(weights, covariance);
+        let limit = tracking.max_tracking_error.max(0.0);
+        if tracking_error <= limit + FEASIBILITY_TOL {
+            return false;
+        }
+
+        let keep = limit / tracking_error;
+        for (w, b) in weights.iter_mut().zip(&tracking.benchmark) {
+            *w = b + keep * (*w - b);
+        }
+        true
+    }
+
+    /// Clip to box constraints and rescale to the full-investment budget
+    fn clip_and_normalize(weights: &mut [f64], problem: &OptimizationProblem) {
+        let n = weights.len();
+
+        // Apply box constraints
+        if let Some(box_constraint) = &problem.constraints.box_constraint {
+            for i in 0..n {
+                weights[i] = weights[i]
+                    .max(box_constraint.lower[i])
+                    .min(box_constraint.upper[i]);
+            }
+        }
+
+        // Normalize to sum to 1 (for full investment constraint)
+        let sum: f64 = weights.iter().sum();
+        if sum > 0.0 {
+            for w in weights.iter_mut() {
+                *w /= sum;
+            }
+
+            // Re-apply box constraints after normalization
+            if let Some(box_constraint) = &problem.constraints.box_constraint {
+                for i in 0..n {
+                    weights[i] = weights[i]
+                        .max(box_constraint.lower[i])
+                        .min(box_constraint.upper[i]);
+                }
+                // Re-normalize
+                let sum: f64 = weights.iter().sum();
+                if sum > 0.0 && (sum - 1.0).abs() > 1e-10 {
+                    for w in weights.iter_mut() {
+                        *w /= sum;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Branch-and-bound node for the cardinality constraint
+#[derive(Debug, Clone, Default)]
+struct CardinalityNode {
+    /// Assets that must be held
+    held: Vec<usize>,
+    /// Assets fixed at zero
+    excluded: Vec<usize>,
+}
+
+impl CardinalityNode {
+    /// The problem restricted to this node, without the position limit
+    ///
+    /// Held assets get lower bounds of at least `min_weight_if_held`; when
+    /// `max_assets` are held every other asset is fixed at zero.
+    fn restrict(
+        &self,
+        problem: &OptimizationProblem,
+        cardinality: &CardinalityConstraint,
+    ) -> OptimizationProblem {
+        let n = problem.n_assets;
+        let mut restricted = problem.clone();
+        restricted.constraints.cardinality_constraint = None;
+
+        let bounds = restricted
+            .constraints
+            .box_constraint
+            .get_or_insert_with(|| BoxConstraint::uniform(n, f64::NEG_INFINITY, f64::INFINITY));
+        let full = self.held.len() >= cardinality.max_assets;
+        for i in 0..n {
+            if self.held.contains(&i) {
+                if cardinality.min_weight_if_held > 0.0 {
+                    bounds.lower[i] = bounds.lower[i].max(cardinality.min_weight_if_held);
+                }
+            } else if full || self.excluded.contains(&i) {
+                bounds.lower[i] = 0.0;
+                bounds.upper[i] = 0.0;
+            }
+        }
+        restricted
+    }
+}
+
+/// Objective of a solved portfolio as a cost to minimize
+///
+/// `None` for the risk parity objectives, which have no objective value.
+fn objective_cost(problem: &OptimizationProblem, result: &OptimizationResult) -> Option<f64> {
+    let weights: &[f64] = &result.weights;
+    match &problem.objective {
+        ObjectiveType::MinimizeVariance => Some(problem.portfolio_variance(weights)),
+        ObjectiveType::MaximizeReturn => Some(-problem.portfolio_return(weights)),
+        ObjectiveType::MaximizeSharpe => Some(-problem.sharpe_ratio(weights)),
+        ObjectiveType::MeanVariance => Some(
+            problem.risk_aversion / 2.0 * problem.portfolio_variance(weights)
+                - problem.portfolio_return(weights),
+        ),
+        ObjectiveType::MinimizeTrackingError { benchmark_weights } => {
+            let active: Vec<f64> = weights
+                .iter()
+                .zip(benchmark_weights)
+                .map(|(w, b)| w - b)
+                .collect();
+            Some(problem.portfolio_variance(&active))
+        }
+        ObjectiveType::MinimizeCVaR { .. } => result.cvar,
+        ObjectiveType::MaxDiversification => Some(-problem.diversification_ratio(weights)),
+        ObjectiveType::RiskParity | ObjectiveType::HierarchicalRiskParity => None,
+    }
+}
+
+/// Constraint rows `lower <= A x <= upper` for OSQP, with sparse rows of `A`
+///
+/// A turnover limit is kept aside and linearized by `run_osqp`, which adds
+/// the auxiliary variables it needs after the caller's; a tracking error
+/// limit is kept aside for `run_osqp` to enforce by cutting planes.
+#[derive(Debug, Clone, Default)]
+struct QpConstraints {
+    rows: Vec<Vec<(usize, f64)>>,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    turnover: Option<QpTurnover>,
+    tracking_error: Option<QpTrackingError>,
+}
+
+/// Turnover limit `sum |w - w_old| <= T` on the leading variables
+#[derive(Debug, Clone)]
+struct QpTurnover {
+    current_weights: Vec<f64>,
+    max_turnover: f64,
+    /// Homogenizing scale variable: the limit reads `sum |y - κ w_old| <= κT`
+    scale: Option<usize>,
+}
+
+/// Tracking error limit `sqrt((w - b)'Σ(w - b)) <= TE` on the leading variables
+#[derive(Debug, Clone)]
+struct QpTrackingError {
+    benchmark: Vec<f64>,
+    max_tracking_error: f64,
+    covariance: Vec<Vec<f64>>,
+    /// Homogenizing scale variable: the limit reads `||y - κb||_Σ <= κTE`
+    scale: Option<usize>,
+}
+
+impl QpTrackingError {
+    /// Tangent cut at a solution `x` exceeding the limit by more than `tol`
+    ///
+    /// With active weights `d = w - b` and tracking error `s = sqrt(d'Σd)`,
+    /// the cone lies below its tangent plane `(Σd)'(w - b) / s <= TE`,
+    /// which separates `x` from it. Returns the cut as a row and its upper
+    /// bound, or `None` when `x` is within the limit.
+    fn cut(&self, x: &[f64], tol: f64) -> Option<(Vec<(usize, f64)>, f64)> {
+        let kappa = self.scale.map_or(1.0, |k| x[k]);
+        let active: Vec<f64> = self
+            .benchmark
+            .iter()
+            .enumerate()
+            .map(|(i, b)| x[i] - kappa * b)
+            .collect();
+        let marginal: Vec<f64> = self
+            .covariance
+            .iter()
+            .map(|row| row.iter().zip(&active).map(|(c, d)| c * d).sum())
+            .collect();
+        let tracking_error = active
+            .iter()
+            .zip(&marginal)
+            .map(|(d, m)| d * m)
+            .sum::<f64>()
+            .max(0.0)
+            .sqrt();
+        if tracking_error <= kappa * (self.max_tracking_error + tol) {
+            return None;
+        }
+
+        let slope: Vec<f64> = marginal.iter().map(|m| m / tracking_error).collect();
+        let mut row: Vec<(usize, f64)> = slope
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, a)| a != 0.0)
+            .collect();
+        let bound = slope
+            .iter()
+            .zip(&self.benchmark)
+            .map(|(a, b)| a * b)
+            .sum::<f64>()
+            + self.max_tracking_error;
+        match self.scale {
+            Some(k) => {
+                row.push((k, -bound));
+                Some((row, 0.0))
+            }
+            None => Some((row, bound)),
+        }
+    }
+}
+
+impl QpConstraints {
+    /// Full-investment budget, box bounds, linear constraints and factor
+    /// exposure bounds on the portfolio weights
+    ///
+    /// The budget row is always present, as on the gradient-descent paths.
+    fn for_weights(problem: &OptimizationProblem) -> Self {
+        let n = problem.n_assets;
+        let constraints = &problem.constraints;
+        let mut qp = Self::default();
+
+        qp.push((0..n).map(|j| (j, 1.0)).collect(), 1.0, 1.0);
+        if let Some(bounds) = &constraints.box_constraint {
+            for i in 0..n {
+                qp.push(vec![(i, 1.0)], bounds.lower[i], bounds.upper[i]);
+            }
+        }
+        for linear in &constraints.linear_constraints {
+            let matrix = linear.sparse_matrix();
+            for (row, &rhs) in matrix.outer_iterator().zip(&linear.rhs) {
+                let lower = if linear.is_equality {
+                    rhs
+                } else {
+                    f64::NEG_INFINITY
+                };
+                qp.push(row.iter().map(|(j, &a)| (j, a)).collect(), lower, rhs);
+            }
+        }
+        if let Some(factors) = &constraints.factor_constraints {
+            qp.append(Self::build_factor_constraint_rows(factors));
+        }
+        qp.turnover = constraints
+            .turnover_constraint
+            .as_ref()
+            .map(|turnover| QpTurnover {
+                current_weights: turnover.current_weights.clone(),
+                max_turnover: turnover.max_turnover,
+                scale: None,
+            });
+        qp.tracking_error = constraints
+            .tracking_error_constraint
+            .as_ref()
+            .map(|tracking| QpTrackingError {
+                benchmark: tracking.benchmark.clone(),
+                max_tracking_error: tracking.max_tracking_error,
+                covariance: problem.covariance.clone(),
+                scale: None,
+            });
+        qp
+    }
+
+    /// Rows `L_k <= B_k'w <= U_k` of a factor exposure constraint
+    ///
+    /// Each factor's loadings `B_k` form one sparse row carrying both
+    /// bounds, which OSQP enforces as the two inequalities `B_k'w >= L_k`
+    /// and `B_k'w <= U_k`.
+    fn build_factor_constraint_rows(factors: &FactorExposureConstraint) -> Self {
+        let mut qp = Self::default();
+        for k in 0..factors.lower.len().min(factors.upper.len()) {
+            let loading = factors
+                .factor_loadings
+                .iter()
+                .enumerate()
+                .filter_map(|(i, row)| row.get(k).map(|&b| (i, b)))
+                .filter(|&(_, b)| b != 0.0)
+                .collect();
+            qp.push(loading, factors.lower[k], factors.upper[k]);
+        }
+        qp
+    }
+
+    /// Rows with the turnover limit linearized over auxiliary variables
+    ///
+    /// With trade sizes `t_i` at `first_aux + i`, the `2n` rows
+    /// `t_i >= w_i - w_old_i` and `t_i >= w_old_i - w_i` bound each trade
+    /// and `sum(t) <= T` caps the total. Returns the rows and the number of
+    /// auxiliary variables.
+    fn linearize_turnover(&self, first_aux: usize) -> (Self, usize) {
+        let mut qp = Self {
+            rows: self.rows.clone(),
+            lower: self.lower.clone(),
+            upper: self.upper.clone(),
+            turnover: None,
+            tracking_error: None,
+        };
+        let turnover = match &self.turnover {
+            Some(turnover) => turnover,
+            None => return (qp, 0),
+        };
+
+        // A constant c becomes c κ when homogenized
+        let with_constant = |mut row: Vec<(usize, f64)>, constant: f64| match turnover.scale {
+            Some(k) => {
+                row.push((k, constant));
+                (row, 0.0)
+            }
+            None => (row, -constant),
+        };
+
+        let n = turnover.current_weights.len();
+        for (i, &old) in turnover.current_weights.iter().enumerate() {
+            let t = first_aux + i;
+            let (row, lower) = with_constant(vec![(t, 1.0), (i, -1.0)], old);
+            qp.push(row, lower, f64::INFINITY);
+            let (row, lower) = with_constant(vec![(t, 1.0), (i, 1.0)], -old);
+            qp.push(row, lower, f64::INFINITY);
+        }
+        let total = (0..n).map(|i| (first_aux + i, 1.0)).collect();
+        let (row, upper) = with_constant(total, -turnover.max_turnover);
+        qp.push(row, f64::NEG_INFINITY, upper);
+
+        (qp, n)
+    }
+
+    /// Append the row `lower <= a'x <= upper`
+    fn push(&mut self, row: Vec<(usize, f64)>, lower: f64, upper: f64) {
+        self.rows.push(row);
+        self.lower.push(lower);
+        self.upper.push(upper);
+    }
+
+    /// Append the rows of `other`, leaving the turnover limit as is
+    fn append(&mut self, other: Self) {
+        self.rows.extend(other.rows);
+        self.lower.extend(other.lower);
+        self.upper.extend(other.upper);
+    }
+
+    /// Rewrite each row `l <= a'w <= u` as `l x_k <= a'x <= u x_k`
+    ///
+    /// Equalities become `a'x - u x_k = 0`; each finite side of an
+    /// inequality becomes its own one-sided row.
+    fn homogenized(self, k: usize) -> Self {
+        let mut qp = Self {
+            turnover: self.turnover.map(|turnover| QpTurnover {
+                scale: Some(k),
+                ..turnover
+            }),
+            tracking_error: self.tracking_error.map(|tracking| QpTrackingError {
+                scale: Some(k),
+                ..tracking
+            }),
+            ..Self::default()
+        };
+        for ((row, lower), upper) in self.rows.into_iter().zip(self.lower).zip(self.upper) {
+            let with_scale = |bound: f64| {
+                let mut row = row.clone();
+                row.push((k, -bound));
+                row
+            };
+            if lower == upper {
+                qp.push(with_scale(upper), 0.0, 0.0);
+                continue;
+            }
+            if upper.is_finite() {
+                qp.push(with_scale(upper), f64::NEG_INFINITY, 0.0);
+            }
+            if lower.is_finite() {
+                qp.push(with_scale(lower), 0.0, f64::INFINITY);
+            }
+        }
+        qp
+    }
+}
+
+/// Empirical CVaR of the portfolio return over `scenarios`
+///
+/// The Rockafellar-Uryasev objective at its minimizer, `z` equal to the
+/// value-at-risk: the `ceil(αS)`-th smallest of the `S` scenario losses.
+fn scenario_cvar(scenarios: &[Vec<f64>], weights: &[f64], confidence: f64) -> f64 {
+    let mut losses: Vec<f64> = scenarios
+        .iter()
+        .map(|r| -r.iter().zip(weights).map(|(r, w)| r * w).sum::<f64>())
+        .collect();
+    losses.sort_by(f64::total_cmp);
+
+    let n = losses.len();
+    let index = ((confidence * n as f64).ceil() as usize).clamp(1, n) - 1;
+    let var = losses[index];
+    let excess: f64 = losses.iter().map(|loss| (loss - var).max(0.0)).sum();
+    var + excess / ((1.0 - confidence) * n as f64)
+}
+
+/// Threshold `θ` with `sum max(x_i - θ, 0) = total` for non-negative sizes
+///
+/// Zero when the sizes already sum to at most `total`.
+fn l1_threshold(sizes: impl Iterator<Item = f64>, total: f64) -> f64 {
+    let mut sizes: Vec<f64> = sizes.collect();
+    if sizes.iter().sum::<f64>() <= total {
+        return 0.0;
+    }
+
+    sizes.sort_by(|a, b| b.total_cmp(a));
+    let mut cumulative = 0.0;
+    let mut theta = 0.0;
+    for (k, size) in sizes.iter().enumerate() {
+        cumulative += size;
+        let candidate = (cumulative - total) / (k + 1) as f64;
+        if size - candidate > 0.0 {
+            theta = candidate;
+        }
+    }
+    theta
+}
+
+/// Single-linkage agglomerative clustering on a distance matrix
+///
+/// Returns the dendrogram as the pair of clusters joined at each merge,
+/// closest first. Clusters `0..n` are the assets and merge `k` creates
+/// cluster `n + k`.
+fn single_linkage(distance: &DMatrix<f64>) -> Vec<(usize, usize)> {
+    let n = distance.nrows();
+    let mut dist = distance.clone();
+    let mut ids: Vec<usize> = (0..n).collect();
+    let mut active = vec![true; n];
+    let mut dendrogram = Vec::with_capacity(n.saturating_sub(1));
+
+    for k in 0..n.saturating_sub(1) {
+        let mut best = (0, 0, f64::INFINITY);
+        for a in 0..n {
+            if !active[a] {
+                continue;
+            }
+            for b in (a + 1)..n {
+                if active[b] && dist[(a, b)] < best.2 {
+                    best = (a, b, dist[(a, b)]);
+                }
+            }
+        }
+        let (a, b, _) = best;
+
+        for c in 0..n {
+            let d = dist[(a, c)].min(dist[(b, c)]);
+            dist[(a, c)] = d;
+            dist[(c, a)] = d;
+        }
+        dendrogram.push((ids[a], ids[b]));
+        ids[a] = n + k;
+        active[b] = false;
+    }
+
+    dendrogram
+}
+
+/// Variance of a cluster under inverse-variance weights on its members
+fn cluster_variance(cov: &DMatrix<f64>, members: &[usize]) -> f64 {
+    let block = cov.select_rows(members).select_columns(members);
+    let inverse = DVector::from_iterator(
+        members.len(),
+        members.iter().map(|&i| 1.0 / cov[(i, i)].max(f64::EPSILON)),
+    );
+    let w = &inverse / inverse.sum();
+    w.dot(&(&block * &w))
+}
+
+/// Nonzero upper-triangle entries of a dense symmetric matrix, by column
+fn upper_triangle(matrix: &[Vec<f64>]) -> Vec<Vec<(usize, f64)>> {
+    (0..matrix.len())
+        .map(|j| {
+            (0..=j)
+                .filter(|&i| matrix[i][j] != 0.0)
+                .map(|i| (i, matrix[i][j]))
+                .collect()
+        })
+        .collect()
+}
+
+/// Compressed sparse column matrix from `(row, value)` entries per column
+fn csc_matrix(nrows: usize, columns: &[Vec<(usize, f64)>]) -> CscMatrix<'static> {
+    let mut indptr = Vec::with_capacity(columns.len() + 1);
+    let mut indices = Vec::new();
+    let mut data = Vec::new();
+    indptr.push(0);
+    for column in columns {
+        for &(i, value) in column {
+            indices.push(i);
+            data.push(value);
+        }
+        indptr.push(indices.len());
+    }
+    CscMatrix {
+        nrows,
+        ncols: columns.len(),
+        indptr: Cow::Owned(indptr),
+        indices: Cow::Owned(indices),
+        data: Cow::Owned(data),
+    }
+}
+
+/// Map an OSQP termination status onto [`SolverStatus`]
+fn osqp_status(status: &Status<'_>) -> SolverStatus {
+    match status {
+        Status::Solved(_) => SolverStatus::Optimal,
+        Status::SolvedInaccurate(_) | Status::TimeLimitReached(_) => SolverStatus::SubOptimal,
+        Status::MaxIterationsReached(_) => SolverStatus::MaxIterations,
+        Status::PrimalInfeasible(_) | Status::PrimalInfeasibleInaccurate(_) => {
+            SolverStatus::Infeasible
+        }
+        Status::DualInfeasible(_) | Status::DualInfeasibleInaccurate(_) => SolverStatus::Unbounded,
+        _ => SolverStatus::NumericalError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::{ConstraintSet, LinearConstraint, WeightDensityConstraint};
+
+    fn create_test_problem() -> OptimizationProblem {
+        let returns = vec![0.10, 0.15, 0.12];
+        let cov = vec![
+            vec![0.04, 0.01, 0.02],
+            vec![0.01, 0.09, 0.03],
+            vec![0.02, 0.03, 0.0625],
+        ];
+
+        OptimizationProblem::builder(3)
             .expected_returns(returns)
             .covariance(cov)
             .constraints(ConstraintSet::long_only_full_investment(3))
@@ -496,6 +2478,204 @@ mod tests {
         assert!(result.weights.iter().all(|&w| w >= 0.0));
     }
 
+    #[test]
+    fn test_penalty_exponential_annealing() {
+        let problem = create_test_problem();
+        let config = SolverConfig {
+            eps_abs: 1e-9,
+            penalty_schedule: PenaltySchedule {
+                initial_rho: 1.0,
+                final_rho: 1e8,
+                schedule: AnnealingSchedule::Exponential,
+            },
+            use_fallback: true,
+            ..SolverConfig::default()
+        };
+        let result = QpSolver::new(config).solve(&problem).unwrap();
+
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-8);
+        assert!(result.weights.iter().all(|&w| w >= 0.0));
+    }
+
+    #[test]
+    fn test_penalty_linear_annealing_slower() {
+        let problem = create_test_problem();
+        let config_for = |schedule| SolverConfig {
+            eps_abs: 1e-9,
+            penalty_schedule: PenaltySchedule {
+                initial_rho: 1.0,
+                final_rho: 1e8,
+                schedule,
+            },
+            use_fallback: true,
+            ..SolverConfig::default()
+        };
+
+        let exponential = QpSolver::new(config_for(AnnealingSchedule::Exponential))
+            .solve(&problem)
+            .unwrap();
+        let linear = QpSolver::new(config_for(AnnealingSchedule::Linear))
+            .solve(&problem)
+            .unwrap();
+
+        assert!((linear.weights.iter().sum::<f64>() - 1.0).abs() < 1e-8);
+        assert!(linear.iterations > exponential.iterations);
+    }
+
+    #[test]
+    fn test_convergence_callback_stops_early() {
+        let config = SolverConfig {
+            eps_abs: 1e-12,
+            convergence_callback: Some(Arc::new(|iteration, _, _| iteration < 10)),
+            use_fallback: true,
+            ..SolverConfig::default()
+        };
+        let solver = QpSolver::new(config);
+
+        let problem = create_test_problem();
+        let result = solver.solve(&problem).unwrap();
+        assert!(result.iterations <= 10);
+        assert_eq!(result.status, SolverStatus::SubOptimal);
+
+        let mut mean_variance = problem.clone();
+        mean_variance.objective = ObjectiveType::MeanVariance;
+        let result = solver.solve(&mean_variance).unwrap();
+        assert!(result.iterations <= 10);
+        assert_eq!(result.status, SolverStatus::SubOptimal);
+    }
+
+    #[test]
+    fn test_weight_density_constraint() {
+        let cov = vec![
+            vec![0.04, 0.006, 0.004, 0.002, 0.003],
+            vec![0.006, 0.05, 0.005, 0.004, 0.002],
+            vec![0.004, 0.005, 0.045, 0.003, 0.004],
+            vec![0.002, 0.004, 0.003, 0.055, 0.005],
+            vec![0.003, 0.002, 0.004, 0.005, 0.06],
+        ];
+        let constraints = ConstraintSet::long_only_full_investment(5)
+            .with_weight_density(WeightDensityConstraint::new(0.1, 2));
+        let problem = OptimizationProblem::builder(5)
+            .expected_returns(vec![0.08, 0.09, 0.10, 0.11, 0.12])
+            .covariance(cov)
+            .constraints(constraints)
+            .build()
+            .unwrap();
+
+        // Unconstrained, the diversified portfolio holds all five assets above 10%
+        let mut unconstrained = problem.clone();
+        unconstrained.constraints.density_constraint = None;
+        let baseline = QpSolver::default().solve(&unconstrained).unwrap();
+        assert!(baseline.weights.iter().all(|&w| w > 0.1));
+        assert_eq!(baseline.n_assets_above_threshold, 0);
+
+        let result = QpSolver::default().solve(&problem).unwrap();
+        assert!(result.weights.iter().filter(|&&w| w > 0.1).count() <= 2);
+        assert!(result.n_assets_above_threshold <= 2);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(result.iterations > baseline.iterations);
+    }
+
+    #[test]
+    fn test_risk_free_asset_reaches_tangency_sharpe() {
+        let returns = vec![0.10, 0.15, 0.12];
+        let cov = vec![
+            vec![0.04, 0.01, 0.02],
+            vec![0.01, 0.09, 0.03],
+            vec![0.02, 0.03, 0.0625],
+        ];
+        let rf = 0.05;
+
+        // Tangency Sharpe of the risky assets: sqrt((μ - rf)' Σ^-1 (μ - rf))
+        let excess = nalgebra::DVector::from_iterator(3, returns.iter().map(|r| r - rf));
+        let sigma_inv = vec_to_dmatrix(&cov).unwrap().try_inverse().unwrap();
+        let tangency_sharpe = excess.dot(&(&sigma_inv * &excess)).sqrt();
+
+        let risky_only = OptimizationProblem::builder(3)
+            .expected_returns(returns.clone())
+            .covariance(cov.clone())
+            .objective(ObjectiveType::MaximizeSharpe)
+            .risk_free_rate(rf)
+            .build()
+            .unwrap();
+        let with_cash = OptimizationProblem::builder(3)
+            .expected_returns(returns)
+            .covariance(cov)
+            .objective(ObjectiveType::MaximizeSharpe)
+            .include_risk_free_asset(rf)
+            .build()
+            .unwrap();
+        assert_eq!(with_cash.n_assets, 4);
+        assert_eq!(with_cash.covariance[3], vec![0.0; 4]);
+
+        let solver = QpSolver::default();
+        let risky_result = solver.solve(&risky_only).unwrap();
+        let cash_result = solver.solve(&with_cash).unwrap();
+
+        assert!(cash_result.regularization_applied.is_none());
+        assert!(cash_result.sharpe_ratio >= risky_result.sharpe_ratio - 1e-6);
+        assert!((cash_result.sharpe_ratio - risky_result.sharpe_ratio).abs() < 1e-6);
+
+        // The QP reaches the analytical tangency Sharpe ratio
+        assert!((cash_result.sharpe_ratio - tangency_sharpe).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_penalty_schedule_endpoints() {
+        for schedule in [
+            AnnealingSchedule::Linear,
+            AnnealingSchedule::Exponential,
+            AnnealingSchedule::Geometric,
+        ] {
+            let penalty = PenaltySchedule {
+                initial_rho: 1.0,
+                final_rho: 1e6,
+                schedule,
+            };
+            assert!((penalty.rho(0, 100) - 1.0).abs() < 1e-10);
+            assert!(penalty.rho(50, 100) > 1.0);
+            assert!(penalty.rho(99, 100) < 1e6);
+            assert_eq!(penalty.rho(100, 100), 1e6);
+        }
+    }
+
+    #[test]
+    fn test_ill_conditioned_covariance_regularized() {
+        // Assets 0 and 1 are almost perfectly collinear
+        let cov = vec![
+            vec![0.04, 0.04, 0.02],
+            vec![0.04, 0.04 + 1e-11, 0.02],
+            vec![0.02, 0.02, 0.0625],
+        ];
+        let problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(cov.clone())
+            .build()
+            .unwrap();
+
+        let cov_matrix = vec_to_dmatrix(&cov).unwrap();
+        assert!(condition_number(&cov_matrix) > 1e8);
+
+        let solver = QpSolver::default();
+        let result = solver.solve(&problem).unwrap();
+
+        assert_eq!(result.status, SolverStatus::SubOptimal);
+        let lambda = result.regularization_applied.unwrap();
+        assert!(lambda > 0.0 && lambda <= 1.0);
+        assert!(condition_number(&regularize(&cov_matrix, lambda)) < 1e8);
+
+        assert!(result.weights.iter().all(|w| w.is_finite() && *w >= 0.0));
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!((result.variance - problem.portfolio_variance(&result.weights)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_well_conditioned_not_regularized() {
+        let problem = create_test_problem();
+        let result = QpSolver::default().solve(&problem).unwrap();
+        assert!(result.regularization_applied.is_none());
+    }
+
     #[test]
     fn test_mean_variance() {
         let mut problem = create_test_problem();
@@ -520,4 +2700,647 @@ mod tests {
         assert_eq!(result.status, SolverStatus::Optimal);
         assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_hierarchical_risk_parity() {
+        // Assets 0 and 1 are 0.8 correlated and merge first. Under equal
+        // inverse-variance weights their cluster has variance 0.036 against
+        // 0.04 for asset 2, which gets 0.036 / 0.076 of the portfolio
+        let problem = |constraints: ConstraintSet| {
+            OptimizationProblem::builder(3)
+                .expected_returns(vec![0.08, 0.09, 0.07])
+                .covariance(vec![
+                    vec![0.04, 0.032, 0.0],
+                    vec![0.032, 0.04, 0.0],
+                    vec![0.0, 0.0, 0.04],
+                ])
+                .constraints(constraints)
+                .objective(ObjectiveType::HierarchicalRiskParity)
+                .build()
+                .unwrap()
+        };
+        let distance =
+            DMatrix::from_row_slice(3, 3, &[0.0, 0.2, 1.0, 0.2, 0.0, 1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(single_linkage(&distance), vec![(0, 1), (3, 2)]);
+
+        let solver = QpSolver::default();
+        let result = solver.solve(&problem(ConstraintSet::new())).unwrap();
+        assert_eq!(result.status, SolverStatus::Optimal);
+        let expected = [0.02 / 0.076, 0.02 / 0.076, 0.036 / 0.076];
+        for (w, e) in result.weights.iter().zip(expected) {
+            assert!((w - e).abs() < 1e-12);
+        }
+
+        // Clipping asset 2 at 0.4 hands the excess to the other cluster
+        let capped = ConstraintSet::new().with_box(BoxConstraint::uniform(3, 0.0, 0.4));
+        let result = solver.solve(&problem(capped)).unwrap();
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < solver.config.eps_abs);
+        for (w, e) in result.weights.iter().zip([0.3, 0.3, 0.4]) {
+            assert!((w - e).abs() < 1e-12);
+        }
+
+        let infeasible = ConstraintSet::new().with_box(BoxConstraint::uniform(3, 0.0, 0.3));
+        assert!(matches!(
+            solver.solve(&problem(infeasible)),
+            Err(OptimizerError::Infeasible(_))
+        ));
+
+        // Without correlation HRP reduces to inverse-variance weights
+        let mut uncorrelated = problem(ConstraintSet::new());
+        uncorrelated.covariance = vec![
+            vec![0.01, 0.0, 0.0],
+            vec![0.0, 0.04, 0.0],
+            vec![0.0, 0.0, 0.09],
+        ];
+        let result = solver.solve(&uncorrelated).unwrap();
+        let total = 1.0 / 0.01 + 1.0 / 0.04 + 1.0 / 0.09;
+        for (w, var) in result.weights.iter().zip([0.01, 0.04, 0.09]) {
+            assert!((w - 1.0 / var / total).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_max_diversification() {
+        // For uncorrelated assets the optimum holds w_i ∝ 1/σ_i, where
+        // every asset contributes equally and DR = sqrt(n)
+        let vols = [0.1, 0.2, 0.3];
+        let mut problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.05, 0.08, 0.12])
+            .covariance(vec![
+                vec![0.01, 0.0, 0.0],
+                vec![0.0, 0.04, 0.0],
+                vec![0.0, 0.0, 0.09],
+            ])
+            .objective(ObjectiveType::MaxDiversification)
+            .build()
+            .unwrap();
+
+        let solver = QpSolver::default();
+        let result = solver.solve(&problem).unwrap();
+        let total: f64 = vols.iter().map(|v| 1.0 / v).sum();
+        for (w, v) in result.weights.iter().zip(vols) {
+            assert!((w - 1.0 / v / total).abs() < 1e-4);
+        }
+        let ratio = result.diversification_ratio.unwrap();
+        assert!((ratio - 3.0_f64.sqrt()).abs() < 1e-6);
+
+        // A cap on the first asset costs diversification
+        problem.constraints = ConstraintSet::new().with_box(BoxConstraint::uniform(3, 0.0, 0.5));
+        let capped = solver.solve(&problem).unwrap();
+        assert!((capped.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(capped.weights[0] <= 0.5 + 1e-6);
+        assert!(capped.diversification_ratio.unwrap() < ratio);
+
+        // Other objectives leave the ratio unset
+        problem.objective = ObjectiveType::MinimizeVariance;
+        assert_eq!(solver.solve(&problem).unwrap().diversification_ratio, None);
+    }
+
+    #[test]
+    fn test_cardinality_branch_and_bound() {
+        // Asset 0 is the least volatile and gets the largest unconstrained
+        // weight, but the 0.95-anticorrelated pair 1 + 2 has variance 0.001
+        // against 0.00154 for asset 0 with either of them
+        let mut problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.05, 0.08, 0.08])
+            .covariance(vec![
+                vec![0.0016, 0.0, 0.0],
+                vec![0.0, 0.04, -0.038],
+                vec![0.0, -0.038, 0.04],
+            ])
+            .constraints(
+                ConstraintSet::new()
+                    .with_box(BoxConstraint::long_only(3))
+                    .with_cardinality(CardinalityConstraint::new(2)),
+            )
+            .objective(ObjectiveType::MinimizeVariance)
+            .build()
+            .unwrap();
+
+        let solver = QpSolver::default();
+        let greedy = solver.solve_cardinality_greedy(&problem).unwrap();
+        assert!(greedy.weights[0] > 0.9);
+        assert!((greedy.variance - 1.0 / 650.0).abs() < 1e-6);
+
+        let result = solver.solve(&problem).unwrap();
+        assert_eq!(result.status, SolverStatus::Optimal);
+        assert!(result.weights[0].abs() < 1e-6);
+        assert!((result.weights[1] - 0.5).abs() < 1e-4);
+        assert!((result.variance - 0.001).abs() < 1e-6);
+        assert!(result.iterations > greedy.iterations);
+
+        // Too few nodes to prove optimality falls back on the greedy portfolio
+        let limited = QpSolver::new(SolverConfig {
+            cardinality_node_limit: 1,
+            ..SolverConfig::default()
+        });
+        let result = limited.solve(&problem).unwrap();
+        assert_eq!(result.status, SolverStatus::SubOptimal);
+        assert!((result.variance - greedy.variance).abs() < 1e-9);
+
+        // A minimum holding of 0.1 lifts the greedy hedge position
+        problem.constraints.cardinality_constraint =
+            Some(CardinalityConstraint::new(2).with_min_weight_if_held(0.1));
+        let greedy = solver.solve_cardinality_greedy(&problem).unwrap();
+        let cardinality = problem.constraints.cardinality_constraint.as_ref().unwrap();
+        assert!(cardinality.is_satisfied(&greedy.weights));
+        assert!((greedy.weights[0] - 0.9).abs() < 1e-4);
+        let result = solver.solve(&problem).unwrap();
+        assert!(cardinality.is_satisfied(&result.weights));
+        assert!((result.variance - 0.001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_projection_respects_sector_limits() {
+        // Assets 0 and 1 (sector 0) have the highest returns
+        let sectors = vec![0, 0, 1, 1];
+        let constraints = ConstraintSet::new()
+            .with_box(BoxConstraint::long_only(4))
+            .with_linear(LinearConstraint::full_investment(4))
+            .with_linear(LinearConstraint::sector_exposure(&sectors, 2, 0.6));
+
+        let problem = OptimizationProblem::builder(4)
+            .expected_returns(vec![0.20, 0.18, 0.05, 0.04])
+            .covariance(vec![
+                vec![0.04, 0.0, 0.0, 0.0],
+                vec![0.0, 0.04, 0.0, 0.0],
+                vec![0.0, 0.0, 0.04, 0.0],
+                vec![0.0, 0.0, 0.0, 0.04],
+            ])
+            .constraints(constraints)
+            .objective(ObjectiveType::MeanVariance)
+            .build()
+            .unwrap();
+
+        let result = QpSolver::default().solve(&problem).unwrap();
+        let sector_limit = &problem.constraints.linear_constraints[1];
+        let exposures = sector_limit.evaluate(&result.weights);
+
+        assert!(exposures[0] <= 0.6 + 1e-6);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(result.weights.iter().all(|&w| w >= 0.0));
+    }
+
+    #[test]
+    fn test_initialization_strategies() {
+        // Lower bounds sum to 0.8; the minimum variance portfolio is
+        // proportional to them, far from equal weight
+        let lower = vec![0.32, 0.24, 0.12, 0.08, 0.04];
+        let cov: Vec<Vec<f64>> = (0..5)
+            .map(|i| {
+                let mut row = vec![0.0; 5];
+                row[i] = 0.008 / lower[i];
+                row
+            })
+            .collect();
+        let problem = OptimizationProblem::builder(5)
+            .expected_returns(vec![0.05, 0.06, 0.07, 0.08, 0.09])
+            .covariance(cov)
+            .constraints(
+                ConstraintSet::new()
+                    .with_box(BoxConstraint::new(lower, vec![0.6; 5]))
+                    .with_linear(LinearConstraint::full_investment(5)),
+            )
+            .build()
+            .unwrap();
+
+        let solve = |initialization| {
+            QpSolver::new(SolverConfig {
+                initialization,
+                penalty_schedule: PenaltySchedule {
+                    initial_rho: 1e8,
+                    final_rho: 1e8,
+                    schedule: AnnealingSchedule::Linear,
+                },
+                use_fallback: true,
+                ..Default::default()
+            })
+            .solve(&problem)
+            .unwrap()
+        };
+        let equal = solve(InitializationStrategy::EqualWeight);
+        let min_lower = solve(InitializationStrategy::MinLower);
+        assert_eq!(min_lower.status, SolverStatus::Optimal);
+        assert!(min_lower.iterations < equal.iterations);
+        for (a, b) in equal.weights.iter().zip(min_lower.weights.iter()) {
+            assert!((a - b).abs() < 1e-4);
+        }
+
+        // Max return spends the budget left above the lower bounds on the best asset
+        let solver = QpSolver::new(SolverConfig {
+            initialization: InitializationStrategy::MaxReturn,
+            ..Default::default()
+        });
+        let start = solver.initial_weights(&problem).unwrap();
+        assert!((start[4] - 0.24).abs() < 1e-12);
+        assert!((start[0] - 0.32).abs() < 1e-12);
+        assert!((start.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let custom = QpSolver::new(SolverConfig {
+            initialization: InitializationStrategy::Custom(vec![0.5; 3]),
+            ..Default::default()
+        });
+        assert!(matches!(
+            custom.solve(&problem),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 5,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_max_sharpe_asymmetric_rf() {
+        let problem = create_test_problem();
+        let solver = QpSolver::default();
+
+        // Equal rates give a single tangency point
+        let symmetric = solver
+            .solve_max_sharpe_asymmetric_rf(&problem, 0.03, 0.03)
+            .unwrap();
+        assert_eq!(symmetric.len(), 2);
+        for (a, b) in symmetric[0].weights.iter().zip(symmetric[1].weights.iter()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+
+        // A higher borrowing rate moves the leveraged tangency up the frontier
+        let asymmetric = solver
+            .solve_max_sharpe_asymmetric_rf(&problem, 0.02, 0.08)
+            .unwrap();
+        assert_eq!(asymmetric.len(), 2);
+        let (lend, borrow) = (&asymmetric[0], &asymmetric[1]);
+        assert!(borrow.expected_return > lend.expected_return);
+        assert!(borrow.volatility > lend.volatility);
+        assert!((lend.sharpe_ratio - problem_sharpe(&problem, &lend.weights, 0.02)).abs() < 1e-12);
+
+        // Borrowing above every asset's return never pays
+        let expensive = solver
+            .solve_max_sharpe_asymmetric_rf(&problem, 0.02, 0.2)
+            .unwrap();
+        assert_eq!(expensive.len(), 1);
+
+        assert!(solver
+            .solve_max_sharpe_asymmetric_rf(&problem, 0.05, 0.02)
+            .is_err());
+    }
+
+    fn problem_sharpe(problem: &OptimizationProblem, weights: &[f64], rf: f64) -> f64 {
+        (problem.portfolio_return(weights) - rf) / problem.portfolio_variance(weights).sqrt()
+    }
+
+    #[test]
+    fn test_gradient_clipping_prevents_divergence() {
+        use std::sync::Mutex;
+
+        // Condition number 1e6, no box constraints to contain the iterates
+        let problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.05, 0.08, 0.10])
+            .covariance(vec![
+                vec![1e4, 0.0, 0.0],
+                vec![0.0, 10.0, 0.0],
+                vec![0.0, 0.0, 1e-2],
+            ])
+            .constraints(ConstraintSet::new().with_linear(LinearConstraint::full_investment(3)))
+            .objective(ObjectiveType::MeanVariance)
+            .build()
+            .unwrap();
+
+        let largest_weight = |gradient_clip| {
+            let largest = Arc::new(Mutex::new(0.0_f64));
+            let sink = Arc::clone(&largest);
+            let solver = QpSolver::new(SolverConfig {
+                max_iterations: 100,
+                gradient_clip,
+                convergence_callback: Some(Arc::new(move |_, weights: &[f64], _| {
+                    let mut largest = sink.lock().unwrap();
+                    for w in weights {
+                        *largest = largest.max(w.abs());
+                    }
+                    true
+                })),
+                use_fallback: true,
+                ..Default::default()
+            });
+            let _ = solver.solve(&problem);
+            let value = *largest.lock().unwrap();
+            value
+        };
+
+        // Unclipped steps on the stiff asset overshoot by a factor of ~100
+        assert!(largest_weight(None) > 50.0);
+        assert!(largest_weight(Some(1.0)) < 1.0);
+
+        let normalizing = QpSolver::new(SolverConfig {
+            gradient_normalize: true,
+            gradient_clip: Some(0.5),
+            ..Default::default()
+        });
+        let mut gradient = vec![3.0, 4.0];
+        normalizing.condition_gradient(&mut gradient);
+        assert!((gradient[0] - 0.3).abs() < 1e-12 && (gradient[1] - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_projection_respects_factor_bounds() {
+        // Factor 1 (market beta) is highest for the highest-return assets
+        let loadings = vec![
+            vec![0.6, 0.2],
+            vec![0.8, -0.1],
+            vec![1.0, 0.4],
+            vec![1.3, 0.0],
+            vec![1.6, -0.3],
+        ];
+        let constraints = ConstraintSet::long_only_full_investment(5).with_factor_exposure(
+            FactorExposureConstraint::new(
+                loadings.clone(),
+                vec![0.0, -0.2],
+                vec![0.9, 0.2],
+                vec!["beta".to_string(), "value".to_string()],
+            ),
+        );
+        let problem = OptimizationProblem::builder(5)
+            .expected_returns(vec![0.04, 0.06, 0.08, 0.10, 0.14])
+            .covariance(vec![
+                vec![0.02, 0.0, 0.0, 0.0, 0.0],
+                vec![0.0, 0.03, 0.0, 0.0, 0.0],
+                vec![0.0, 0.0, 0.04, 0.0, 0.0],
+                vec![0.0, 0.0, 0.0, 0.05, 0.0],
+                vec![0.0, 0.0, 0.0, 0.0, 0.06],
+            ])
+            .constraints(constraints)
+            .objective(ObjectiveType::MeanVariance)
+            .build()
+            .unwrap();
+
+        let result = QpSolver::default().solve(&problem).unwrap();
+        let exposure = |k: usize| -> f64 {
+            result
+                .weights
+                .iter()
+                .zip(&loadings)
+                .map(|(w, b)| w * b[k])
+                .sum()
+        };
+
+        // The beta cap binds: unconstrained, the optimizer would load up on asset 4
+        assert!(exposure(0) <= 0.9 + 1e-6 && exposure(0) > 0.85);
+        assert!(exposure(1) >= -0.2 - 1e-6 && exposure(1) <= 0.2 + 1e-6);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(result.weights.iter().all(|&w| w >= -1e-12));
+    }
+
+    #[test]
+    fn test_beta_neutral_constraint() {
+        // Two negative-beta assets make a long-only, fully invested
+        // portfolio with near-zero market exposure possible
+        let betas = [1.1, 0.9, 1.0, -0.4, -0.8];
+        let loadings: Vec<Vec<f64>> = betas.iter().map(|&b| vec![b]).collect();
+        let constraints = ConstraintSet::long_only_full_investment(5).with_factor_exposure(
+            FactorExposureConstraint::new(
+                loadings,
+                vec![-0.05],
+                vec![0.05],
+                vec!["market".to_string()],
+            ),
+        );
+        let fallback = QpSolver::new(SolverConfig {
+            use_fallback: true,
+            ..Default::default()
+        });
+
+        for objective in [ObjectiveType::MinimizeVariance, ObjectiveType::MeanVariance] {
+            let problem = OptimizationProblem::builder(5)
+                .expected_returns(vec![0.08, 0.07, 0.09, 0.03, 0.02])
+                .covariance(vec![
+                    vec![0.040, 0.010, 0.012, 0.0, 0.0],
+                    vec![0.010, 0.030, 0.009, 0.0, 0.0],
+                    vec![0.012, 0.009, 0.035, 0.0, 0.0],
+                    vec![0.0, 0.0, 0.0, 0.050, 0.004],
+                    vec![0.0, 0.0, 0.0, 0.004, 0.060],
+                ])
+                .constraints(constraints.clone())
+                .objective(objective.clone())
+                .build()
+                .unwrap();
+
+            for solver in [&QpSolver::default(), &fallback] {
+                let result = solver.solve(&problem).unwrap();
+                let beta: f64 = result.weights.iter().zip(&betas).map(|(w, b)| w * b).sum();
+                assert!(beta.abs() <= 0.05 + 1e-5, "{:?}: beta {}", objective, beta);
+                assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-5);
+                assert!(result.weights.iter().all(|&w| w >= -1e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn test_osqp_large_max_sharpe_matches_tangency() {
+        // 200 assets on a one-factor covariance, budget constraint only, so
+        // the tangency portfolio is Σ^-1 (μ - rf) / 1'Σ^-1 (μ - rf)
+        let n = 200;
+        let betas: Vec<f64> = (0..n).map(|i| 0.6 + 0.8 * (i as f64 / n as f64)).collect();
+        let cov: Vec<Vec<f64>> = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| {
+                        let specific = if i == j {
+                            0.01 + 0.0002 * (i % 7) as f64
+                        } else {
+                            0.0
+                        };
+                        0.03 * betas[i] * betas[j] + specific
+                    })
+                    .collect()
+            })
+            .collect();
+        let returns: Vec<f64> = (0..n)
+            .map(|i| 0.03 + 0.05 * betas[i] + 0.002 * ((i * 37 % 11) as f64 - 5.0))
+            .collect();
+        let rf = 0.02;
+
+        let problem = OptimizationProblem::builder(n)
+            .expected_returns(returns.clone())
+            .covariance(cov.clone())
+            .constraints(ConstraintSet::new().with_linear(LinearConstraint::full_investment(n)))
+            .objective(ObjectiveType::MaximizeSharpe)
+            .risk_free_rate(rf)
+            .build()
+            .unwrap();
+        let result = QpSolver::default().solve(&problem).unwrap();
+        assert_eq!(result.status, SolverStatus::Optimal);
+
+        let excess = nalgebra::DVector::from_iterator(n, returns.iter().map(|r| r - rf));
+        let direction = vec_to_dmatrix(&cov).unwrap().lu().solve(&excess).unwrap();
+        let tangency = &direction / direction.sum();
+        for (w, t) in result.weights.iter().zip(tangency.iter()) {
+            assert!((w - t).abs() < 1e-4);
+        }
+        let tangency_sharpe = excess.dot(&direction).sqrt();
+        assert!((result.sharpe_ratio - tangency_sharpe).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_osqp_min_variance_and_infeasible_bounds() {
+        // Budget only: w = Σ^-1 1 / 1'Σ^-1 1
+        let mut problem = create_test_problem();
+        problem.constraints = ConstraintSet::new();
+        let result = QpSolver::default().solve(&problem).unwrap();
+        let sigma_inv = vec_to_dmatrix(&problem.covariance)
+            .unwrap()
+            .try_inverse()
+            .unwrap();
+        let direction = sigma_inv * nalgebra::DVector::from_element(3, 1.0);
+        for (w, d) in result.weights.iter().zip(direction.iter()) {
+            assert!((w - d / direction.sum()).abs() < 1e-6);
+        }
+
+        // Matches the gradient-descent fallback on the long-only problem
+        let problem = create_test_problem();
+        let osqp = QpSolver::default().solve(&problem).unwrap();
+        let fallback = QpSolver::new(SolverConfig {
+            use_fallback: true,
+            ..Default::default()
+        })
+        .solve(&problem)
+        .unwrap();
+        for (a, b) in osqp.weights.iter().zip(fallback.weights.iter()) {
+            assert!((a - b).abs() < 1e-4);
+        }
+
+        // Lower bounds summing past the budget cannot be met
+        let mut infeasible = create_test_problem();
+        infeasible.constraints = ConstraintSet::new().with_box(BoxConstraint::uniform(3, 0.4, 1.0));
+        assert!(matches!(
+            QpSolver::default().solve(&infeasible),
+            Err(OptimizerError::Infeasible(_))
+        ));
+    }
+
+    #[test]
+    fn test_efficient_frontier() {
+        let problem = create_test_problem();
+        let solver = QpSolver::default();
+        let frontier = solver.compute_efficient_frontier(&problem, 8).unwrap();
+        assert_eq!(frontier.len(), 8);
+
+        // Starts at the minimum-variance portfolio, ends fully in the best asset
+        let mut min_variance = problem.clone();
+        min_variance.objective = ObjectiveType::MinimizeVariance;
+        let start = solver.solve(&min_variance).unwrap();
+        assert!((frontier[0].variance - start.variance).abs() < 1e-9);
+        assert!((frontier[7].expected_return - 0.15).abs() < 1e-6);
+        assert!((frontier[7].weights[1] - 1.0).abs() < 1e-6);
+
+        for pair in frontier.windows(2) {
+            assert!(pair[1].expected_return > pair[0].expected_return);
+            assert!(pair[1].volatility >= pair[0].volatility - 1e-9);
+        }
+        for point in &frontier {
+            assert!((point.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+            assert!(point.weights.iter().all(|&w| w >= -1e-6));
+        }
+
+        // Identical returns leave nothing to trade off
+        let mut flat = problem.clone();
+        flat.expected_returns = vec![0.1; 3];
+        let single = solver.compute_efficient_frontier(&flat, 8).unwrap();
+        assert_eq!(single.len(), 1);
+
+        assert!(matches!(
+            solver.compute_efficient_frontier(&problem, 1),
+            Err(OptimizerError::InvalidInput(_))
+        ));
+
+        // Unlimited short sales make the maximum return unbounded
+        let mut unbounded = problem.clone();
+        unbounded.constraints =
+            ConstraintSet::new().with_linear(LinearConstraint::full_investment(3));
+        assert!(matches!(
+            solver.compute_efficient_frontier(&unbounded, 8),
+            Err(OptimizerError::SolverFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_min_cvar_historical_scenarios() {
+        // Assets 0 and 1 hedge each other exactly at 60/40; asset 2 pays
+        // 1% except for two crashes
+        let scenarios: Vec<Vec<f64>> = (0..100)
+            .map(|k| {
+                let cycle = ((k * 37) % 100) as f64 / 100.0 - 0.5;
+                let crash = if k % 50 == 7 { -0.30 } else { 0.01 };
+                vec![0.004 + 0.02 * cycle, 0.006 - 0.03 * cycle, crash]
+            })
+            .collect();
+        let cov = vec![
+            vec![0.0004, -0.0003, 0.0],
+            vec![-0.0003, 0.0009, 0.0],
+            vec![0.0, 0.0, 0.0018],
+        ];
+        let problem = OptimizationProblem::builder(3)
+            .expected_returns(vec![0.004, 0.006, 0.0042])
+            .covariance(cov)
+            .scenarios(scenarios.clone())
+            .objective(ObjectiveType::MinimizeCVaR {
+                confidence: 0.95,
+                n_scenarios: 0,
+            })
+            .build()
+            .unwrap();
+
+        let result = QpSolver::default().solve(&problem).unwrap();
+        let cvar = result.cvar.unwrap();
+        assert!((cvar - scenario_cvar(&scenarios, &result.weights, 0.95)).abs() < 1e-12);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!(result.weights.iter().all(|&w| w >= -1e-6));
+
+        // The hedged pair locks in 0.48% in every scenario
+        assert!((result.weights[0] - 0.6).abs() < 1e-3);
+        assert!((result.weights[1] - 0.4).abs() < 1e-3);
+        assert!((cvar + 0.0048).abs() < 1e-4);
+        for weights in [[1.0 / 3.0; 3], [0.5, 0.5, 0.0], [0.0, 0.0, 1.0]] {
+            assert!(cvar < scenario_cvar(&scenarios, &weights, 0.95));
+        }
+
+        let mut bad_confidence = problem.clone();
+        bad_confidence.objective = ObjectiveType::MinimizeCVaR {
+            confidence: 1.0,
+            n_scenarios: 0,
+        };
+        assert!(matches!(
+            QpSolver::default().solve(&bad_confidence),
+            Err(OptimizerError::InvalidInput(_))
+        ));
+        let mut short_scenario = problem.clone();
+        short_scenario.scenarios = Some(vec![vec![0.01, 0.02]]);
+        assert!(matches!(
+            short_scenario.validate(),
+            Err(OptimizerError::DimensionMismatch {
+                expected: 3,
+                got: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_min_cvar_normal_scenarios() {
+        // Without scenarios, returns are drawn from N(μ, Σ); for normal
+        // returns CVaR = -μ'w + σ_p φ(Φ^-1(α)) / (1 - α)
+        let mut problem = create_test_problem();
+        problem.objective = ObjectiveType::MinimizeCVaR {
+            confidence: 0.9,
+            n_scenarios: 500,
+        };
+        let result = QpSolver::default().solve(&problem).unwrap();
+        let cvar = result.cvar.unwrap();
+
+        let z: f64 = 1.2815515655446004;
+        let density = (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        let normal_cvar = -result.expected_return + result.volatility * density / 0.1;
+        assert!((cvar - normal_cvar).abs() < 0.1 * normal_cvar);
+
+        // The same seed gives the same portfolio
+        let again = QpSolver::default().solve(&problem).unwrap();
+        assert_eq!(again.cvar, result.cvar);
+    }
 }
@@ -0,0 +1,136 @@
+//! Dynamic allocation strategies
+//!
+//! Rules that split a portfolio between a risky sleeve (e.g., optimized
+//! weights) and a risk-free asset as the portfolio value evolves.
+
+use serde::{Deserialize, Serialize};
+
+/// Constant Proportion Portfolio Insurance (Black & Jones, 1987)
+///
+/// Exposure to the risky sleeve is `multiplier` times the cushion above the
+/// floor, capped at the portfolio value (no leverage); the rest is held in
+/// cash. Rebalancing every period keeps the portfolio above the floor as
+/// long as no single-period risky return falls below `-1 / multiplier`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CppiStrategy {
+    /// Exposure multiple of the cushion
+    pub multiplier: f64,
+    /// Protected portfolio value
+    pub floor: f64,
+    /// Per-period return on cash
+    pub risk_free_rate: f64,
+}
+
+impl CppiStrategy {
+    /// Create a CPPI strategy
+    pub fn new(multiplier: f64, floor: f64, risk_free_rate: f64) -> Self {
+        Self {
+            multiplier,
+            floor,
+            risk_free_rate,
+        }
+    }
+
+    /// Split `portfolio_value` into risky positions and cash
+    ///
+    /// The risky allocation `min(multiplier * cushion, portfolio_value)` is
+    /// distributed proportionally to `risky_weights` (normalized to sum to
+    /// one). Returns position values for each risky asset followed by the
+    /// cash position. A cushion at or below zero is held entirely in cash.
+    pub fn allocate(&self, portfolio_value: f64, risky_weights: &[f64]) -> Vec<f64> {
+        let cushion = (portfolio_value - self.floor).max(0.0);
+        let weight_sum: f64 = risky_weights.iter().sum();
+
+        let (risky, scale) = if weight_sum > 0.0 {
+            let risky = (self.multiplier * cushion).min(portfolio_value).max(0.0);
+            (risky, risky / weight_sum)
+        } else {
+            (0.0, 0.0)
+        };
+
+        let mut allocation: Vec<f64> = risky_weights.iter().map(|w| w * scale).collect();
+        allocation.push(portfolio_value - risky);
+        allocation
+    }
+
+    /// Simulate the portfolio value path, rebalancing every period
+    ///
+    /// Each element of `returns_path` holds one period's returns of the
+    /// risky assets, which are equally weighted within the risky sleeve.
+    /// The returned path starts with `initial_value` and has one value per
+    /// period after it.
+    pub fn simulate(&self, initial_value: f64, returns_path: &[Vec<f64>]) -> Vec<f64> {
+        let mut path = Vec::with_capacity(returns_path.len() + 1);
+        let mut value = initial_value;
+        path.push(value);
+
+        for returns in returns_path {
+            let allocation = self.allocate(value, &vec![1.0; returns.len()]);
+            let cash = allocation[returns.len()];
+
+            value = allocation
+                .iter()
+                .zip(returns)
+                .map(|(position, r)| position * (1.0 + r))
+                .sum::<f64>()
+                + cash * (1.0 + self.risk_free_rate);
+            path.push(value);
+        }
+
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_allocate() {
+        let cppi = CppiStrategy::new(3.0, 80.0, 0.0);
+
+        // Cushion 20, risky 60 split 2:1
+        let allocation = cppi.allocate(100.0, &[0.5, 0.25]);
+        assert!((allocation[0] - 40.0).abs() < 1e-12);
+        assert!((allocation[1] - 20.0).abs() < 1e-12);
+        assert!((allocation[2] - 40.0).abs() < 1e-12);
+
+        // Exposure is capped at the portfolio value
+        let capped = cppi.allocate(200.0, &[1.0]);
+        assert_eq!(capped, vec![200.0, 0.0]);
+
+        // Below the floor everything is in cash
+        let defensive = cppi.allocate(70.0, &[0.5, 0.5]);
+        assert_eq!(defensive, vec![0.0, 0.0, 70.0]);
+    }
+
+    #[test]
+    fn test_simulate_stays_above_floor() {
+        let cppi = CppiStrategy::new(4.0, 90.0, 0.0001);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Daily returns with occasional crashes, all above -1 / multiplier
+        let returns_path: Vec<Vec<f64>> = (0..500)
+            .map(|t| {
+                (0..3)
+                    .map(|_| {
+                        if t % 97 == 50 {
+                            -0.2
+                        } else {
+                            rng.gen_range(-0.04..0.04)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let path = cppi.simulate(100.0, &returns_path);
+        assert_eq!(path.len(), 501);
+        assert_eq!(path[0], 100.0);
+
+        let epsilon = 1e-9;
+        assert!(path.iter().all(|&v| v >= cppi.floor * (1.0 - epsilon)));
+    }
+}
@@ -0,0 +1,343 @@
+//! Solver hyperparameter tuning
+//!
+//! Searches `SolverConfig` settings for a given problem, either exhaustively
+//! over a parameter grid or with Bayesian optimization using a Gaussian
+//! process surrogate and expected improvement.
+
+use std::collections::HashMap;
+
+use covariance::distribution::{normal_cdf, normal_pdf};
+use nalgebra::{DMatrix, DVector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::problem::{OptimizationProblem, OptimizationResult};
+use crate::solver::{QpSolver, SolverConfig};
+use crate::{OptimizerError, Result};
+
+/// Results violating the budget by more than this are never selected
+const BUDGET_TOLERANCE: f64 = 1e-4;
+
+/// Random initial trials before the surrogate drives the search
+const N_INITIAL_TRIALS: u32 = 3;
+
+/// Random candidates scored by the acquisition function per trial
+const N_CANDIDATES: usize = 256;
+
+/// RBF kernel length scale in the unit-cube search space
+const KERNEL_LENGTH_SCALE: f64 = 0.2;
+
+/// Observation noise added to the kernel diagonal
+const KERNEL_NOISE: f64 = 1e-6;
+
+/// Bayesian search space: (parameter, log10 lower bound, log10 upper bound)
+///
+/// The penalty schedule only drives the gradient fallback, so it is left out.
+const BAYESIAN_SEARCH_SPACE: [(&str, f64, f64); 1] = [("eps_abs", -10.0, -3.0)];
+
+/// Objective used to rank solver configurations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TuningMetric {
+    /// Fewest solver iterations
+    MinIterations,
+    /// Highest Sharpe ratio
+    MaxSharpe,
+    /// Lowest portfolio variance
+    MinVariance,
+}
+
+impl TuningMetric {
+    /// Score a result (lower is better)
+    fn score(&self, result: &OptimizationResult) -> f64 {
+        let budget_violation = (result.weights.iter().sum::<f64>() - 1.0).abs();
+        if budget_violation > BUDGET_TOLERANCE {
+            return f64::INFINITY;
+        }
+
+        match self {
+            TuningMetric::MinIterations => result.iterations as f64,
+            TuningMetric::MaxSharpe => -result.sharpe_ratio,
+            TuningMetric::MinVariance => result.variance,
+        }
+    }
+}
+
+/// Hyperparameter tuner for `QpSolver`
+pub struct HyperparameterTuner<'a> {
+    problem: &'a OptimizationProblem,
+    metric: TuningMetric,
+}
+
+impl<'a> HyperparameterTuner<'a> {
+    /// Create a tuner for a problem
+    pub fn new(problem: &'a OptimizationProblem, metric: TuningMetric) -> Self {
+        Self { problem, metric }
+    }
+
+    /// Exhaustive search over the Cartesian product of parameter values
+    ///
+    /// Supported parameters: `eps_abs`, `eps_rel`, `max_iterations`,
+    /// `max_condition_number`, `initial_rho` and `final_rho`. Unspecified
+    /// parameters keep their default values.
+    pub fn tune_grid(
+        &self,
+        parameter_grid: HashMap<String, Vec<f64>>,
+    ) -> Result<(SolverConfig, OptimizationResult)> {
+        // Sort names so the search order (and tie-breaking) is deterministic
+        let mut names: Vec<&String> = parameter_grid.keys().collect();
+        names.sort();
+
+        let mut configs = vec![SolverConfig::default()];
+        for name in names {
+            let values = &parameter_grid[name];
+            if values.is_empty() {
+                return Err(OptimizerError::InvalidInput(format!(
+                    "No values for parameter {}",
+                    name
+                )));
+            }
+
+            let mut expanded = Vec::with_capacity(configs.len() * values.len());
+            for config in &configs {
+                for &value in values {
+                    let mut config = config.clone();
+                    set_parameter(&mut config, name, value)?;
+                    expanded.push(config);
+                }
+            }
+            configs = expanded;
+        }
+
+        let mut best: Option<(f64, SolverConfig, OptimizationResult)> = None;
+        for config in configs {
+            let (score, result) = self.evaluate(&config)?;
+            if best.as_ref().is_none_or(|(s, _, _)| score < *s) {
+                best = Some((score, config, result));
+            }
+        }
+
+        best.map(|(_, config, result)| (config, result))
+            .ok_or_else(|| OptimizerError::InvalidInput("Empty parameter grid".to_string()))
+    }
+
+    /// Bayesian optimization over `eps_abs`
+    ///
+    /// The tolerance is searched on a log scale. After a few random
+    /// trials, each new configuration maximizes expected improvement under a
+    /// Gaussian process fitted to the scores observed so far.
+    pub fn tune_bayesian(
+        &self,
+        n_trials: u32,
+        seed: u64,
+    ) -> Result<(SolverConfig, OptimizationResult)> {
+        if n_trials == 0 {
+            return Err(OptimizerError::InvalidInput(
+                "n_trials must be positive".to_string(),
+            ));
+        }
+
+        let dim = BAYESIAN_SEARCH_SPACE.len();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut points: Vec<Vec<f64>> = Vec::new();
+        let mut scores: Vec<f64> = Vec::new();
+        let mut best: Option<(f64, SolverConfig, OptimizationResult)> = None;
+
+        for trial in 0..n_trials {
+            let point = if trial < N_INITIAL_TRIALS {
+                (0..dim).map(|_| rng.gen::<f64>()).collect()
+            } else {
+                let mut candidates: Vec<Vec<f64>> = (0..N_CANDIDATES)
+                    .map(|_| (0..dim).map(|_| rng.gen::<f64>()).collect())
+                    .collect();
+                match GaussianProcess::fit(&points, &scores) {
+                    Some(gp) => gp.argmax_expected_improvement(candidates),
+                    None => candidates.swap_remove(0),
+                }
+            };
+
+            let config = config_from_unit_point(&point)?;
+            let (score, result) = self.evaluate(&config)?;
+
+            points.push(point);
+            scores.push(score);
+            if best.as_ref().is_none_or(|(s, _, _)| score < *s) {
+                best = Some((score, config, result));
+            }
+        }
+
+        best.map(|(_, config, result)| (config, result))
+            .ok_or_else(|| OptimizerError::SolverFailed("No trials evaluated".to_string()))
+    }
+
+    /// Solve with a configuration and score the result
+    fn evaluate(&self, config: &SolverConfig) -> Result<(f64, OptimizationResult)> {
+        let result = QpSolver::new(config.clone()).solve(self.problem)?;
+        Ok((self.metric.score(&result), result))
+    }
+}
+
+/// Set a named solver parameter
+fn set_parameter(config: &mut SolverConfig, name: &str, value: f64) -> Result<()> {
+    match name {
+        "eps_abs" => config.eps_abs = value,
+        "eps_rel" => config.eps_rel = value,
+        "max_iterations" => config.max_iterations = value as u32,
+        "max_condition_number" => config.max_condition_number = value,
+        "initial_rho" => config.penalty_schedule.initial_rho = value,
+        "final_rho" => config.penalty_schedule.final_rho = value,
+        _ => {
+            return Err(OptimizerError::InvalidInput(format!(
+                "Unknown solver parameter: {}",
+                name
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Map a point in the unit cube to a configuration on the log-scale search space
+fn config_from_unit_point(point: &[f64]) -> Result<SolverConfig> {
+    let mut config = SolverConfig::default();
+    for (&x, &(name, lo, hi)) in point.iter().zip(BAYESIAN_SEARCH_SPACE.iter()) {
+        set_parameter(&mut config, name, 10f64.powf(lo + x * (hi - lo)))?;
+    }
+    Ok(config)
+}
+
+/// Gaussian process regression with an RBF kernel on standardized targets
+struct GaussianProcess {
+    points: Vec<Vec<f64>>,
+    alpha: DVector<f64>,
+    chol: nalgebra::Cholesky<f64, nalgebra::Dyn>,
+    best: f64,
+}
+
+impl GaussianProcess {
+    /// Fit to finite observations; `None` if too few remain
+    fn fit(points: &[Vec<f64>], scores: &[f64]) -> Option<Self> {
+        let (points, values): (Vec<Vec<f64>>, Vec<f64>) = points
+            .iter()
+            .zip(scores)
+            .filter(|(_, s)| s.is_finite())
+            .map(|(p, &s)| (p.clone(), s))
+            .unzip();
+        if values.len() < 2 {
+            return None;
+        }
+
+        let n = values.len();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64).sqrt();
+        let scale = if std > 0.0 { std } else { 1.0 };
+        let y = DVector::from_iterator(n, values.iter().map(|v| (v - mean) / scale));
+
+        let k = DMatrix::from_fn(n, n, |i, j| {
+            rbf(&points[i], &points[j]) + if i == j { KERNEL_NOISE } else { 0.0 }
+        });
+        let chol = k.cholesky()?;
+        let alpha = chol.solve(&y);
+        let best = y.min();
+
+        Some(Self {
+            points,
+            alpha,
+            chol,
+            best,
+        })
+    }
+
+    /// Posterior mean and standard deviation at `x`
+    fn predict(&self, x: &[f64]) -> (f64, f64) {
+        let k_star =
+            DVector::from_iterator(self.points.len(), self.points.iter().map(|p| rbf(p, x)));
+        let mean = k_star.dot(&self.alpha);
+        let v = self.chol.solve(&k_star);
+        let variance = (1.0 - k_star.dot(&v)).max(0.0);
+        (mean, variance.sqrt())
+    }
+
+    /// Expected improvement below the best observation (minimization)
+    fn expected_improvement(&self, x: &[f64]) -> f64 {
+        let (mean, std) = self.predict(x);
+        if std < 1e-12 {
+            return (self.best - mean).max(0.0);
+        }
+        let z = (self.best - mean) / std;
+        (self.best - mean) * normal_cdf(z) + std * normal_pdf(z)
+    }
+
+    fn argmax_expected_improvement(&self, candidates: Vec<Vec<f64>>) -> Vec<f64> {
+        candidates
+            .into_iter()
+            .map(|c| (self.expected_improvement(&c), c))
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, c)| c)
+            .unwrap_or_default()
+    }
+}
+
+/// Squared-exponential kernel with unit signal variance
+fn rbf(a: &[f64], b: &[f64]) -> f64 {
+    let dist_sq: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-dist_sq / (2.0 * KERNEL_LENGTH_SCALE * KERNEL_LENGTH_SCALE)).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::ConstraintSet;
+
+    fn create_test_problem() -> OptimizationProblem {
+        OptimizationProblem::builder(3)
+            .expected_returns(vec![0.10, 0.15, 0.12])
+            .covariance(vec![
+                vec![0.04, 0.01, 0.02],
+                vec![0.01, 0.09, 0.03],
+                vec![0.02, 0.03, 0.0625],
+            ])
+            .constraints(ConstraintSet::long_only_full_investment(3))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_grid_search_improves_on_default() {
+        let problem = create_test_problem();
+        let tuner = HyperparameterTuner::new(&problem, TuningMetric::MinIterations);
+
+        let default_result = QpSolver::default().solve(&problem).unwrap();
+
+        let grid = HashMap::from([("eps_abs".to_string(), vec![1e-9, 1e-6, 1e-5])]);
+        let (config, result) = tuner.tune_grid(grid).unwrap();
+
+        assert_eq!(config.eps_abs, 1e-5);
+        assert!(result.iterations < default_result.iterations);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < BUDGET_TOLERANCE);
+    }
+
+    #[test]
+    fn test_grid_search_rejects_unknown_parameter() {
+        let problem = create_test_problem();
+        let tuner = HyperparameterTuner::new(&problem, TuningMetric::MinVariance);
+
+        let grid = HashMap::from([("learning_rate".to_string(), vec![0.1])]);
+        assert!(tuner.tune_grid(grid).is_err());
+    }
+
+    #[test]
+    fn test_bayesian_search() {
+        let problem = create_test_problem();
+        let tuner = HyperparameterTuner::new(&problem, TuningMetric::MinIterations);
+
+        let (config, result) = tuner.tune_bayesian(8, 7).unwrap();
+        assert!(config.eps_abs >= 1e-10 && config.eps_abs <= 1e-3);
+        assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < BUDGET_TOLERANCE);
+
+        // Deterministic for a fixed seed
+        let (again, _) = tuner.tune_bayesian(8, 7).unwrap();
+        assert_eq!(config.eps_abs, again.eps_abs);
+
+        assert!(tuner.tune_bayesian(0, 7).is_err());
+    }
+}
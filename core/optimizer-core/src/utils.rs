@@ -0,0 +1,126 @@
+//! Cross-sectional utilities
+//!
+//! Transforms for putting expected return estimates from different models
+//! on a common scale before optimization.
+
+/// Standardize values to zero mean and unit (population) standard deviation
+///
+/// Returns all zeros if the values have no dispersion (std below 1e-12).
+pub fn cross_sectional_zscore(returns: &[f64]) -> Vec<f64> {
+    let n = returns.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mean = returns.iter().sum::<f64>() / n as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / n as f64;
+    let std = variance.sqrt();
+
+    if std < 1e-12 {
+        return vec![0.0; n];
+    }
+
+    returns.iter().map(|r| (r - mean) / std).collect()
+}
+
+/// Rank values cross-sectionally, scaled to [0, 1]
+///
+/// The smallest value maps to 0 and the largest to 1 (0-based rank / (n - 1)).
+/// Ties receive the average of their ranks.
+pub fn cross_sectional_rank(returns: &[f64]) -> Vec<f64> {
+    let n = returns.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| returns[a].total_cmp(&returns[b]));
+
+    let mut ranks = vec![0.0; n];
+    let mut start = 0;
+    while start < n {
+        // Find the run of tied values
+        let mut end = start + 1;
+        while end < n && returns[order[end]] == returns[order[start]] {
+            end += 1;
+        }
+
+        let avg_rank = (start + end - 1) as f64 / 2.0;
+        for &idx in &order[start..end] {
+            ranks[idx] = avg_rank / (n - 1) as f64;
+        }
+        start = end;
+    }
+
+    ranks
+}
+
+/// Clip values outside the given percentiles
+///
+/// `lower_pct` and `upper_pct` are percentiles in [0, 100]; percentiles are
+/// computed with linear interpolation between order statistics.
+pub fn winsorize(values: &[f64], lower_pct: f64, upper_pct: f64) -> Vec<f64> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+
+    let lower = percentile(&sorted, lower_pct.clamp(0.0, 100.0));
+    let upper = percentile(&sorted, upper_pct.clamp(0.0, 100.0));
+
+    values.iter().map(|v| v.max(lower).min(upper)).collect()
+}
+
+/// Percentile of sorted data with linear interpolation
+pub(crate) fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    let pos = pct / 100.0 * (sorted.len() - 1) as f64;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+    let frac = pos - lo as f64;
+    sorted[lo] + (sorted[hi] - sorted[lo]) * frac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zscore() {
+        let returns = vec![0.05, 0.10, 0.02, 0.08, 0.15];
+        let z = cross_sectional_zscore(&returns);
+
+        let mean = z.iter().sum::<f64>() / z.len() as f64;
+        let std = (z.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / z.len() as f64).sqrt();
+
+        assert!(mean.abs() < 1e-10);
+        assert!((std - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_zscore_constant() {
+        let z = cross_sectional_zscore(&[0.1, 0.1, 0.1]);
+        assert!(z.iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_rank() {
+        let ranks = cross_sectional_rank(&[0.3, 0.1, 0.2]);
+        assert_eq!(ranks, vec![1.0, 0.0, 0.5]);
+
+        // Ties share the average rank
+        let tied = cross_sectional_rank(&[0.1, 0.1, 0.2]);
+        assert_eq!(tied, vec![0.25, 0.25, 1.0]);
+    }
+
+    #[test]
+    fn test_winsorize() {
+        let values: Vec<f64> = (0..=100).map(|i| i as f64).collect();
+        let clipped = winsorize(&values, 5.0, 95.0);
+
+        assert_eq!(clipped[0], 5.0);
+        assert_eq!(clipped[50], 50.0);
+        assert_eq!(clipped[100], 95.0);
+    }
+}
@@ -0,0 +1,133 @@
+//! Portfolio weight vectors
+//!
+//! Newtype around raw weights carrying the full-investment invariant.
+
+use std::ops::Deref;
+
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use crate::{OptimizerError, Result};
+
+/// Portfolio weights that sum to one (unless explicitly unconstrained)
+///
+/// Serialized as a plain array. The invariant is checked where weights are
+/// constructed, so deserialization accepts any array, including
+/// unconstrained weights.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PortfolioWeights {
+    weights: Vec<f64>,
+}
+
+impl PortfolioWeights {
+    /// Create validated weights
+    ///
+    /// Fails if any weight is not finite or if `|sum(w) - 1| > tol`.
+    pub fn try_new(weights: Vec<f64>, tol: f64) -> Result<Self> {
+        if let Some(w) = weights.iter().find(|w| !w.is_finite()) {
+            return Err(OptimizerError::InvalidWeights(format!(
+                "Weight {} is not finite",
+                w
+            )));
+        }
+
+        let sum: f64 = weights.iter().sum();
+        if (sum - 1.0).abs() > tol {
+            return Err(OptimizerError::InvalidWeights(format!(
+                "Weights sum to {}, expected 1.0",
+                sum
+            )));
+        }
+
+        Ok(Self { weights })
+    }
+
+    /// Equal-weight long-only portfolio over `n` assets
+    pub fn long_only(n: usize) -> Self {
+        Self {
+            weights: vec![1.0 / n as f64; n],
+        }
+    }
+
+    /// Wrap weights without validation (e.g., long-short or partially invested)
+    pub fn unconstrained(weights: Vec<f64>) -> Self {
+        Self { weights }
+    }
+
+    /// Number of assets
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Copy into a column vector for linear algebra
+    pub fn to_dvector(&self) -> DVector<f64> {
+        DVector::from_column_slice(&self.weights)
+    }
+
+    /// Unwrap into the raw weight vector
+    pub fn into_inner(self) -> Vec<f64> {
+        self.weights
+    }
+}
+
+impl Deref for PortfolioWeights {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new() {
+        let weights = PortfolioWeights::try_new(vec![0.6, 0.4], 1e-10).unwrap();
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[0], 0.6);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        assert!(PortfolioWeights::try_new(vec![0.5, 0.6], 1e-6).is_err());
+        assert!(PortfolioWeights::try_new(vec![f64::NAN, 1.0], 1e-6).is_err());
+        assert!(PortfolioWeights::try_new(vec![f64::INFINITY, 1.0], 1e-6).is_err());
+    }
+
+    #[test]
+    fn test_long_only() {
+        let weights = PortfolioWeights::long_only(4);
+        assert!(weights.iter().all(|&w| w == 0.25));
+    }
+
+    #[test]
+    fn test_unconstrained() {
+        // Dollar-neutral weights bypass validation
+        let weights = PortfolioWeights::unconstrained(vec![0.5, -0.5]);
+        assert_eq!(weights.iter().sum::<f64>(), 0.0);
+        assert_eq!(weights.to_dvector().len(), 2);
+    }
+
+    #[test]
+    fn test_serde_transparent() {
+        let weights = PortfolioWeights::try_new(vec![0.25, 0.75], 1e-10).unwrap();
+        let json = serde_json::to_string(&weights).unwrap();
+        assert_eq!(json, "[0.25,0.75]");
+        let back: PortfolioWeights = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, weights);
+
+        // Unconstrained weights round-trip too
+        let neutral = PortfolioWeights::unconstrained(vec![0.5, -0.5]);
+        let json = serde_json::to_string(&neutral).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PortfolioWeights>(&json).unwrap(),
+            neutral
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! JSON round-trip tests for the optimization workflow
+
+use optimizer_core::constraints::{
+    BoxConstraint, CardinalityConstraint, ConstraintSet, FactorExposureConstraint,
+    LinearConstraint, TurnoverConstraint, WeightDensityConstraint,
+};
+use optimizer_core::problem::{
+    ObjectiveType, OptimizationProblem, OptimizationResult, TransactionCostModel,
+};
+use optimizer_core::solver::{AnnealingSchedule, PenaltySchedule, QpSolver, SolverConfig};
+
+fn full_problem() -> OptimizationProblem {
+    let n = 4;
+    let current = vec![0.25, 0.25, 0.25, 0.25];
+
+    let constraints = ConstraintSet::new()
+        .with_box(BoxConstraint::new(vec![0.0; n], vec![0.6, 0.6, 0.5, 0.5]))
+        .with_linear(LinearConstraint::full_investment(n))
+        .with_linear(LinearConstraint::sector_exposure(&[0, 0, 1, 1], 2, 0.7))
+        .with_turnover(TurnoverConstraint::new(current.clone(), 0.5))
+        .with_factor_exposure(FactorExposureConstraint::new(
+            vec![vec![1.1], vec![0.9], vec![1.0], vec![1.2]],
+            vec![0.8],
+            vec![1.2],
+            vec!["market".to_string()],
+        ))
+        .with_weight_density(WeightDensityConstraint::new(0.3, 2))
+        .with_cardinality(CardinalityConstraint::new(4));
+
+    OptimizationProblem::builder(n)
+        .expected_returns(vec![0.08, 0.10, 0.12, 0.09])
+        .covariance(vec![
+            vec![0.040, 0.006, 0.004, 0.002],
+            vec![0.006, 0.050, 0.005, 0.004],
+            vec![0.004, 0.005, 0.045, 0.003],
+            vec![0.002, 0.004, 0.003, 0.055],
+        ])
+        .constraints(constraints)
+        .objective(ObjectiveType::MeanVariance)
+        .risk_aversion(3.0)
+        .risk_free_rate(0.02)
+        .transaction_costs(TransactionCostModel {
+            linear_cost: 0.001,
+            ..Default::default()
+        })
+        .current_weights(current)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_problem_round_trip_and_solve() {
+    let problem = full_problem();
+
+    let json = serde_json::to_string_pretty(&problem).unwrap();
+    let back: OptimizationProblem = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.n_assets, problem.n_assets);
+    assert_eq!(back.expected_returns, problem.expected_returns);
+    assert_eq!(back.covariance, problem.covariance);
+    assert_eq!(back.constraints, problem.constraints);
+    assert_eq!(back.objective, problem.objective);
+    assert_eq!(back.risk_aversion, problem.risk_aversion);
+    assert_eq!(back.risk_free_rate, problem.risk_free_rate);
+    assert_eq!(back.transaction_costs, problem.transaction_costs);
+    assert_eq!(back.current_weights, problem.current_weights);
+    assert_eq!(back, problem);
+
+    let solver = QpSolver::default();
+    let original = solver.solve(&problem).unwrap();
+    let restored = solver.solve(&back).unwrap();
+
+    assert_eq!(original.weights.len(), restored.weights.len());
+    for (a, b) in original.weights.iter().zip(restored.weights.iter()) {
+        assert!((a - b).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_tracking_objective_round_trip() {
+    let objective = ObjectiveType::MinimizeTrackingError {
+        benchmark_weights: vec![0.5, 0.3, 0.2],
+    };
+    let json = serde_json::to_string(&objective).unwrap();
+    let back: ObjectiveType = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, objective);
+}
+
+#[test]
+fn test_result_round_trip() {
+    let result = QpSolver::default().solve(&full_problem()).unwrap();
+
+    let json = serde_json::to_string_pretty(&result).unwrap();
+    let back: OptimizationResult = serde_json::from_str(&json).unwrap();
+
+    // serde_json's default float parser may be off by one ulp
+    let close = |a: f64, b: f64| (a - b).abs() <= 1e-15 * a.abs().max(1.0);
+    assert_eq!(back.weights.len(), result.weights.len());
+    assert!(back
+        .weights
+        .iter()
+        .zip(result.weights.iter())
+        .all(|(a, b)| close(*a, *b)));
+    assert!(close(back.expected_return, result.expected_return));
+    assert!(close(back.variance, result.variance));
+    assert!(close(back.volatility, result.volatility));
+    assert!(close(back.sharpe_ratio, result.sharpe_ratio));
+    let close_opt = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) => close(a, b),
+        (a, b) => a.is_none() && b.is_none(),
+    };
+    assert!(close_opt(back.transaction_cost, result.transaction_cost));
+    assert!(close_opt(
+        back.regularization_applied,
+        result.regularization_applied
+    ));
+    assert!(close_opt(back.tracking_error, result.tracking_error));
+    assert_eq!(back.iterations, result.iterations);
+    assert_eq!(back.status, result.status);
+    assert_eq!(
+        back.n_assets_above_threshold,
+        result.n_assets_above_threshold
+    );
+}
+
+#[test]
+fn test_solver_config_round_trip() {
+    let config = SolverConfig {
+        max_iterations: 500,
+        eps_abs: 1e-8,
+        penalty_schedule: PenaltySchedule {
+            initial_rho: 10.0,
+            final_rho: 1e6,
+            schedule: AnnealingSchedule::Geometric,
+        },
+        ..Default::default()
+    };
+
+    let json = serde_json::to_string_pretty(&config).unwrap();
+    assert!(!json.contains("convergence_callback"));
+    let back: SolverConfig = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(back.max_iterations, config.max_iterations);
+    assert_eq!(back.eps_abs, config.eps_abs);
+    assert_eq!(back.eps_rel, config.eps_rel);
+    assert_eq!(back.verbose, config.verbose);
+    assert_eq!(back.penalty_schedule, config.penalty_schedule);
+    assert_eq!(back.max_condition_number, config.max_condition_number);
+    assert!(back.convergence_callback.is_none());
+}
+
+#[test]
+fn test_unknown_fields_are_ignored() {
+    let problem = full_problem();
+    let mut value = serde_json::to_value(&problem).unwrap();
+    value["schema_version"] = serde_json::json!(2);
+    value["constraints"]["comment"] = serde_json::json!("added by a newer client");
+
+    let back: OptimizationProblem = serde_json::from_value(value).unwrap();
+    assert_eq!(back, problem);
+
+    let config: SolverConfig = serde_json::from_str(
+        r#"{
+            "max_iterations": 100,
+            "eps_abs": 1e-6,
+            "eps_rel": 1e-6,
+            "verbose": false,
+            "penalty_schedule": {
+                "initial_rho": 1.0,
+                "final_rho": 1e8,
+                "schedule": "Exponential"
+            },
+            "max_condition_number": 1e8,
+            "warm_start": true
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.max_iterations, 100);
+}
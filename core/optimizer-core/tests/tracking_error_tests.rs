@@ -0,0 +1,84 @@
+//! Tracking error limits against a benchmark
+
+use optimizer_core::constraints::{ConstraintSet, TrackingErrorConstraint};
+use optimizer_core::problem::{ObjectiveType, OptimizationProblem};
+use optimizer_core::solver::{QpSolver, SolverConfig};
+
+/// Five assets whose returns favour the last ones, benchmarked to equal weights
+fn benchmarked_problem(objective: ObjectiveType) -> OptimizationProblem {
+    let n = 5;
+    let constraints = ConstraintSet::long_only_full_investment(n)
+        .with_tracking_error(TrackingErrorConstraint::new(vec![0.2; n], 0.02));
+
+    OptimizationProblem::builder(n)
+        .expected_returns(vec![0.04, 0.06, 0.08, 0.10, 0.14])
+        .covariance(vec![
+            vec![0.040, 0.006, 0.004, 0.002, 0.003],
+            vec![0.006, 0.050, 0.005, 0.004, 0.002],
+            vec![0.004, 0.005, 0.045, 0.003, 0.004],
+            vec![0.002, 0.004, 0.003, 0.055, 0.005],
+            vec![0.003, 0.002, 0.004, 0.005, 0.060],
+        ])
+        .constraints(constraints)
+        .objective(objective)
+        .risk_aversion(2.0)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_tracking_error_limit_binds() {
+    let problem = benchmarked_problem(ObjectiveType::MeanVariance);
+    let tracking = problem
+        .constraints
+        .tracking_error_constraint
+        .clone()
+        .unwrap();
+    let unconstrained = {
+        let mut free = problem.clone();
+        free.constraints.tracking_error_constraint = None;
+        QpSolver::default().solve(&free).unwrap()
+    };
+    assert!(tracking.tracking_error(&unconstrained.weights, &problem.covariance) > 0.05);
+    assert_eq!(unconstrained.tracking_error, None);
+
+    let result = QpSolver::default().solve(&problem).unwrap();
+    let eps = SolverConfig::default().eps_abs;
+    let realized = tracking.tracking_error(&result.weights, &problem.covariance);
+    assert!(realized <= 0.02 + eps);
+    assert!((realized - 0.02).abs() < 1e-3);
+    assert_eq!(result.tracking_error, Some(realized));
+    assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < eps);
+    // The active bet still leans towards the high-return assets
+    assert!(result.weights[4] > 0.2 && result.weights[0] < 0.2);
+}
+
+#[test]
+fn test_tracking_error_limit_on_every_path() {
+    let eps = SolverConfig::default().eps_abs;
+    let fallback = QpSolver::new(SolverConfig {
+        use_fallback: true,
+        ..SolverConfig::default()
+    });
+
+    for objective in [
+        ObjectiveType::MinimizeVariance,
+        ObjectiveType::MeanVariance,
+        ObjectiveType::MaximizeSharpe,
+        ObjectiveType::MaximizeReturn,
+        ObjectiveType::MaxDiversification,
+    ] {
+        let problem = benchmarked_problem(objective.clone());
+        for solver in [&QpSolver::default(), &fallback] {
+            let result = solver.solve(&problem).unwrap();
+            let tracking_error = result.tracking_error.unwrap();
+            assert!(
+                tracking_error <= 0.02 + eps,
+                "{:?}: tracking error {}",
+                objective,
+                tracking_error
+            );
+            assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Turnover limits on rebalancing from current holdings
+
+use optimizer_core::constraints::{BoxConstraint, ConstraintSet, TurnoverConstraint};
+use optimizer_core::problem::{ObjectiveType, OptimizationProblem};
+use optimizer_core::solver::{QpSolver, SolverConfig};
+
+/// Equal-weight holdings facing returns that favour the last assets
+fn rebalance_problem(objective: ObjectiveType) -> OptimizationProblem {
+    let n = 5;
+    let constraints = ConstraintSet::new()
+        .with_box(BoxConstraint::long_only(n))
+        .with_turnover(TurnoverConstraint::new(vec![0.2; n], 0.1));
+
+    OptimizationProblem::builder(n)
+        .expected_returns(vec![0.04, 0.06, 0.08, 0.10, 0.14])
+        .covariance(vec![
+            vec![0.040, 0.006, 0.004, 0.002, 0.003],
+            vec![0.006, 0.050, 0.005, 0.004, 0.002],
+            vec![0.004, 0.005, 0.045, 0.003, 0.004],
+            vec![0.002, 0.004, 0.003, 0.055, 0.005],
+            vec![0.003, 0.002, 0.004, 0.005, 0.060],
+        ])
+        .constraints(constraints)
+        .objective(objective)
+        .risk_aversion(2.0)
+        .build()
+        .unwrap()
+}
+
+fn turnover(weights: &[f64]) -> f64 {
+    weights.iter().map(|w| (w - 0.2).abs()).sum()
+}
+
+#[test]
+fn test_turnover_limit_binds() {
+    let problem = rebalance_problem(ObjectiveType::MeanVariance);
+    let unconstrained = {
+        let mut free = problem.clone();
+        free.constraints.turnover_constraint = None;
+        QpSolver::default().solve(&free).unwrap()
+    };
+    assert!(turnover(&unconstrained.weights) > 0.3);
+
+    let solver = QpSolver::default();
+    let result = solver.solve(&problem).unwrap();
+    let eps = SolverConfig::default().eps_abs;
+    assert!(turnover(&result.weights) <= 0.1 + eps);
+    assert!((turnover(&result.weights) - 0.1).abs() < 1e-4);
+    assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < eps);
+    // The limited trade still moves towards the high-return assets
+    assert!(result.weights[4] > 0.2 && result.weights[0] < 0.2);
+}
+
+#[test]
+fn test_turnover_limit_on_every_path() {
+    let eps = SolverConfig::default().eps_abs;
+    let fallback = QpSolver::new(SolverConfig {
+        use_fallback: true,
+        ..SolverConfig::default()
+    });
+
+    for objective in [
+        ObjectiveType::MinimizeVariance,
+        ObjectiveType::MeanVariance,
+        ObjectiveType::MaximizeSharpe,
+        ObjectiveType::MaximizeReturn,
+        ObjectiveType::RiskParity,
+    ] {
+        let problem = rebalance_problem(objective.clone());
+        for solver in [&QpSolver::default(), &fallback] {
+            let result = solver.solve(&problem).unwrap();
+            assert!(
+                turnover(&result.weights) <= 0.1 + eps,
+                "{:?}: turnover {}",
+                objective,
+                turnover(&result.weights)
+            );
+            assert!((result.weights.iter().sum::<f64>() - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Risk attribution output structures
+
+use serde::{Deserialize, Serialize};
+
+use crate::portfolio::RiskDecomposition;
+
+/// Waterfall chart data for a risk decomposition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterfallData {
+    /// Segments in plotting order, ending with the total
+    pub segments: Vec<WaterfallSegment>,
+}
+
+/// A single bar in the waterfall
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterfallSegment {
+    /// Segment label (factor name, "Specific" or "Total")
+    pub label: String,
+    /// Cumulative risk before this segment
+    pub start: f64,
+    /// Cumulative risk after this segment
+    pub end: f64,
+    /// Contribution to total risk
+    pub contribution: f64,
+    /// Percentage of total risk
+    pub contribution_pct: f64,
+    /// Whether this is a subtotal bar drawn from zero
+    pub is_subtotal: bool,
+}
+
+impl WaterfallData {
+    /// Build the waterfall from a risk decomposition
+    ///
+    /// Factors are ordered by absolute contribution (largest first) and
+    /// stacked from zero, followed by specific risk and a total bar. The
+    /// specific segment contributes `σ_s² / σ` with `σ_s` the decomposition's
+    /// `specific_risk`, so for a consistent decomposition the cumulative
+    /// series closes at the total.
+    pub fn from_decomposition(decomp: &RiskDecomposition) -> WaterfallData {
+        let total = decomp.total_risk;
+        let pct = |contribution: f64| {
+            if total == 0.0 {
+                0.0
+            } else {
+                contribution / total * 100.0
+            }
+        };
+
+        let mut factors: Vec<_> = decomp.factor_contributions.iter().collect();
+        factors.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+
+        let mut segments = Vec::with_capacity(factors.len() + 2);
+        let mut running = 0.0;
+        for factor in factors {
+            segments.push(WaterfallSegment {
+                label: factor.factor_name.clone(),
+                start: running,
+                end: running + factor.contribution,
+                contribution: factor.contribution,
+                contribution_pct: pct(factor.contribution),
+                is_subtotal: false,
+            });
+            running += factor.contribution;
+        }
+
+        let specific = if total == 0.0 {
+            0.0
+        } else {
+            decomp.specific_risk * decomp.specific_risk / total
+        };
+        segments.push(WaterfallSegment {
+            label: "Specific".to_string(),
+            start: running,
+            end: running + specific,
+            contribution: specific,
+            contribution_pct: pct(specific),
+            is_subtotal: false,
+        });
+
+        segments.push(WaterfallSegment {
+            label: "Total".to_string(),
+            start: 0.0,
+            end: total,
+            contribution: total,
+            contribution_pct: pct(total),
+            is_subtotal: true,
+        });
+
+        WaterfallData { segments }
+    }
+
+    /// Render segments as CSV with a header row
+    pub fn as_csv(&self) -> String {
+        let mut csv = String::from("label,start,end,contribution,contribution_pct,is_subtotal\n");
+        for seg in &self.segments {
+            let label = if seg.label.contains([',', '"']) {
+                format!("\"{}\"", seg.label.replace('"', "\"\""))
+            } else {
+                seg.label.clone()
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                label, seg.start, seg.end, seg.contribution, seg.contribution_pct, seg.is_subtotal
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::FactorContribution;
+
+    fn contribution(name: &str, contribution: f64) -> FactorContribution {
+        FactorContribution {
+            factor_name: name.to_string(),
+            exposure: 1.0,
+            contribution,
+            contribution_pct: contribution / 0.20 * 100.0,
+        }
+    }
+
+    /// σ = 0.20 with factor variance 0.028 and specific variance 0.012,
+    /// so factors contribute 0.14 and specific risk 0.06
+    fn make_decomposition() -> RiskDecomposition {
+        RiskDecomposition {
+            total_risk: 0.20,
+            systematic_risk: 0.028f64.sqrt(),
+            specific_risk: 0.012f64.sqrt(),
+            factor_contributions: vec![
+                contribution("value", 0.03),
+                contribution("size", 0.12),
+                contribution("momentum", -0.01),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_waterfall_segments() {
+        let waterfall = WaterfallData::from_decomposition(&make_decomposition());
+
+        let labels: Vec<&str> = waterfall
+            .segments
+            .iter()
+            .map(|s| s.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            vec!["size", "value", "momentum", "Specific", "Total"]
+        );
+
+        // Non-subtotal segments are contiguous and start from zero
+        let bars: Vec<&WaterfallSegment> = waterfall
+            .segments
+            .iter()
+            .filter(|s| !s.is_subtotal)
+            .collect();
+        assert_eq!(bars[0].start, 0.0);
+        for pair in bars.windows(2) {
+            assert!((pair[1].start - pair[0].end).abs() < 1e-12);
+        }
+
+        let last = waterfall.segments.last().unwrap();
+        assert!(last.is_subtotal);
+        assert!((last.end - 0.20).abs() < 1e-12);
+        assert!((bars.last().unwrap().end - 0.20).abs() < 1e-12);
+
+        // Specific risk contributes σ_s² / σ
+        assert!((bars[3].contribution - 0.06).abs() < 1e-12);
+        assert!((bars[3].contribution_pct - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_waterfall_csv() {
+        let waterfall = WaterfallData::from_decomposition(&make_decomposition());
+        let csv = waterfall.as_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[0],
+            "label,start,end,contribution,contribution_pct,is_subtotal"
+        );
+        assert!(lines[1].starts_with("size,0,0.12,0.12,"));
+        assert!(lines[5].ends_with(",true"));
+    }
+}
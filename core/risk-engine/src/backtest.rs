@@ -0,0 +1,237 @@
+//! VaR backtesting
+//!
+//! Compares predicted value-at-risk against realized P&L, classifying the
+//! model with the Basel traffic light over the last 250 trading days and
+//! testing the exception rate with Kupiec's proportion-of-failures test.
+
+use covariance::distribution::normal_cdf;
+use serde::{Deserialize, Serialize};
+
+/// Trading days in the Basel backtesting window
+pub const BASEL_WINDOW: usize = 250;
+
+/// Significance level of the Kupiec test
+const KUPIEC_SIGNIFICANCE: f64 = 0.05;
+
+/// Basel traffic light zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrafficLightZone {
+    /// 0 to 4 exceptions: the model is acceptable
+    Green,
+    /// 5 to 9 exceptions: the model may be understating risk
+    Yellow,
+    /// 10 or more exceptions: the model is rejected
+    Red,
+}
+
+impl TrafficLightZone {
+    /// Zone for the number of exceptions in a 250-day window
+    pub fn from_exceptions(exceptions: usize) -> Self {
+        match exceptions {
+            0..=4 => Self::Green,
+            5..=9 => Self::Yellow,
+            _ => Self::Red,
+        }
+    }
+}
+
+/// Kupiec proportion-of-failures test result
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KupiecResult {
+    /// Likelihood ratio statistic, chi-squared with one degree of freedom
+    pub lr_statistic: f64,
+    /// Probability of a statistic at least this large under H0
+    pub p_value: f64,
+    /// Whether H0 (exception rate equals `1 - confidence`) is rejected at 5%
+    pub reject_h0: bool,
+}
+
+/// Daily VaR backtest
+#[derive(Debug, Clone)]
+pub struct VarBacktester {
+    confidence: f64,
+    exceptions: Vec<bool>,
+}
+
+impl VarBacktester {
+    /// Create a backtester for VaR at `confidence` (e.g. 0.99)
+    ///
+    /// # Panics
+    ///
+    /// Panics if `confidence` is not strictly between 0 and 1.
+    pub fn new(confidence: f64) -> Self {
+        assert!(
+            confidence > 0.0 && confidence < 1.0,
+            "confidence must be in (0, 1)"
+        );
+        Self {
+            confidence,
+            exceptions: Vec::new(),
+        }
+    }
+
+    /// VaR confidence level
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+
+    /// Record one day's predicted VaR (a positive loss) and realized P&L
+    ///
+    /// The day is an exception when the loss `-actual_pnl` exceeds
+    /// `predicted_var`.
+    pub fn record_day(&mut self, predicted_var: f64, actual_pnl: f64) {
+        self.exceptions.push(-actual_pnl > predicted_var);
+    }
+
+    /// Number of days recorded
+    pub fn n_days(&self) -> usize {
+        self.exceptions.len()
+    }
+
+    /// Number of exceptions across all recorded days
+    pub fn n_exceptions(&self) -> usize {
+        self.exceptions.iter().filter(|&&e| e).count()
+    }
+
+    /// Traffic light zone over the last 250 days
+    ///
+    /// The zone boundaries are the Basel ones for 99% VaR. Returns `None`
+    /// until 250 days have been recorded.
+    pub fn traffic_light_zone(&self) -> Option<TrafficLightZone> {
+        if self.exceptions.len() < BASEL_WINDOW {
+            return None;
+        }
+        let recent = &self.exceptions[self.exceptions.len() - BASEL_WINDOW..];
+        let count = recent.iter().filter(|&&e| e).count();
+        Some(TrafficLightZone::from_exceptions(count))
+    }
+
+    /// Kupiec proportion-of-failures test over all recorded days
+    ///
+    /// With `x` exceptions in `n` days and expected rate `p = 1 -
+    /// confidence`, `LR = -2 ln[(1-p)^(n-x) p^x] + 2 ln[(1-x/n)^(n-x)
+    /// (x/n)^x]`, compared against a chi-squared distribution with one
+    /// degree of freedom. Too few exceptions are rejected as well as too
+    /// many. With no days recorded the statistic is zero.
+    pub fn kupiec_test(&self) -> KupiecResult {
+        let n = self.exceptions.len() as f64;
+        let x = self.n_exceptions() as f64;
+        let p = 1.0 - self.confidence;
+
+        let lr_statistic = if n > 0.0 {
+            let observed = x / n;
+            let log_null = xlogy(n - x, 1.0 - p) + xlogy(x, p);
+            let log_alt = xlogy(n - x, 1.0 - observed) + xlogy(x, observed);
+            (2.0 * (log_alt - log_null)).max(0.0)
+        } else {
+            0.0
+        };
+        let p_value = chi_squared_1_sf(lr_statistic);
+
+        KupiecResult {
+            lr_statistic,
+            p_value,
+            reject_h0: p_value < KUPIEC_SIGNIFICANCE,
+        }
+    }
+}
+
+/// `x ln y`, taken as zero when `x` is zero
+fn xlogy(x: f64, y: f64) -> f64 {
+    if x == 0.0 {
+        0.0
+    } else {
+        x * y.ln()
+    }
+}
+
+/// Survival function of the chi-squared distribution with one degree of freedom
+///
+/// `P(X > s) = P(|Z| > sqrt(s)) = 2 Φ(-sqrt(s))` for standard normal `Z`.
+fn chi_squared_1_sf(statistic: f64) -> f64 {
+    2.0 * normal_cdf(-statistic.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 250 days of VaR 1.0 with exceptions spread evenly through the window
+    fn backtester_with_exceptions(exceptions: usize) -> VarBacktester {
+        let mut backtester = VarBacktester::new(0.99);
+        for day in 0..BASEL_WINDOW {
+            let pnl = if day % 25 == 0 && day / 25 < exceptions {
+                -1.5
+            } else if day >= 240 && day - 240 + 10 < exceptions {
+                // Exceptions beyond ten fill the last days
+                -2.0
+            } else {
+                0.3
+            };
+            backtester.record_day(1.0, pnl);
+        }
+        backtester
+    }
+
+    #[test]
+    fn test_traffic_light_zones() {
+        let mut backtester = VarBacktester::new(0.99);
+        backtester.record_day(1.0, -1.0);
+        assert_eq!(backtester.n_exceptions(), 0);
+        assert_eq!(backtester.traffic_light_zone(), None);
+
+        for (exceptions, zone) in [
+            (0, TrafficLightZone::Green),
+            (4, TrafficLightZone::Green),
+            (5, TrafficLightZone::Yellow),
+            (9, TrafficLightZone::Yellow),
+            (10, TrafficLightZone::Red),
+            (12, TrafficLightZone::Red),
+        ] {
+            let backtester = backtester_with_exceptions(exceptions);
+            assert_eq!(backtester.n_days(), 250);
+            assert_eq!(backtester.n_exceptions(), exceptions);
+            assert_eq!(backtester.traffic_light_zone(), Some(zone));
+        }
+
+        // Only the last 250 days count towards the zone
+        let mut backtester = backtester_with_exceptions(7);
+        for _ in 0..BASEL_WINDOW {
+            backtester.record_day(1.0, 0.1);
+        }
+        assert_eq!(backtester.n_exceptions(), 7);
+        assert_eq!(
+            backtester.traffic_light_zone(),
+            Some(TrafficLightZone::Green)
+        );
+    }
+
+    #[test]
+    fn test_kupiec_statistic() {
+        // 7 exceptions against 2.5 expected: LR = 5.4970, p = 0.0190
+        let result = backtester_with_exceptions(7).kupiec_test();
+        assert!((result.lr_statistic - 5.496990).abs() < 1e-5);
+        assert!((result.p_value - 0.019049).abs() < 1e-5);
+        assert!(result.reject_h0);
+
+        // 2 exceptions is close to the expected rate
+        let result = backtester_with_exceptions(2).kupiec_test();
+        assert!((result.lr_statistic - 0.108435).abs() < 1e-5);
+        assert!((result.p_value - 0.741933).abs() < 1e-5);
+        assert!(!result.reject_h0);
+
+        // No exceptions at all also rejects: VaR is too conservative
+        let result = backtester_with_exceptions(0).kupiec_test();
+        assert!((result.lr_statistic - 5.025168).abs() < 1e-5);
+        assert!(result.reject_h0);
+
+        // Exactly the expected rate gives a zero statistic
+        let mut backtester = VarBacktester::new(0.99);
+        for day in 0..500 {
+            backtester.record_day(1.0, if day % 100 == 0 { -1.2 } else { 0.0 });
+        }
+        let result = backtester.kupiec_test();
+        assert!(result.lr_statistic.abs() < 1e-9);
+        assert!((result.p_value - 1.0).abs() < 1e-6);
+    }
+}
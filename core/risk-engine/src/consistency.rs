@@ -0,0 +1,295 @@
+//! Consistency checks between a factor risk model and an optimization problem
+//!
+//! A factor model fed to the optimizer has to describe the same universe
+//! and factors as the problem. Mismatches are reported rather than raised so
+//! that every problem is visible in one pass.
+
+use std::collections::HashSet;
+
+use optimizer_core::problem::OptimizationProblem;
+use serde::{Deserialize, Serialize};
+
+use crate::factor::{FactorCovariance, FactorExposures};
+
+/// How serious a consistency issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueSeverity {
+    /// Inputs can be used but are likely misaligned
+    Warning,
+    /// Inputs cannot be combined
+    Error,
+}
+
+/// Kind of consistency issue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueCode {
+    /// Problem and factor exposures cover different numbers of assets
+    AssetCountMismatch,
+    /// Factor names differ between the problem and the factor model
+    FactorNameMismatch,
+    /// Factor exposures and factor covariance have different factor counts
+    FactorCountMismatch,
+    /// Specific risk does not have one entry per security
+    SpecificRiskDimensionMismatch,
+}
+
+/// A detected inconsistency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsistencyIssue {
+    /// Severity
+    pub severity: IssueSeverity,
+    /// Issue kind
+    pub code: IssueCode,
+    /// Human-readable description
+    pub message: String,
+}
+
+impl ConsistencyIssue {
+    fn error(code: IssueCode, message: String) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            code,
+            message,
+        }
+    }
+
+    fn warning(code: IssueCode, message: String) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            code,
+            message,
+        }
+    }
+}
+
+/// Validates that a factor model matches an optimization problem
+pub struct ConsistencyChecker;
+
+impl ConsistencyChecker {
+    /// Check `problem` against a factor model, returning every issue found
+    ///
+    /// An empty result means the inputs are consistent. Factor names in the
+    /// problem come from its factor exposure constraint, if it has one.
+    pub fn validate(
+        problem: &OptimizationProblem,
+        factor_cov: &FactorCovariance,
+        factor_exposures: &FactorExposures,
+    ) -> Vec<ConsistencyIssue> {
+        let mut issues = Vec::new();
+
+        let n_securities = factor_exposures.exposures.nrows();
+        if problem.n_assets != n_securities {
+            issues.push(ConsistencyIssue::error(
+                IssueCode::AssetCountMismatch,
+                format!(
+                    "problem has {} assets but factor exposures cover {} securities",
+                    problem.n_assets, n_securities
+                ),
+            ));
+        }
+
+        if let Some(constraint) = &problem.constraints.factor_constraints {
+            if let Some(issue) = Self::compare_factor_names(
+                "problem factor constraint",
+                &constraint.factor_names,
+                &factor_exposures.factors,
+            ) {
+                issues.push(issue);
+            }
+        }
+
+        if factor_exposures.n_factors() != factor_cov.n_factors() {
+            issues.push(ConsistencyIssue::error(
+                IssueCode::FactorCountMismatch,
+                format!(
+                    "factor exposures have {} factors but factor covariance has {}",
+                    factor_exposures.n_factors(),
+                    factor_cov.n_factors()
+                ),
+            ));
+        } else if let Some(issue) = Self::compare_factor_names(
+            "factor covariance",
+            &factor_cov.factors,
+            &factor_exposures.factors,
+        ) {
+            issues.push(issue);
+        }
+
+        if factor_exposures.specific_risk.len() != n_securities {
+            issues.push(ConsistencyIssue::error(
+                IssueCode::SpecificRiskDimensionMismatch,
+                format!(
+                    "specific risk has {} entries for {} securities",
+                    factor_exposures.specific_risk.len(),
+                    n_securities
+                ),
+            ));
+        }
+
+        issues
+    }
+
+    /// Compare factor names against those of the factor exposures
+    ///
+    /// Different name sets are an error; the same names in a different
+    /// order are a warning, since columns would be matched by position.
+    fn compare_factor_names(
+        source: &str,
+        names: &[String],
+        exposure_factors: &[String],
+    ) -> Option<ConsistencyIssue> {
+        if names == exposure_factors {
+            return None;
+        }
+
+        let expected: HashSet<&String> = exposure_factors.iter().collect();
+        let actual: HashSet<&String> = names.iter().collect();
+        if expected == actual && names.len() == exposure_factors.len() {
+            return Some(ConsistencyIssue::warning(
+                IssueCode::FactorNameMismatch,
+                format!(
+                    "{} lists factors in a different order than factor exposures",
+                    source
+                ),
+            ));
+        }
+
+        let mut missing: Vec<&str> = exposure_factors
+            .iter()
+            .filter(|f| !actual.contains(f))
+            .map(String::as_str)
+            .collect();
+        let mut unknown: Vec<&str> = names
+            .iter()
+            .filter(|f| !expected.contains(f))
+            .map(String::as_str)
+            .collect();
+        missing.sort_unstable();
+        unknown.sort_unstable();
+
+        Some(ConsistencyIssue::error(
+            IssueCode::FactorNameMismatch,
+            format!(
+                "{} factors do not match factor exposures (missing: [{}], unknown: [{}])",
+                source,
+                missing.join(", "),
+                unknown.join(", ")
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::DVector;
+    use optimizer_core::constraints::{ConstraintSet, FactorExposureConstraint};
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn make_model() -> (FactorExposures, FactorCovariance) {
+        let factors = names(&["market", "size"]);
+        let exposures = FactorExposures::new(
+            names(&["A", "B", "C"]),
+            factors.clone(),
+            vec![vec![1.1, 0.5], vec![0.9, -0.3], vec![1.0, 0.1]],
+            vec![0.10, 0.15, 0.12],
+        )
+        .unwrap();
+        let covariance =
+            FactorCovariance::new(factors, vec![vec![0.04, 0.002], vec![0.002, 0.01]]).unwrap();
+        (exposures, covariance)
+    }
+
+    fn make_problem(n_assets: usize, factor_names: &[&str]) -> OptimizationProblem {
+        let factor_constraint = FactorExposureConstraint::new(
+            vec![vec![1.0; factor_names.len()]; n_assets],
+            vec![-1.0; factor_names.len()],
+            vec![2.0; factor_names.len()],
+            names(factor_names),
+        );
+        OptimizationProblem::builder(n_assets)
+            .expected_returns(vec![0.05; n_assets])
+            .covariance(
+                (0..n_assets)
+                    .map(|i| {
+                        (0..n_assets)
+                            .map(|j| if i == j { 0.04 } else { 0.0 })
+                            .collect()
+                    })
+                    .collect(),
+            )
+            .constraints(ConstraintSet::new().with_factor_exposure(factor_constraint))
+            .build()
+            .unwrap()
+    }
+
+    fn codes(issues: &[ConsistencyIssue]) -> Vec<IssueCode> {
+        issues.iter().map(|issue| issue.code).collect()
+    }
+
+    #[test]
+    fn test_consistent_inputs() {
+        let (exposures, factor_cov) = make_model();
+        let problem = make_problem(3, &["market", "size"]);
+        assert!(ConsistencyChecker::validate(&problem, &factor_cov, &exposures).is_empty());
+    }
+
+    #[test]
+    fn test_detects_each_mismatch() {
+        let (exposures, factor_cov) = make_model();
+
+        // Asset count
+        let issues = ConsistencyChecker::validate(
+            &make_problem(4, &["market", "size"]),
+            &factor_cov,
+            &exposures,
+        );
+        assert_eq!(codes(&issues), vec![IssueCode::AssetCountMismatch]);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+
+        // Factor names in the problem
+        let issues = ConsistencyChecker::validate(
+            &make_problem(3, &["market", "value"]),
+            &factor_cov,
+            &exposures,
+        );
+        assert_eq!(codes(&issues), vec![IssueCode::FactorNameMismatch]);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert!(issues[0].message.contains("missing: [size]"));
+        assert!(issues[0].message.contains("unknown: [value]"));
+
+        let issues = ConsistencyChecker::validate(
+            &make_problem(3, &["size", "market"]),
+            &factor_cov,
+            &exposures,
+        );
+        assert_eq!(codes(&issues), vec![IssueCode::FactorNameMismatch]);
+        assert_eq!(issues[0].severity, IssueSeverity::Warning);
+
+        // Factor count between exposures and covariance
+        let problem = make_problem(3, &["market", "size"]);
+        let three_factor_cov = FactorCovariance::new(
+            names(&["market", "size", "value"]),
+            vec![
+                vec![0.04, 0.0, 0.0],
+                vec![0.0, 0.01, 0.0],
+                vec![0.0, 0.0, 0.02],
+            ],
+        )
+        .unwrap();
+        let issues = ConsistencyChecker::validate(&problem, &three_factor_cov, &exposures);
+        assert_eq!(codes(&issues), vec![IssueCode::FactorCountMismatch]);
+
+        // Specific risk dimensions
+        let (mut short_specific, _) = make_model();
+        short_specific.specific_risk = DVector::from_vec(vec![0.10, 0.15]);
+        let issues = ConsistencyChecker::validate(&problem, &factor_cov, &short_specific);
+        assert_eq!(
+            codes(&issues),
+            vec![IssueCode::SpecificRiskDimensionMismatch]
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Currency risk decomposition for multi-currency portfolios
+//!
+//! An asset priced in a foreign currency returns `(1 + r_local)(1 + r_fx) - 1`
+//! in the base currency. With local and FX returns independent, the base
+//! currency variance splits exactly into a local term, a currency term and
+//! a cross term from the product `r_local * r_fx`.
+
+use std::collections::HashMap;
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use crate::portfolio::Portfolio;
+use crate::{Result, RiskError};
+
+/// Portfolio variance split into local, currency and cross components
+///
+/// All figures are variances, so the components add up to `total_risk`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyRiskReport {
+    /// Variance of the portfolio's base currency return
+    pub total_risk: f64,
+    /// Variance from local asset returns: `w' Σ_L w`
+    pub local_risk: f64,
+    /// Variance from exchange rates on the currency notionals: `c' Σ_FX c`
+    pub currency_risk: f64,
+    /// Variance of the local-FX product term: `w' (Σ_L ∘ A Σ_FX A') w`
+    pub cross_risk: f64,
+    /// Contribution of each currency to `currency_risk`: `c_k (Σ_FX c)_k`
+    pub per_currency_risk: HashMap<String, f64>,
+}
+
+/// Currency risk decomposer
+pub struct CurrencyRiskDecomposer;
+
+impl CurrencyRiskDecomposer {
+    /// Decompose the portfolio variance by source
+    ///
+    /// `fx_covariance` covers the distinct currencies in `asset_currencies`
+    /// in order of first appearance, with returns measured against the base
+    /// currency; the base currency itself has a zero row and column. The
+    /// currency notional `c = A' w` sums the weights held in each currency.
+    ///
+    /// Fails with `DimensionMismatch` if `asset_currencies` or
+    /// `asset_covariance` do not match the number of holdings, or
+    /// `fx_covariance` does not match the currency count.
+    pub fn decompose(
+        portfolio: &Portfolio,
+        asset_currencies: &[String],
+        fx_covariance: &DMatrix<f64>,
+        asset_covariance: &DMatrix<f64>,
+    ) -> Result<CurrencyRiskReport> {
+        let n = portfolio.weights.len();
+        check_len(n, asset_currencies.len())?;
+        check_square(n, asset_covariance)?;
+
+        let mut currencies: Vec<&str> = Vec::new();
+        let currency_of: Vec<usize> = asset_currencies
+            .iter()
+            .map(|ccy| match currencies.iter().position(|c| c == ccy) {
+                Some(k) => k,
+                None => {
+                    currencies.push(ccy);
+                    currencies.len() - 1
+                }
+            })
+            .collect();
+        let m = currencies.len();
+        check_square(m, fx_covariance)?;
+
+        let w = portfolio.weights.to_dvector();
+        let local_risk = w.dot(&(asset_covariance * &w));
+
+        let mut notional = DVector::zeros(m);
+        for (i, &k) in currency_of.iter().enumerate() {
+            notional[k] += w[i];
+        }
+        let fx_exposure = fx_covariance * &notional;
+        let currency_risk = notional.dot(&fx_exposure);
+
+        let interaction = DMatrix::from_fn(n, n, |i, j| {
+            asset_covariance[(i, j)] * fx_covariance[(currency_of[i], currency_of[j])]
+        });
+        let cross_risk = w.dot(&(interaction * &w));
+
+        let per_currency_risk = currencies
+            .iter()
+            .enumerate()
+            .map(|(k, ccy)| (ccy.to_string(), notional[k] * fx_exposure[k]))
+            .collect();
+
+        Ok(CurrencyRiskReport {
+            total_risk: local_risk + currency_risk + cross_risk,
+            local_risk,
+            currency_risk,
+            cross_risk,
+            per_currency_risk,
+        })
+    }
+}
+
+fn check_len(expected: usize, actual: usize) -> Result<()> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(RiskError::DimensionMismatch { expected, actual })
+    }
+}
+
+fn check_square(n: usize, matrix: &DMatrix<f64>) -> Result<()> {
+    check_len(n, matrix.nrows())?;
+    check_len(n, matrix.ncols())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    fn currencies(codes: &[&str]) -> Vec<String> {
+        codes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_components_sum_to_total() {
+        let portfolio = Portfolio::new(
+            currencies(&["SPY", "EWJ", "EWU", "AAPL"]),
+            vec![0.4, 0.25, 0.15, 0.2],
+        )
+        .unwrap();
+        let asset_ccy = currencies(&["USD", "JPY", "GBP", "USD"]);
+        // USD is the base currency
+        let fx_cov = dmatrix![
+            0.0, 0.0, 0.0;
+            0.0, 0.0100, 0.0030;
+            0.0, 0.0030, 0.0081
+        ];
+        let asset_cov = dmatrix![
+            0.040, 0.012, 0.010, 0.020;
+            0.012, 0.050, 0.015, 0.010;
+            0.010, 0.015, 0.045, 0.008;
+            0.020, 0.010, 0.008, 0.090
+        ];
+
+        let report =
+            CurrencyRiskDecomposer::decompose(&portfolio, &asset_ccy, &fx_cov, &asset_cov).unwrap();
+
+        // Base currency returns r_L + r_FX + r_L r_FX have covariance
+        // Σ_L + F + Σ_L ∘ F with F = A Σ_FX A' the FX covariance per asset
+        let exposure: DMatrix<f64> = dmatrix![
+            1.0, 0.0, 0.0;
+            0.0, 1.0, 0.0;
+            0.0, 0.0, 1.0;
+            1.0, 0.0, 0.0
+        ];
+        let fx_per_asset = &exposure * &fx_cov * exposure.transpose();
+        let base_cov = &asset_cov + &fx_per_asset + asset_cov.component_mul(&fx_per_asset);
+        let expected_total = portfolio.variance(&base_cov).unwrap();
+        assert!((report.total_risk - expected_total).abs() < 1e-12);
+        assert!((report.local_risk - portfolio.variance(&asset_cov).unwrap()).abs() < 1e-12);
+        assert!(report.currency_risk > 0.0 && report.cross_risk > 0.0);
+
+        // c = [0.6, 0.25, 0.15]
+        let expected_fx = 0.25 * 0.25 * 0.01 + 2.0 * 0.25 * 0.15 * 0.003 + 0.15 * 0.15 * 0.0081;
+        assert!((report.currency_risk - expected_fx).abs() < 1e-12);
+        assert_eq!(report.per_currency_risk["USD"], 0.0);
+        let per_ccy: f64 = report.per_currency_risk.values().sum();
+        assert!((per_ccy - report.currency_risk).abs() < 1e-12);
+
+        // One foreign asset: Var((1 + X)(1 + Y) - 1) = a + b + ab for independent X, Y
+        let single = Portfolio::new(currencies(&["EWJ"]), vec![1.0]).unwrap();
+        let report = CurrencyRiskDecomposer::decompose(
+            &single,
+            &currencies(&["JPY"]),
+            &dmatrix![0.01],
+            &dmatrix![0.05],
+        )
+        .unwrap();
+        assert!((report.total_risk - (0.05 + 0.01 + 0.05 * 0.01)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_single_currency_has_no_fx_risk() {
+        let portfolio = Portfolio::new(currencies(&["A", "B"]), vec![0.6, 0.4]).unwrap();
+        let asset_cov = dmatrix![
+            0.04, 0.01;
+            0.01, 0.09
+        ];
+
+        let report = CurrencyRiskDecomposer::decompose(
+            &portfolio,
+            &currencies(&["USD", "USD"]),
+            &dmatrix![0.0],
+            &asset_cov,
+        )
+        .unwrap();
+
+        assert_eq!(report.currency_risk, 0.0);
+        assert_eq!(report.cross_risk, 0.0);
+        assert!((report.total_risk - portfolio.variance(&asset_cov).unwrap()).abs() < 1e-15);
+    }
+    #[test]
+    fn test_dimension_mismatch() {
+        let portfolio = Portfolio::new(currencies(&["A", "B"]), vec![0.6, 0.4]).unwrap();
+        let asset_cov: DMatrix<f64> = dmatrix![
+            0.04, 0.01;
+            0.01, 0.09
+        ];
+        let fx_cov: DMatrix<f64> = dmatrix![
+            0.0, 0.0;
+            0.0, 0.01
+        ];
+
+        let short_currencies = CurrencyRiskDecomposer::decompose(
+            &portfolio,
+            &currencies(&["USD"]),
+            &fx_cov,
+            &asset_cov,
+        );
+        assert!(matches!(
+            short_currencies,
+            Err(RiskError::DimensionMismatch {
+                expected: 2,
+                actual: 1
+            })
+        ));
+
+        let one_currency = currencies(&["USD", "USD"]);
+        let wrong_fx =
+            CurrencyRiskDecomposer::decompose(&portfolio, &one_currency, &fx_cov, &asset_cov);
+        assert!(matches!(
+            wrong_fx,
+            Err(RiskError::DimensionMismatch {
+                expected: 1,
+                actual: 2
+            })
+        ));
+
+        let wrong_cov = CurrencyRiskDecomposer::decompose(
+            &portfolio,
+            &one_currency,
+            &dmatrix![0.0],
+            &DMatrix::zeros(1, 2),
+        );
+        assert!(matches!(
+            wrong_cov,
+            Err(RiskError::DimensionMismatch { .. })
+        ));
+    }
+}
@@ -3,6 +3,9 @@
 use nalgebra::{DMatrix, DVector};
 use crate::{Result, RiskError};
 
+/// Factor name used for short-rate sensitivity
+pub const SHORT_RATE_FACTOR: &str = "short_rate";
+
 /// Factor exposures for a universe of securities
 pub struct FactorExposures {
     /// Security codes
@@ -53,6 +56,33 @@ impl FactorExposures {
         })
     }
     
+    /// Single-factor rate model for fixed income
+    ///
+    /// The factor is the short rate and each bond's loading is `-duration`,
+    /// so a rate rise of `dr` moves its price by `-duration * dr`. Specific
+    /// risk is zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `durations` and `symbols` have different lengths.
+    pub fn from_durations(durations: Vec<f64>, symbols: Vec<String>) -> Self {
+        assert_eq!(durations.len(), symbols.len(), "one duration per symbol");
+        let n = symbols.len();
+        let loadings: Vec<f64> = durations.iter().map(|d| -d).collect();
+
+        Self {
+            securities: symbols,
+            factors: vec![SHORT_RATE_FACTOR.to_string()],
+            exposures: DMatrix::from_column_slice(n, 1, &loadings),
+            specific_risk: DVector::zeros(n),
+        }
+    }
+
+    /// Number of factors
+    pub fn n_factors(&self) -> usize {
+        self.factors.len()
+    }
+
     /// Get portfolio factor exposures
     pub fn portfolio_exposures(&self, weights: &DVector<f64>) -> Result<DVector<f64>> {
         if weights.len() != self.securities.len() {
@@ -112,6 +142,11 @@ impl FactorCovariance {
         Ok(Self { factors, covariance })
     }
     
+    /// Number of factors
+    pub fn n_factors(&self) -> usize {
+        self.factors.len()
+    }
+
     /// Calculate full stock covariance matrix
     /// Sigma = X * F * X' + D
     /// where X = factor exposures, F = factor covariance, D = diagonal specific risk
@@ -135,6 +170,26 @@ impl FactorCovariance {
     }
 }
 
+/// Weighted average duration of a portfolio
+///
+/// Durations are read from the short-rate loadings of `exposures` (see
+/// [`FactorExposures::from_durations`]) and averaged with `weights`
+/// normalized by their sum.
+pub fn portfolio_duration(exposures: &FactorExposures, weights: &DVector<f64>) -> Result<f64> {
+    let rate = exposures
+        .factors
+        .iter()
+        .position(|f| f == SHORT_RATE_FACTOR)
+        .ok_or_else(|| RiskError::CalculationError(format!("no '{}' factor", SHORT_RATE_FACTOR)))?;
+    let total_weight = weights.sum();
+    if total_weight == 0.0 {
+        return Err(RiskError::InvalidWeights("weights sum to zero".to_string()));
+    }
+
+    let rate_exposure = exposures.portfolio_exposures(weights)?[rate];
+    Ok(-rate_exposure / total_weight)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,4 +215,32 @@ mod tests {
         assert!((port_exp[0] - 0.22).abs() < 1e-6);
         assert!((port_exp[1] - 0.50).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_duration_factor() {
+        let bonds = FactorExposures::from_durations(
+            vec![5.0, 10.0],
+            vec!["BOND5Y".to_string(), "BOND10Y".to_string()],
+        );
+        assert_eq!(bonds.factors, vec![SHORT_RATE_FACTOR.to_string()]);
+        assert_eq!(bonds.exposures[(1, 0)], -10.0);
+
+        let weights = DVector::from_vec(vec![0.5, 0.5]);
+        let duration = portfolio_duration(&bonds, &weights).unwrap();
+        assert!((duration - 7.5).abs() < 1e-12);
+
+        // A 1% rate rise loses duration * 1% of portfolio value
+        let rate_shock = 0.01;
+        let pnl = bonds.portfolio_exposures(&weights).unwrap()[0] * rate_shock;
+        assert!((pnl + 0.075).abs() < 1e-12);
+
+        let equities = FactorExposures::new(
+            vec!["A".to_string()],
+            vec!["market".to_string()],
+            vec![vec![1.0]],
+            vec![0.1],
+        )
+        .unwrap();
+        assert!(portfolio_duration(&equities, &DVector::from_vec(vec![1.0])).is_err());
+    }
 }
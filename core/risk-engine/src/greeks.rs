@@ -0,0 +1,277 @@
+//! Portfolio sensitivities to a benchmark index
+//!
+//! Beta, tracking error and correlation of an equity portfolio against an
+//! index, computed from either a full stock covariance matrix or a factor
+//! model. The factor path never forms the n x n covariance.
+
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use crate::factor::{FactorCovariance, FactorExposures};
+use crate::portfolio::Portfolio;
+use crate::{Result, RiskError};
+
+/// Stock covariance, either as a full matrix or a factor model
+pub enum CovarianceInput<'a> {
+    /// Full stock covariance matrix (n x n)
+    Full(&'a DMatrix<f64>),
+    /// Factor model `X F X' + D`
+    Factor {
+        /// Factor exposures and specific risk
+        exposures: &'a FactorExposures,
+        /// Factor covariance
+        covariance: &'a FactorCovariance,
+    },
+}
+
+impl<'a> From<&'a DMatrix<f64>> for CovarianceInput<'a> {
+    fn from(covariance: &'a DMatrix<f64>) -> Self {
+        CovarianceInput::Full(covariance)
+    }
+}
+
+impl CovarianceInput<'_> {
+    /// Check that the input describes `n` securities consistently
+    fn check_dim(&self, n: usize) -> Result<()> {
+        let (lengths, n_factors) = match self {
+            CovarianceInput::Full(cov) => (vec![cov.nrows(), cov.ncols()], None),
+            CovarianceInput::Factor {
+                exposures,
+                covariance,
+            } => (
+                vec![exposures.exposures.nrows(), exposures.specific_risk.len()],
+                Some((exposures.exposures.ncols(), &covariance.covariance)),
+            ),
+        };
+        for len in lengths {
+            if len != n {
+                return Err(RiskError::DimensionMismatch {
+                    expected: n,
+                    actual: len,
+                });
+            }
+        }
+        if let Some((k, factor_cov)) = n_factors {
+            for len in [factor_cov.nrows(), factor_cov.ncols()] {
+                if len != k {
+                    return Err(RiskError::DimensionMismatch {
+                        expected: k,
+                        actual: len,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Covariance-vector product `Σv`
+    fn mul(&self, v: &DVector<f64>) -> DVector<f64> {
+        match self {
+            CovarianceInput::Full(cov) => *cov * v,
+            CovarianceInput::Factor {
+                exposures,
+                covariance,
+            } => {
+                let factor_exposure = exposures.exposures.transpose() * v;
+                let mut product = &exposures.exposures * (&covariance.covariance * factor_exposure);
+                for i in 0..product.len() {
+                    product[i] += exposures.specific_risk[i].powi(2) * v[i];
+                }
+                product
+            }
+        }
+    }
+}
+
+/// Portfolio sensitivities to an index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioGreeksResult {
+    /// Beta to the index: `w'Σb / b'Σb`
+    pub portfolio_beta: f64,
+    /// Volatility of active returns: `sqrt((w - b)'Σ(w - b))`
+    pub tracking_error_to_index: f64,
+    /// Correlation of portfolio and index returns
+    pub correlation_to_index: f64,
+    /// Portfolio beta to each factor, `(F X'w)_k / F_kk` (empty without a factor model)
+    pub cross_factor_sensitivities: Vec<f64>,
+}
+
+/// Portfolio Greeks calculator
+pub struct PortfolioGreeks;
+
+impl PortfolioGreeks {
+    /// Compute index sensitivities of a portfolio
+    pub fn compute<'a>(
+        portfolio: &Portfolio,
+        covariance: impl Into<CovarianceInput<'a>>,
+        index_weights: &DVector<f64>,
+    ) -> Result<PortfolioGreeksResult> {
+        let covariance = covariance.into();
+        let n = portfolio.weights.len();
+        if index_weights.len() != n {
+            return Err(RiskError::DimensionMismatch {
+                expected: n,
+                actual: index_weights.len(),
+            });
+        }
+        covariance.check_dim(n)?;
+
+        let w = portfolio.weights.to_dvector();
+        let sigma_w = covariance.mul(&w);
+        let sigma_b = covariance.mul(index_weights);
+
+        let port_var = w.dot(&sigma_w);
+        let index_var = index_weights.dot(&sigma_b);
+        let cross_cov = w.dot(&sigma_b);
+
+        if port_var <= 0.0 || index_var <= 0.0 {
+            return Err(RiskError::NonPositiveDefinite);
+        }
+
+        // (w - b)'Σ(w - b) = w'Σw - 2w'Σb + b'Σb
+        let active_var = (port_var - 2.0 * cross_cov + index_var).max(0.0);
+
+        let cross_factor_sensitivities = match &covariance {
+            CovarianceInput::Full(_) => Vec::new(),
+            CovarianceInput::Factor {
+                exposures,
+                covariance,
+            } => {
+                let factor_exposure = exposures.exposures.transpose() * &w;
+                let factor_cov = &covariance.covariance * factor_exposure;
+                factor_cov
+                    .iter()
+                    .enumerate()
+                    .map(|(k, c)| {
+                        let factor_var = covariance.covariance[(k, k)];
+                        if factor_var > 0.0 {
+                            Ok(c / factor_var)
+                        } else {
+                            Err(RiskError::CalculationError(format!(
+                                "factor {} has no variance",
+                                exposures.factors.get(k).map_or("?", String::as_str)
+                            )))
+                        }
+                    })
+                    .collect::<Result<Vec<f64>>>()?
+            }
+        };
+
+        Ok(PortfolioGreeksResult {
+            portfolio_beta: cross_cov / index_var,
+            tracking_error_to_index: active_var.sqrt(),
+            correlation_to_index: (cross_cov / (port_var * index_var).sqrt()).clamp(-1.0, 1.0),
+            cross_factor_sensitivities,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_model() -> (FactorExposures, FactorCovariance) {
+        let securities = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        let factors = vec!["market".to_string(), "size".to_string()];
+        let exposures = FactorExposures::new(
+            securities,
+            factors.clone(),
+            vec![vec![1.1, 0.5], vec![0.9, -0.3], vec![1.0, 0.1]],
+            vec![0.10, 0.15, 0.12],
+        )
+        .unwrap();
+        let covariance =
+            FactorCovariance::new(factors, vec![vec![0.04, 0.002], vec![0.002, 0.01]]).unwrap();
+        (exposures, covariance)
+    }
+
+    fn make_portfolio(weights: Vec<f64>) -> Portfolio {
+        let securities = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+        Portfolio::new(securities, weights).unwrap()
+    }
+
+    #[test]
+    fn test_index_portfolio_has_unit_beta() {
+        let (exposures, factor_cov) = make_model();
+        let full = factor_cov.stock_covariance(&exposures).unwrap();
+        let index = DVector::from_vec(vec![0.5, 0.3, 0.2]);
+        let portfolio = make_portfolio(vec![0.5, 0.3, 0.2]);
+
+        let greeks = PortfolioGreeks::compute(&portfolio, &full, &index).unwrap();
+
+        assert!((greeks.portfolio_beta - 1.0).abs() < 1e-12);
+        assert!((greeks.correlation_to_index - 1.0).abs() < 1e-12);
+        assert!(greeks.tracking_error_to_index < 1e-6);
+        assert!(greeks.cross_factor_sensitivities.is_empty());
+    }
+
+    #[test]
+    fn test_factor_input_matches_full_matrix() {
+        let (exposures, factor_cov) = make_model();
+        let full = factor_cov.stock_covariance(&exposures).unwrap();
+        let index = DVector::from_vec(vec![1.0 / 3.0; 3]);
+        let portfolio = make_portfolio(vec![0.7, 0.1, 0.2]);
+
+        let from_full = PortfolioGreeks::compute(&portfolio, &full, &index).unwrap();
+        let from_factor = PortfolioGreeks::compute(
+            &portfolio,
+            CovarianceInput::Factor {
+                exposures: &exposures,
+                covariance: &factor_cov,
+            },
+            &index,
+        )
+        .unwrap();
+
+        assert!((from_full.portfolio_beta - from_factor.portfolio_beta).abs() < 1e-12);
+        assert!(
+            (from_full.tracking_error_to_index - from_factor.tracking_error_to_index).abs() < 1e-12
+        );
+        assert!((from_full.correlation_to_index - from_factor.correlation_to_index).abs() < 1e-12);
+        assert_eq!(from_factor.cross_factor_sensitivities.len(), 2);
+        assert!(from_factor.tracking_error_to_index > 0.0);
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let (exposures, factor_cov) = make_model();
+        let full = factor_cov.stock_covariance(&exposures).unwrap();
+        let index = DVector::from_vec(vec![0.5, 0.5]);
+
+        let result = PortfolioGreeks::compute(&make_portfolio(vec![0.5, 0.3, 0.2]), &full, &index);
+        assert!(matches!(result, Err(RiskError::DimensionMismatch { .. })));
+
+        let portfolio = make_portfolio(vec![0.5, 0.3, 0.2]);
+        let index = DVector::from_vec(vec![0.5, 0.3, 0.2]);
+        let wide = DMatrix::from_element(3, 4, 0.01);
+        let result = PortfolioGreeks::compute(&portfolio, &wide, &index);
+        assert!(matches!(result, Err(RiskError::DimensionMismatch { .. })));
+
+        let (_, mut one_factor) = make_model();
+        one_factor.covariance = DMatrix::from_element(1, 1, 0.04);
+        let result = PortfolioGreeks::compute(
+            &portfolio,
+            CovarianceInput::Factor {
+                exposures: &exposures,
+                covariance: &one_factor,
+            },
+            &index,
+        );
+        assert!(matches!(result, Err(RiskError::DimensionMismatch { .. })));
+    }
+
+    #[test]
+    fn test_zero_variance_factor_rejected() {
+        let (exposures, mut factor_cov) = make_model();
+        factor_cov.covariance = DMatrix::from_row_slice(2, 2, &[0.04, 0.0, 0.0, 0.0]);
+        let result = PortfolioGreeks::compute(
+            &make_portfolio(vec![0.5, 0.3, 0.2]),
+            CovarianceInput::Factor {
+                exposures: &exposures,
+                covariance: &factor_cov,
+            },
+            &DVector::from_vec(vec![0.5, 0.3, 0.2]),
+        );
+        assert!(matches!(result, Err(RiskError::CalculationError(_))));
+    }
+}
@@ -0,0 +1,111 @@
+//! Minimum variance hedging
+//!
+//! Hedges one portfolio with another: holding `h` units of the hedge short
+//! against the portfolio gives the combined weights `w_P - h * w_H`.
+
+use nalgebra::DMatrix;
+
+use crate::portfolio::Portfolio;
+use crate::{Result, RiskError};
+
+/// Minimum variance hedge ratio `h* = Cov(P, H) / Var(H)`
+///
+/// `Cov(P, H) = w_P' Σ w_H` and `Var(H) = w_H' Σ w_H`. Fails if the hedge
+/// has zero variance.
+pub fn hedge_ratio(
+    portfolio: &Portfolio,
+    hedge: &Portfolio,
+    covariance: &DMatrix<f64>,
+) -> Result<f64> {
+    check_dimensions(portfolio, hedge)?;
+
+    let var_h = hedge.variance(covariance)?;
+    if var_h <= 0.0 {
+        return Err(RiskError::CalculationError(
+            "Hedge portfolio has zero variance".to_string(),
+        ));
+    }
+
+    Ok(covariance_between(portfolio, hedge, covariance) / var_h)
+}
+
+/// Variance of the hedged position `Var(P - h * H)`
+pub fn hedged_portfolio_variance(
+    portfolio: &Portfolio,
+    hedge: &Portfolio,
+    h: f64,
+    covariance: &DMatrix<f64>,
+) -> Result<f64> {
+    check_dimensions(portfolio, hedge)?;
+
+    let var_p = portfolio.variance(covariance)?;
+    let var_h = hedge.variance(covariance)?;
+    let cov_ph = covariance_between(portfolio, hedge, covariance);
+
+    Ok((var_p - 2.0 * h * cov_ph + h * h * var_h).max(0.0))
+}
+
+/// `w_P' Σ w_H` (dimensions already checked)
+fn covariance_between(portfolio: &Portfolio, hedge: &Portfolio, covariance: &DMatrix<f64>) -> f64 {
+    let w_p = portfolio.weights.to_dvector();
+    let w_h = hedge.weights.to_dvector();
+    w_p.dot(&(covariance * w_h))
+}
+
+fn check_dimensions(portfolio: &Portfolio, hedge: &Portfolio) -> Result<()> {
+    if hedge.weights.len() != portfolio.weights.len() {
+        return Err(RiskError::DimensionMismatch {
+            expected: portfolio.weights.len(),
+            actual: hedge.weights.len(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    fn securities() -> Vec<String> {
+        vec!["A".to_string(), "B".to_string(), "C".to_string()]
+    }
+
+    fn covariance() -> DMatrix<f64> {
+        dmatrix![
+            0.04, 0.01, 0.006;
+            0.01, 0.09, 0.012;
+            0.006, 0.012, 0.0625
+        ]
+    }
+
+    #[test]
+    fn test_identical_portfolios() {
+        let portfolio = Portfolio::new(securities(), vec![0.5, 0.3, 0.2]).unwrap();
+        let hedge = Portfolio::new(securities(), vec![0.5, 0.3, 0.2]).unwrap();
+
+        let h = hedge_ratio(&portfolio, &hedge, &covariance()).unwrap();
+        assert!((h - 1.0).abs() < 1e-12);
+
+        let hedged = hedged_portfolio_variance(&portfolio, &hedge, h, &covariance()).unwrap();
+        assert!(hedged.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_optimal_ratio_minimizes_variance() {
+        let portfolio = Portfolio::new(securities(), vec![0.6, 0.1, 0.3]).unwrap();
+        let hedge = Portfolio::new(securities(), vec![0.2, 0.5, 0.3]).unwrap();
+        let cov = covariance();
+
+        let h = hedge_ratio(&portfolio, &hedge, &cov).unwrap();
+        let at_optimum = hedged_portfolio_variance(&portfolio, &hedge, h, &cov).unwrap();
+        let unhedged = hedged_portfolio_variance(&portfolio, &hedge, 0.0, &cov).unwrap();
+
+        assert!((unhedged - portfolio.variance(&cov).unwrap()).abs() < 1e-12);
+        assert!(at_optimum < unhedged);
+        for bump in [-0.05, 0.05] {
+            let nearby = hedged_portfolio_variance(&portfolio, &hedge, h + bump, &cov).unwrap();
+            assert!(nearby > at_optimum);
+        }
+    }
+}
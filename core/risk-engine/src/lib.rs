@@ -3,8 +3,19 @@
 //! This crate provides real-time risk calculation capabilities for portfolio management,
 //! including factor-based risk decomposition, VaR calculation, and covariance estimation.
 
+pub mod attribution;
+pub mod backtest;
+pub mod consistency;
+pub mod currency;
 pub mod factor;
+pub mod greeks;
+pub mod hedge;
+pub mod limits;
+pub mod liquidity;
 pub mod portfolio;
+pub mod stability;
+pub mod stress;
+pub mod var;
 // pub mod grpc;
 
 use thiserror::Error;
@@ -0,0 +1,295 @@
+//! Risk limit monitoring and escalation
+//!
+//! Limits are registered with a response; evaluating the framework measures
+//! every limit against the portfolio and escalates each breach according to
+//! its response.
+
+use nalgebra::DMatrix;
+use optimizer_core::problem::OptimizationProblem;
+use serde::{Deserialize, Serialize};
+
+use crate::portfolio::Portfolio;
+use crate::Result;
+
+/// A risk metric with an upper limit
+pub trait RiskLimitMonitor {
+    /// Current value of the monitored metric
+    fn measure(&self, portfolio: &Portfolio, covariance: &DMatrix<f64>) -> Result<f64>;
+
+    /// Limit the metric must not exceed
+    fn limit(&self) -> f64;
+}
+
+/// Per-period portfolio volatility limit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolatilityLimit {
+    /// Maximum volatility, in the units of the covariance matrix
+    pub max_volatility: f64,
+}
+
+impl VolatilityLimit {
+    /// Create a volatility limit
+    pub fn new(max_volatility: f64) -> Self {
+        Self { max_volatility }
+    }
+}
+
+impl RiskLimitMonitor for VolatilityLimit {
+    fn measure(&self, portfolio: &Portfolio, covariance: &DMatrix<f64>) -> Result<f64> {
+        portfolio.volatility(covariance)
+    }
+
+    fn limit(&self) -> f64 {
+        self.max_volatility
+    }
+}
+
+/// Details of a breached limit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitBreach {
+    /// Registered limit name
+    pub limit_name: String,
+    /// Measured metric value
+    pub value: f64,
+    /// Limit that was exceeded
+    pub limit: f64,
+}
+
+impl LimitBreach {
+    /// Amount by which the value exceeds the limit
+    pub fn excess(&self) -> f64 {
+        self.value - self.limit
+    }
+}
+
+/// Response to a limit breach
+pub enum EscalationResponse {
+    /// Record the breach in the log
+    Log,
+    /// Invoke a callback with the breach
+    Alert(Box<dyn Fn(&LimitBreach)>),
+    /// Stop further trading
+    BlockTrading,
+    /// Build a rebalancing problem to restore compliance
+    ForceRebalance(Box<dyn Fn() -> OptimizationProblem>),
+}
+
+/// Action taken for a breached limit
+#[derive(Debug, Clone)]
+pub enum EscalationAction {
+    /// Breach was logged
+    Logged(LimitBreach),
+    /// Alert callback was invoked
+    Alerted(LimitBreach),
+    /// Trading must be blocked
+    TradingBlocked(LimitBreach),
+    /// Portfolio must be rebalanced by solving `problem`
+    Rebalance {
+        /// Breach that triggered the rebalance
+        breach: LimitBreach,
+        /// Rebalancing problem
+        problem: Box<OptimizationProblem>,
+    },
+}
+
+impl EscalationAction {
+    /// Breach that triggered the action
+    pub fn breach(&self) -> &LimitBreach {
+        match self {
+            EscalationAction::Logged(breach)
+            | EscalationAction::Alerted(breach)
+            | EscalationAction::TradingBlocked(breach)
+            | EscalationAction::Rebalance { breach, .. } => breach,
+        }
+    }
+}
+
+/// Registered limit with its response
+struct RegisteredLimit {
+    name: String,
+    monitor: Box<dyn RiskLimitMonitor>,
+    response: EscalationResponse,
+}
+
+/// Risk limit escalation framework
+#[derive(Default)]
+pub struct EscalationFramework {
+    limits: Vec<RegisteredLimit>,
+}
+
+impl EscalationFramework {
+    /// Create a framework with no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a limit and the response to its breaches
+    pub fn register_limit(
+        &mut self,
+        name: &str,
+        limit: Box<dyn RiskLimitMonitor>,
+        response: EscalationResponse,
+    ) {
+        self.limits.push(RegisteredLimit {
+            name: name.to_string(),
+            monitor: limit,
+            response,
+        });
+    }
+
+    /// Number of registered limits
+    pub fn len(&self) -> usize {
+        self.limits.len()
+    }
+
+    /// Check if no limits are registered
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Measure all limits and escalate breaches, in registration order
+    ///
+    /// A limit is breached when its metric strictly exceeds the limit.
+    /// Alert callbacks and rebalance builders run during evaluation.
+    pub fn evaluate(
+        &self,
+        portfolio: &Portfolio,
+        cov: &DMatrix<f64>,
+    ) -> Result<Vec<EscalationAction>> {
+        let mut actions = Vec::new();
+
+        for registered in &self.limits {
+            let value = registered.monitor.measure(portfolio, cov)?;
+            let limit = registered.monitor.limit();
+            if value <= limit {
+                continue;
+            }
+
+            let breach = LimitBreach {
+                limit_name: registered.name.clone(),
+                value,
+                limit,
+            };
+
+            let action = match &registered.response {
+                EscalationResponse::Log => {
+                    tracing::warn!(
+                        limit = %breach.limit_name,
+                        value = breach.value,
+                        threshold = breach.limit,
+                        "risk limit breached"
+                    );
+                    EscalationAction::Logged(breach)
+                }
+                EscalationResponse::Alert(callback) => {
+                    callback(&breach);
+                    EscalationAction::Alerted(breach)
+                }
+                EscalationResponse::BlockTrading => EscalationAction::TradingBlocked(breach),
+                EscalationResponse::ForceRebalance(build) => EscalationAction::Rebalance {
+                    breach,
+                    problem: Box::new(build()),
+                },
+            };
+            actions.push(action);
+        }
+
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn portfolio() -> Portfolio {
+        Portfolio::new(vec!["A".to_string(), "B".to_string()], vec![0.6, 0.4]).unwrap()
+    }
+
+    fn covariance() -> DMatrix<f64> {
+        dmatrix![
+            0.04, 0.01;
+            0.01, 0.09
+        ]
+    }
+
+    #[test]
+    fn test_volatility_alert() {
+        let expected_vol = portfolio().volatility(&covariance()).unwrap();
+        let received: Rc<RefCell<Vec<LimitBreach>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let mut framework = EscalationFramework::new();
+        let sink = Rc::clone(&received);
+        framework.register_limit(
+            "vol_15pct",
+            Box::new(VolatilityLimit::new(0.15)),
+            EscalationResponse::Alert(Box::new(move |breach| {
+                sink.borrow_mut().push(breach.clone())
+            })),
+        );
+        framework.register_limit(
+            "vol_50pct",
+            Box::new(VolatilityLimit::new(0.5)),
+            EscalationResponse::BlockTrading,
+        );
+
+        let actions = framework.evaluate(&portfolio(), &covariance()).unwrap();
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(actions[0], EscalationAction::Alerted(_)));
+
+        let received = received.borrow();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].limit_name, "vol_15pct");
+        assert!((received[0].value - expected_vol).abs() < 1e-12);
+        assert_eq!(received[0].limit, 0.15);
+        assert!(received[0].excess() > 0.0);
+        assert_eq!(actions[0].breach(), &received[0]);
+    }
+
+    #[test]
+    fn test_block_and_rebalance() {
+        let mut framework = EscalationFramework::new();
+        framework.register_limit(
+            "hard",
+            Box::new(VolatilityLimit::new(0.1)),
+            EscalationResponse::BlockTrading,
+        );
+        framework.register_limit(
+            "rebalance",
+            Box::new(VolatilityLimit::new(0.1)),
+            EscalationResponse::ForceRebalance(Box::new(|| {
+                OptimizationProblem::builder(2)
+                    .expected_returns(vec![0.05, 0.08])
+                    .covariance(vec![vec![0.04, 0.01], vec![0.01, 0.09]])
+                    .build()
+                    .unwrap()
+            })),
+        );
+        assert_eq!(framework.len(), 2);
+
+        let actions = framework.evaluate(&portfolio(), &covariance()).unwrap();
+        assert!(matches!(actions[0], EscalationAction::TradingBlocked(_)));
+        match &actions[1] {
+            EscalationAction::Rebalance { breach, problem } => {
+                assert_eq!(breach.limit_name, "rebalance");
+                assert_eq!(problem.n_assets, 2);
+            }
+            other => panic!("unexpected action {:?}", other),
+        }
+
+        // Within limits nothing escalates
+        let mut relaxed = EscalationFramework::new();
+        relaxed.register_limit(
+            "loose",
+            Box::new(VolatilityLimit::new(1.0)),
+            EscalationResponse::Log,
+        );
+        assert!(relaxed
+            .evaluate(&portfolio(), &covariance())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 //! Portfolio risk calculation
 
 use nalgebra::{DMatrix, DVector};
+use optimizer_core::weights::PortfolioWeights;
+use optimizer_core::OptimizerError;
+use crate::factor::{FactorCovariance, FactorExposures};
 use crate::{Result, RiskError};
 
 /// Portfolio holdings
@@ -8,7 +11,7 @@ pub struct Portfolio {
     /// Security codes
     pub securities: Vec<String>,
     /// Weights (must sum to 1)
-    pub weights: DVector<f64>,
+    pub weights: PortfolioWeights,
 }
 
 impl Portfolio {
@@ -22,15 +25,11 @@ impl Portfolio {
             });
         }
         
-        let weights = DVector::from_vec(weights);
-        
         // Check weights sum to approximately 1
-        let sum = weights.sum();
-        if (sum - 1.0).abs() > 1e-6 {
-            return Err(RiskError::InvalidWeights(
-                format!("Weights sum to {}, expected 1.0", sum)
-            ));
-        }
+        let weights = PortfolioWeights::try_new(weights, 1e-6).map_err(|e| match e {
+            OptimizerError::InvalidWeights(msg) => RiskError::InvalidWeights(msg),
+            other => RiskError::InvalidWeights(other.to_string()),
+        })?;
         
         Ok(Self { securities, weights })
     }
@@ -46,7 +45,8 @@ impl Portfolio {
         }
         
         // w' * Sigma * w
-        let var = (self.weights.transpose() * covariance * &self.weights)[(0, 0)];
+        let w = self.weights.to_dvector();
+        let var = (w.transpose() * covariance * &w)[(0, 0)];
         
         if var < 0.0 {
             return Err(RiskError::NonPositiveDefinite);
@@ -66,6 +66,61 @@ impl Portfolio {
         let daily_vol = self.volatility(covariance)?;
         Ok(daily_vol * (252.0_f64).sqrt())
     }
+
+    /// Mean absolute deviation of portfolio returns across scenarios
+    ///
+    /// `scenarios` holds one row of asset returns per scenario, equally
+    /// likely. MAD is `E[|r_p - E[r_p]|]`; for normal returns it equals
+    /// `σ sqrt(2/π)`.
+    pub fn mean_absolute_deviation(&self, scenarios: &DMatrix<f64>) -> Result<f64> {
+        let returns = self.scenario_returns(scenarios)?;
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        Ok(returns.iter().map(|r| (r - mean).abs()).sum::<f64>() / returns.len() as f64)
+    }
+
+    /// Mean shortfall below the mean return in the worst scenarios
+    ///
+    /// The MAD counterpart of CVaR: the average of `E[r_p] - r_p` over the
+    /// worst `ceil((1 - confidence) * n)` scenarios (at least one). It is
+    /// at least as large as the MAD, and for normal returns approaches
+    /// `σ φ(z) / (1 - confidence)` with `z` the `confidence` quantile.
+    pub fn conditional_mean_absolute_deviation(
+        &self,
+        scenarios: &DMatrix<f64>,
+        confidence: f64,
+    ) -> Result<f64> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return Err(RiskError::CalculationError(format!(
+                "confidence {} must be in (0, 1)",
+                confidence
+            )));
+        }
+        let mut returns = self.scenario_returns(scenarios)?;
+        let n = returns.len();
+        let mean = returns.iter().sum::<f64>() / n as f64;
+
+        returns.sort_by(|a, b| a.total_cmp(b));
+        let tail = (((1.0 - confidence) * n as f64).ceil() as usize).clamp(1, n);
+        Ok(returns[..tail].iter().map(|r| mean - r).sum::<f64>() / tail as f64)
+    }
+
+    /// Portfolio return in each scenario
+    fn scenario_returns(&self, scenarios: &DMatrix<f64>) -> Result<Vec<f64>> {
+        let n = self.weights.len();
+        if scenarios.ncols() != n {
+            return Err(RiskError::DimensionMismatch {
+                expected: n,
+                actual: scenarios.ncols(),
+            });
+        }
+        if scenarios.nrows() == 0 {
+            return Err(RiskError::CalculationError(
+                "no return scenarios".to_string(),
+            ));
+        }
+        let returns = scenarios * self.weights.to_dvector();
+        Ok(returns.iter().copied().collect())
+    }
 }
 
 /// Risk decomposition result
@@ -92,6 +147,96 @@ pub struct FactorContribution {
     pub contribution_pct: f64,
 }
 
+impl RiskDecomposition {
+    /// Decompose portfolio risk into factor and specific components
+    ///
+    /// Portfolio securities are looked up by name in `exposures`, so the
+    /// portfolio may hold any subset of the model universe. With factor
+    /// exposures `x = B'w`, factor `k` contributes `x_k (F x)_k / σ` to the
+    /// total risk `σ` and specific risk contributes `σ_s² / σ`, so the
+    /// contributions add up to `σ` and `contribution_pct` is each factor's
+    /// share of total variance. Factors are sorted by `contribution_pct`,
+    /// largest first.
+    pub fn compute(
+        portfolio: &Portfolio,
+        exposures: &FactorExposures,
+        factor_cov: &FactorCovariance,
+    ) -> Result<RiskDecomposition> {
+        if factor_cov.n_factors() != exposures.n_factors() {
+            return Err(RiskError::DimensionMismatch {
+                expected: exposures.n_factors(),
+                actual: factor_cov.n_factors(),
+            });
+        }
+        if factor_cov.factors != exposures.factors {
+            return Err(RiskError::CalculationError(
+                "factor covariance and exposures list different factors".to_string(),
+            ));
+        }
+
+        let rows = portfolio
+            .securities
+            .iter()
+            .map(|security| {
+                exposures
+                    .securities
+                    .iter()
+                    .position(|s| s == security)
+                    .ok_or_else(|| RiskError::MissingExposure(security.clone()))
+            })
+            .collect::<Result<Vec<usize>>>()?;
+        let mut universe_weights = DVector::zeros(exposures.securities.len());
+        for (&row, w) in rows.iter().zip(portfolio.weights.iter()) {
+            universe_weights[row] += w;
+        }
+
+        let covariance = factor_cov
+            .stock_covariance(exposures)?
+            .select_rows(&rows)
+            .select_columns(&rows);
+        let total_risk = portfolio.volatility(&covariance)?;
+        let specific_risk = exposures.portfolio_specific_risk(&universe_weights)?;
+
+        let factor_exposures = exposures.portfolio_exposures(&universe_weights)?;
+        let marginal = &factor_cov.covariance * &factor_exposures;
+        let systematic_var = factor_exposures.dot(&marginal);
+        if systematic_var < 0.0 {
+            return Err(RiskError::NonPositiveDefinite);
+        }
+
+        let mut factor_contributions: Vec<FactorContribution> = exposures
+            .factors
+            .iter()
+            .enumerate()
+            .map(|(k, name)| {
+                let variance = factor_exposures[k] * marginal[k];
+                let (contribution, contribution_pct) = if total_risk > 0.0 {
+                    (
+                        variance / total_risk,
+                        variance / (total_risk * total_risk) * 100.0,
+                    )
+                } else {
+                    (0.0, 0.0)
+                };
+                FactorContribution {
+                    factor_name: name.clone(),
+                    exposure: factor_exposures[k],
+                    contribution,
+                    contribution_pct,
+                }
+            })
+            .collect();
+        factor_contributions.sort_by(|a, b| b.contribution_pct.total_cmp(&a.contribution_pct));
+
+        Ok(RiskDecomposition {
+            total_risk,
+            systematic_risk: systematic_var.sqrt(),
+            specific_risk,
+            factor_contributions,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,4 +269,112 @@ mod tests {
         let result = Portfolio::new(securities, weights);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_risk_decomposition() {
+        let universe: Vec<String> = ["A", "B", "C", "D"].iter().map(|s| s.to_string()).collect();
+        let factors = vec![
+            "market".to_string(),
+            "size".to_string(),
+            "value".to_string(),
+        ];
+        let exposures = FactorExposures::new(
+            universe,
+            factors.clone(),
+            vec![
+                vec![1.1, 0.5, -0.2],
+                vec![0.9, -0.3, 0.4],
+                vec![1.3, 0.8, 0.1],
+                vec![0.7, -0.6, -0.5],
+            ],
+            vec![0.10, 0.08, 0.15, 0.12],
+        )
+        .unwrap();
+        let factor_cov = FactorCovariance::new(
+            factors,
+            vec![
+                vec![0.0400, 0.0020, -0.0010],
+                vec![0.0020, 0.0100, 0.0005],
+                vec![-0.0010, 0.0005, 0.0060],
+            ],
+        )
+        .unwrap();
+
+        // Holds a subset of the universe, out of order
+        let securities = vec!["C".to_string(), "A".to_string(), "D".to_string()];
+        let portfolio = Portfolio::new(securities, vec![0.5, 0.3, 0.2]).unwrap();
+        let decomp = RiskDecomposition::compute(&portfolio, &exposures, &factor_cov).unwrap();
+
+        let total_sq = decomp.total_risk.powi(2);
+        let parts_sq = decomp.systematic_risk.powi(2) + decomp.specific_risk.powi(2);
+        assert!((parts_sq - total_sq).abs() < 1e-12);
+
+        let contributions: f64 = decomp
+            .factor_contributions
+            .iter()
+            .map(|c| c.contribution)
+            .sum();
+        let specific_contribution = decomp.specific_risk.powi(2) / decomp.total_risk;
+        assert!((contributions + specific_contribution - decomp.total_risk).abs() < 1e-12);
+
+        assert_eq!(decomp.factor_contributions.len(), 3);
+        assert_eq!(decomp.factor_contributions[0].factor_name, "market");
+        // Market exposure is 0.5 * 1.3 + 0.3 * 1.1 + 0.2 * 0.7
+        assert!((decomp.factor_contributions[0].exposure - 1.12).abs() < 1e-12);
+        for pair in decomp.factor_contributions.windows(2) {
+            assert!(pair[0].contribution_pct >= pair[1].contribution_pct);
+        }
+        let factor_pct: f64 = decomp
+            .factor_contributions
+            .iter()
+            .map(|c| c.contribution_pct)
+            .sum();
+        assert!((factor_pct - 100.0 * decomp.systematic_risk.powi(2) / total_sq).abs() < 1e-9);
+
+        let unknown = Portfolio::new(vec!["E".to_string()], vec![1.0]).unwrap();
+        assert!(matches!(
+            RiskDecomposition::compute(&unknown, &exposures, &factor_cov),
+            Err(RiskError::MissingExposure(s)) if s == "E"
+        ));
+    }
+
+    #[test]
+    fn test_mean_absolute_deviation_normal() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        use rand_distr::{Distribution, StandardNormal};
+
+        let securities = vec!["A".to_string(), "B".to_string()];
+        let portfolio = Portfolio::new(securities, vec![0.6, 0.4]).unwrap();
+
+        // Correlated normal scenarios with the covariance of the test above
+        let cov = DMatrix::from_row_slice(2, 2, &[0.04, 0.01, 0.01, 0.09]);
+        let l = cov.clone().cholesky().unwrap().l();
+        let mut rng = StdRng::seed_from_u64(3);
+        let z = DMatrix::<f64>::from_fn(200_000, 2, |_, _| StandardNormal.sample(&mut rng));
+        let scenarios = z * l.transpose();
+        let sigma = portfolio.volatility(&cov).unwrap();
+
+        let mad = portfolio.mean_absolute_deviation(&scenarios).unwrap();
+        let expected_mad = sigma * (2.0 / std::f64::consts::PI).sqrt();
+        assert!((mad / expected_mad - 1.0).abs() < 0.01);
+
+        // 95% tail: φ(1.6449) / 0.05 = 2.0627
+        let cmad = portfolio
+            .conditional_mean_absolute_deviation(&scenarios, 0.95)
+            .unwrap();
+        assert!(cmad > mad);
+        assert!((cmad / (2.0627 * sigma) - 1.0).abs() < 0.02);
+
+        assert!(portfolio
+            .conditional_mean_absolute_deviation(&scenarios, 1.0)
+            .is_err());
+        assert!(matches!(
+            portfolio.mean_absolute_deviation(&DMatrix::zeros(10, 3)),
+            Err(RiskError::DimensionMismatch {
+                expected: 2,
+                actual: 3
+            })
+        ));
+    }
 }
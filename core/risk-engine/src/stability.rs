@@ -0,0 +1,191 @@
+//! Stability of risk contributions over time
+//!
+//! Tracks each asset's percentage contribution to portfolio variance over a
+//! rolling window. A portfolio whose risk budget keeps the same shape from
+//! one period to the next is stable even if its total risk moves.
+
+use std::collections::VecDeque;
+
+use nalgebra::{DMatrix, DVector};
+
+/// Contribution vectors with less dispersion than this are treated as flat
+const FLAT_TOLERANCE: f64 = 1e-12;
+
+/// Rolling tracker of percentage risk contributions
+pub struct RiskContributionTracker {
+    window: usize,
+    history: VecDeque<DVector<f64>>,
+}
+
+impl RiskContributionTracker {
+    /// Create a tracker over the last `window` observations (at least 2)
+    pub fn new(window: usize) -> Self {
+        let window = window.max(2);
+        Self {
+            window,
+            history: VecDeque::with_capacity(window),
+        }
+    }
+
+    /// Window length
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Check if the window is full
+    pub fn is_ready(&self) -> bool {
+        self.history.len() == self.window
+    }
+
+    /// Record the risk contributions of `weights` under `covariance`
+    ///
+    /// Contributions are `w_i (Σw)_i / w'Σw`, summing to one; a portfolio
+    /// with zero variance contributes all zeros. The oldest observation is
+    /// dropped once the window is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weights` and `covariance` dimensions differ.
+    pub fn push(&mut self, weights: &DVector<f64>, covariance: &DMatrix<f64>) {
+        let marginal = covariance * weights;
+        let variance = weights.dot(&marginal);
+        let contributions = if variance > 0.0 {
+            weights.component_mul(&marginal) / variance
+        } else {
+            DVector::zeros(weights.len())
+        };
+
+        if self.history.len() == self.window {
+            self.history.pop_front();
+        }
+        self.history.push_back(contributions);
+    }
+
+    /// Stability of the risk contributions across the window, in [0, 1]
+    ///
+    /// One minus the average correlation distance `(1 - ρ) / 2` between
+    /// consecutive contribution vectors, so an unchanged risk budget scores
+    /// 1 and one that flips every period scores 0. Two flat vectors count as
+    /// perfectly correlated if equal and uncorrelated otherwise. Returns NaN
+    /// until `window` observations have been pushed.
+    pub fn stability_score(&self) -> f64 {
+        if !self.is_ready() {
+            return f64::NAN;
+        }
+
+        let n_pairs = self.history.len() - 1;
+        let total_distance: f64 = self
+            .history
+            .iter()
+            .zip(self.history.iter().skip(1))
+            .map(|(prev, next)| (1.0 - correlation(prev, next)) / 2.0)
+            .sum();
+        1.0 - total_distance / n_pairs as f64
+    }
+
+    /// Asset whose risk contribution varied most across the window
+    ///
+    /// # Panics
+    ///
+    /// Panics if nothing has been pushed.
+    pub fn most_volatile_contributor(&self) -> usize {
+        let n_obs = self.history.len();
+        assert!(n_obs > 0, "no risk contributions recorded");
+        let n_assets = self.history[0].len();
+
+        (0..n_assets)
+            .map(|i| {
+                let mean = self.history.iter().map(|rc| rc[i]).sum::<f64>() / n_obs as f64;
+                let variance = self
+                    .history
+                    .iter()
+                    .map(|rc| (rc[i] - mean).powi(2))
+                    .sum::<f64>()
+                    / n_obs as f64;
+                (i, variance)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i)
+    }
+}
+
+/// Pearson correlation across assets of two contribution vectors
+fn correlation(a: &DVector<f64>, b: &DVector<f64>) -> f64 {
+    let n = a.len() as f64;
+    let (mean_a, mean_b) = (a.sum() / n, b.sum() / n);
+    let da = a.add_scalar(-mean_a);
+    let db = b.add_scalar(-mean_b);
+    let (norm_a, norm_b) = (da.norm(), db.norm());
+
+    if norm_a < FLAT_TOLERANCE || norm_b < FLAT_TOLERANCE {
+        return if (a - b).amax() < FLAT_TOLERANCE {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    (da.dot(&db) / (norm_a * norm_b)).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covariance(vols: &[f64], rho: f64) -> DMatrix<f64> {
+        let n = vols.len();
+        DMatrix::from_fn(n, n, |i, j| {
+            let r = if i == j { 1.0 } else { rho };
+            r * vols[i] * vols[j]
+        })
+    }
+
+    #[test]
+    fn test_stable_portfolio() {
+        let weights = DVector::from_vec(vec![0.3, 0.3, 0.2, 0.2]);
+        let mut tracker = RiskContributionTracker::new(20);
+
+        for t in 0..20 {
+            // Market-wide vol swings leave the risk budget unchanged; only
+            // asset 2 drifts a little on its own
+            let level = 1.0 + 0.5 * (t as f64 * 0.7).sin();
+            let drift = 0.01 * (t as f64 * 1.3).cos();
+            let vols = [
+                0.15 * level,
+                0.20 * level,
+                (0.25 + drift) * level,
+                0.30 * level,
+            ];
+            tracker.push(&weights, &covariance(&vols, 0.3));
+            if t < 19 {
+                assert!(tracker.stability_score().is_nan());
+            }
+        }
+
+        assert!(tracker.is_ready());
+        assert!(tracker.stability_score() > 0.99);
+        assert_eq!(tracker.most_volatile_contributor(), 2);
+    }
+
+    #[test]
+    fn test_rotating_risk_is_unstable() {
+        let weights = DVector::from_vec(vec![0.25; 4]);
+        let mut tracker = RiskContributionTracker::new(20);
+
+        // The high-vol asset rotates every period
+        for t in 0..25 {
+            let mut vols = [0.1; 4];
+            vols[t % 4] = 0.5;
+            tracker.push(&weights, &covariance(&vols, 0.0));
+        }
+
+        assert!(tracker.is_ready());
+        assert!(tracker.stability_score() < 0.5);
+
+        // Equal risk contributions every period are perfectly stable
+        let mut flat = RiskContributionTracker::new(3);
+        for _ in 0..3 {
+            flat.push(&weights, &covariance(&[0.2; 4], 0.0));
+        }
+        assert_eq!(flat.stability_score(), 1.0);
+    }
+}
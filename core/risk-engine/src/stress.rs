@@ -0,0 +1,292 @@
+//! Regulatory stress testing
+//!
+//! Applies standardized factor shocks to a portfolio and reports the
+//! resulting P&L per scenario. P&L is linear in the shocks: for each factor,
+//! portfolio exposure times shock, expressed as a fraction of portfolio value.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::factor::FactorExposures;
+use crate::portfolio::Portfolio;
+use crate::{Result, RiskError};
+
+/// Named set of factor shocks
+///
+/// Equity and FX shocks are returns (-0.40 = -40%); interest rate and credit
+/// spread shocks are absolute changes in rate units (0.02 = +200bps).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressScenario {
+    /// Scenario name
+    pub name: String,
+    /// Shock per factor name
+    pub factor_shocks: HashMap<String, f64>,
+}
+
+impl StressScenario {
+    /// Create a scenario from (factor, shock) pairs
+    pub fn new(name: &str, shocks: &[(&str, f64)]) -> Self {
+        Self {
+            name: name.to_string(),
+            factor_shocks: shocks.iter().map(|&(f, s)| (f.to_string(), s)).collect(),
+        }
+    }
+}
+
+/// Regulatory stress testing standard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RegulatoryStandard {
+    /// Basel III market risk shocks
+    Basel3,
+    /// ESMA stress test guidelines (representative shock set)
+    #[serde(rename = "ESMA_Guidelines")]
+    EsmaGuidelines,
+    /// User-defined scenarios
+    Custom(Vec<StressScenario>),
+}
+
+impl RegulatoryStandard {
+    /// Standard name used in reports
+    pub fn name(&self) -> &str {
+        match self {
+            RegulatoryStandard::Basel3 => "Basel3",
+            RegulatoryStandard::EsmaGuidelines => "ESMA_Guidelines",
+            RegulatoryStandard::Custom(_) => "Custom",
+        }
+    }
+
+    /// Scenarios prescribed by the standard
+    pub fn scenarios(&self) -> Vec<StressScenario> {
+        match self {
+            RegulatoryStandard::Basel3 => vec![
+                StressScenario::new("equity_crash", &[("equity", -0.40)]),
+                StressScenario::new("rates_up", &[("interest_rate", 0.02)]),
+                StressScenario::new("fx_shock", &[("fx", 0.25)]),
+                StressScenario::new("credit_widening", &[("credit_spread", 0.015)]),
+            ],
+            RegulatoryStandard::EsmaGuidelines => vec![
+                StressScenario::new("equity_crash", &[("equity", -0.30)]),
+                StressScenario::new("rates_up", &[("interest_rate", 0.015)]),
+                StressScenario::new("fx_shock", &[("fx", 0.15)]),
+                StressScenario::new("credit_widening", &[("credit_spread", 0.01)]),
+            ],
+            RegulatoryStandard::Custom(scenarios) => scenarios.clone(),
+        }
+    }
+}
+
+/// P&L of a portfolio under one scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    /// Scenario name
+    pub scenario: String,
+    /// Total P&L (fraction of portfolio value)
+    pub pnl: f64,
+    /// P&L by shocked factor
+    pub factor_pnl: Vec<(String, f64)>,
+}
+
+/// Stress test report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegulatoryReport {
+    /// Standard the scenarios were taken from
+    pub standard: String,
+    /// Per-scenario results, in scenario order
+    pub results: Vec<ScenarioResult>,
+}
+
+impl RegulatoryReport {
+    /// Worst (most negative) scenario P&L
+    ///
+    /// Fails if the report has no scenarios.
+    pub fn worst_case_pnl(&self) -> Result<f64> {
+        self.results
+            .iter()
+            .map(|r| r.pnl)
+            .min_by(f64::total_cmp)
+            .ok_or_else(|| RiskError::CalculationError("no stress scenarios".to_string()))
+    }
+
+    /// Render the report in the submission JSON layout
+    pub fn to_regulatory_json(&self) -> Result<serde_json::Value> {
+        let scenarios: Vec<serde_json::Value> = self
+            .results
+            .iter()
+            .map(|r| {
+                let contributions: serde_json::Map<String, serde_json::Value> = r
+                    .factor_pnl
+                    .iter()
+                    .map(|(factor, pnl)| (factor.clone(), json!(pnl)))
+                    .collect();
+                json!({
+                    "name": r.scenario,
+                    "pnl": r.pnl,
+                    "factor_contributions": contributions,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "standard": self.standard,
+            "scenarios": scenarios,
+            "worst_case_pnl": self.worst_case_pnl()?,
+        }))
+    }
+}
+
+/// Stress tester for a regulatory standard
+pub struct RegulatoryStressTester {
+    standard: RegulatoryStandard,
+}
+
+impl RegulatoryStressTester {
+    /// Create a stress tester
+    pub fn new(standard: RegulatoryStandard) -> Self {
+        Self { standard }
+    }
+
+    /// Compute portfolio P&L under each scenario of the standard
+    ///
+    /// Shocks to factors absent from `factor_exposures` contribute nothing.
+    /// Fails if the standard has no scenarios.
+    pub fn run(
+        &self,
+        portfolio: &Portfolio,
+        factor_exposures: &FactorExposures,
+    ) -> Result<RegulatoryReport> {
+        if portfolio.securities != factor_exposures.securities {
+            return Err(RiskError::MissingExposure(
+                "portfolio securities do not match exposure universe".to_string(),
+            ));
+        }
+
+        let scenarios = self.standard.scenarios();
+        if scenarios.is_empty() {
+            return Err(RiskError::CalculationError(
+                "no stress scenarios".to_string(),
+            ));
+        }
+
+        let exposures = factor_exposures.portfolio_exposures(&portfolio.weights.to_dvector())?;
+
+        let results = scenarios
+            .into_iter()
+            .map(|scenario| {
+                let factor_pnl: Vec<(String, f64)> = factor_exposures
+                    .factors
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(k, factor)| {
+                        scenario
+                            .factor_shocks
+                            .get(factor)
+                            .map(|shock| (factor.clone(), exposures[k] * shock))
+                    })
+                    .collect();
+                ScenarioResult {
+                    scenario: scenario.name,
+                    pnl: factor_pnl.iter().map(|(_, pnl)| pnl).sum(),
+                    factor_pnl,
+                }
+            })
+            .collect();
+
+        Ok(RegulatoryReport {
+            standard: self.standard.name().to_string(),
+            results,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_universe(exposures: Vec<Vec<f64>>) -> FactorExposures {
+        FactorExposures::new(
+            vec!["A".to_string(), "B".to_string()],
+            vec![
+                "equity".to_string(),
+                "interest_rate".to_string(),
+                "fx".to_string(),
+                "credit_spread".to_string(),
+            ],
+            exposures,
+            vec![0.02, 0.03],
+        )
+        .unwrap()
+    }
+
+    fn make_portfolio() -> Portfolio {
+        Portfolio::new(vec!["A".to_string(), "B".to_string()], vec![0.6, 0.4]).unwrap()
+    }
+
+    #[test]
+    fn test_basel3_equity_shock() {
+        // Full-equity portfolio: unit equity beta, no other exposures
+        let universe = make_universe(vec![vec![1.0, 0.0, 0.0, 0.0], vec![1.0, 0.0, 0.0, 0.0]]);
+        let tester = RegulatoryStressTester::new(RegulatoryStandard::Basel3);
+        let report = tester.run(&make_portfolio(), &universe).unwrap();
+
+        assert_eq!(report.standard, "Basel3");
+        assert_eq!(report.results.len(), 4);
+
+        let equity = &report.results[0];
+        assert_eq!(equity.scenario, "equity_crash");
+        assert!((equity.pnl + 0.40).abs() < 1e-12);
+        assert!(report.results[1..].iter().all(|r| r.pnl == 0.0));
+        assert!((report.worst_case_pnl().unwrap() + 0.40).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_custom_scenarios_and_json() {
+        // Bond-like exposure: duration 5 on A, spread duration 4 on B
+        let universe = make_universe(vec![vec![0.0, -5.0, 0.0, 0.0], vec![0.0, 0.0, 0.0, -4.0]]);
+        let scenario = StressScenario::new(
+            "stagflation",
+            &[("interest_rate", 0.01), ("credit_spread", 0.02)],
+        );
+        let tester = RegulatoryStressTester::new(RegulatoryStandard::Custom(vec![scenario]));
+        let report = tester.run(&make_portfolio(), &universe).unwrap();
+
+        // 0.6 * -5 * 0.01 + 0.4 * -4 * 0.02 = -0.03 - 0.032
+        assert!((report.results[0].pnl + 0.062).abs() < 1e-12);
+
+        let json = report.to_regulatory_json().unwrap();
+        assert_eq!(json["standard"], "Custom");
+        assert_eq!(json["scenarios"][0]["name"], "stagflation");
+        let rates = json["scenarios"][0]["factor_contributions"]["interest_rate"]
+            .as_f64()
+            .unwrap();
+        assert!((rates + 0.03).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mismatched_universe() {
+        let universe = make_universe(vec![vec![1.0, 0.0, 0.0, 0.0], vec![1.0, 0.0, 0.0, 0.0]]);
+        let portfolio =
+            Portfolio::new(vec!["B".to_string(), "A".to_string()], vec![0.5, 0.5]).unwrap();
+        let tester = RegulatoryStressTester::new(RegulatoryStandard::EsmaGuidelines);
+
+        assert!(tester.run(&portfolio, &universe).is_err());
+    }
+
+    #[test]
+    fn test_empty_scenarios_rejected() {
+        let universe = make_universe(vec![vec![1.0, 0.0, 0.0, 0.0], vec![1.0, 0.0, 0.0, 0.0]]);
+        let tester = RegulatoryStressTester::new(RegulatoryStandard::Custom(vec![]));
+        assert!(matches!(
+            tester.run(&make_portfolio(), &universe),
+            Err(RiskError::CalculationError(_))
+        ));
+
+        let report = RegulatoryReport {
+            standard: "Custom".to_string(),
+            results: vec![],
+        };
+        assert!(report.worst_case_pnl().is_err());
+        assert!(report.to_regulatory_json().is_err());
+    }
+}
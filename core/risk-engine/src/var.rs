@@ -0,0 +1,314 @@
+//! Value at risk and expected shortfall
+//!
+//! Three ways to estimate the loss a portfolio exceeds with probability
+//! `1 - confidence`: from the empirical distribution of past returns, from
+//! a normal distribution, and from multivariate normal simulation. All
+//! figures are returned as positive losses in return units, the convention
+//! of [`crate::backtest::VarBacktester`].
+
+use covariance::distribution::normal_quantile;
+use nalgebra::{DMatrix, DVector};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, StandardNormal};
+
+use crate::{Result, RiskError};
+
+/// Eigenvalues above `-PSD_TOLERANCE` times the largest eigenvalue are
+/// treated as zero when factoring a singular covariance
+const PSD_TOLERANCE: f64 = 1e-10;
+
+/// Historical simulation VaR
+///
+/// Reads VaR off the sorted returns: the `ceil(c n)`-th smallest of the
+/// `n` losses at confidence `c`.
+#[derive(Debug, Clone, Copy)]
+pub struct HistoricalVaR;
+
+impl HistoricalVaR {
+    /// VaR of `returns` at `confidence`
+    pub fn compute(returns: &[f64], confidence: f64) -> Result<f64> {
+        let (losses, index) = sorted_losses(returns, confidence)?;
+        Ok(losses[index])
+    }
+
+    /// Expected shortfall of `returns` at `confidence`
+    ///
+    /// The mean of the losses at or beyond the VaR order statistic, so it
+    /// is never below VaR.
+    pub fn expected_shortfall(returns: &[f64], confidence: f64) -> Result<f64> {
+        let (losses, index) = sorted_losses(returns, confidence)?;
+        let tail = &losses[index..];
+        Ok(tail.iter().sum::<f64>() / tail.len() as f64)
+    }
+}
+
+/// Variance-covariance VaR under normally distributed returns
+#[derive(Debug, Clone, Copy)]
+pub struct ParametricVaR;
+
+impl ParametricVaR {
+    /// VaR `σ z_c - μ` of a normal return with mean `mean` and standard
+    /// deviation `std`
+    pub fn compute(mean: f64, std: f64, confidence: f64) -> Result<f64> {
+        check_confidence(confidence)?;
+        check_std(std)?;
+        Ok(std * normal_quantile(confidence) - mean)
+    }
+
+    /// Expected shortfall `σ φ(z_c) / (1 - c) - μ` of a normal return
+    pub fn expected_shortfall(mean: f64, std: f64, confidence: f64) -> Result<f64> {
+        check_confidence(confidence)?;
+        check_std(std)?;
+        let z = normal_quantile(confidence);
+        let density = (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        Ok(std * density / (1.0 - confidence) - mean)
+    }
+}
+
+/// Monte Carlo VaR from multivariate normal asset returns
+///
+/// Draws `n_sims` zero-mean asset return vectors `L z` with `L L' = Σ`,
+/// and applies historical simulation to the resulting portfolio returns.
+/// `L` is the Cholesky factor of `Σ`, or `V Λ^½` from its eigendecomposition
+/// when `Σ` is positive semi-definite but singular (a riskless asset, a
+/// rank-deficient factor model). The same seed gives the same estimate.
+#[derive(Debug, Clone, Copy)]
+pub struct MonteCarloVaR;
+
+impl MonteCarloVaR {
+    /// Simulated VaR of the portfolio `weights` at `confidence`
+    pub fn compute(
+        weights: &DVector<f64>,
+        cov: &DMatrix<f64>,
+        n_sims: u32,
+        confidence: f64,
+        seed: u64,
+    ) -> Result<f64> {
+        check_confidence(confidence)?;
+        let returns = simulate_portfolio_returns(weights, cov, n_sims, seed)?;
+        HistoricalVaR::compute(&returns, confidence)
+    }
+
+    /// Simulated expected shortfall of the portfolio `weights` at
+    /// `confidence`
+    pub fn expected_shortfall(
+        weights: &DVector<f64>,
+        cov: &DMatrix<f64>,
+        n_sims: u32,
+        confidence: f64,
+        seed: u64,
+    ) -> Result<f64> {
+        check_confidence(confidence)?;
+        let returns = simulate_portfolio_returns(weights, cov, n_sims, seed)?;
+        HistoricalVaR::expected_shortfall(&returns, confidence)
+    }
+}
+
+fn check_confidence(confidence: f64) -> Result<()> {
+    if confidence > 0.0 && confidence < 1.0 {
+        Ok(())
+    } else {
+        Err(RiskError::CalculationError(format!(
+            "confidence {} must be in (0, 1)",
+            confidence
+        )))
+    }
+}
+
+fn check_std(std: f64) -> Result<()> {
+    if std >= 0.0 && std.is_finite() {
+        Ok(())
+    } else {
+        Err(RiskError::CalculationError(format!(
+            "standard deviation {} must be finite and non-negative",
+            std
+        )))
+    }
+}
+
+/// Losses sorted ascending and the index of the VaR order statistic
+fn sorted_losses(returns: &[f64], confidence: f64) -> Result<(Vec<f64>, usize)> {
+    check_confidence(confidence)?;
+    if returns.is_empty() {
+        return Err(RiskError::CalculationError(
+            "no returns to estimate VaR from".to_string(),
+        ));
+    }
+
+    let mut losses: Vec<f64> = returns.iter().map(|r| -r).collect();
+    losses.sort_by(f64::total_cmp);
+    let n = losses.len();
+    let index = ((confidence * n as f64).ceil() as usize).clamp(1, n) - 1;
+    Ok((losses, index))
+}
+
+/// Portfolio returns `w' L z` for `n_sims` standard normal draws `z`
+fn simulate_portfolio_returns(
+    weights: &DVector<f64>,
+    cov: &DMatrix<f64>,
+    n_sims: u32,
+    seed: u64,
+) -> Result<Vec<f64>> {
+    let n = weights.len();
+    if cov.nrows() != n || cov.ncols() != n {
+        return Err(RiskError::DimensionMismatch {
+            expected: n,
+            actual: cov.nrows(),
+        });
+    }
+    if n_sims == 0 {
+        return Err(RiskError::CalculationError(
+            "at least one simulation is required".to_string(),
+        ));
+    }
+
+    // w' L z = (L' w) . z, so each draw costs one dot product
+    let loadings = covariance_factor(cov)?.transpose() * weights;
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    Ok((0..n_sims)
+        .map(|_| {
+            loadings
+                .iter()
+                .map(|b| {
+                    let z: f64 = StandardNormal.sample(&mut rng);
+                    b * z
+                })
+                .sum()
+        })
+        .collect())
+}
+
+/// Factor `L` with `L L' = cov`
+///
+/// The Cholesky factor when `cov` is positive definite, otherwise
+/// `V Λ^½` with eigenvalues within the tolerance of zero clamped to zero.
+/// Fails if `cov` has a clearly negative eigenvalue.
+fn covariance_factor(cov: &DMatrix<f64>) -> Result<DMatrix<f64>> {
+    if let Some(cholesky) = cov.clone().cholesky() {
+        return Ok(cholesky.l());
+    }
+
+    let eigen = cov.clone().symmetric_eigen();
+    let largest = eigen.eigenvalues.amax();
+    if eigen
+        .eigenvalues
+        .iter()
+        .any(|&lambda| lambda.is_nan() || lambda < -PSD_TOLERANCE * largest)
+    {
+        return Err(RiskError::NonPositiveDefinite);
+    }
+    let scale = eigen.eigenvalues.map(|lambda| lambda.max(0.0).sqrt());
+    Ok(eigen.eigenvectors * DMatrix::from_diagonal(&scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::{dmatrix, dvector};
+
+    #[test]
+    fn test_historical_var() {
+        // Losses -0.049, ..., 0.050: the 95th smallest is 0.045
+        let returns: Vec<f64> = (0..100).map(|i| (i as f64 - 50.0) / 1000.0).collect();
+        let var = HistoricalVaR::compute(&returns, 0.95).unwrap();
+        assert!((var - 0.045).abs() < 1e-12);
+        let es = HistoricalVaR::expected_shortfall(&returns, 0.95).unwrap();
+        assert!((es - 0.0475).abs() < 1e-12);
+
+        // Order of the returns does not matter
+        let reversed: Vec<f64> = returns.iter().rev().copied().collect();
+        assert_eq!(HistoricalVaR::compute(&reversed, 0.95).unwrap(), var);
+
+        assert!(HistoricalVaR::compute(&[], 0.95).is_err());
+    }
+
+    #[test]
+    fn test_parametric_var() {
+        let var = ParametricVaR::compute(0.0, 0.02, 0.99).unwrap();
+        assert!((var - 2.326348 * 0.02).abs() < 1e-7);
+        // A positive mean return offsets the loss
+        let shifted = ParametricVaR::compute(0.005, 0.02, 0.99).unwrap();
+        assert!((var - shifted - 0.005).abs() < 1e-12);
+
+        // ES = σ φ(z) / (1 - c) = 0.02 * 0.026652 / 0.01
+        let es = ParametricVaR::expected_shortfall(0.0, 0.02, 0.99).unwrap();
+        assert!((es - 0.053304).abs() < 1e-5);
+        assert!(es > var);
+
+        assert!(ParametricVaR::compute(0.0, -0.02, 0.99).is_err());
+    }
+
+    #[test]
+    fn test_monte_carlo_matches_parametric() {
+        let weights: DVector<f64> = dvector![0.5, 0.3, 0.2];
+        let cov: DMatrix<f64> = dmatrix![
+            0.0004, 0.0001, 0.00005;
+            0.0001, 0.0009, 0.0002;
+            0.00005, 0.0002, 0.0016
+        ];
+        let std = (weights.transpose() * &cov * &weights)[(0, 0)].sqrt();
+
+        let var = MonteCarloVaR::compute(&weights, &cov, 100_000, 0.99, 7).unwrap();
+        let expected = ParametricVaR::compute(0.0, std, 0.99).unwrap();
+        assert!((var / expected - 1.0).abs() < 0.03);
+
+        let es = MonteCarloVaR::expected_shortfall(&weights, &cov, 100_000, 0.99, 7).unwrap();
+        let expected = ParametricVaR::expected_shortfall(0.0, std, 0.99).unwrap();
+        assert!((es / expected - 1.0).abs() < 0.03);
+
+        // Reproducible from the seed
+        assert_eq!(
+            MonteCarloVaR::compute(&weights, &cov, 1000, 0.99, 7).unwrap(),
+            MonteCarloVaR::compute(&weights, &cov, 1000, 0.99, 7).unwrap()
+        );
+
+        assert!(matches!(
+            MonteCarloVaR::compute(&dvector![1.0, 0.0], &cov, 1000, 0.99, 7),
+            Err(RiskError::DimensionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_monte_carlo_singular_covariance() {
+        // Asset 1 duplicates asset 0 and asset 2 is riskless
+        let weights: DVector<f64> = dvector![0.5, 0.3, 0.2];
+        let cov: DMatrix<f64> = dmatrix![
+            0.0004, 0.0004, 0.0;
+            0.0004, 0.0004, 0.0;
+            0.0, 0.0, 0.0
+        ];
+        let std = 0.8 * 0.02;
+
+        let var = MonteCarloVaR::compute(&weights, &cov, 100_000, 0.99, 7).unwrap();
+        let expected = ParametricVaR::compute(0.0, std, 0.99).unwrap();
+        assert!((var / expected - 1.0).abs() < 0.03);
+
+        let indefinite = dmatrix![0.0004, 0.0008; 0.0008, 0.0004];
+        assert!(matches!(
+            MonteCarloVaR::compute(&dvector![0.5, 0.5], &indefinite, 1000, 0.99, 7),
+            Err(RiskError::NonPositiveDefinite)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_confidence() {
+        let weights = dvector![1.0];
+        let cov = dmatrix![0.0004];
+        for confidence in [0.0, 1.0, 1.5, f64::NAN] {
+            assert!(matches!(
+                HistoricalVaR::compute(&[0.01, -0.02], confidence),
+                Err(RiskError::CalculationError(_))
+            ));
+            assert!(matches!(
+                ParametricVaR::expected_shortfall(0.0, 0.02, confidence),
+                Err(RiskError::CalculationError(_))
+            ));
+            assert!(matches!(
+                MonteCarloVaR::compute(&weights, &cov, 100, confidence, 1),
+                Err(RiskError::CalculationError(_))
+            ));
+        }
+    }
+}